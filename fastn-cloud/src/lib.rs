@@ -1,9 +1,12 @@
 extern crate self as fastn_cloud;
 
 mod http;
+mod publish;
 mod upload;
 mod utils;
 
+pub use publish::{publish, PublishError, Published};
+
 #[derive(thiserror::Error, Debug)]
 pub enum UploadError {
     #[error("BuildDirNotFound: {}", _0)]
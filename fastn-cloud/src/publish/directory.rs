@@ -0,0 +1,30 @@
+/// Copies the changed files of `.build` to `dir` and deletes the ones no longer built from it.
+pub(crate) async fn publish(
+    build_dir: &camino::Utf8Path,
+    dir: &camino::Utf8Path,
+    changed: &[String],
+    deleted: &[String],
+) -> Result<(), fastn_cloud::PublishError> {
+    for path in changed {
+        let destination = dir.join(path);
+        if let Some(parent) = destination.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::copy(build_dir.join(path), destination).await?;
+    }
+    for path in deleted {
+        let destination = dir.join(path);
+        if destination.exists() {
+            tokio::fs::remove_file(destination.as_path()).await?;
+        }
+        // and the folders left empty, `about/` of `about/index.html`
+        let mut parent = destination.parent();
+        while let Some(folder) = parent.filter(|p| p.starts_with(dir) && !p.eq(&dir)) {
+            if tokio::fs::remove_dir(folder).await.is_err() {
+                break;
+            }
+            parent = folder.parent();
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,283 @@
+//! `fastn publish-static` uploads `.build` to one of the `fastn.publish` destinations of the
+//! package, or to fastn cloud if it has none. Only the files that changed since the last publish
+//! to the destination are uploaded, and the files that are no longer built are deleted. What was
+//! published is kept at the destination in `.fastn/publish/<name>.json`, the sha256 of every file,
+//! so publishing from another checkout or machine is incremental too. `--full` ignores it and
+//! uploads everything.
+
+mod directory;
+mod rsync;
+mod s3;
+
+#[derive(thiserror::Error, Debug)]
+pub enum PublishError {
+    #[error("BuildDirNotFound: {}", _0)]
+    BuildDirNotFound(String),
+    #[error("TargetNotFound: {}", _0)]
+    TargetNotFound(String),
+    #[error("InvalidTarget: {}", _0)]
+    InvalidTarget(String),
+    #[error("UploadError: {}", _0)]
+    Upload(#[from] fastn_cloud::UploadError),
+    #[error("FastnCoreError: {}", _0)]
+    FastnCore(#[from] fastn_core::Error),
+    #[error("StdIOError: {}", _0)]
+    StdIOError(#[from] std::io::Error),
+    #[error("ManifestParseError: {}", _0)]
+    ManifestParse(#[from] serde_json::Error),
+    #[error("ReqwestError: {}", _0)]
+    Reqwest(#[from] reqwest::Error),
+    #[error("S3Error: {key}: {status}: {body}")]
+    S3 {
+        key: String,
+        status: reqwest::StatusCode,
+        body: String,
+    },
+    #[error("RsyncError: {}", _0)]
+    Rsync(String),
+}
+
+/// The files of `.build` by their path, with the sha256 of their content
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default)]
+struct Manifest {
+    files: std::collections::BTreeMap<String, String>,
+}
+
+impl Manifest {
+    fn of(build_dir: &camino::Utf8Path) -> Result<Manifest, PublishError> {
+        use sha2::Digest;
+
+        let mut files = std::collections::BTreeMap::new();
+        for file in fastn_cloud::utils::walkdir_util(build_dir) {
+            let path = match file.path.strip_prefix(build_dir) {
+                Ok(path) => path.to_string(),
+                Err(_) => continue,
+            };
+            // left behind by the fastn cloud upload
+            if path.ends_with(".tejar-list") || path.ends_with(".tejar-data") {
+                continue;
+            }
+            let hash = sha2::Sha256::digest(std::fs::read(file.path.as_path())?);
+            files.insert(path, hex(hash.as_slice()));
+        }
+        Ok(Manifest { files })
+    }
+}
+
+enum Destination {
+    Directory(camino::Utf8PathBuf),
+    Rsync(String),
+    S3(Box<s3::Bucket>),
+}
+
+impl Destination {
+    fn from_target(
+        config: &fastn_core::Config,
+        target: &fastn_core::publish::Target,
+    ) -> Result<Destination, PublishError> {
+        let required = |value: &Option<String>, field: &str| {
+            value.clone().ok_or_else(|| {
+                PublishError::InvalidTarget(format!(
+                    "`{}` of the {} target `{}` is not set",
+                    field, target.kind, target.name
+                ))
+            })
+        };
+        Ok(match target.kind.as_str() {
+            "directory" => {
+                Destination::Directory(config.root.join(required(&target.path, "path")?))
+            }
+            "rsync" => Destination::Rsync(required(&target.path, "path")?),
+            "s3" => Destination::S3(Box::new(s3::Bucket::new(
+                required(&target.bucket, "bucket")?,
+                target.endpoint.as_deref(),
+                target.region.as_deref(),
+                target.prefix.as_deref(),
+            )?)),
+            kind => {
+                return Err(PublishError::InvalidTarget(format!(
+                    "`{}` has the unknown kind `{}`, it can be `fastn-cloud`, `s3`, `directory` or `rsync`",
+                    target.name, kind
+                )))
+            }
+        })
+    }
+
+    async fn publish(
+        &self,
+        build_dir: &camino::Utf8Path,
+        changed: &[String],
+        deleted: &[String],
+    ) -> Result<(), PublishError> {
+        match self {
+            Destination::Directory(dir) => {
+                directory::publish(build_dir, dir, changed, deleted).await
+            }
+            Destination::Rsync(destination) => {
+                rsync::publish(build_dir, destination.as_str(), changed, deleted).await
+            }
+            Destination::S3(bucket) => bucket.publish(build_dir, changed, deleted).await,
+        }
+    }
+
+    /// What was last published to the destination by the target `name`, if anything was
+    async fn manifest(&self, name: &str) -> Result<Option<Manifest>, PublishError> {
+        let path = manifest_path(name);
+        let content = match self {
+            Destination::Directory(dir) => {
+                let path = dir.join(path);
+                if !path.exists() {
+                    return Ok(None);
+                }
+                tokio::fs::read(path).await?
+            }
+            Destination::Rsync(destination) => match rsync::read(destination, path.as_str()).await?
+            {
+                Some(content) => content,
+                None => return Ok(None),
+            },
+            Destination::S3(bucket) => match bucket.get(path.as_str()).await? {
+                Some(content) => content,
+                None => return Ok(None),
+            },
+        };
+        Ok(Some(serde_json::from_slice(content.as_slice())?))
+    }
+
+    async fn save_manifest(&self, name: &str, manifest: &Manifest) -> Result<(), PublishError> {
+        let path = manifest_path(name);
+        let content = serde_json::to_vec_pretty(manifest)?;
+        match self {
+            Destination::Directory(dir) => {
+                let path = dir.join(path);
+                if let Some(parent) = path.parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                tokio::fs::write(path, content).await?;
+            }
+            Destination::Rsync(destination) => {
+                rsync::write(destination, path.as_str(), content).await?
+            }
+            Destination::S3(bucket) => bucket.put(path.as_str(), content).await?,
+        }
+        Ok(())
+    }
+}
+
+fn manifest_path(name: &str) -> String {
+    format!(".fastn/publish/{}.json", name)
+}
+
+/// What `publish` uploaded and deleted, or would with `dry_run`, for the caller to show
+#[derive(Debug)]
+pub enum Published {
+    /// fastn cloud finds the files to upload itself, there is nothing to show of a dry run
+    FastnCloud { dry_run: bool },
+    Target {
+        name: String,
+        changed: Vec<String>,
+        deleted: Vec<String>,
+        unchanged: usize,
+        dry_run: bool,
+    },
+}
+
+/// `target` is the name of a `fastn.publish` of the package, it can be left out if there is only
+/// one.
+pub async fn publish(
+    target: Option<&str>,
+    full: bool,
+    dry_run: bool,
+) -> Result<Published, PublishError> {
+    let config = fastn_core::Config::read(None, false, None).await?;
+    let build_dir = config.build_dir();
+    if !build_dir.exists() {
+        return Err(PublishError::BuildDirNotFound(
+            "Run `fastn build` to create a .build directory before running this".to_string(),
+        ));
+    }
+
+    let target = match (target, config.package.publish.as_slice()) {
+        (Some(name), targets) => {
+            Some(targets.iter().find(|t| t.name.eq(name)).ok_or_else(|| {
+                PublishError::TargetNotFound(format!("No `fastn.publish: {}`", name))
+            })?)
+        }
+        (None, []) => None,
+        (None, [target]) => Some(target),
+        (None, targets) => {
+            return Err(PublishError::TargetNotFound(format!(
+                "Which target to publish to? The package has {}",
+                targets
+                    .iter()
+                    .map(|t| t.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )))
+        }
+    };
+    let target = match target {
+        Some(target) if !target.kind.eq("fastn-cloud") => target,
+        _ => {
+            if !dry_run {
+                fastn_cloud::upload().await?;
+            }
+            return Ok(Published::FastnCloud { dry_run });
+        }
+    };
+    let destination = Destination::from_target(&config, target)?;
+    publish_to(
+        &destination,
+        target.name.as_str(),
+        build_dir.as_path(),
+        full,
+        dry_run,
+    )
+    .await
+}
+
+async fn publish_to(
+    destination: &Destination,
+    name: &str,
+    build_dir: &camino::Utf8Path,
+    full: bool,
+    dry_run: bool,
+) -> Result<Published, PublishError> {
+    let published = if full {
+        Default::default()
+    } else {
+        destination.manifest(name).await?.unwrap_or_default()
+    };
+    let current = Manifest::of(build_dir)?;
+
+    let changed = current
+        .files
+        .iter()
+        .filter(|(path, hash)| published.files.get(*path) != Some(hash))
+        .map(|(path, _)| path.to_string())
+        .collect::<Vec<_>>();
+    let deleted = published
+        .files
+        .keys()
+        .filter(|path| !current.files.contains_key(*path))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    if !dry_run {
+        destination
+            .publish(build_dir, changed.as_slice(), deleted.as_slice())
+            .await?;
+        destination.save_manifest(name, &current).await?;
+    }
+    Ok(Published::Target {
+        name: name.to_string(),
+        unchanged: current.files.len() - changed.len(),
+        changed,
+        deleted,
+        dry_run,
+    })
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
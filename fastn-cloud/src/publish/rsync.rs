@@ -0,0 +1,155 @@
+/// Runs `rsync` to copy the changed files of `.build` to `destination`, a folder or
+/// `[user@]host:path`, and again to delete the ones no longer built from it. The files are sent
+/// on the standard input of `rsync`, so there can be any number of them.
+pub(crate) async fn publish(
+    build_dir: &camino::Utf8Path,
+    destination: &str,
+    changed: &[String],
+    deleted: &[String],
+) -> Result<(), fastn_cloud::PublishError> {
+    let source = format!("{}/", build_dir);
+    let destination = format!("{}/", destination.trim_end_matches('/'));
+
+    if !changed.is_empty() {
+        // `--files-from` copies the listed files only, with their paths relative to `source`
+        rsync(
+            &[
+                "--archive",
+                "--files-from=-",
+                source.as_str(),
+                destination.as_str(),
+            ],
+            changed.iter().map(|v| v.to_string()).collect(),
+        )
+        .await?;
+    }
+
+    if !deleted.is_empty() {
+        // everything but the deleted files is excluded, and `--delete` does not delete the
+        // excluded files
+        rsync(
+            &[
+                "--recursive",
+                "--delete",
+                "--include=*/",
+                "--include-from=-",
+                "--exclude=*",
+                source.as_str(),
+                destination.as_str(),
+            ],
+            deleted.iter().map(|v| pattern(v.as_str())).collect(),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// The file at `path` in `destination`, `None` if there is none.
+pub(crate) async fn read(
+    destination: &str,
+    path: &str,
+) -> Result<Option<Vec<u8>>, fastn_cloud::PublishError> {
+    let dir = temp_dir();
+    tokio::fs::create_dir_all(dir.as_path()).await?;
+    let local = dir.join("read");
+    let remote = format!("{}/{}", destination.trim_end_matches('/'), path);
+    // 23 is a partial transfer, which is what a missing file is
+    let content = match run(&[remote.as_str(), local.as_str()], vec![]).await? {
+        status if status.success() => Ok(Some(tokio::fs::read(local.as_path()).await?)),
+        status if status.code() == Some(23) => Ok(None),
+        status => Err(fastn_cloud::PublishError::Rsync(format!(
+            "rsync {} failed with {}",
+            remote, status
+        ))),
+    };
+    tokio::fs::remove_dir_all(dir.as_path()).await.ok();
+    content
+}
+
+/// Writes `content` to the file at `path` in `destination`, creating its folders.
+pub(crate) async fn write(
+    destination: &str,
+    path: &str,
+    content: Vec<u8>,
+) -> Result<(), fastn_cloud::PublishError> {
+    let dir = temp_dir();
+    let local = dir.join(path);
+    if let Some(parent) = local.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(local.as_path(), content).await?;
+    // with `--relative` the part of the source after `/./` is kept, and its folders created
+    let source = format!("{}/./{}", dir, path);
+    let destination = format!("{}/", destination.trim_end_matches('/'));
+    let result = rsync(
+        &["--relative", source.as_str(), destination.as_str()],
+        vec![],
+    )
+    .await;
+    tokio::fs::remove_dir_all(dir.as_path()).await.ok();
+    result
+}
+
+fn temp_dir() -> camino::Utf8PathBuf {
+    camino::Utf8PathBuf::from_path_buf(std::env::temp_dir())
+        .unwrap_or_else(|_| camino::Utf8PathBuf::from("/tmp"))
+        .join(format!("fastn-publish-{}", std::process::id()))
+}
+
+/// The `--include-from` pattern of exactly `path`. `*`, `?` and `[` are wildcards in a pattern,
+/// and `\` escapes them only in a pattern that has one.
+fn pattern(path: &str) -> String {
+    if !path.contains(['*', '?', '[']) {
+        return format!("/{}", path);
+    }
+    let mut pattern = "/".to_string();
+    for c in path.chars() {
+        if matches!(c, '*' | '?' | '[' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern
+}
+
+async fn rsync(args: &[&str], input: Vec<String>) -> Result<(), fastn_cloud::PublishError> {
+    let status = run(args, input).await?;
+    if !status.success() {
+        return Err(fastn_cloud::PublishError::Rsync(format!(
+            "rsync {} failed with {}",
+            args.join(" "),
+            status
+        )));
+    }
+    Ok(())
+}
+
+async fn run(
+    args: &[&str],
+    input: Vec<String>,
+) -> Result<std::process::ExitStatus, fastn_cloud::PublishError> {
+    use tokio::io::AsyncWriteExt;
+
+    let mut child = tokio::process::Command::new("rsync")
+        .args(args)
+        .stdin(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| fastn_cloud::PublishError::Rsync(format!("failed to run rsync: {}", e)))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input.join("\n").as_bytes()).await?;
+    }
+    Ok(child.wait().await?)
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn pattern() {
+        assert_eq!(super::pattern("blog/index.html"), "/blog/index.html");
+        assert_eq!(super::pattern("a\\b.html"), "/a\\b.html");
+        assert_eq!(
+            super::pattern("what?/[draft]*\\.html"),
+            "/what\\?/\\[draft]\\*\\\\.html"
+        );
+    }
+}
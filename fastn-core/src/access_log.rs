@@ -0,0 +1,184 @@
+// Structured (JSON lines) access log for `fastn serve`, enabled with `--access-log <FILE>`.
+
+tokio::task_local! {
+    static CURRENT: std::rc::Rc<std::cell::RefCell<Entry>>;
+}
+
+static NEXT_REQUEST_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+#[derive(Debug, Default, Clone, serde::Serialize)]
+pub struct Entry {
+    /// nanoseconds since unix epoch when the request was received
+    pub timestamp: u128,
+    pub request_id: String,
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+    pub latency_ms: f64,
+    /// id of the document that was resolved for this path, if any
+    pub document: Option<String>,
+    pub package: Option<String>,
+    /// `<provider>:<user-name>` of the logged-in user, if any
+    pub identity: Option<String>,
+}
+
+impl Entry {
+    pub(crate) fn new(req: &actix_web::HttpRequest) -> Entry {
+        let request_id = req
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(ToString::to_string)
+            .unwrap_or_else(|| {
+                format!(
+                    "{:x}-{:06x}",
+                    std::process::id(),
+                    NEXT_REQUEST_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+                )
+            });
+
+        Entry {
+            timestamp: fastn_core::timestamp_nanosecond(),
+            request_id,
+            method: req.method().to_string(),
+            path: req.path().to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Runs `f` with `entry` as the current request's log entry, so that code deep inside the
+/// request handling can call [`set_document`], and returns the updated entry.
+pub(crate) async fn scope<F: std::future::Future>(entry: Entry, f: F) -> (F::Output, Entry) {
+    let entry = std::rc::Rc::new(std::cell::RefCell::new(entry));
+    let output = CURRENT.scope(entry.clone(), f).await;
+    let entry = entry.borrow().clone();
+    (output, entry)
+}
+
+/// Records the document (and the package it belongs to) resolved for the current request. Does
+/// nothing when called outside [`scope`].
+pub(crate) fn set_document(document: &str, package: &str) {
+    let _ = CURRENT.try_with(|entry| {
+        let mut entry = entry.borrow_mut();
+        entry.document = Some(document.to_string());
+        entry.package = Some(package.to_string());
+    });
+}
+
+pub struct AccessLog {
+    writer: antidote::Mutex<Box<dyn std::io::Write + Send>>,
+}
+
+impl std::fmt::Debug for AccessLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AccessLog").finish()
+    }
+}
+
+impl AccessLog {
+    /// `-` writes the log to stdout, anything else is a file the log is appended to.
+    pub fn new(path: &str) -> fastn_core::Result<AccessLog> {
+        let writer: Box<dyn std::io::Write + Send> = if path == "-" {
+            Box::new(std::io::stdout())
+        } else {
+            Box::new(
+                std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)?,
+            )
+        };
+
+        Ok(AccessLog {
+            writer: antidote::Mutex::new(writer),
+        })
+    }
+
+    pub(crate) fn write(&self, entry: &Entry) {
+        let mut line = match serde_json::to_string(entry) {
+            Ok(line) => line,
+            Err(e) => {
+                fastn_core::warning!("failed to serialize access log entry: {}", e);
+                return;
+            }
+        };
+        line.push('\n');
+
+        let mut writer = self.writer.lock();
+        if let Err(e) = writer
+            .write_all(line.as_bytes())
+            .and_then(|_| writer.flush())
+        {
+            fastn_core::warning!("failed to write access log: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn entry() {
+        let req = actix_web::test::TestRequest::get()
+            .uri("/blog/?page=2")
+            .insert_header((super::REQUEST_ID_HEADER, "from-proxy"))
+            .to_http_request();
+        let entry = super::Entry::new(&req);
+        assert_eq!(entry.request_id, "from-proxy");
+        assert_eq!(entry.method, "GET");
+        assert_eq!(entry.path, "/blog/");
+
+        // ids are generated when the request has none
+        let req = actix_web::test::TestRequest::get().to_http_request();
+        let (first, second) = (super::Entry::new(&req), super::Entry::new(&req));
+        assert!(!first.request_id.is_empty());
+        assert_ne!(first.request_id, second.request_id);
+    }
+
+    #[tokio::test]
+    async fn scope() {
+        let (output, entry) = super::scope(Default::default(), async {
+            super::set_document("blog.ftd", "example.com");
+            "done"
+        })
+        .await;
+        assert_eq!(output, "done");
+        assert_eq!(entry.document.as_deref(), Some("blog.ftd"));
+        assert_eq!(entry.package.as_deref(), Some("example.com"));
+
+        // outside of a request it does nothing
+        super::set_document("blog.ftd", "example.com");
+    }
+
+    #[test]
+    fn write() {
+        let path = std::env::temp_dir().join(format!("fastn-access-{}.log", std::process::id()));
+        let log = super::AccessLog::new(path.to_str().unwrap()).unwrap();
+        let entry = super::Entry {
+            request_id: "1".to_string(),
+            method: "GET".to_string(),
+            path: "/".to_string(),
+            status: 200,
+            document: Some("index.ftd".to_string()),
+            ..Default::default()
+        };
+        log.write(&entry);
+        log.write(&super::Entry {
+            status: 404,
+            ..entry
+        });
+
+        let content = std::fs::read_to_string(path.as_path()).unwrap();
+        std::fs::remove_file(path).unwrap();
+        let lines = content
+            .lines()
+            .map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["document"], "index.ftd");
+        assert_eq!(lines[0]["identity"], serde_json::Value::Null);
+        assert_eq!(lines[1]["status"], 404);
+    }
+}
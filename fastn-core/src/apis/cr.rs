@@ -27,15 +27,69 @@ async fn create_cr_worker(
     let config = fastn_core::Config::read(None, false, Some(req)).await?;
     let cr_number = config.extract_cr_number().await?;
     let default_title = format!("CR#{cr_number}");
+    let identities = fastn_core::review::package_identities(&config)?;
     let cr_meta = fastn_core::cr::CRMeta {
         title: cr_request.title.unwrap_or(default_title),
         cr_number: cr_number as usize,
         open: true,
+        author: fastn_core::review::signed_in(req, identities.as_slice()).await?,
     };
     fastn_core::commands::create_cr::add_cr_to_workspace(&config, &cr_meta).await?;
     Ok(cr_number as usize)
 }
 
+#[derive(serde::Deserialize, serde::Serialize, std::fmt::Debug)]
+pub struct ReviewCRRequest {
+    pub cr: usize,
+    pub status: fastn_core::review::ReviewStatus,
+    pub message: Option<String>,
+}
+
+/// `/-/review-cr/` approves the CR or requests changes to it, as the reviewer signed in with the
+/// request.
+pub async fn review_cr(
+    req: &fastn_core::http::Request,
+    review_req: ReviewCRRequest,
+) -> fastn_core::Result<fastn_core::http::Response> {
+    let cr = review_req.cr;
+    match review_cr_worker(req, review_req).await {
+        Ok(Some(reviewer)) => {
+            #[derive(serde::Serialize)]
+            struct ReviewCRResponse {
+                reviewer: String,
+            }
+            fastn_core::http::api_ok(ReviewCRResponse { reviewer })
+        }
+        Ok(None) => Ok(fastn_core::unauthorised!(
+            "You are not signed in as a reviewer of CR#{}",
+            cr
+        )),
+        Err(err) => fastn_core::http::api_error(err.to_string()),
+    }
+}
+
+async fn review_cr_worker(
+    req: &fastn_core::http::Request,
+    review_req: ReviewCRRequest,
+) -> fastn_core::Result<Option<String>> {
+    let config = fastn_core::Config::read(None, false, Some(req)).await?;
+    let mut reviews = fastn_core::review::get_cr_reviews(&config, review_req.cr).await?;
+    let reviewer = match fastn_core::review::signed_in_reviewer(req, &reviews).await? {
+        Some(reviewer) => reviewer,
+        None => return Ok(None),
+    };
+    fastn_core::review::add_review(
+        &config,
+        review_req.cr,
+        &mut reviews,
+        reviewer.as_str(),
+        review_req.status,
+        review_req.message.as_deref(),
+    )
+    .await?;
+    Ok(Some(reviewer))
+}
+
 pub async fn create_cr_page(
     req: fastn_core::http::Request,
 ) -> fastn_core::Result<fastn_core::http::Response> {
@@ -62,3 +116,64 @@ async fn create_cr_page_worker(
         .await
         .map(Into::into)
 }
+
+#[derive(serde::Deserialize, serde::Serialize, std::fmt::Debug)]
+pub struct CommentCRRequest {
+    pub cr: usize,
+    pub message: String,
+    /// The file and line the comment is about, leave out to comment on the whole CR
+    pub file: Option<String>,
+    pub line: Option<usize>,
+    /// The id of the comment this replies to
+    #[serde(rename = "reply-to")]
+    pub reply_to: Option<usize>,
+}
+
+/// `/-/comment-cr/` comments on the CR, or on a line of a file in it, as the author, a reviewer
+/// or a writer of the files of the CR signed in with the request.
+pub async fn comment_cr(
+    req: &fastn_core::http::Request,
+    comment_req: CommentCRRequest,
+) -> fastn_core::Result<fastn_core::http::Response> {
+    let cr = comment_req.cr;
+    match comment_cr_worker(req, comment_req).await {
+        Ok(Some(id)) => {
+            #[derive(serde::Serialize)]
+            struct CommentCRResponse {
+                id: usize,
+            }
+            fastn_core::http::api_ok(CommentCRResponse { id })
+        }
+        Ok(None) => Ok(fastn_core::unauthorised!(
+            "You are not signed in as someone who can comment on CR#{}",
+            cr
+        )),
+        Err(err) => fastn_core::http::api_error(err.to_string()),
+    }
+}
+
+async fn comment_cr_worker(
+    req: &fastn_core::http::Request,
+    comment_req: CommentCRRequest,
+) -> fastn_core::Result<Option<usize>> {
+    let config = fastn_core::Config::read(None, false, Some(req)).await?;
+    if !fastn_core::cr::is_open_cr_exists(&config, comment_req.cr).await? {
+        return fastn_core::usage_error(format!("CR#{} is closed", comment_req.cr));
+    }
+    let mut reviews = fastn_core::review::get_cr_reviews(&config, comment_req.cr).await?;
+    let commenters = fastn_core::review::commenters(&config, comment_req.cr, &reviews).await?;
+    let author = match fastn_core::review::signed_in(req, commenters.as_slice()).await? {
+        Some(author) => author,
+        None => return Ok(None),
+    };
+    let id = reviews.add_comment(
+        author.as_str(),
+        comment_req.message.as_str(),
+        comment_req.file.as_deref(),
+        comment_req.line,
+        comment_req.reply_to,
+    )?;
+    fastn_core::review::create_cr_reviews(&config, comment_req.cr, &reviews, Some(author.as_str()))
+        .await?;
+    Ok(Some(id))
+}
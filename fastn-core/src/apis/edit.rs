@@ -52,7 +52,20 @@ pub async fn edit(
         }
     };
 
-    match edit_worker(config, req_data).await {
+    // the commits of the `git` history backend are by the signed in writer
+    let author = match fastn_core::user_group::access_identities(
+        &config,
+        req,
+        req_data.path.as_str(),
+        false,
+    )
+    .await
+    {
+        Ok(identities) => identities.first().map(ToString::to_string),
+        Err(err) => return fastn_core::http::api_error(err.to_string()),
+    };
+
+    match edit_worker(config, req_data, author).await {
         Ok(data) => fastn_core::http::api_ok(data),
         Err(err) => fastn_core::http::api_error(err.to_string()),
     }
@@ -61,6 +74,7 @@ pub async fn edit(
 pub(crate) async fn edit_worker(
     config: fastn_core::Config,
     request: EditRequest,
+    author: Option<String>,
 ) -> fastn_core::Result<EditResponse> {
     if request.is_delete() {
         let path = config.root.join(&request.path);
@@ -69,6 +83,16 @@ pub(crate) async fn edit_worker(
         } else if path.is_file() {
             tokio::fs::remove_file(&path).await?;
         }
+        if config.package.history_backend.is_git() {
+            let file = request.path.trim_start_matches('/');
+            fastn_core::git::commit_async(
+                &config.root,
+                vec![file.to_string()],
+                format!("Delete {}", file),
+                author,
+            )
+            .await?;
+        }
         return Ok(EditResponse {
             path: request.path,
             url: None,
@@ -92,7 +116,14 @@ pub(crate) async fn edit_worker(
             rename
         };
 
-        tokio::fs::rename(config.root.join(&request.path), config.root.join(new_path)).await?;
+        tokio::fs::rename(config.root.join(&request.path), config.root.join(&new_path)).await?;
+        if config.package.history_backend.is_git() {
+            let old_path = request.path.trim_start_matches('/').to_string();
+            let new_path = new_path.trim_start_matches('/').to_string();
+            let message = format!("Rename {} to {}", old_path, new_path);
+            fastn_core::git::commit_async(&config.root, vec![old_path, new_path], message, author)
+                .await?;
+        }
 
         // TODO: redirect to renamed file, if folder so it will redirect to renamed folder with
         // index.ftd, if index.ftd does not exists so it will redirected to main project index.ftd
@@ -109,19 +140,25 @@ pub(crate) async fn edit_worker(
         .get_file_path_and_resolve(request.path.as_str())
         .await
     {
-        let snapshots = fastn_core::snapshot::get_latest_snapshots(&config.root).await?;
-        let workspaces = fastn_core::snapshot::get_workspace(&config).await?;
+        if config.package.history_backend.is_git() {
+            // there is no status to compare with, every edit is committed
+            (path.to_string(), None, None)
+        } else {
+            let snapshots = fastn_core::snapshot::get_latest_snapshots(&config.root).await?;
+            let workspaces = fastn_core::snapshot::get_workspace(&config).await?;
 
-        let file = fastn_core::get_file(
-            config.package.name.to_string(),
-            &config.root.join(&path),
-            &config.root,
-        )
-        .await?;
-        let before_update_status =
-            fastn_core::commands::status::get_file_status(&file, &snapshots, &workspaces).await?;
+            let file = fastn_core::get_file(
+                config.package.name.to_string(),
+                &config.root.join(&path),
+                &config.root,
+            )
+            .await?;
+            let before_update_status =
+                fastn_core::commands::status::get_file_status(&file, &snapshots, &workspaces)
+                    .await?;
 
-        (path.to_string(), None, Some(before_update_status))
+            (path.to_string(), None, Some(before_update_status))
+        }
     } else if request.path.ends_with('/') {
         let path = format!("{}index.ftd", request.path);
         (
@@ -147,6 +184,17 @@ pub(crate) async fn edit_worker(
     )
     .await?;
 
+    if config.package.history_backend.is_git() {
+        let file = file_name.trim_start_matches('/');
+        fastn_core::git::commit_async(
+            &config.root,
+            vec![file.to_string()],
+            format!("Edit {}", file),
+            author,
+        )
+        .await?;
+    }
+
     if let Some(before_update_status) = before_update_status {
         let snapshots = fastn_core::snapshot::get_latest_snapshots(&config.root).await?;
         let workspaces = fastn_core::snapshot::get_workspace(&config).await?;
@@ -1,3 +1,7 @@
+/// `/-/edit-src/<path>` is an editor for the source of the file: the file tree of the package, the
+/// source with the problems `fastn check` finds in it, and a preview that is updated as the
+/// source is edited, using `/-/preview/`. Saving, creating, renaming and deleting files is done
+/// with `/-/edit/`, so only the writers of a file can change it.
 pub(crate) async fn edit_source(req: &fastn_core::http::Request) -> fastn_core::http::Response {
     // TODO: Need to remove unwrap
     let path = {
@@ -9,43 +13,381 @@ pub(crate) async fn edit_source(req: &fastn_core::http::Request) -> fastn_core::
         path
     };
 
-    match handle_view_source(req, path.as_str()).await {
-        Ok(body) => fastn_core::http::ok(body),
+    let mut config = match fastn_core::Config::read(None, false, Some(req)).await {
+        Ok(config) => config,
+        Err(e) => return fastn_core::server_error!("new_path: {}, Error: {:?}", path, e),
+    };
+    match handle_edit_source(&mut config, req, path.as_str()).await {
+        Ok(response) => response,
         Err(e) => {
             fastn_core::server_error!("new_path: {}, Error: {:?}", path, e)
         }
     }
 }
 
-async fn handle_view_source(
+async fn handle_edit_source(
+    config: &mut fastn_core::Config,
     req: &fastn_core::http::Request,
     path: &str,
-) -> fastn_core::Result<Vec<u8>> {
-    let mut config = fastn_core::Config::read(None, false, Some(req)).await?;
+) -> fastn_core::Result<fastn_core::http::Response> {
+    #[derive(serde::Serialize)]
+    struct Editor {
+        path: String,
+        source: String,
+        files: Vec<String>,
+        can_write: bool,
+        /// only the writers can preview, the editor has no preview pane for the others
+        preview: bool,
+    }
+
     let file_name = config.get_file_path_and_resolve(path).await?;
     let file = config.get_file_and_package_by_id(path).await?;
 
     match file {
         fastn_core::File::Ftd(_) | fastn_core::File::Markdown(_) | fastn_core::File::Code(_) => {
-            let editor_ftd = fastn_core::package_editor_source(&config, file_name.as_str())?;
-            let main_document = fastn_core::Document {
-                id: "editor-source.ftd".to_string(),
-                content: editor_ftd,
-                parent_path: config.root.as_str().to_string(),
-                package_name: config.package.name.clone(),
+            if !config.can_read(req, file_name.as_str(), true).await? {
+                return Ok(fastn_core::unauthorised!(
+                    "You are unauthorized to access: {}",
+                    file_name
+                ));
+            }
+
+            let root = config.get_root_for_package(&config.package);
+            let mut files = config
+                .get_all_file_paths1(&config.package, true)?
+                .into_iter()
+                .filter(|v| v.is_file())
+                .filter_map(|v| v.strip_prefix(&root).ok().map(ToString::to_string))
+                .collect::<Vec<_>>();
+            files.sort();
+
+            let can_write = config.can_write(req, file_name.as_str()).await?;
+            let editor = Editor {
+                source: tokio::fs::read_to_string(config.root.join(file_name.as_str())).await?,
+                files,
+                can_write,
+                preview: can_write && fastn_core::utils::is_ftd_path(file_name.as_str()),
+                path: file_name,
             };
-            fastn_core::package::package_doc::read_ftd(
-                &mut config,
-                &main_document,
-                "/",
-                false,
-                false,
-            )
-            .await
-            .map(|r| r.html())
+            let html = fastn_core::editor_html().replace(
+                "__EDITOR_DATA__",
+                fastn_core::font::escape(serde_json::to_string(&editor)?.as_str()).as_str(),
+            );
+            Ok(fastn_core::http::ok_with_content_type(
+                html.into_bytes(),
+                mime_guess::mime::TEXT_HTML_UTF_8,
+            ))
         }
         fastn_core::File::Static(ref file) | fastn_core::File::Image(ref file) => {
-            Ok(file.content.to_owned())
+            Ok(fastn_core::http::ok(file.content.to_owned()))
+        }
+    }
+}
+
+#[derive(serde::Deserialize, std::fmt::Debug)]
+pub struct PreviewRequest {
+    pub path: String,
+    pub value: String,
+}
+
+#[derive(serde::Serialize, std::fmt::Debug)]
+pub struct PreviewResponse {
+    /// `None` if the document has errors, the editor keeps showing the last preview then
+    pub html: Option<String>,
+    pub diagnostics: Vec<fastn_core::commands::check::Diagnostic>,
+}
+
+/// Renders `value` as if it were the content of the ftd document `path`, without saving it. Only
+/// the writers of the document can preview it, as rendering runs its processors.
+pub async fn preview(
+    req: &fastn_core::http::Request,
+    preview: PreviewRequest,
+) -> fastn_core::Result<fastn_core::http::Response> {
+    let mut config = match fastn_core::Config::read(None, false, Some(req)).await {
+        Ok(config) => config,
+        Err(err) => return fastn_core::http::api_error(err.to_string()),
+    };
+    handle_preview(&mut config, req, preview).await
+}
+
+async fn handle_preview(
+    config: &mut fastn_core::Config,
+    req: &fastn_core::http::Request,
+    preview: PreviewRequest,
+) -> fastn_core::Result<fastn_core::http::Response> {
+    let path = preview.path.trim_start_matches('/').to_string();
+    // the sitemap has the writers of the document, `index.ftd` is `/` there
+    config.current_document = Some(path.clone());
+
+    match config.can_write(req, path.as_str()).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return Ok(fastn_core::unauthorised!(
+                "You are unauthorized to access: {}",
+                path
+            ));
+        }
+        Err(e) => {
+            return Ok(fastn_core::server_error!(
+                "fastn-Error: can_write error: {}, {:?}",
+                path,
+                e
+            ));
+        }
+    }
+
+    if !fastn_core::utils::is_ftd_path(path.as_str()) {
+        return fastn_core::http::api_ok(PreviewResponse {
+            html: None,
+            diagnostics: vec![],
+        });
+    }
+
+    let mut diagnostics =
+        match fastn_core::lsp::check(config, path.as_str(), preview.value.as_str()).await {
+            Ok(diagnostics) => diagnostics,
+            Err(err) => return fastn_core::http::api_error(err.to_string()),
+        };
+    if diagnostics
+        .iter()
+        .any(|d| d.severity.eq(&fastn_core::commands::check::Severity::Error))
+    {
+        return fastn_core::http::api_ok(PreviewResponse {
+            html: None,
+            diagnostics,
+        });
+    }
+
+    let document = fastn_core::Document {
+        package_name: config.package.name.clone(),
+        id: path.clone(),
+        content: preview.value,
+        parent_path: config.root.to_string(),
+    };
+    let html = match fastn_core::package::package_doc::read_ftd(
+        config, &document, "/", false, false,
+    )
+    .await
+    {
+        Ok(r) => Some(String::from_utf8_lossy(r.html().as_slice()).to_string()),
+        Err(e) => {
+            // `fastn check` does not render, so this is not reported yet
+            diagnostics.push(fastn_core::commands::check::Diagnostic {
+                file: path,
+                line: 0,
+                severity: fastn_core::commands::check::Severity::Error,
+                code: "render-error",
+                message: e.to_string(),
+            });
+            None
+        }
+    };
+    fastn_core::http::api_ok(PreviewResponse { html, diagnostics })
+}
+
+#[cfg(test)]
+mod test {
+    /// alice is in `team`, which writes `/` and is the only reader of `/secret/`
+    async fn package(req: &fastn_core::http::Request) -> fastn_core::Config {
+        // the tests run in parallel, each gets a package of its own
+        static PACKAGES: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+        let root: camino::Utf8PathBuf = std::env::temp_dir()
+            .join(format!(
+                "fastn-edit-source-{}-{}",
+                std::process::id(),
+                PACKAGES.fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+            ))
+            .try_into()
+            .unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        for (path, content) in [
+            (
+                "FASTN.ftd",
+                indoc::indoc! {"
+                    -- import: fastn
+
+                    -- fastn.package: www.example.com
+
+                    -- fastn.user-group: team
+                    github: alice
+
+                    -- fastn.sitemap:
+
+                    # Home: /
+                      writers: team
+
+                    # Secret: /secret/
+                      readers: team
+                "},
+            ),
+            (
+                "index.ftd",
+                "-- ftd.text: a </script><script>alert(1)</script> & <!-- b\n",
+            ),
+            ("secret.ftd", "-- ftd.text: secret\n"),
+        ] {
+            std::fs::write(root.join(path), content).unwrap();
         }
+        fastn_core::Config::read(Some(root.to_string()), false, Some(req))
+            .await
+            .unwrap()
+    }
+
+    async fn request(uri: &str, user: Option<&str>) -> fastn_core::http::Request {
+        let mut req = actix_web::test::TestRequest::get().uri(uri);
+        if let Some(user) = user {
+            let user = serde_json::to_string(&fastn_core::auth::github::UserDetail {
+                token: "".to_string(),
+                user_name: user.to_string(),
+            })
+            .unwrap();
+            let cookie = fastn_core::auth::utils::encrypt_str(&user).await;
+            req = req.insert_header(("cookie", format!("github={}", cookie)));
+        }
+        fastn_core::http::Request::from_actix(req.to_http_request(), Default::default())
+    }
+
+    async fn body(response: fastn_core::http::Response) -> (u16, String) {
+        let status = response.status().as_u16();
+        let body = actix_web::body::to_bytes(response.into_body())
+            .await
+            .unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    /// The status of `/-/edit-src/<path>`, and the data the editor is given
+    async fn edit_source(path: &str, user: Option<&str>) -> (u16, Option<serde_json::Value>) {
+        let req = request(format!("/-/edit-src/{}", path).as_str(), user).await;
+        let mut config = package(&req).await;
+        let response = super::handle_edit_source(&mut config, &req, path)
+            .await
+            .unwrap();
+        std::fs::remove_dir_all(&config.root).unwrap();
+
+        let (status, html) = body(response).await;
+        let data = html
+            .split_once("const editor = ")
+            .and_then(|(_, rest)| rest.split_once(";\n"))
+            .map(|(data, _)| {
+                // the source ends in a script element, it must not be able to close it
+                assert!(!data.contains('<'), "{}", data);
+                serde_json::from_str(data).unwrap()
+            });
+        (status, data)
+    }
+
+    #[tokio::test]
+    async fn editor_data() {
+        let (status, data) = edit_source("index.ftd", None).await;
+        let data = data.unwrap();
+        assert_eq!(status, 200);
+        assert_eq!(data["path"], "index.ftd");
+        assert_eq!(
+            data["source"],
+            "-- ftd.text: a </script><script>alert(1)</script> & <!-- b\n"
+        );
+        assert_eq!(
+            data["files"],
+            serde_json::json!(["FASTN.ftd", "index.ftd", "secret.ftd"])
+        );
+    }
+
+    #[tokio::test]
+    async fn editor_access() {
+        let mut access = vec![];
+        for (path, user) in [
+            ("index.ftd", None),
+            ("index.ftd", Some("bob")),
+            ("index.ftd", Some("alice")),
+            ("secret.ftd", None),
+            ("secret.ftd", Some("bob")),
+            ("secret.ftd", Some("alice")),
+        ] {
+            let (status, data) = edit_source(path, user).await;
+            access.push((
+                status,
+                data.map(|data| (data["can_write"].clone(), data["preview"].clone())),
+            ));
+        }
+        let editor = |can_write: bool, preview: bool| Some((can_write.into(), preview.into()));
+        assert_eq!(
+            access,
+            vec![
+                (200, editor(false, false)),
+                (200, editor(false, false)),
+                (200, editor(true, true)),
+                (401, None),
+                (401, None),
+                (200, editor(false, false)),
+            ]
+        );
+    }
+
+    /// The status of `/-/preview/` of `value` as `index.ftd`, and what it returns
+    fn preview(value: &str, user: Option<&str>) -> (u16, serde_json::Value) {
+        // rendering takes more than the stack of a test thread, this is the stack of `main`
+        std::thread::scope(|scope| {
+            std::thread::Builder::new()
+                .stack_size(8 * 1024 * 1024)
+                .spawn_scoped(scope, || {
+                    tokio::runtime::Builder::new_current_thread()
+                        .enable_all()
+                        .build()
+                        .unwrap()
+                        .block_on(handle_preview(value, user))
+                })
+                .unwrap()
+                .join()
+                .unwrap()
+        })
+    }
+
+    async fn handle_preview(value: &str, user: Option<&str>) -> (u16, serde_json::Value) {
+        let req = request("/-/preview/", user).await;
+        let mut config = package(&req).await;
+        let preview = super::PreviewRequest {
+            path: "/index.ftd".to_string(),
+            value: value.to_string(),
+        };
+        let response = super::handle_preview(&mut config, &req, preview)
+            .await
+            .unwrap();
+        std::fs::remove_dir_all(&config.root).unwrap();
+
+        let (status, body) = body(response).await;
+        (
+            status,
+            serde_json::from_str(body.as_str()).unwrap_or(serde_json::Value::Null),
+        )
+    }
+
+    #[test]
+    fn preview_access() {
+        assert_eq!(preview("-- ftd.text: hello\n", None).0, 401);
+        assert_eq!(preview("-- ftd.text: hello\n", Some("bob")).0, 401);
+        let (status, body) = preview("-- ftd.text: hello\n", Some("alice"));
+        assert_eq!(status, 200);
+        assert_eq!(body["data"]["diagnostics"], serde_json::json!([]));
+        assert!(body["data"]["html"].as_str().unwrap().contains("hello"));
+    }
+
+    #[test]
+    fn preview_errors() {
+        let (status, body) = preview(
+            "-- ftd.text: hello\n\n-- ftd.text: $missing\n",
+            Some("alice"),
+        );
+        assert_eq!(status, 200);
+        assert_eq!(body["data"]["html"], serde_json::Value::Null);
+        let diagnostics = body["data"]["diagnostics"].as_array().unwrap();
+        assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+        assert_eq!(diagnostics[0]["file"], "index.ftd");
+        assert_eq!(diagnostics[0]["line"], 3);
+        assert_eq!(diagnostics[0]["severity"], "error");
+        assert!(diagnostics[0]["message"]
+            .as_str()
+            .unwrap()
+            .contains("missing"));
     }
 }
@@ -0,0 +1,411 @@
+/// `/-/history/` lists the files with history, `/-/history/<path>` lists the versions of a
+/// file, `?version=N` returns the file as it was at version `N` and `?from=A&to=B` shows the
+/// changes between two versions.
+pub(crate) async fn history(req: &fastn_core::http::Request) -> fastn_core::http::Response {
+    let path = req
+        .path()
+        .trim_start_matches("/-/history/")
+        .trim_end_matches('/')
+        .to_string();
+
+    let mut config = match fastn_core::Config::read(None, false, Some(req)).await {
+        Ok(config) => config,
+        Err(e) => return fastn_core::server_error!("path: {}, Error: {:?}", path, e),
+    };
+    match handle_history(&mut config, req, path.as_str()).await {
+        Ok(response) => response,
+        Err(fastn_core::Error::UsageError { message }) => fastn_core::not_found!("{}", message),
+        Err(e) => fastn_core::server_error!("path: {}, Error: {:?}", path, e),
+    }
+}
+
+async fn handle_history(
+    config: &mut fastn_core::Config,
+    req: &fastn_core::http::Request,
+    path: &str,
+) -> fastn_core::Result<fastn_core::http::Response> {
+    if !path.is_empty() && !can_read(config, req, path).await? {
+        return Ok(fastn_core::unauthorised!(
+            "You are unauthorized to access: {}",
+            path
+        ));
+    }
+    let version = |key: &str| {
+        req.query()
+            .get(key)
+            .and_then(|v| v.as_str())
+            .and_then(|v| v.parse::<i32>().ok())
+    };
+
+    let body = if path.is_empty() {
+        files_page(config, req).await?
+    } else if let Some(version) = version("version") {
+        return Ok(fastn_core::http::ok_with_content_type(
+            config.get_file_at_version(path, version).await?,
+            mime_guess::mime::TEXT_PLAIN_UTF_8,
+        ));
+    } else {
+        let file_history = config.get_file_history(path).await?;
+        match (version("from"), version("to")) {
+            (Some(from), Some(to)) => diff_page(config, &file_history, from, to).await?,
+            _ => versions_page(&file_history),
+        }
+    };
+
+    let body_prefix = config
+        .package
+        .generate_prefix_string(false)
+        .unwrap_or_default();
+    let main_document = fastn_core::Document {
+        id: "history.ftd".to_string(),
+        content: format!("{}\n\n{}", body_prefix, body),
+        parent_path: config.root.as_str().to_string(),
+        package_name: config.package.name.clone(),
+    };
+    fastn_core::package::package_doc::read_ftd(config, &main_document, "/", false, false)
+        .await
+        .map(|r| fastn_core::http::ok(r.html()))
+}
+
+/// If the user can read `file`, by the `readers` of its url in the sitemap, the history of a
+/// file shows all of its content
+async fn can_read(
+    config: &fastn_core::Config,
+    req: &fastn_core::http::Request,
+    file: &str,
+) -> fastn_core::Result<bool> {
+    config
+        .can_read(req, fastn_core::utils::id_to_path(file).as_str(), true)
+        .await
+}
+
+async fn files_page(
+    config: &fastn_core::Config,
+    req: &fastn_core::http::Request,
+) -> fastn_core::Result<String> {
+    let mut history = vec![];
+    if config.history_file().exists() {
+        for file_history in config.get_history().await? {
+            if can_read(config, req, file_history.filename.as_str()).await? {
+                history.push(file_history);
+            }
+        }
+    }
+
+    let mut page = page_start("History");
+    if history.is_empty() {
+        page.push_str("-- ftd.text: No file has any history yet\n\n");
+    }
+    for file_history in history.iter() {
+        page.push_str(
+            format!(
+                "-- ftd.text: {file}\nlink: /-/history/{file}\n\n",
+                file = fastn_core::utils::escape_ftd_page(file_history.filename.as_str())
+            )
+            .as_str(),
+        );
+    }
+    Ok(page_end(page))
+}
+
+fn versions_page(file_history: &fastn_core::history::FileHistory) -> String {
+    let file = fastn_core::utils::escape_ftd_page(file_history.filename.as_str());
+    let latest = file_history.file_edit.first().map(|v| v.version);
+
+    let mut page = page_start(format!("History of {}", file).as_str());
+    page.push_str("-- ftd.text: Compare any two versions A and B with ?from=A&to=B\n\n");
+    for (index, edit) in file_history.file_edit.iter().enumerate() {
+        let mut summary = format!(
+            "Version {} · {:?} · {} · {}",
+            edit.version,
+            edit.operation,
+            edit.author.as_deref().unwrap_or("unknown author"),
+            fastn_core::utils::nanos_to_rfc3339(&edit.timestamp)
+        );
+        if let Some(cr) = edit.src_cr {
+            summary = format!("{} · CR {}", summary, cr);
+        }
+        if let Some(message) = edit.message.as_ref().filter(|v| !v.trim().is_empty()) {
+            summary = format!(
+                "{} · {}",
+                summary,
+                message.split_whitespace().collect::<Vec<_>>().join(" ")
+            );
+        }
+
+        page.push_str("-- ftd.row:\nspacing.fixed.px: 16\n\n");
+        page.push_str(
+            format!(
+                "-- ftd.text: {}\n\n",
+                fastn_core::utils::escape_ftd_page(summary.as_str())
+            )
+            .as_str(),
+        );
+        if !edit.is_deleted() {
+            page.push_str(
+                format!(
+                    "-- ftd.text: view\nlink: /-/history/{}?version={}\n\n",
+                    file, edit.version
+                )
+                .as_str(),
+            );
+        }
+        // `file_edit` is newest first
+        if let Some(previous) = file_history.file_edit.get(index + 1) {
+            page.push_str(
+                format!(
+                    "-- ftd.text: changes\nlink: /-/history/{}?from={}&to={}\n\n",
+                    file, previous.version, edit.version
+                )
+                .as_str(),
+            );
+        }
+        if let Some(latest) = latest.filter(|v| index > 0 && !v.eq(&edit.version)) {
+            page.push_str(
+                format!(
+                    "-- ftd.text: compare with latest\nlink: /-/history/{}?from={}&to={}\n\n",
+                    file, edit.version, latest
+                )
+                .as_str(),
+            );
+        }
+        page.push_str("-- end: ftd.row\n\n");
+    }
+    page_end(page)
+}
+
+async fn diff_page(
+    config: &fastn_core::Config,
+    file_history: &fastn_core::history::FileHistory,
+    from: i32,
+    to: i32,
+) -> fastn_core::Result<String> {
+    let file = file_history.filename.as_str();
+    let then = content_at(config, file_history, from).await?;
+    let now = content_at(config, file_history, to).await?;
+
+    let mut page = page_start(
+        format!(
+            "Changes to {} from version {} to version {}",
+            fastn_core::utils::escape_ftd_page(file),
+            from,
+            to
+        )
+        .as_str(),
+    );
+    page.push_str(
+        format!(
+            "-- ftd.text: Back to the history of {}\nlink: /-/history/{}\n\n",
+            fastn_core::utils::escape_ftd_page(file),
+            fastn_core::utils::escape_ftd_page(file)
+        )
+        .as_str(),
+    );
+    if then.eq(&now) {
+        page.push_str("-- ftd.text: No changes\n\n");
+    } else {
+        let diff = fastn_core::commands::diff::patch(then.as_str(), now.as_str(), false);
+        page.push_str(
+            format!(
+                "-- ftd.code:\nlang: diff\nwidth: fill-container\n\n{}\n\n",
+                fastn_core::utils::escape_ftd_page(diff.as_str())
+            )
+            .as_str(),
+        );
+    }
+    Ok(page_end(page))
+}
+
+/// The content of the file at `version`, empty if the file was deleted in that version.
+async fn content_at(
+    config: &fastn_core::Config,
+    file_history: &fastn_core::history::FileHistory,
+    version: i32,
+) -> fastn_core::Result<String> {
+    if file_history.get_file_edit(version)?.is_deleted() {
+        return Ok(String::new());
+    }
+    Ok(
+        tokio::fs::read_to_string(config.history_path(file_history.filename.as_str(), version))
+            .await?,
+    )
+}
+
+fn page_start(title: &str) -> String {
+    format!(
+        indoc::indoc! {"
+            -- ftd.column:
+            padding.px: 24
+            spacing.fixed.px: 12
+            width: fill-container
+
+            -- ftd.text: {title}
+            role: $inherited.types.heading-medium
+
+        "},
+        title = title
+    )
+}
+
+fn page_end(page: String) -> String {
+    format!("{}-- end: ftd.column\n", page)
+}
+
+#[cfg(test)]
+mod test {
+    use fastn_core::history::{FileEdit, FileHistory, FileOperation};
+
+    fn edit(version: i32, operation: FileOperation, message: Option<&str>) -> FileEdit {
+        FileEdit {
+            message: message.map(ToString::to_string),
+            timestamp: 0,
+            version,
+            author: Some("amitu".to_string()),
+            src_cr: None,
+            operation,
+        }
+    }
+
+    #[test]
+    fn versions_page() {
+        let history = FileHistory {
+            filename: "a;;b.ftd".to_string(),
+            // newest first
+            file_edit: vec![
+                edit(3, FileOperation::Deleted, None),
+                edit(
+                    2,
+                    FileOperation::Updated,
+                    Some("fix\n-- ftd.text: typo ;; wip"),
+                ),
+                edit(1, FileOperation::Added, None),
+            ],
+        };
+        let page = super::versions_page(&history);
+        let sections = ftd::p1::parse(page.as_str(), "history.ftd").unwrap();
+        // the title, the help text and a row of links for each version
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].sub_sections.len(), 2 + 3);
+
+        assert!(page.contains("-- ftd.text: History of a\\;;b.ftd\n"));
+        assert!(page.contains(
+            "-- ftd.text: Version 2 · Updated · amitu · 1970-01-01T00:00:00Z · \
+             fix -- ftd.text: typo \\;; wip\n"
+        ));
+        let links = page
+            .lines()
+            .filter_map(|line| line.strip_prefix("link: /-/history/a\\;;b.ftd"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            links,
+            vec![
+                // version 3 is deleted, there is nothing to view
+                "?from=2&to=3",
+                "?version=2",
+                "?from=1&to=2",
+                "?from=2&to=3",
+                "?version=1",
+                "?from=1&to=3",
+            ]
+        );
+    }
+
+    #[test]
+    fn versions_page_without_history() {
+        let history = FileHistory {
+            filename: "index.ftd".to_string(),
+            file_edit: vec![],
+        };
+        let page = super::versions_page(&history);
+        assert!(ftd::p1::parse(page.as_str(), "history.ftd").is_ok());
+        assert!(!page.contains("link:"));
+    }
+
+    /// A package whose `secret.ftd` only the `team` can read, both files have a version
+    async fn package(req: &fastn_core::http::Request) -> fastn_core::Config {
+        let root: camino::Utf8PathBuf = std::env::temp_dir()
+            .join(format!("fastn-history-{}", std::process::id()))
+            .try_into()
+            .unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+        for (path, content) in [
+            (
+                "FASTN.ftd",
+                indoc::indoc! {"
+                    -- import: fastn
+
+                    -- fastn.package: www.example.com
+
+                    -- fastn.user-group: team
+                    github: alice
+
+                    -- fastn.sitemap:
+
+                    # Home: /
+
+                    # Secret: /secret/
+                      readers: team
+                "},
+            ),
+            ("index.ftd", "-- ftd.text: hello\n"),
+            ("secret.ftd", "-- ftd.text: secret\n"),
+        ] {
+            std::fs::create_dir_all(root.join(path).parent().unwrap()).unwrap();
+            std::fs::write(root.join(path), content).unwrap();
+        }
+        let config = fastn_core::Config::read(Some(root.to_string()), false, Some(req))
+            .await
+            .unwrap();
+
+        let history = ["index.ftd", "secret.ftd"].map(|file| FileHistory {
+            filename: file.to_string(),
+            file_edit: vec![edit(1, FileOperation::Added, None)],
+        });
+        for file in history.iter() {
+            let content = std::fs::read(root.join(file.filename.as_str())).unwrap();
+            let path = config.history_path(file.filename.as_str(), 1);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        std::fs::write(
+            config.history_file(),
+            FileHistory::to_ftd(history.iter().collect::<Vec<_>>().as_slice()),
+        )
+        .unwrap();
+        config
+    }
+
+    fn request(uri: &str) -> fastn_core::http::Request {
+        fastn_core::http::Request::from_actix(
+            actix_web::test::TestRequest::get()
+                .uri(uri)
+                .to_http_request(),
+            Default::default(),
+        )
+    }
+
+    #[tokio::test]
+    async fn readers() {
+        let req = request("/-/history/");
+        let mut config = package(&req).await;
+        let files = super::files_page(&config, &req).await.unwrap();
+
+        let mut status = vec![];
+        for (path, uri) in [
+            ("index.ftd", "/-/history/index.ftd?version=1"),
+            ("secret.ftd", "/-/history/secret.ftd?version=1"),
+            ("secret.ftd", "/-/history/secret.ftd"),
+            ("secret.ftd", "/-/history/secret.ftd?from=1&to=1"),
+        ] {
+            let response = super::handle_history(&mut config, &request(uri), path)
+                .await
+                .unwrap();
+            status.push(response.status().as_u16());
+        }
+        std::fs::remove_dir_all(&config.root).unwrap();
+
+        assert!(files.contains("-- ftd.text: index.ftd\n"));
+        assert!(!files.contains("secret.ftd"));
+        assert_eq!(status, vec![200, 401, 401, 401]);
+    }
+}
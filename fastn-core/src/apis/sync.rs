@@ -176,10 +176,12 @@ pub(crate) async fn sync_worker(
                         let ours_content = String::from_utf8(content.clone())
                             .map_err(|e| fastn_core::Error::APIResponseError(e.to_string()))?;
 
-                        match diffy::MergeOptions::new()
-                            .set_conflict_style(diffy::ConflictStyle::Merge)
-                            .merge(&ancestor_content, &ours_content, &theirs_content)
-                        {
+                        match fastn_core::merge::merge(
+                            path,
+                            &ancestor_content,
+                            &ours_content,
+                            &theirs_content,
+                        ) {
                             Ok(data) => {
                                 fastn_core::utils::update1(&config.root, path, data.as_bytes())
                                     .await?;
@@ -206,10 +206,12 @@ pub(crate) async fn do_sync(
                         let theirs_content = tokio::fs::read_to_string(theirs_path).await?;
                         let ours_content = String::from_utf8(content.clone())
                             .map_err(|e| fastn_core::Error::APIResponseError(e.to_string()))?;
-                        match diffy::MergeOptions::new()
-                            .set_conflict_style(diffy::ConflictStyle::Merge)
-                            .merge(&ancestor_content, &ours_content, &theirs_content)
-                        {
+                        match fastn_core::merge::merge(
+                            path,
+                            &ancestor_content,
+                            &ours_content,
+                            &theirs_content,
+                        ) {
                             Ok(data) => {
                                 fastn_core::utils::update(&config.root.join(path), data.as_bytes())
                                     .await?;
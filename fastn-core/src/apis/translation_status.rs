@@ -0,0 +1,20 @@
+/// `/-/translation-status/` shows the coverage report of `fastn translation-status`.
+pub(crate) async fn translation_status(
+    req: &fastn_core::http::Request,
+) -> fastn_core::http::Response {
+    match handle_translation_status(req).await {
+        Ok(response) => response,
+        Err(fastn_core::Error::UsageError { message }) => fastn_core::not_found!("{}", message),
+        Err(e) => fastn_core::server_error!("translation-status, Error: {:?}", e),
+    }
+}
+
+async fn handle_translation_status(
+    req: &fastn_core::http::Request,
+) -> fastn_core::Result<fastn_core::http::Response> {
+    let mut config = fastn_core::Config::read(None, false, Some(req)).await?;
+    let report = fastn_core::commands::translation_status::report(&config).await?;
+    fastn_core::commands::translation_status::report_html(&mut config, &report)
+        .await
+        .map(fastn_core::http::ok)
+}
@@ -122,6 +122,8 @@ pub async fn matched_identities(
     }
 
     let mut matched_identities = vec![];
+    // matched: github
+    matched_identities.extend(matched_user(&ud, github_identities.as_slice()));
     // matched_starred_repositories
     matched_identities.extend(matched_starred_repos(&ud, github_identities.as_slice()).await?);
     // matched: github-watches
@@ -140,6 +142,17 @@ pub async fn matched_identities(
     Ok(matched_identities)
 }
 
+pub fn matched_user(
+    ud: &UserDetail,
+    identities: &[&fastn_core::user_group::UserIdentity],
+) -> Vec<fastn_core::user_group::UserIdentity> {
+    identities
+        .iter()
+        .filter(|i| i.key.eq("github") && i.value.eq_ignore_ascii_case(ud.user_name.as_str()))
+        .map(|i| (*i).clone())
+        .collect()
+}
+
 pub async fn matched_starred_repos(
     ud: &UserDetail,
     identities: &[&fastn_core::user_group::UserIdentity],
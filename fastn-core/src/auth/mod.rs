@@ -215,6 +215,22 @@ pub async fn get_user_data_from_cookies(
     Ok(None)
 }
 
+/// `<platform>:<user-name>` of the first platform the user is logged in with, if any
+pub async fn get_logged_in_identity(
+    cookies: &std::collections::HashMap<String, String>,
+) -> Option<String> {
+    for platform in ["github", "telegram", "discord"] {
+        if !cookies.contains_key(platform) {
+            continue;
+        }
+        if let Ok(Some(user_name)) = get_user_data_from_cookies(platform, "username", cookies).await
+        {
+            return Some(format!("{}:{}", platform, user_name));
+        }
+    }
+    None
+}
+
 // TODO: rename the method later
 // bridge between fastn_core to auth to check
 pub async fn get_auth_identities(
@@ -0,0 +1,58 @@
+pub const COMMAND: &str = "assign-reviewers";
+
+pub fn command() -> clap::Command {
+    clap::Command::new(COMMAND)
+        .about("Assign reviewers to a CR, the writers of the files in it by default")
+        .arg(clap::arg!(cr: <CR> "The CR to assign reviewers to"))
+        .arg(clap::arg!(reviewer: <REVIEWER>... "The identities to assign, e.g. `github: amitu` (leave empty to assign the writers of the files in the CR)").required(false))
+        .hide(true) // hidden since the feature is not being released yet.
+}
+
+pub async fn handle_command(matches: &clap::ArgMatches) -> fastn_core::Result<()> {
+    use fastn_core::utils::ValueOf;
+
+    assign_reviewers(
+        &fastn_core::Config::read(None, true, None).await?,
+        matches.value_of_("cr").unwrap().parse::<usize>()?,
+        matches.values_of_("reviewer"),
+    )
+    .await
+}
+
+async fn assign_reviewers(
+    config: &fastn_core::Config,
+    cr: usize,
+    reviewers: Vec<String>,
+) -> fastn_core::Result<()> {
+    if !fastn_core::cr::is_open_cr_exists(config, cr).await? {
+        return fastn_core::usage_error(format!("CR#{} is closed", cr));
+    }
+
+    let reviewers = reviewers
+        .iter()
+        .map(|v| fastn_core::review::identity(v))
+        .collect::<fastn_core::Result<Vec<String>>>()?;
+    let reviewers = fastn_core::review::choose_reviewers(
+        cr,
+        reviewers,
+        fastn_core::review::cr_writers(config, cr).await?,
+        fastn_core::cr::get_cr_meta(config, cr)
+            .await?
+            .author
+            .as_deref(),
+    )?;
+
+    let mut reviews = fastn_core::review::get_cr_reviews(config, cr).await?;
+    for reviewer in reviewers {
+        if !reviews.is_reviewer(reviewer.as_str()) {
+            reviews.reviewers.push(reviewer);
+        }
+    }
+    fastn_core::review::create_cr_reviews(config, cr, &reviews, None).await?;
+
+    println!("Reviewers of CR#{}:", cr);
+    for reviewer in reviews.reviewers.iter() {
+        println!("    {}", reviewer);
+    }
+    Ok(())
+}
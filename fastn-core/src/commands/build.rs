@@ -47,6 +47,18 @@ pub async fn build(
         handle_file(document, config, base_url, ignore_failed, test, false).await?;
     }
 
+    // the documents again with the `i18n` messages of every other locale, in `.build/<locale>/`
+    for locale in fastn_core::i18n::package::other_locales(config) {
+        config.locale = Some(locale);
+        for document in documents
+            .values()
+            .filter(|d| matches!(d, fastn_core::File::Ftd(_)))
+        {
+            handle_file(document, config, base_url, ignore_failed, test, false).await?;
+        }
+    }
+    config.locale = None;
+
     config.download_fonts().await
 }
 
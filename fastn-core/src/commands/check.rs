@@ -0,0 +1,821 @@
+pub const COMMAND: &str = "check";
+
+pub fn command() -> clap::Command {
+    clap::Command::new(COMMAND)
+        .about("Check the documents and FASTN.ftd of this package for errors, without building it")
+        .arg(
+            clap::arg!(--format <FORMAT> "How to print the problems found")
+                .value_parser(["text", "json"])
+                .default_value("text"),
+        )
+}
+
+pub async fn handle_command(matches: &clap::ArgMatches) -> fastn_core::Result<()> {
+    use fastn_core::utils::ValueOf;
+
+    check(matches.value_of_("format").unwrap_or("text")).await
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Diagnostic {
+    /// relative to the package root
+    pub file: String,
+    /// 1-based, 0 if the problem is not about a particular line
+    pub line: usize,
+    pub severity: Severity,
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn error(file: &str, line: usize, code: &'static str, message: String) -> Diagnostic {
+        Diagnostic {
+            file: file.to_string(),
+            line,
+            severity: Severity::Error,
+            code,
+            message,
+        }
+    }
+
+    fn warning(file: &str, line: usize, code: &'static str, message: String) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Warning,
+            ..Diagnostic::error(file, line, code, message)
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+struct Report {
+    documents: usize,
+    errors: usize,
+    warnings: usize,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// Interprets every document of the package, without rendering them, and reports everything
+/// that would fail or is likely a mistake. Errors if any error was found, so it can be used in CI.
+#[tracing::instrument]
+pub async fn check(format: &str) -> fastn_core::Result<()> {
+    let (documents, mut diagnostics) = check_package().await?;
+    diagnostics.sort_by(|a, b| (a.file.as_str(), a.line).cmp(&(b.file.as_str(), b.line)));
+    // an error in a document is also found when interpreting the documents importing it
+    diagnostics.dedup_by(|a, b| {
+        (a.file.as_str(), a.line, a.code, a.message.as_str())
+            == (b.file.as_str(), b.line, b.code, b.message.as_str())
+    });
+
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+    let report = Report {
+        documents,
+        errors,
+        warnings: diagnostics.len() - errors,
+        diagnostics,
+    };
+
+    if format.eq("json") {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_report(&report);
+    }
+
+    if report.errors > 0 {
+        return Err(fastn_core::Error::GenericError(format!(
+            "fastn check found {} errors",
+            report.errors
+        )));
+    }
+    Ok(())
+}
+
+fn print_report(report: &Report) {
+    use colored::Colorize;
+
+    for d in report.diagnostics.iter() {
+        let severity = match d.severity {
+            Severity::Error => "error".red(),
+            Severity::Warning => "warning".yellow(),
+        };
+        if d.line > 0 {
+            println!(
+                "{}:{}: {}[{}]: {}",
+                d.file, d.line, severity, d.code, d.message
+            );
+        } else {
+            println!("{}: {}[{}]: {}", d.file, severity, d.code, d.message);
+        }
+    }
+    println!(
+        "Checked {} documents: {} errors, {} warnings.",
+        report.documents, report.errors, report.warnings
+    );
+}
+
+/// Returns the number of documents checked and the problems found.
+async fn check_package() -> fastn_core::Result<(usize, Vec<Diagnostic>)> {
+    let current_dir: camino::Utf8PathBuf = std::env::current_dir()?.canonicalize()?.try_into()?;
+    let root = fastn_core::Config::get_root_path(&current_dir).await?;
+    let fastn_ftd = tokio::fs::read_to_string(root.join("FASTN.ftd")).await?;
+
+    let mut diagnostics = vec![];
+
+    // checked before reading the config, as reading it fails on the first missing document
+    let package = match fastn_core::config::utils::fastn_doc(&root.join("FASTN.ftd"))
+        .await
+        .and_then(|doc| fastn_core::Package::from_fastn_doc(&root, &doc))
+    {
+        Ok(package) => package,
+        Err(e) => {
+            diagnostics.push(Diagnostic::error(
+                "FASTN.ftd",
+                location(&e).map(|(_, line)| line).unwrap_or_default(),
+                "invalid-fastn-ftd",
+                message(&e),
+            ));
+            return Ok((0, diagnostics));
+        }
+    };
+    check_sitemap(&root, &package, fastn_ftd.as_str(), &mut diagnostics);
+
+    let config = match fastn_core::Config::read(Some(root.to_string()), true, None).await {
+        Ok(config) => config,
+        Err(e) => {
+            // missing sitemap documents are already reported, with their line numbers
+            if diagnostics.is_empty() {
+                diagnostics.push(Diagnostic::error(
+                    "FASTN.ftd",
+                    location(&e).map(|(_, line)| line).unwrap_or_default(),
+                    "invalid-fastn-ftd",
+                    message(&e),
+                ));
+            }
+            return Ok((0, diagnostics));
+        }
+    };
+    check_fastn_ftd(&config, fastn_ftd.as_str(), &mut diagnostics);
+
+    let files = config.get_files(&config.package).await?;
+    let documents: Vec<&fastn_core::Document> = files
+        .iter()
+        .filter_map(|f| match f {
+            fastn_core::File::Ftd(doc) if !doc.id.eq("FASTN.ftd") => Some(doc),
+            _ => None,
+        })
+        .collect();
+
+    let mut references = std::collections::HashMap::new();
+    for document in documents.iter() {
+        references.insert(document.id.as_str(), references_of(document));
+    }
+
+    for document in documents.iter() {
+        diagnostics.extend(
+            check_document(
+                &config,
+                document,
+                documents.as_slice(),
+                files.as_slice(),
+                &references,
+            )
+            .await,
+        );
+    }
+
+    Ok((documents.len(), diagnostics))
+}
+
+/// The problems found in `document`. `documents` are the ftd documents of the package and
+/// `references` what `references_of()` finds in each of them.
+pub(crate) async fn check_document(
+    config: &fastn_core::Config,
+    document: &fastn_core::Document,
+    documents: &[&fastn_core::Document],
+    files: &[fastn_core::File],
+    references: &std::collections::HashMap<&str, std::collections::HashSet<String>>,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    let ast = match ftd::p1::parse(document.content.as_str(), document.id.as_str())
+        .map_err(ftd::ast::Error::from)
+        .and_then(|sections| {
+            ftd::ast::AST::from_sections(sections.as_slice(), document.id.as_str())
+        }) {
+        Ok(ast) => ast,
+        Err(e) => {
+            diagnostics.push(Diagnostic::error(
+                document.id.as_str(),
+                e.location().map(|(_, line)| line).unwrap_or_default(),
+                "syntax-error",
+                e.to_string(),
+            ));
+            return diagnostics;
+        }
+    };
+
+    let (result, unresolved) = interpret(config, document, ast.as_slice()).await;
+    for import in unresolved.iter() {
+        diagnostics.push(unresolved_import(
+            document,
+            import.module.as_str(),
+            import.line_number,
+        ));
+    }
+    match result {
+        // reported above already
+        Err(ftd::interpreter::Error::LibraryNotFound { ref module })
+            if unresolved.iter().any(|i| i.module.eq(module)) => {}
+        Err(e) => diagnostics.push(interpreter_diagnostic(
+            &e,
+            document,
+            documents,
+            ast.as_slice(),
+        )),
+        Ok(()) => {}
+    }
+    check_unused_imports(document, ast.as_slice(), &mut diagnostics);
+    check_unused_variables(ast.as_slice(), document, references, &mut diagnostics);
+    check_links(config, document, files, &mut diagnostics);
+    diagnostics
+}
+
+/// Same as what `fastn build` does for a document, minus the rendering and the processors, see
+/// `Library2022::skip_processors`. Also returns the imports of `ast` that can not be resolved, the
+/// interpreter only resolves the ones used.
+async fn interpret<'a>(
+    config: &fastn_core::Config,
+    document: &fastn_core::Document,
+    ast: &'a [ftd::ast::AST],
+) -> (ftd::interpreter::Result<()>, Vec<&'a ftd::ast::Import>) {
+    let mut lib = fastn_core::Library2022 {
+        config: config.clone(),
+        markdown: None,
+        document_id: document.id.clone(),
+        translated_data: Default::default(),
+        base_url: "/".to_string(),
+        module_package_map: Default::default(),
+        skip_processors: true,
+    };
+    let name = document.id_with_package();
+
+    let content =
+        config
+            .package
+            .get_prefixed_body(document.content.as_str(), document.id.as_str(), true);
+    let result = match config
+        .package
+        .fix_imports_in_body(content.as_str(), document.id.as_str())
+    {
+        Ok(content) => {
+            let line_number = content.split('\n').count() - document.content.split('\n').count();
+            fastn_core::doc::interpret_helper(
+                name.as_str(),
+                content.as_str(),
+                &mut lib,
+                "/",
+                false,
+                line_number,
+            )
+            .await
+            .map(|_| ())
+        }
+        Err(e) => Err(e.into()),
+    };
+
+    let mut unresolved = vec![];
+    for import in ast.iter().filter_map(|a| match a {
+        ftd::ast::AST::Import(i) => Some(i),
+        _ => None,
+    }) {
+        // provided by fastn itself, see `resolve_import_2022()`
+        let module = import.module.as_str();
+        if module.eq("fastn/time") || module.eq("fastn/processors") || module.ends_with("assets") {
+            continue;
+        }
+        if lib.get(module, name.as_str()).await.is_none() {
+            unresolved.push(import);
+        }
+    }
+
+    // dependencies resolved while interpreting are not resolved again for the next document
+    config
+        .all_packages
+        .borrow_mut()
+        .extend(lib.config.all_packages.into_inner());
+    (result, unresolved)
+}
+
+fn interpreter_diagnostic(
+    e: &ftd::interpreter::Error,
+    document: &fastn_core::Document,
+    documents: &[&fastn_core::Document],
+    ast: &[ftd::ast::AST],
+) -> Diagnostic {
+    if let ftd::interpreter::Error::LibraryNotFound { module } = e {
+        let line = ast
+            .iter()
+            .find_map(|a| match a {
+                ftd::ast::AST::Import(i) if module.ends_with(i.module.as_str()) => {
+                    Some(i.line_number)
+                }
+                _ => None,
+            })
+            .unwrap_or_default();
+        return unresolved_import(document, module, line);
+    }
+
+    let message = match e {
+        ftd::interpreter::Error::ParseError { message, .. }
+        | ftd::interpreter::Error::InvalidKind { message, .. }
+        | ftd::interpreter::Error::ValueNotFound { message, .. }
+        | ftd::interpreter::Error::P1Error(ftd::p1::Error::ParseError { message, .. }) => {
+            message.to_string()
+        }
+        e => e.to_string(),
+    };
+
+    let (code, message) = if let ftd::interpreter::Error::NotFound { name, .. } = e {
+        // `foo#bar` is `bar` of the document `foo`
+        let name = name.rsplit_once('#').map(|(_, n)| n).unwrap_or(name);
+        ("unresolved-name", format!("`{}` is not defined", name))
+    } else if matches!(e, ftd::interpreter::Error::InvalidKind { .. })
+        || message.contains("Expected kind")
+    {
+        ("type-error", message)
+    } else if matches!(
+        e,
+        ftd::interpreter::Error::P1Error(_)
+            | ftd::interpreter::Error::OldP1Error(_)
+            | ftd::interpreter::Error::ASTError(_)
+    ) {
+        ("syntax-error", message)
+    } else {
+        ("interpreter-error", message)
+    };
+
+    // the error can be in a document imported by this one
+    let (file, line) = match e.location() {
+        Some((doc_id, line)) => {
+            let doc_id = doc_id.trim_matches('/');
+            match documents
+                .iter()
+                .find(|d| d.id_with_package().trim_matches('/').eq(doc_id))
+            {
+                Some(d) => (d.id.to_string(), line),
+                None if doc_id.is_empty() => (document.id.to_string(), line),
+                None => (doc_id.to_string(), line),
+            }
+        }
+        None => (document.id.to_string(), 0),
+    };
+    Diagnostic::error(file.as_str(), line, code, message)
+}
+
+fn unresolved_import(document: &fastn_core::Document, module: &str, line: usize) -> Diagnostic {
+    Diagnostic::error(
+        document.id.as_str(),
+        line,
+        "unresolved-import",
+        format!(
+            "`{}` can not be imported, it is not in this package or a dependency",
+            module
+        ),
+    )
+}
+
+fn check_unused_imports(
+    document: &fastn_core::Document,
+    ast: &[ftd::ast::AST],
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let words = words_of(document.content.as_str(), true);
+    for import in ast.iter().filter_map(|a| match a {
+        ftd::ast::AST::Import(i) => Some(i),
+        _ => None,
+    }) {
+        // re-exported, or everything exposed, we can not tell if it is used
+        if import.exports.is_some() || matches!(import.exposing, Some(ftd::ast::Exposing::All)) {
+            continue;
+        }
+
+        let used = words.keys().any(|w| {
+            w.eq(&import.alias)
+                || w.starts_with(format!("{}.", import.alias).as_str())
+                || w.starts_with(format!("{}#", import.alias).as_str())
+        }) || match import.exposing {
+            Some(ftd::ast::Exposing::Things(ref things)) => {
+                things.iter().any(|t| words.contains_key(t.as_str()))
+            }
+            _ => false,
+        };
+        if !used {
+            diagnostics.push(Diagnostic::warning(
+                document.id.as_str(),
+                import.line_number,
+                "unused-import",
+                format!("`{}` is imported but never used", import.module),
+            ));
+        }
+    }
+}
+
+/// A variable is unused if no document of the package refers to it, as other documents can import
+/// it.
+fn check_unused_variables(
+    ast: &[ftd::ast::AST],
+    document: &fastn_core::Document,
+    references: &std::collections::HashMap<&str, std::collections::HashSet<String>>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for variable in ast.iter().filter_map(|a| match a {
+        ftd::ast::AST::VariableDefinition(v) => Some(v),
+        _ => None,
+    }) {
+        let name = variable.name.as_str();
+        let used = references.values().flatten().any(|r| {
+            r.eq(name)
+                || r.starts_with(format!("{}.", name).as_str())
+                || r.ends_with(format!(".{}", name).as_str())
+                || r.ends_with(format!("#{}", name).as_str())
+                || r.contains(format!(".{}.", name).as_str())
+        });
+        if !used {
+            diagnostics.push(Diagnostic::warning(
+                document.id.as_str(),
+                variable.line_number,
+                "unused-variable",
+                format!("`{}` is never used", name),
+            ));
+        }
+    }
+}
+
+/// The names `document` refers to: every `$name` and `$a.b.c`, which includes the variables it
+/// updates, the bare names used in the bodies of its functions, and the names its imports expose
+/// or export. Every
+/// word is taken for a reference if the document can not be parsed.
+pub(crate) fn references_of(document: &fastn_core::Document) -> std::collections::HashSet<String> {
+    let ast = match ftd::p1::parse(document.content.as_str(), document.id.as_str())
+        .map_err(ftd::ast::Error::from)
+        .and_then(|sections| {
+            ftd::ast::AST::from_sections(sections.as_slice(), document.id.as_str())
+        }) {
+        Ok(ast) => ast,
+        Err(_) => {
+            return words_of(document.content.as_str(), false)
+                .into_keys()
+                .collect()
+        }
+    };
+
+    let mut references = std::collections::HashSet::new();
+    for line in document.content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with(";;") || trimmed.starts_with("/--") {
+            continue;
+        }
+        let mut rest = trimmed;
+        while let Some((_, after)) = rest.split_once('$') {
+            let end = after
+                .find(|c: char| !(c.is_alphanumeric() || "-_.#".contains(c)))
+                .unwrap_or(after.len());
+            // `$processor$` and `$on-click$` are markers, not references
+            if !after[end..].starts_with('$') {
+                let name = after[..end].trim_matches(|c| c == '.' || c == '-');
+                if !name.is_empty() {
+                    references.insert(name.to_string());
+                }
+            }
+            rest = &after[end..];
+        }
+    }
+
+    for a in ast.iter() {
+        match a {
+            ftd::ast::AST::FunctionDefinition(f) => {
+                references.extend(words_of(f.definition.value.as_str(), false).into_keys());
+            }
+            ftd::ast::AST::Import(i) => {
+                if let Some(ftd::ast::Exposing::Things(ref things)) = i.exposing {
+                    references.extend(things.iter().cloned());
+                }
+                if let Some(ftd::ast::Export::Things(ref things)) = i.exports {
+                    references.extend(things.iter().cloned());
+                }
+            }
+            _ => {}
+        }
+    }
+    references
+}
+
+/// Identifiers, and `a.b.c` paths, mentioned in `content` with how many times they are.
+/// Comments are skipped, and so are imports if `skip_imports`.
+pub(crate) fn words_of(
+    content: &str,
+    skip_imports: bool,
+) -> std::collections::HashMap<String, usize> {
+    let mut words = std::collections::HashMap::new();
+    let mut in_import = false;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("-- ") || trimmed.is_empty() {
+            in_import = trimmed.starts_with("-- import:");
+        }
+        if (skip_imports && in_import) || trimmed.starts_with(";;") || trimmed.starts_with("/--") {
+            continue;
+        }
+        for word in trimmed.split(|c: char| !(c.is_alphanumeric() || "-_.#".contains(c))) {
+            let word = word.trim_matches(|c| c == '.' || c == '-');
+            if !word.is_empty() {
+                *words.entry(word.to_string()).or_insert(0) += 1;
+            }
+        }
+    }
+    words
+}
+
+/// Links to `/...` urls, from markdown and `link` headers, that no document or static file of
+/// the package is served at.
+fn check_links(
+    config: &fastn_core::Config,
+    document: &fastn_core::Document,
+    files: &[fastn_core::File],
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for (index, line) in document.content.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with(";;") {
+            continue;
+        }
+
+        let mut links = vec![];
+        if let Some((key, value)) = trimmed.split_once(':') {
+            let key = key.trim();
+            if key.eq("link") || key.starts_with("link if ") {
+                links.push(value.trim());
+            }
+        }
+        let mut rest = trimmed;
+        while let Some((_, after)) = rest.split_once("](") {
+            let (link, after) = after.split_once(')').unwrap_or((after, ""));
+            links.push(link.split_whitespace().next().unwrap_or_default());
+            rest = after;
+        }
+
+        for link in links {
+            if !link.starts_with('/') || link.starts_with("//") || link.starts_with("/-/") {
+                continue;
+            }
+            let path = link.split(['#', '?']).next().unwrap_or_default();
+            if !is_served(config, files, path) {
+                diagnostics.push(Diagnostic::error(
+                    document.id.as_str(),
+                    index + 1,
+                    "broken-link",
+                    format!("`{}` does not point to any document of the package", link),
+                ));
+            }
+        }
+    }
+}
+
+fn is_served(config: &fastn_core::Config, files: &[fastn_core::File], path: &str) -> bool {
+    let url = path.trim_matches('/');
+    let served = files.iter().any(|f| match f {
+        fastn_core::File::Ftd(d) | fastn_core::File::Markdown(d) => {
+            fastn_core::utils::id_to_path(d.id.as_str())
+                .replace(std::path::MAIN_SEPARATOR, "/")
+                .trim_matches('/')
+                .eq(url)
+        }
+        fastn_core::File::Static(s) | fastn_core::File::Image(s) => s.id.eq(url),
+        fastn_core::File::Code(d) => d.id.eq(url),
+    });
+    if served {
+        return true;
+    }
+
+    if let Some(ref redirects) = config.package.redirects {
+        if redirects.keys().any(|from| from.trim_matches('/').eq(url)) {
+            return true;
+        }
+    }
+
+    matches!(
+        fastn_core::sitemap::resolve(&config.package, path),
+        Ok((Some(_), _, _))
+    )
+}
+
+/// Sitemap entries pointing to documents that do not exist.
+fn check_sitemap(
+    root: &camino::Utf8PathBuf,
+    package: &fastn_core::Package,
+    fastn_ftd: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let sitemap = match package.sitemap_temp {
+        Some(ref sitemap) => sitemap,
+        None => return,
+    };
+    let lines: Vec<&str> = fastn_ftd.lines().collect();
+    let mut cursor = lines
+        .iter()
+        .position(|l| l.trim().starts_with("-- fastn.sitemap"))
+        .unwrap_or_default();
+
+    // (line in FASTN.ftd, url, `document` of the entry)
+    let mut entries: Vec<(usize, &str, Option<&str>)> = vec![];
+    for line in sitemap.body.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        if let Some(position) = lines[cursor..].iter().position(|l| l.trim().eq(trimmed)) {
+            cursor += position;
+        }
+
+        if trimmed.starts_with('#') || trimmed.starts_with('-') {
+            let entry = trimmed.trim_start_matches(['#', '-']).trim();
+            if let Some((_, url)) = entry.rsplit_once(": ") {
+                entries.push((cursor + 1, url.trim(), None));
+            }
+        } else if let Some(("document", document)) = trimmed.split_once(':') {
+            if let Some(entry) = entries.last_mut() {
+                entry.2 = Some(document.trim());
+            }
+        }
+    }
+
+    for (line, url, document) in entries {
+        if url.contains("://") || url.starts_with("-/") || url.contains('<') {
+            continue;
+        }
+        let found = match document {
+            Some(document) => {
+                root.join(document.trim_start_matches('/')).exists()
+                    || fastn_core::Config::get_file_name(root, document).is_ok()
+            }
+            None => fastn_core::Config::get_file_name(root, url).is_ok(),
+        };
+        if !found {
+            diagnostics.push(Diagnostic::error(
+                "FASTN.ftd",
+                line,
+                "sitemap-missing-document",
+                format!(
+                    "no document for `{}` in the sitemap",
+                    document.unwrap_or(url)
+                ),
+            ));
+        }
+    }
+}
+
+/// Problems in `FASTN.ftd` that do not stop it from being read.
+fn check_fastn_ftd(
+    config: &fastn_core::Config,
+    fastn_ftd: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let line_of = |prefix: &str, name: &str| {
+        fastn_ftd
+            .lines()
+            .position(|l| {
+                l.trim()
+                    .strip_prefix(prefix)
+                    .map(|rest| rest.trim().eq(name))
+                    .unwrap_or(false)
+            })
+            .map(|p| p + 1)
+            .unwrap_or_default()
+    };
+
+    let mut seen = std::collections::HashSet::new();
+    for dependency in config.package.dependencies.iter() {
+        if !seen.insert(dependency.package.name.as_str()) {
+            diagnostics.push(Diagnostic::error(
+                "FASTN.ftd",
+                line_of("-- fastn.dependency:", dependency.package.name.as_str()),
+                "duplicate-dependency",
+                format!("{} is a dependency more than once", dependency.package.name),
+            ));
+        }
+    }
+
+    for auto_import in config.package.auto_import.iter() {
+        // only paths that look like `<package name>/<module>` can be checked
+        let package_name = auto_import.path.split('/').next().unwrap_or_default();
+        if !package_name.contains('.') {
+            continue;
+        }
+        let known = auto_import.path.starts_with(config.package.name.as_str())
+            || config
+                .package
+                .dependencies
+                .iter()
+                .any(|d| auto_import.path.starts_with(d.package.name.as_str()));
+        if !known {
+            diagnostics.push(Diagnostic::error(
+                "FASTN.ftd",
+                line_of("-- fastn.auto-import:", auto_import.path.as_str()),
+                "unknown-auto-import",
+                format!(
+                    "`{}` is not in this package or a dependency, add the package it is in to \
+                    the dependencies",
+                    auto_import.path
+                ),
+            ));
+        }
+    }
+}
+
+fn location(e: &fastn_core::Error) -> Option<(&str, usize)> {
+    match e {
+        fastn_core::Error::FTDError(e) => e.location(),
+        fastn_core::Error::FTDP1Error(e) => e.location(),
+        fastn_core::Error::FTDAstError(e) => e.location(),
+        fastn_core::Error::FTDInterpreterError(e) => e.location(),
+        _ => None,
+    }
+}
+
+fn message(e: &fastn_core::Error) -> String {
+    match e {
+        fastn_core::Error::UsageError { message } | fastn_core::Error::PackageError { message } => {
+            message.to_string()
+        }
+        e => e.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    fn references(content: &str) -> Vec<String> {
+        let document = fastn_core::Document {
+            package_name: "www.example.com".to_string(),
+            id: "index.ftd".to_string(),
+            content: content.to_string(),
+            parent_path: "/".to_string(),
+        };
+        let mut references: Vec<String> = super::references_of(&document).into_iter().collect();
+        references.sort();
+        references
+    }
+
+    #[test]
+    fn references_of() {
+        let content = indoc::indoc! {"
+            -- import: www.example.com/lib
+            exposing: title
+
+            -- string name: $lib.user.name
+            ;; -- ftd.text: $commented
+
+            -- string list tokens:
+            $processor$: figma-typo-token
+
+            -- ftd.text: $name
+            $on-click$: $ftd.toggle($a = $open)
+
+            -- $count: 2
+
+            -- integer double(a):
+            integer a:
+
+            a * factor
+        "};
+        assert_eq!(
+            references(content),
+            vec![
+                "a",
+                "count",
+                "factor",
+                "ftd.toggle",
+                "lib.user.name",
+                "name",
+                "open",
+                "title"
+            ]
+        );
+    }
+
+    #[test]
+    fn references_of_invalid_document() {
+        // every word is a reference, so nothing is reported as unused
+        assert_eq!(
+            references("-- ftd.text: hello\n\n-- end: foo\n"),
+            vec!["end", "foo", "ftd.text", "hello"]
+        );
+    }
+}
@@ -1,4 +1,7 @@
 pub async fn create_cr(config: &fastn_core::Config, title: Option<&str>) -> fastn_core::Result<()> {
+    if config.package.history_backend.is_git() {
+        return fastn_core::git::create_cr(config, title).await;
+    }
     let cr_number = config.extract_cr_number().await?;
     let cr_meta_content = fastn_core::cr::generate_cr_meta_content(&fastn_core::cr::CRMeta {
         title: title
@@ -6,6 +9,7 @@ pub async fn create_cr(config: &fastn_core::Config, title: Option<&str>) -> fast
             .unwrap_or(format!("CR#{cr_number}")),
         cr_number: cr_number as usize,
         open: true,
+        author: None,
     });
     /*let cr_meta_content = fastn_core::cr::resolve_cr_meta(
         edit::edit(cr_meta_content)
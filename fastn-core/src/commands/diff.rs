@@ -3,6 +3,9 @@ pub async fn diff(
     files: Option<Vec<String>>,
     all: bool,
 ) -> fastn_core::Result<()> {
+    if config.package.history_backend.is_git() {
+        return fastn_core::git::diff(config, files).await;
+    }
     let snapshots = fastn_core::snapshot::get_latest_snapshots(&config.root).await?;
     let all = all || files.is_some();
     let documents = if let Some(ref files) = files {
@@ -27,6 +30,18 @@ pub async fn diff(
     Ok(())
 }
 
+/// The changes from `then` to `now`, as a unified diff.
+pub(crate) fn patch(then: &str, now: &str, with_color: bool) -> String {
+    let patch = diffy::create_patch(then, now);
+    let formatter = if with_color {
+        diffy::PatchFormatter::new().with_color()
+    } else {
+        diffy::PatchFormatter::new()
+    };
+    let diff = formatter.fmt_patch(&patch).to_string();
+    diff
+}
+
 async fn get_diffy(
     doc: &fastn_core::File,
     snapshots: &std::collections::BTreeMap<String, u128>,
@@ -39,12 +54,7 @@ async fn get_diffy(
         if content.eq(&existing_doc) {
             return Ok(None);
         }
-        let patch = diffy::create_patch(&existing_doc, &content);
-        let diff = diffy::PatchFormatter::new()
-            .with_color()
-            .fmt_patch(&patch)
-            .to_string();
-        return Ok(Some(diff));
+        return Ok(Some(patch(&existing_doc, &content, true)));
     }
     Ok(None)
 }
@@ -78,11 +88,7 @@ async fn get_track_diff(
             if now_doc.eq(&then_doc) {
                 continue;
             }
-            let patch = diffy::create_patch(&then_doc, &now_doc);
-            let diff = diffy::PatchFormatter::new()
-                .with_color()
-                .fmt_patch(&patch)
-                .to_string();
+            let diff = patch(&then_doc, &now_doc, true);
             println!(
                 "diff {} -> {}: {}",
                 doc.get_id(),
@@ -0,0 +1,91 @@
+pub const COMMAND: &str = "fmt";
+
+pub fn command() -> clap::Command {
+    clap::Command::new(COMMAND)
+        .about("Format the ftd documents of this package")
+        .arg(
+            clap::arg!(--check "Do not write anything, list the documents that are not formatted and fail if there are any"),
+        )
+        .arg(clap::arg!(files: [FILE] ... "The documents to format, all the ftd documents of the package if none is given"))
+}
+
+pub async fn handle_command(matches: &clap::ArgMatches) -> fastn_core::Result<()> {
+    use fastn_core::utils::ValueOf;
+
+    fmt(matches.values_of_("files"), matches.get_flag("check")).await
+}
+
+/// Rewrites the documents in the layout of `ftd::p1::format()`. With `check`, only lists the
+/// documents that would change, and errors if there are any, so it can be used in CI.
+#[tracing::instrument]
+pub async fn fmt(files: Vec<String>, check: bool) -> fastn_core::Result<()> {
+    let files = if files.is_empty() {
+        package_documents().await?
+    } else {
+        files.into_iter().map(camino::Utf8PathBuf::from).collect()
+    };
+
+    let mut changed = 0;
+    let mut failed = 0;
+    for path in files.iter() {
+        let content = tokio::fs::read_to_string(path).await?;
+        let formatted = match ftd::p1::format(content.as_str(), path.as_str()) {
+            Ok(formatted) => formatted,
+            Err(e) => {
+                eprintln!("{}", e);
+                failed += 1;
+                continue;
+            }
+        };
+        if formatted.eq(&content) {
+            continue;
+        }
+
+        changed += 1;
+        if check {
+            println!("{}", path);
+        } else {
+            fastn_core::utils::update(path, formatted.as_bytes()).await?;
+            println!("Formatted {}", path);
+        }
+    }
+
+    if failed > 0 {
+        return Err(fastn_core::Error::GenericError(format!(
+            "fastn fmt could not format {} documents",
+            failed
+        )));
+    }
+    if check && changed > 0 {
+        return Err(fastn_core::Error::GenericError(format!(
+            "{} of {} documents are not formatted, run `fastn fmt`",
+            changed,
+            files.len()
+        )));
+    }
+    Ok(())
+}
+
+/// The `.ftd` files of the package the current directory is in, `fastn build` ignores the same
+/// ones. Only reads `FASTN.ftd`, so nothing is downloaded.
+async fn package_documents() -> fastn_core::Result<Vec<camino::Utf8PathBuf>> {
+    let current_dir: camino::Utf8PathBuf = std::env::current_dir()?.canonicalize()?.try_into()?;
+    let root = fastn_core::Config::get_root_path(&current_dir).await?;
+    let doc = fastn_core::config::utils::fastn_doc(&root.join("FASTN.ftd")).await?;
+    let package = fastn_core::Package::from_fastn_doc(&root, &doc)?;
+
+    let mut walk = ignore::WalkBuilder::new(&root);
+    walk.overrides(fastn_core::file::package_ignores(&package, &root, true)?);
+    let mut documents: Vec<camino::Utf8PathBuf> = walk
+        .build()
+        .flatten()
+        .filter_map(|e| camino::Utf8PathBuf::from_path_buf(e.into_path()).ok())
+        .filter(|p| p.is_file() && p.extension() == Some("ftd"))
+        .map(|p| match p.strip_prefix(&current_dir) {
+            Ok(relative) => relative.to_path_buf(),
+            Err(_) => p,
+        })
+        .collect();
+    documents.sort();
+    Ok(documents)
+}
@@ -0,0 +1,70 @@
+pub const COMMAND: &str = "log";
+
+pub fn command() -> clap::Command {
+    clap::Command::new(COMMAND)
+        .about("Show the edit history of a file, or of all files in this fastn package")
+        .arg(clap::arg!(file: <FILE> "The file to see the history of (leave empty to see the history of all files)").required(false))
+        .hide(true) // hidden since the feature is not being released yet.
+}
+
+pub async fn handle_command(matches: &clap::ArgMatches) -> fastn_core::Result<()> {
+    use fastn_core::utils::ValueOf;
+
+    log(
+        &fastn_core::Config::read(None, true, None).await?,
+        matches.value_of_("file"),
+    )
+    .await
+}
+
+async fn log(config: &fastn_core::Config, file: Option<&str>) -> fastn_core::Result<()> {
+    let history = match file {
+        Some(file) => vec![config.get_file_history(file).await?],
+        None if config.history_file().exists() => config.get_history().await?,
+        None => vec![],
+    };
+
+    let mut edits = history
+        .iter()
+        .flat_map(|v| v.file_edit.iter().map(|e| (v.filename.as_str(), e)))
+        .collect::<Vec<_>>();
+    // newest first, like `git log`
+    edits.sort_by(|(a_file, a), (b_file, b)| {
+        (b.timestamp, a_file, b.version).cmp(&(a.timestamp, b_file, a.version))
+    });
+
+    for (file, edit) in edits {
+        print_edit(file, edit);
+    }
+    Ok(())
+}
+
+fn print_edit(file: &str, edit: &fastn_core::history::FileEdit) {
+    use colored::Colorize;
+
+    println!(
+        "{}",
+        format!(
+            "version {} of {} ({:?})",
+            edit.version, file, edit.operation
+        )
+        .yellow()
+    );
+    if let Some(ref author) = edit.author {
+        println!("Author: {}", author);
+    }
+    println!(
+        "Date:   {}",
+        fastn_core::utils::nanos_to_rfc3339(&edit.timestamp)
+    );
+    if let Some(cr) = edit.src_cr {
+        println!("CR:     {}", cr);
+    }
+    if let Some(message) = edit.message.as_ref().filter(|v| !v.trim().is_empty()) {
+        println!();
+        for line in message.trim().lines() {
+            println!("    {}", line);
+        }
+    }
+    println!();
+}
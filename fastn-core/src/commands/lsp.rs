@@ -0,0 +1,11 @@
+pub const COMMAND: &str = "lsp";
+
+pub fn command() -> clap::Command {
+    clap::Command::new(COMMAND).about(
+        "Run a Language Server Protocol server for the ftd documents of this package, over stdio",
+    )
+}
+
+pub async fn handle_command(_matches: &clap::ArgMatches) -> fastn_core::Result<()> {
+    fastn_core::lsp::serve().await
+}
@@ -6,6 +6,10 @@ pub async fn merge(
 ) -> fastn_core::Result<()> {
     let src = src.unwrap_or("main");
 
+    if config.package.history_backend.is_git() {
+        return fastn_core::git::merge(config, src, dest, file).await;
+    }
+
     if src.eq("main") {
         let dest = dest.parse::<usize>()?;
         merge_main_into_cr(config, dest, file).await?;
@@ -275,10 +279,12 @@ async fn merge_main_into_cr(
 
         let ours_content = String::from_utf8(ours_content_bytes.clone())?;
 
-        match diffy::MergeOptions::new()
-            .set_conflict_style(diffy::ConflictStyle::Merge)
-            .merge(&ancestor_content, &ours_content, &theirs_content)
-        {
+        match fastn_core::merge::merge(
+            filename.as_str(),
+            &ancestor_content,
+            &ours_content,
+            &theirs_content,
+        ) {
             Ok(data) => {
                 new_file_status.insert(
                     cr_file_path.to_string(),
@@ -339,6 +345,7 @@ async fn merge_cr_into_main(
     use sha2::Digest;
 
     //TODO: check if cr is closed
+    fastn_core::review::check_approvals(config, src).await?;
     let remote_manifest: std::collections::BTreeMap<String, fastn_core::history::FileEdit> = config
         .get_remote_manifest(true)
         .await?
@@ -614,10 +621,12 @@ async fn merge_cr_into_main(
 
         let ours_content = String::from_utf8(ours_content_bytes.clone())?;
 
-        match diffy::MergeOptions::new()
-            .set_conflict_style(diffy::ConflictStyle::Merge)
-            .merge(&ancestor_content, &ours_content, &theirs_content)
-        {
+        match fastn_core::merge::merge(
+            filename.as_str(),
+            &ancestor_content,
+            &ours_content,
+            &theirs_content,
+        ) {
             Ok(data) => {
                 new_file_status.insert(
                     filename.to_string(),
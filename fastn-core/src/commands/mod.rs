@@ -1,25 +1,36 @@
 pub mod abort_merge;
 pub mod add;
+pub mod assign_reviewers;
 pub mod build;
+pub mod check;
 pub mod clone;
 pub mod close_cr;
 pub mod create_cr;
 pub mod create_package;
 pub mod diff;
 pub mod edit;
+pub mod fmt;
+pub mod log;
+pub mod lsp;
 pub mod mark_resolved;
 pub mod mark_upto_date;
 pub mod merge;
 pub mod query;
 pub mod resolve_conflict;
+pub mod restore;
 pub mod revert;
+pub mod review_cr;
 pub mod rm;
 pub mod serve;
+pub mod show;
 pub mod start_tracking;
 pub mod status;
 pub mod stop_tracking;
 pub mod sync;
 pub mod sync2;
 pub mod sync_status;
+pub mod tokens;
+pub mod translate;
 pub mod translation_status;
 pub mod update;
+pub mod vendor;
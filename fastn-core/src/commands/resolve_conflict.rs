@@ -23,7 +23,14 @@ pub async fn resolve_conflict(
             });
         };
     let conflicted_data = get_conflict_data(config, file_status).await?;
-    if use_ours {
+    if let (Some(content), true, 0) = (
+        conflicted_data.marker.as_ref(),
+        conflicted_data.merged,
+        number_of_times_flag_used,
+    ) {
+        fastn_core::utils::update(&config.root.join(path), content.as_bytes()).await?;
+        println!("{} merged without conflicts", path);
+    } else if use_ours {
         let content = conflicted_data
             .ours
             .get_content()
@@ -164,7 +171,9 @@ impl Content {
 struct ConflictData {
     ours: Content,
     theirs: Content,
+    /// The merged content, with conflict markers unless `merged` is set.
     marker: Option<String>,
+    merged: bool,
 }
 
 async fn get_conflict_data(
@@ -184,26 +193,29 @@ async fn get_conflict_data(
             };
             let history_path = config.history_path(path, remote_version);
             let history_content = tokio::fs::read(history_path).await?;
-            /* if let Ok(theirs_string) = String::from_utf8(history_content.to_vec()) {
-                let ours_string = String::from_utf8(content.to_vec())?;
-                let patch = diffy::create_patch(ours_string.as_str(), theirs_string.as_str());
-                let patch_content = if with_formatter {
-                    let patch_formatter = diffy::PatchFormatter::new().with_color();
-                    patch_formatter.fmt_patch(&patch).to_string()
-                } else {
-                    path.to_string()
-                };
+            if let (Ok(ours_string), Ok(theirs_string)) = (
+                std::str::from_utf8(content),
+                std::str::from_utf8(&history_content),
+            ) {
+                // Both added the file, so the common ancestor is empty
+                let (marker, merged) =
+                    match fastn_core::merge::merge(path, "", ours_string, theirs_string) {
+                        Ok(data) => (data, true),
+                        Err(data) => (data, false),
+                    };
                 return Ok(ConflictData {
                     ours: Content::Content(content.to_vec()),
                     theirs: Content::Content(history_content),
-                    marker: Some(patch_content),
+                    marker: Some(marker),
+                    merged,
                 });
-            }*/
+            }
 
             Ok(ConflictData {
                 ours: Content::Content(content.to_vec()),
                 theirs: Content::Content(history_content),
                 marker: None,
+                merged: false,
             })
         }
         fastn_core::sync_utils::FileStatus::Update {
@@ -225,6 +237,7 @@ async fn get_conflict_data(
                     ours: Content::Content(content.to_vec()),
                     theirs: Content::Deleted,
                     marker: None,
+                    merged: false,
                 });
             }
 
@@ -241,28 +254,28 @@ async fn get_conflict_data(
                 let ancestor_path = config.history_path(path, *version);
                 let ancestor_content = tokio::fs::read(ancestor_path).await?;
                 let ancestor_string = String::from_utf8(ancestor_content)?;
-                match diffy::MergeOptions::new()
-                    .set_conflict_style(diffy::ConflictStyle::Merge)
-                    .merge(&ancestor_string, &ours_string, &theirs_string)
-                {
-                    Ok(data) => {
-                        // Not possible to reach here
-                        tokio::fs::write(path, &data).await?;
-                        return fastn_core::usage_error(format!("`{}` already resolved", path));
-                    }
-                    Err(data) => {
-                        return Ok(ConflictData {
-                            ours: Content::Content(content.to_vec()),
-                            theirs: Content::Content(theirs_content),
-                            marker: Some(data),
-                        });
-                    }
-                }
+                // Files that conflicted before section aware merging may merge cleanly now
+                let (marker, merged) = match fastn_core::merge::merge(
+                    path,
+                    &ancestor_string,
+                    &ours_string,
+                    &theirs_string,
+                ) {
+                    Ok(data) => (data, true),
+                    Err(data) => (data, false),
+                };
+                return Ok(ConflictData {
+                    ours: Content::Content(content.to_vec()),
+                    theirs: Content::Content(theirs_content),
+                    marker: Some(marker),
+                    merged,
+                });
             }
             Ok(ConflictData {
                 ours: Content::Content(content.to_vec()),
                 theirs: Content::Content(theirs_content),
                 marker: None,
+                merged: false,
             })
         }
         fastn_core::sync_utils::FileStatus::Delete { path, status, .. } => {
@@ -281,6 +294,7 @@ async fn get_conflict_data(
                 theirs: Content::Content(theirs_content),
                 ours: Content::Deleted,
                 marker: None,
+                merged: false,
             })
         }
         fastn_core::sync_utils::FileStatus::Uptodate { path, version, .. } => {
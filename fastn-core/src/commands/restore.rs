@@ -0,0 +1,91 @@
+pub const COMMAND: &str = "restore";
+
+pub fn command() -> clap::Command {
+    clap::Command::new(COMMAND)
+        .about("Restore a file to an earlier version, recording it as a new edit")
+        .arg(clap::arg!(file: <FILE> "The file to restore"))
+        .arg(
+            clap::arg!(--version <VERSION> "The version to restore")
+                .value_parser(clap::value_parser!(i32))
+                .required(true),
+        )
+        .hide(true) // hidden since the feature is not being released yet.
+}
+
+pub async fn handle_command(matches: &clap::ArgMatches) -> fastn_core::Result<()> {
+    use fastn_core::utils::ValueOf;
+
+    restore(
+        &fastn_core::Config::read(None, true, None).await?,
+        matches.value_of_("file").unwrap(),
+        *matches.get_one::<i32>("version").unwrap(),
+    )
+    .await
+}
+
+/// Writes `file` as it was at `version`. History is never rewritten: in a clone the restored
+/// content is recorded by the next `fastn sync`, otherwise it is recorded right away as a new
+/// version.
+async fn restore(config: &fastn_core::Config, file: &str, version: i32) -> fastn_core::Result<()> {
+    let content = config.get_file_at_version(file, version).await?;
+    fastn_core::utils::update(&config.root.join(file), content.as_slice()).await?;
+
+    if config.workspace_file().exists() {
+        println!(
+            "Restored {} to version {}, run `fastn sync` to record it",
+            file, version
+        );
+        return Ok(());
+    }
+
+    let mut history = config.get_history().await?;
+    let operation = match history
+        .iter()
+        .find(|v| v.filename.eq(file))
+        .and_then(|v| v.get_latest_file_edit(true))
+    {
+        Some(latest) if !latest.is_deleted() => fastn_core::history::FileOperation::Updated,
+        _ => fastn_core::history::FileOperation::Added,
+    };
+    let file_list = std::iter::once((
+        file.to_string(),
+        fastn_core::history::FileEditTemp {
+            message: Some(format!("Restored version {}", version)),
+            author: fastn_core::git::user(&config.root),
+            src_cr: None,
+            operation,
+        },
+    ))
+    .collect();
+    fastn_core::history::insert_into_history(&config.root, &file_list, &mut history).await?;
+
+    let new_version = history
+        .iter()
+        .find(|v| v.filename.eq(file))
+        .and_then(|v| v.file_edit.first())
+        .map(|v| v.version)
+        .unwrap_or_default();
+    println!(
+        "Restored {} to version {}, as version {}",
+        file, version, new_version
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn command() {
+        let matches = super::command()
+            .try_get_matches_from(["restore", "index.ftd", "--version", "2"])
+            .unwrap();
+        assert_eq!(matches.get_one::<i32>("version"), Some(&2));
+        assert_eq!(
+            super::command()
+                .try_get_matches_from(["restore", "index.ftd"])
+                .unwrap_err()
+                .kind(),
+            clap::error::ErrorKind::MissingRequiredArgument
+        );
+    }
+}
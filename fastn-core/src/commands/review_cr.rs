@@ -0,0 +1,91 @@
+pub const COMMAND: &str = "review-cr";
+
+/// Reviews and comments are only added by `/-/review-cr/` and `/-/comment-cr/` of `fastn serve`,
+/// where the reviewer or the commenter is the signed in user, so nobody can approve or comment on a
+/// CR on behalf of someone else.
+pub fn command() -> clap::Command {
+    clap::Command::new(COMMAND)
+        .about("Show the reviews and comments of a CR")
+        .arg(clap::arg!(cr: <CR> "The CR to show the reviews of"))
+        .hide(true) // hidden since the feature is not being released yet.
+}
+
+pub async fn handle_command(matches: &clap::ArgMatches) -> fastn_core::Result<()> {
+    use fastn_core::utils::ValueOf;
+
+    review_cr(
+        &fastn_core::Config::read(None, true, None).await?,
+        matches.value_of_("cr").unwrap().parse::<usize>()?,
+    )
+    .await
+}
+
+async fn review_cr(config: &fastn_core::Config, cr: usize) -> fastn_core::Result<()> {
+    let cr_meta = fastn_core::cr::get_cr_meta(config, cr).await?;
+    let reviews = fastn_core::review::get_cr_reviews(config, cr).await?;
+    let version = fastn_core::review::cr_version(config, cr).await?;
+    print_reviews(
+        &cr_meta,
+        &reviews,
+        config.package.required_approvals,
+        version.as_str(),
+    );
+    Ok(())
+}
+
+fn print_reviews(
+    cr_meta: &fastn_core::cr::CRMeta,
+    reviews: &fastn_core::review::CRReviews,
+    required_approvals: usize,
+    version: &str,
+) {
+    use colored::Colorize;
+
+    println!("CR#{}: {}", cr_meta.cr_number, cr_meta.title);
+    let latest_reviews = reviews.latest_reviews();
+    if reviews.reviewers.is_empty() {
+        println!("No reviewers assigned");
+    } else {
+        println!("Reviewers:");
+    }
+    for reviewer in reviews.reviewers.iter() {
+        let review = latest_reviews.get(reviewer.as_str());
+        let status = match review.map(|v| &v.status) {
+            Some(fastn_core::review::ReviewStatus::Approved)
+                if review.and_then(|v| v.version.as_deref()).ne(&Some(version)) =>
+            {
+                "approved an older version".yellow()
+            }
+            Some(fastn_core::review::ReviewStatus::Approved) => "approved".green(),
+            Some(fastn_core::review::ReviewStatus::ChangesRequested) => "changes requested".red(),
+            None => "pending".yellow(),
+        };
+        println!("    {}: {}", reviewer, status);
+    }
+    println!(
+        "{} of {} required approvals",
+        reviews.approvals(version),
+        required_approvals
+    );
+
+    if !reviews.comments.is_empty() {
+        println!();
+        print_comments(reviews, None, 0);
+    }
+}
+
+fn print_comments(reviews: &fastn_core::review::CRReviews, reply_to: Option<usize>, depth: usize) {
+    let indent = "    ".repeat(depth);
+    for comment in reviews.comments.iter().filter(|v| v.reply_to.eq(&reply_to)) {
+        let location = match (&comment.file, comment.line) {
+            (Some(file), Some(line)) => format!(" on {}:{}", file, line),
+            (Some(file), None) => format!(" on {}", file),
+            _ => "".to_string(),
+        };
+        println!("{}#{} {}{}", indent, comment.id, comment.author, location);
+        for line in comment.message.lines() {
+            println!("{}    {}", indent, line);
+        }
+        print_comments(reviews, Some(comment.id), depth + 1);
+    }
+}
@@ -39,6 +39,7 @@ async fn serve_file(
             return fastn_core::not_found!("fastn-Error: path: {}, {:?}", path, e);
         }
     };
+    fastn_core::access_log::set_document(f.get_id(), f.get_package_name());
 
     // Auth Stuff
     if !f.is_static() {
@@ -145,6 +146,7 @@ async fn serve_cr_file(
             return fastn_core::server_error!("fastn-Error: path: {}, {:?}", path, e);
         }
     };
+    fastn_core::access_log::set_document(f.get_id(), f.get_package_name());
 
     // Auth Stuff
     if !f.is_static() {
@@ -260,6 +262,15 @@ pub async fn serve(
         .add_external_css(external_css)
         .add_inline_css(inline_css);
 
+    // `/<locale>/<path>` is `<path>` with the `i18n` messages of the locale
+    let path = match fastn_core::i18n::package::strip_locale(&config, path.as_str()) {
+        Some((locale, path)) => {
+            config.locale = Some(locale);
+            path.parse().unwrap()
+        }
+        None => path,
+    };
+
     Ok(if path.eq(&camino::Utf8PathBuf::new().join("FASTN.ftd")) {
         serve_fastn_file(&config).await
     } else if path.eq(&camino::Utf8PathBuf::new().join("")) {
@@ -502,6 +513,27 @@ pub(crate) async fn edit_source(
     Ok(fastn_core::apis::edit_source(&req).await)
 }
 
+pub async fn preview(
+    req: fastn_core::http::Request,
+) -> fastn_core::Result<fastn_core::http::Response> {
+    let _lock = LOCK.read().await;
+    fastn_core::apis::preview(&req, req.json()?).await
+}
+
+pub(crate) async fn history(
+    req: fastn_core::http::Request,
+) -> fastn_core::Result<fastn_core::http::Response> {
+    let _lock = LOCK.read().await;
+    Ok(fastn_core::apis::history(&req).await)
+}
+
+pub(crate) async fn translation_status(
+    req: fastn_core::http::Request,
+) -> fastn_core::Result<fastn_core::http::Response> {
+    let _lock = LOCK.read().await;
+    Ok(fastn_core::apis::translation_status(&req).await)
+}
+
 pub async fn edit(
     req: fastn_core::http::Request,
 ) -> fastn_core::Result<fastn_core::http::Response> {
@@ -530,6 +562,20 @@ pub async fn create_cr(
     fastn_core::apis::cr::create_cr(&req, req.json()?).await
 }
 
+pub async fn review_cr(
+    req: fastn_core::http::Request,
+) -> fastn_core::Result<fastn_core::http::Response> {
+    let _lock = LOCK.write().await;
+    fastn_core::apis::cr::review_cr(&req, req.json()?).await
+}
+
+pub async fn comment_cr(
+    req: fastn_core::http::Request,
+) -> fastn_core::Result<fastn_core::http::Response> {
+    let _lock = LOCK.write().await;
+    fastn_core::apis::cr::comment_cr(&req, req.json()?).await
+}
+
 pub async fn create_cr_page(
     req: fastn_core::http::Request,
 ) -> fastn_core::Result<fastn_core::http::Response> {
@@ -543,6 +589,7 @@ struct AppData {
     inline_js: Vec<String>,
     external_css: Vec<String>,
     inline_css: Vec<String>,
+    access_log: Option<std::sync::Arc<fastn_core::access_log::AccessLog>>,
 }
 
 fn handle_default_route(req: &actix_web::HttpRequest) -> Option<fastn_core::http::Response> {
@@ -640,6 +687,52 @@ async fn route(
 ) -> fastn_core::Result<fastn_core::http::Response> {
     tracing::info!(method = req.method().as_str(), uri = req.path());
 
+    let start = std::time::Instant::now();
+    let mut entry = fastn_core::access_log::Entry::new(&req);
+    if app_data.access_log.is_some() {
+        let cookies = req
+            .cookies()
+            .map(|c| {
+                c.iter()
+                    .map(|c| (c.name().to_string(), c.value().to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        entry.identity = fastn_core::auth::get_logged_in_identity(&cookies).await;
+    }
+
+    let (response, mut entry) =
+        fastn_core::access_log::scope(entry, handle_route(req, body, app_data.clone())).await;
+
+    let latency = start.elapsed();
+    entry.status = match response {
+        Ok(ref r) => r.status().as_u16(),
+        Err(_) => 500,
+    };
+    entry.latency_ms = latency.as_secs_f64() * 1000.0;
+    fastn_core::metrics::record_request(entry.method.as_str(), entry.status, latency);
+    if let Some(ref access_log) = app_data.access_log {
+        access_log.write(&entry);
+    }
+
+    response.map(|mut r| {
+        if let Ok(id) = actix_web::http::header::HeaderValue::from_str(entry.request_id.as_str()) {
+            r.headers_mut().insert(
+                actix_web::http::header::HeaderName::from_static(
+                    fastn_core::access_log::REQUEST_ID_HEADER,
+                ),
+                id,
+            );
+        }
+        r
+    })
+}
+
+async fn handle_route(
+    req: actix_web::HttpRequest,
+    body: actix_web::web::Bytes,
+    app_data: actix_web::web::Data<AppData>,
+) -> fastn_core::Result<fastn_core::http::Response> {
     if let Some(default_response) = handle_default_route(&req) {
         return Ok(default_response);
     }
@@ -662,13 +755,19 @@ async fn route(
         ("get", "/-/clone/") if cfg!(feature = "remote") => clone(req).await,
         ("get", t) if t.starts_with("/-/view-src/") => view_source(req).await,
         ("get", t) if t.starts_with("/-/edit-src/") => edit_source(req).await,
+        ("get", t) if t.starts_with("/-/history/") => history(req).await,
+        ("get", "/-/translation-status/") => translation_status(req).await,
         ("post", "/-/edit/") => edit(req).await,
+        ("post", "/-/preview/") => preview(req).await,
         ("post", "/-/revert/") => revert(req).await,
         ("get", "/-/editor-sync/") => editor_sync(req).await,
         ("post", "/-/create-cr/") => create_cr(req).await,
+        ("post", "/-/review-cr/") => review_cr(req).await,
+        ("post", "/-/comment-cr/") => comment_cr(req).await,
         ("get", "/-/create-cr-page/") => create_cr_page(req).await,
         ("get", "/-/clear-cache/") => clear_cache(req).await,
         ("get", "/-/poll/") => fastn_core::watcher::poll().await,
+        ("get", "/-/metrics") | ("get", "/-/metrics/") => fastn_core::metrics::serve().await,
         ("get", "/favicon.ico") => favicon().await,
         ("get", "/test/") => test().await,
         (_, _) => {
@@ -695,10 +794,18 @@ pub async fn listen(
     inline_js: Vec<String>,
     external_css: Vec<String>,
     inline_css: Vec<String>,
+    access_log: Option<String>,
 ) -> fastn_core::Result<()> {
     use colored::Colorize;
     env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
 
+    let access_log = match access_log {
+        Some(path) => Some(std::sync::Arc::new(fastn_core::access_log::AccessLog::new(
+            path.as_str(),
+        )?)),
+        None => None,
+    };
+
     if package_download_base_url.is_some() {
         download_init_package(package_download_base_url).await?;
     }
@@ -745,6 +852,7 @@ You can try without providing port, it will automatically pick unused port."#,
                 inline_js: inline_js.clone(),
                 external_css: external_css.clone(),
                 inline_css: inline_css.clone(),
+                access_log: access_log.clone(),
             }))
             .wrap(
                 actix_web::middleware::Logger::new(
@@ -0,0 +1,50 @@
+pub const COMMAND: &str = "show";
+
+pub fn command() -> clap::Command {
+    clap::Command::new(COMMAND)
+        .about("Print a file as it was at a version, `fastn show index.ftd@3`")
+        .arg(clap::arg!(file: <FILE> "The file and version, the latest version if no version is given"))
+        .hide(true) // hidden since the feature is not being released yet.
+}
+
+pub async fn handle_command(matches: &clap::ArgMatches) -> fastn_core::Result<()> {
+    use fastn_core::utils::ValueOf;
+
+    show(
+        &fastn_core::Config::read(None, true, None).await?,
+        matches.value_of_("file").unwrap(),
+    )
+    .await
+}
+
+async fn show(config: &fastn_core::Config, source: &str) -> fastn_core::Result<()> {
+    use std::io::Write;
+
+    let (file, version) = parse_source(source);
+    let version = match version {
+        Some(version) => version,
+        None => config
+            .get_file_history(file)
+            .await?
+            .get_latest_file_edit(true)
+            .map(|v| v.version)
+            .ok_or_else(|| fastn_core::Error::UsageError {
+                message: format!("No history found for `{}`", file),
+            })?,
+    };
+
+    let content = config.get_file_at_version(file, version).await?;
+    std::io::stdout().write_all(content.as_slice())?;
+    Ok(())
+}
+
+/// `index.ftd@3` -> (`index.ftd`, Some(3)), `index.ftd` -> (`index.ftd`, None)
+fn parse_source(source: &str) -> (&str, Option<i32>) {
+    match source
+        .rsplit_once('@')
+        .and_then(|(file, version)| Some((file, version.parse().ok()?)))
+    {
+        Some((file, version)) => (file, Some(version)),
+        None => (source, None),
+    }
+}
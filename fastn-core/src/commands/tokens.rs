@@ -0,0 +1,90 @@
+pub const COMMAND: &str = "tokens";
+
+pub fn command() -> clap::Command {
+    clap::Command::new(COMMAND)
+        .about("Work with design tokens")
+        .subcommand_required(true)
+        .subcommand(
+            clap::Command::new("import")
+                .about("Import design tokens (W3C Design Tokens, Tokens Studio or Figma JSON) as an ftd color scheme and type data")
+                .arg(clap::arg!(file: <FILE> "The JSON file with the tokens"))
+                .arg(clap::arg!(--name <NAME> "The name of the color scheme and the type data, the name of the file by default"))
+                .arg(clap::arg!(-o --output <OUTPUT> "The ftd file to write, the ftd is printed if not given")),
+        )
+}
+
+pub async fn handle_command(matches: &clap::ArgMatches) -> fastn_core::Result<()> {
+    use fastn_core::utils::ValueOf;
+
+    match matches.subcommand() {
+        Some(("import", matches)) => {
+            import(
+                matches.value_of_("file").unwrap(),
+                matches.value_of_("name"),
+                matches.value_of_("output"),
+            )
+            .await
+        }
+        _ => unreachable!("clap requires a subcommand"),
+    }
+}
+
+/// Writes the color scheme as `<name>-colors` and the type data as `<name>-types`, with defaults
+/// from `ftd.default-colors` and `ftd.default-types` for what the tokens do not have.
+async fn import(file: &str, name: Option<&str>, output: Option<&str>) -> fastn_core::Result<()> {
+    let content = tokio::fs::read_to_string(file).await?;
+    let json: serde_json::Value =
+        serde_json::from_str(content.as_str()).map_err(|e| fastn_core::Error::UsageError {
+            message: format!("{} is not json: {}", file, e),
+        })?;
+    let name = match name {
+        Some(name) => name.to_string(),
+        None => camino::Utf8Path::new(file)
+            .file_stem()
+            .unwrap_or("tokens")
+            .to_lowercase()
+            .replace([' ', '_', '.'], "-"),
+    };
+
+    let imported = fastn_core::tokens::import(&json);
+    if imported.color_scheme.is_none() && imported.type_data.is_none() {
+        return fastn_core::usage_error(format!("No color or typography tokens found in {}", file));
+    }
+    let mut sections = vec![format!(
+        ";; Imported from {} using `fastn tokens import`",
+        file
+    )];
+    if let Some(ref color_scheme) = imported.color_scheme {
+        sections.push(fastn_core::tokens::to_ftd(
+            format!("{}-colors", name).as_str(),
+            color_scheme,
+        ));
+    }
+    if let Some(ref type_data) = imported.type_data {
+        sections.push(fastn_core::tokens::to_ftd(
+            format!("{}-types", name).as_str(),
+            type_data,
+        ));
+    }
+    let ftd = format!("{}\n", sections.join("\n\n\n"));
+
+    match output {
+        Some(output) => {
+            fastn_core::utils::update(&camino::Utf8PathBuf::from(output), ftd.as_bytes()).await?;
+            if imported.color_scheme.is_some() {
+                println!("Wrote the color scheme `{}-colors` to {}", name, output);
+            }
+            if imported.type_data.is_some() {
+                println!("Wrote the type data `{}-types` to {}", name, output);
+            }
+        }
+        None => print!("{}", ftd),
+    }
+    if !imported.skipped.is_empty() {
+        eprintln!(
+            "Skipped the tokens that are not a color or a type of ftd: {}",
+            imported.skipped.join(", ")
+        );
+    }
+    Ok(())
+}
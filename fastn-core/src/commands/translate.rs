@@ -0,0 +1,337 @@
+pub const COMMAND: &str = "translate";
+
+pub fn command() -> clap::Command {
+    clap::Command::new(COMMAND)
+        .about("Translate the documents of the original package with XLIFF or PO files")
+        .subcommand_required(true)
+        .subcommand(
+            clap::Command::new("extract")
+                .about("Write the strings of the original documents, with their translations so far, as XLIFF or PO")
+                .arg(clap::arg!(files: [FILE] ... "The documents to extract, all the documents of the original package if none is given"))
+                .arg(clap::arg!(--format <FORMAT> "`xliff` or `po`, by the extension of the output if not given, else `xliff`"))
+                .arg(clap::arg!(-o --output <OUTPUT> "The file to write, it is printed if not given"))
+                .arg(clap::arg!(--backend <URL> "A translation service to suggest translations for the untranslated strings, FASTN_TRANSLATION_BACKEND if not given")),
+        )
+        .subcommand(
+            clap::Command::new("apply")
+                .about("Write the translations of an XLIFF or PO file to the documents of this package")
+                .arg(clap::arg!(file: <FILE> "The XLIFF or PO file"))
+                .arg(clap::arg!(--fuzzy "Also apply the translations that still need a review")),
+        )
+        .hide(true) // hidden since the feature is not being released yet.
+}
+
+pub async fn handle_command(matches: &clap::ArgMatches) -> fastn_core::Result<()> {
+    use fastn_core::utils::ValueOf;
+
+    let config = fastn_core::Config::read(None, true, None).await?;
+    match matches.subcommand() {
+        Some(("extract", matches)) => {
+            let output = matches.value_of_("output");
+            let format = match matches.value_of_("format") {
+                Some(format) => fastn_core::translate::Format::from_str(format)?,
+                None => output
+                    .and_then(fastn_core::translate::Format::from_path)
+                    .unwrap_or(fastn_core::translate::Format::Xliff),
+            };
+            let backend = matches
+                .value_of_("backend")
+                .map(ToString::to_string)
+                .or_else(|| std::env::var("FASTN_TRANSLATION_BACKEND").ok());
+            extract(
+                &config,
+                matches.values_of_("files"),
+                format,
+                output,
+                backend.as_deref(),
+            )
+            .await
+        }
+        Some(("apply", matches)) => {
+            apply(
+                &config,
+                matches.value_of_("file").unwrap(),
+                matches.get_flag("fuzzy"),
+            )
+            .await
+        }
+        _ => unreachable!("clap requires a subcommand"),
+    }
+}
+
+/// The languages translated from and to
+fn languages(config: &fastn_core::Config) -> fastn_core::Result<(String, String)> {
+    let original = match config.package.translation_of.as_ref() {
+        Some(original) => original,
+        None => return fastn_core::usage_error(
+            "`translate` works only in a translation package, set `translation-of` in FASTN.ftd"
+                .to_string(),
+        ),
+    };
+    let target = match config.package.language.as_ref() {
+        Some(language) => language.to_string(),
+        None => {
+            return fastn_core::usage_error(
+                "Set the `language` of this package in FASTN.ftd to translate it".to_string(),
+            )
+        }
+    };
+    Ok((
+        original
+            .language
+            .clone()
+            .unwrap_or_else(|| "en".to_string()),
+        target,
+    ))
+}
+
+/// The ftd documents of the original package
+fn original_documents(
+    config: &fastn_core::Config,
+    files: &[String],
+) -> fastn_core::Result<Vec<String>> {
+    let original_root = config.original_path()?;
+    let mut documents: Vec<String> = ignore::WalkBuilder::new(&original_root)
+        .build()
+        .flatten()
+        .filter_map(|e| camino::Utf8PathBuf::from_path_buf(e.into_path()).ok())
+        .filter(|p| p.is_file() && p.extension() == Some("ftd"))
+        .filter_map(|p| p.strip_prefix(&original_root).ok().map(|p| p.to_string()))
+        .filter(|p| !p.eq("FASTN.ftd"))
+        .collect();
+    if !files.is_empty() {
+        for file in files {
+            if !documents.contains(file) {
+                return fastn_core::usage_error(format!(
+                    "{} is not a document of the original package",
+                    file
+                ));
+            }
+        }
+        documents.retain(|d| files.contains(d));
+    }
+    documents.sort();
+    Ok(documents)
+}
+
+async fn translated_strings(
+    config: &fastn_core::Config,
+    document: &str,
+) -> fastn_core::Result<Option<Vec<(String, usize, String)>>> {
+    let path = config.root.join(document);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = tokio::fs::read_to_string(&path).await?;
+    Ok(Some(fastn_core::translate::strings(
+        content.as_str(),
+        document,
+    )?))
+}
+
+/// The translations are the strings of the translated documents that differ from the original,
+/// or all of them once the document is marked up to date. They need a review if the original has
+/// changed since. Strings that are not translated get the translation of the same text elsewhere
+/// in the package, or of the `backend`, for review too.
+async fn extract(
+    config: &fastn_core::Config,
+    files: Vec<String>,
+    format: fastn_core::translate::Format,
+    output: Option<&str>,
+    backend: Option<&str>,
+) -> fastn_core::Result<()> {
+    use fastn_core::commands::translation_status::TranslationStatus;
+
+    let (source_language, target_language) = languages(config)?;
+    let original_root = config.original_path()?;
+    let original_snapshots = fastn_core::snapshot::get_latest_snapshots(&original_root).await?;
+    let status = fastn_core::commands::translation_status::get_translation_status(
+        &original_snapshots,
+        &config.root,
+    )?;
+
+    let mut units = vec![];
+    for document in original_documents(config, files.as_slice())? {
+        let content = tokio::fs::read_to_string(original_root.join(document.as_str())).await?;
+        let translated: std::collections::HashMap<String, String> =
+            translated_strings(config, document.as_str())
+                .await?
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(id, _, value)| (id, value))
+                .collect();
+        let upto_date = matches!(status.get(&document), Some(TranslationStatus::UptoDate));
+        let outdated = matches!(status.get(&document), Some(TranslationStatus::Outdated));
+
+        for (id, line_number, source) in
+            fastn_core::translate::strings(content.as_str(), document.as_str())?
+        {
+            let target = translated
+                .get(&id)
+                .filter(|v| upto_date || !v.eq(&&source))
+                .cloned();
+            units.push(fastn_core::translate::Unit {
+                document: document.clone(),
+                fuzzy: target.is_some() && outdated,
+                note: (target.is_some() && outdated)
+                    .then(|| "the original has changed since this was translated".to_string()),
+                id,
+                line_number,
+                source,
+                target,
+            });
+        }
+    }
+
+    let memory: std::collections::HashMap<String, String> = units
+        .iter()
+        .filter(|u| !u.fuzzy)
+        .filter_map(|u| u.target.clone().map(|t| (u.source.clone(), t)))
+        .collect();
+    for unit in units.iter_mut().filter(|u| u.target.is_none()) {
+        if let Some(target) = memory.get(&unit.source) {
+            unit.target = Some(target.to_string());
+            unit.fuzzy = true;
+            unit.note = Some("from the translation memory".to_string());
+        }
+    }
+
+    if let Some(backend) = backend {
+        let mut texts: Vec<String> = units
+            .iter()
+            .filter(|u| u.target.is_none())
+            .map(|u| u.source.clone())
+            .collect();
+        texts.sort();
+        texts.dedup();
+        if !texts.is_empty() {
+            let translations = fastn_core::translate::backend_translate(
+                backend,
+                source_language.as_str(),
+                target_language.as_str(),
+                texts.as_slice(),
+            )
+            .await?;
+            let translations: std::collections::HashMap<&String, String> =
+                texts.iter().zip(translations).collect();
+            for unit in units.iter_mut().filter(|u| u.target.is_none()) {
+                if let Some(target) = translations.get(&unit.source).filter(|v| !v.is_empty()) {
+                    unit.target = Some(target.to_string());
+                    unit.fuzzy = true;
+                    unit.note = Some("a machine translation".to_string());
+                }
+            }
+        }
+    }
+
+    let content = fastn_core::translate::write(
+        format,
+        units.as_slice(),
+        source_language.as_str(),
+        target_language.as_str(),
+    );
+    match output {
+        Some(output) => {
+            fastn_core::utils::update(&camino::Utf8PathBuf::from(output), content.as_bytes())
+                .await?
+        }
+        None => print!("{}", content),
+    }
+
+    let translated = units.iter().filter(|u| u.target.is_some() && !u.fuzzy);
+    eprintln!(
+        "Extracted {} strings: {} translated, {} to review, {} not translated",
+        units.len(),
+        translated.count(),
+        units.iter().filter(|u| u.fuzzy).count(),
+        units.iter().filter(|u| u.target.is_none()).count(),
+    );
+    Ok(())
+}
+
+/// A translation is applied if the original still has the text it translates. The document is
+/// written in the structure of its translation if that has the same strings as the original, else
+/// in the structure of the original, keeping the translations the document already has.
+async fn apply(config: &fastn_core::Config, file: &str, fuzzy: bool) -> fastn_core::Result<()> {
+    languages(config)?;
+    let original_root = config.original_path()?;
+    let content = tokio::fs::read_to_string(file).await?;
+    let format = fastn_core::translate::Format::from_path(file)
+        .unwrap_or_else(|| fastn_core::translate::Format::detect(content.as_str()));
+
+    let mut documents: std::collections::BTreeMap<String, Vec<fastn_core::translate::Unit>> =
+        Default::default();
+    for unit in fastn_core::translate::read(format, content.as_str())? {
+        documents
+            .entry(unit.document.clone())
+            .or_default()
+            .push(unit);
+    }
+    if documents.is_empty() {
+        return fastn_core::usage_error(format!("{} has no translations", file));
+    }
+
+    for (document, units) in documents {
+        let original_path = original_root.join(document.as_str());
+        if !original_path.exists() {
+            eprintln!(
+                "Skipped {}, it is not a document of the original package",
+                document
+            );
+            continue;
+        }
+        let original = tokio::fs::read_to_string(&original_path).await?;
+        let strings = fastn_core::translate::strings(original.as_str(), document.as_str())?;
+        let sources: std::collections::HashMap<String, String> = strings
+            .iter()
+            .map(|(id, _, value)| (id.to_string(), value.to_string()))
+            .collect();
+        let existing = translated_strings(config, document.as_str()).await?;
+
+        let same_strings = existing.as_ref().is_some_and(|existing| {
+            existing.len() == sources.len()
+                && existing.iter().all(|(id, _, _)| sources.contains_key(id))
+        });
+        let mut translations: std::collections::HashMap<String, String> = existing
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(id, _, _)| sources.contains_key(id))
+            .map(|(id, _, value)| (id, value))
+            .collect();
+
+        let units = units
+            .into_iter()
+            .filter(|unit| fuzzy || !unit.fuzzy)
+            .collect();
+        let (matched, changed) = fastn_core::translate::match_units(strings.as_slice(), units);
+        let applied = matched.len();
+        translations.extend(matched);
+
+        let base = if same_strings {
+            tokio::fs::read_to_string(config.root.join(document.as_str())).await?
+        } else {
+            original
+        };
+        let translated =
+            fastn_core::translate::translate(base.as_str(), document.as_str(), &translations)?;
+        fastn_core::utils::update(&config.root.join(document.as_str()), translated.as_bytes())
+            .await?;
+
+        let untranslated = sources
+            .iter()
+            .filter(|(id, source)| translations.get(*id).map_or(true, |t| t.eq(*source)))
+            .count();
+        let mut summary = format!("{}: applied {} translations", document, applied);
+        if changed > 0 {
+            summary = format!(
+                "{}, skipped {} as the original has changed",
+                summary, changed
+            );
+        }
+        if untranslated > 0 {
+            summary = format!("{}, {} strings are not translated", summary, untranslated);
+        }
+        println!("{}", summary);
+    }
+    Ok(())
+}
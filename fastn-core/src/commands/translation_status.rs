@@ -1,9 +1,122 @@
-pub async fn translation_status(config: &fastn_core::Config) -> fastn_core::Result<()> {
-    // it can be original package or translation
-    if config.is_translation_package() {
-        translation_package_status(config).await?;
+pub async fn translation_status(
+    config: &fastn_core::Config,
+    format: &str,
+    fail_under: Option<f64>,
+) -> fastn_core::Result<()> {
+    let report = report(config).await?;
+    match format {
+        "json" => println!("{}", serde_json::to_string_pretty(&report)?),
+        "html" => {
+            let html = report_html(&mut config.clone(), &report).await?;
+            println!("{}", String::from_utf8_lossy(html.as_slice()));
+        }
+        _ => print_report(&report),
+    }
+
+    if let Some(fail_under) = fail_under {
+        let under = report
+            .languages
+            .iter()
+            .filter(|l| l.coverage.map_or(true, |c| c < fail_under))
+            .map(|l| match l.coverage {
+                Some(coverage) => format!("{}: {:.1}%", l.name(), coverage),
+                None => format!("{}: no status", l.name()),
+            })
+            .collect::<Vec<_>>();
+        if !under.is_empty() {
+            return Err(fastn_core::Error::GenericError(format!(
+                "translation coverage is under {}%, {}",
+                fail_under,
+                under.join(", ")
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct Report {
+    pub package: String,
+    pub languages: Vec<LanguageReport>,
+}
+
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct LanguageReport {
+    pub package: String,
+    pub language: Option<String>,
+    /// `None` for a translation that has not published its `fastn.translation-status-summary`
+    pub summary: Option<fastn_core::translation::TranslationStatusSummary>,
+    /// percentage of the documents that are up to date
+    pub coverage: Option<f64>,
+    /// only known for the translation package being reported on
+    pub documents: Vec<DocumentReport>,
+}
+
+impl LanguageReport {
+    fn new(
+        package: &fastn_core::Package,
+        summary: Option<fastn_core::translation::TranslationStatusSummary>,
+        documents: Vec<DocumentReport>,
+    ) -> LanguageReport {
+        LanguageReport {
+            package: package.name.to_string(),
+            language: package.language.clone(),
+            coverage: summary.as_ref().map(|s| s.coverage()),
+            summary,
+            documents,
+        }
+    }
+
+    fn name(&self) -> String {
+        match self.language {
+            Some(ref language) => format!("{} ({})", self.package, language),
+            None => self.package.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DocumentReport {
+    pub document: String,
+    pub status: TranslationStatus,
+    /// when the translation was last marked up to date with the original
+    pub last_marked_on: Option<String>,
+    /// when the original first changed after that, for out-dated translations
+    pub outdated_since: Option<String>,
+    pub outdated_days: Option<u64>,
+    /// the changes to the original the translation is missing
+    pub diff: Option<String>,
+}
+
+/// The coverage of every language: of this package if it is a translation, else of all its
+/// translations, from the summaries they publish.
+pub async fn report(config: &fastn_core::Config) -> fastn_core::Result<Report> {
+    let languages = if config.is_translation_package() {
+        let original_path = config.original_path()?;
+        let original_snapshots = fastn_core::snapshot::get_latest_snapshots(&original_path).await?;
+        let summary = fastn_core::translation::get_translation_status_counts(
+            &original_snapshots,
+            &config.root,
+        )?;
+        let documents = document_reports(config, &original_path, &original_snapshots).await?;
+        vec![LanguageReport::new(
+            &config.package,
+            Some(summary),
+            documents,
+        )]
     } else if !config.package.translations.is_empty() {
-        original_package_status(config).await?;
+        let mut languages = vec![];
+        for translation in config.package.translations.iter() {
+            let translation = config.resolve_package(translation).await?;
+            languages.push(LanguageReport::new(
+                &translation,
+                translation.translation_status_summary.clone(),
+                vec![],
+            ));
+        }
+        languages
     } else {
         return Err(fastn_core::Error::UsageError {
             message:
@@ -11,25 +124,212 @@ pub async fn translation_status(config: &fastn_core::Config) -> fastn_core::Resu
                     .to_string(),
         });
     };
-    Ok(())
+    Ok(Report {
+        package: config.package.name.to_string(),
+        languages,
+    })
 }
 
-async fn translation_package_status(config: &fastn_core::Config) -> fastn_core::Result<()> {
-    let original_snapshots =
-        fastn_core::snapshot::get_latest_snapshots(&config.original_path()?).await?;
-    let translation_status = get_translation_status(&original_snapshots, &config.root)?;
-    print_translation_status(&translation_status);
-    Ok(())
+async fn document_reports(
+    config: &fastn_core::Config,
+    original_path: &camino::Utf8PathBuf,
+    original_snapshots: &std::collections::BTreeMap<String, u128>,
+) -> fastn_core::Result<Vec<DocumentReport>> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+
+    let mut documents = vec![];
+    for (document, status) in get_translation_status(original_snapshots, &config.root)? {
+        let track_path = fastn_core::utils::track_path(document.as_str(), config.root.as_str());
+        let last_marked = if track_path.exists() {
+            fastn_core::tracker::get_tracks(config.root.as_str(), &track_path)?
+                .get(&document)
+                .and_then(|t| t.last_merged_version)
+        } else {
+            None
+        };
+
+        let mut report = DocumentReport {
+            document,
+            status,
+            last_marked_on: last_marked
+                .as_ref()
+                .map(fastn_core::utils::nanos_to_rfc3339),
+            outdated_since: None,
+            outdated_days: None,
+            diff: None,
+        };
+        if let (TranslationStatus::Outdated, Some(last_marked), Some(latest)) = (
+            &report.status,
+            last_marked,
+            original_snapshots.get(&report.document),
+        ) {
+            let since = original_versions(original_path, report.document.as_str())
+                .into_iter()
+                .find(|v| *v > last_marked)
+                .unwrap_or(*latest);
+            report.outdated_since = Some(fastn_core::utils::nanos_to_rfc3339(&since));
+            report.outdated_days = Some((now.saturating_sub(since) / 86_400_000_000_000) as u64);
+
+            let then = fastn_core::utils::history_path(
+                report.document.as_str(),
+                original_path.as_str(),
+                &last_marked,
+            );
+            let now = fastn_core::utils::history_path(
+                report.document.as_str(),
+                original_path.as_str(),
+                latest,
+            );
+            if let (Ok(then), Ok(now)) = (
+                tokio::fs::read_to_string(then).await,
+                tokio::fs::read_to_string(now).await,
+            ) {
+                report.diff = Some(fastn_core::commands::diff::patch(
+                    then.as_str(),
+                    now.as_str(),
+                    false,
+                ));
+            }
+        }
+        documents.push(report);
+    }
+    Ok(documents)
 }
 
-async fn original_package_status(config: &fastn_core::Config) -> fastn_core::Result<()> {
-    for translation in config.package.translations.iter() {
-        if let Some(ref status) = translation.translation_status_summary {
-            println!("Status for `{}` package:", translation.name);
-            println!("{}", status.to_string());
+/// The timestamps of the snapshots of `document` in the `.history` of the original, oldest first
+fn original_versions(original_path: &camino::Utf8PathBuf, document: &str) -> Vec<u128> {
+    let history = fastn_core::utils::history_path(document, original_path.as_str(), &0);
+    let (dir, name) = match (history.parent(), history.file_name()) {
+        (Some(dir), Some(name)) => (dir, name),
+        _ => return vec![],
+    };
+    let (prefix, suffix) = name.split_once(".0").unwrap_or((name, ""));
+    let mut versions = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|e| e.file_name().into_string().ok())
+        .filter_map(|file| {
+            file.strip_prefix(prefix)?
+                .strip_prefix('.')?
+                .strip_suffix(suffix)?
+                .parse::<u128>()
+                .ok()
+        })
+        .collect::<Vec<_>>();
+    versions.sort();
+    versions
+}
+
+fn print_report(report: &Report) {
+    for language in report.languages.iter() {
+        for document in language.documents.iter() {
+            println!("{}: {}", document.status.as_str(), document.document);
+        }
+        match (language.summary.as_ref(), language.coverage) {
+            (Some(summary), Some(coverage)) => {
+                if language.documents.is_empty() {
+                    println!("Status for `{}` package:", language.package);
+                    print!("{}", summary.to_string());
+                }
+                println!(
+                    "Coverage of {}: {:.1}% ({} of {} documents up to date)",
+                    language.name(),
+                    coverage,
+                    summary.upto_date,
+                    summary.total()
+                );
+            }
+            _ => println!("`{}` has no translation status", language.package),
         }
     }
-    Ok(())
+}
+
+/// The report as a page, `/-/translation-status/` in `fastn serve`
+pub(crate) async fn report_html(
+    config: &mut fastn_core::Config,
+    report: &Report,
+) -> fastn_core::Result<Vec<u8>> {
+    let body_prefix = config
+        .package
+        .generate_prefix_string(false)
+        .unwrap_or_default();
+    let main_document = fastn_core::Document {
+        id: "translation-status.ftd".to_string(),
+        content: format!("{}\n\n{}", body_prefix, report_page(report)),
+        parent_path: config.root.as_str().to_string(),
+        package_name: config.package.name.clone(),
+    };
+    fastn_core::package::package_doc::read_ftd(config, &main_document, "/", false, false)
+        .await
+        .map(|r| r.html())
+}
+
+fn report_page(report: &Report) -> String {
+    let mut page = format!(
+        indoc::indoc! {"
+            -- ftd.column:
+            padding.px: 24
+            spacing.fixed.px: 12
+            width: fill-container
+
+            -- ftd.text: Translation status of {package}
+            role: $inherited.types.heading-medium
+
+        "},
+        package = report.package
+    );
+    for language in report.languages.iter() {
+        page.push_str(
+            format!(
+                "-- ftd.text: {}\nrole: $inherited.types.heading-small\n\n",
+                language.name()
+            )
+            .as_str(),
+        );
+        let summary = match (language.summary.as_ref(), language.coverage) {
+            (Some(summary), Some(coverage)) => format!(
+                "{:.1}% up to date · {} up to date · {} out-dated · {} never marked · {} missing",
+                coverage,
+                summary.upto_date,
+                summary.out_dated,
+                summary.never_marked,
+                summary.missing
+            ),
+            _ => "No translation status published".to_string(),
+        };
+        page.push_str(format!("-- ftd.text: {}\n\n", summary).as_str());
+
+        for document in language.documents.iter() {
+            let mut line = format!("{} · {}", document.document, document.status.as_str());
+            if let Some(ref last_marked_on) = document.last_marked_on {
+                line = format!("{} · last marked on {}", line, last_marked_on);
+            }
+            if let Some(days) = document.outdated_days {
+                line = format!("{} · out-dated for {} days", line, days);
+            }
+            page.push_str(
+                format!(
+                    "-- ftd.text: {}\n\n",
+                    fastn_core::utils::escape_ftd_page(line.as_str())
+                )
+                .as_str(),
+            );
+            if let Some(ref diff) = document.diff {
+                page.push_str(
+                    format!(
+                        "-- ftd.code:\nlang: diff\nwidth: fill-container\n\n{}\n\n",
+                        fastn_core::utils::escape_ftd_page(diff.as_str())
+                    )
+                    .as_str(),
+                );
+            }
+        }
+    }
+    format!("{}-- end: ftd.column\n", page)
 }
 
 pub(crate) fn get_translation_status(
@@ -65,18 +365,14 @@ pub(crate) fn get_translation_status(
     Ok(translation_status)
 }
 
-fn print_translation_status(
-    translation_status: &std::collections::BTreeMap<String, TranslationStatus>,
-) {
-    for (file, status) in translation_status {
-        println!("{}: {}", status.as_str(), file);
-    }
-}
-
-pub(crate) enum TranslationStatus {
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TranslationStatus {
     Missing,
     NeverMarked,
+    #[serde(rename = "out-dated")]
     Outdated,
+    #[serde(rename = "upto-date")]
     UptoDate,
 }
 
@@ -90,3 +386,139 @@ impl TranslationStatus {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    fn temp_dir(name: &str) -> camino::Utf8PathBuf {
+        let dir: camino::Utf8PathBuf = std::env::temp_dir()
+            .join(format!(
+                "fastn-translation-status-{}-{}",
+                name,
+                std::process::id()
+            ))
+            .try_into()
+            .unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write(path: camino::Utf8PathBuf, content: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    fn track(root: &camino::Utf8PathBuf, document: &str, last_merged_version: Option<u128>) {
+        let mut content = format!(
+            "-- import: fastn\n\n-- fastn.track: {}\nself-timestamp: 1",
+            document
+        );
+        if let Some(last_merged_version) = last_merged_version {
+            content = format!("{}\nlast-merged-version: {}", content, last_merged_version);
+        }
+        write(
+            fastn_core::utils::track_path(document, root.as_str()),
+            content.as_str(),
+        );
+    }
+
+    #[test]
+    fn get_translation_status() {
+        let root = temp_dir("status");
+        for document in ["marked.ftd", "untracked.ftd", "outdated.ftd", "current.ftd"] {
+            write(root.join(document), "-- ftd.text: hello\n");
+        }
+        track(&root, "marked.ftd", None);
+        track(&root, "outdated.ftd", Some(10));
+        track(&root, "current.ftd", Some(20));
+
+        let snapshots = std::collections::BTreeMap::from_iter(
+            [
+                ("absent.ftd", 20),
+                ("marked.ftd", 20),
+                ("untracked.ftd", 20),
+                ("outdated.ftd", 20),
+                ("current.ftd", 20),
+            ]
+            .map(|(document, timestamp)| (document.to_string(), timestamp)),
+        );
+        let status = super::get_translation_status(&snapshots, &root).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        pretty_assertions::assert_eq!(
+            status
+                .iter()
+                .map(|(document, status)| (document.as_str(), status.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("absent.ftd", "Missing"),
+                ("current.ftd", "Up to date"),
+                ("marked.ftd", "Never marked"),
+                ("outdated.ftd", "Out-dated"),
+                ("untracked.ftd", "Never marked"),
+            ]
+        );
+    }
+
+    #[test]
+    fn original_versions() {
+        let original = temp_dir("versions");
+        for version in [30, 5, 100] {
+            write(
+                fastn_core::utils::history_path("a.ftd", original.as_str(), &version),
+                "",
+            );
+        }
+        write(
+            fastn_core::utils::history_path("ab.ftd", original.as_str(), &7),
+            "",
+        );
+        write(
+            fastn_core::utils::history_path("a.md", original.as_str(), &8),
+            "",
+        );
+
+        let versions = super::original_versions(&original, "a.ftd");
+        let missing = super::original_versions(&original, "b.ftd");
+        std::fs::remove_dir_all(&original).unwrap();
+
+        assert_eq!(versions, vec![5, 30, 100]);
+        assert!(missing.is_empty());
+    }
+
+    #[test]
+    fn report_page() {
+        let report = super::Report {
+            package: "www.example.com".to_string(),
+            languages: vec![super::LanguageReport {
+                package: "hi.example.com".to_string(),
+                language: Some("hi".to_string()),
+                summary: Some(fastn_core::translation::TranslationStatusSummary {
+                    never_marked: 0,
+                    missing: 0,
+                    out_dated: 1,
+                    upto_date: 3,
+                    last_modified_on: None,
+                }),
+                coverage: Some(75.0),
+                documents: vec![super::DocumentReport {
+                    document: "a;;b.ftd".to_string(),
+                    status: super::TranslationStatus::Outdated,
+                    last_marked_on: None,
+                    outdated_since: None,
+                    outdated_days: Some(2),
+                    diff: Some("-- ftd.text: old\n+;; comment".to_string()),
+                }],
+            }],
+        };
+        let page = super::report_page(&report);
+
+        let sections = ftd::p1::parse(page.as_str(), "translation-status.ftd").unwrap();
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].sub_sections.len(), 5);
+        assert!(page.contains("-- ftd.text: hi.example.com (hi)\n"));
+        assert!(page.contains("-- ftd.text: 75.0% up to date · 3 up to date · 1 out-dated"));
+        assert!(page.contains("-- ftd.text: a\\;;b.ftd · Out-dated · out-dated for 2 days\n"));
+        assert!(page.contains("\n\\-- ftd.text: old\n+\\;; comment\n"));
+    }
+}
@@ -6,6 +6,9 @@ pub async fn update(config: &fastn_core::Config) -> fastn_core::Result<()> {
         }
     };
 
+    let lock = fastn_core::package::lock::resolve(config).await?;
+    lock.write(&config.root).await?;
+
     let c = fastn_core::Config::read(None, false, None).await?;
     if c.package.dependencies.is_empty() {
         println!("No dependencies to update.")
@@ -15,5 +18,18 @@ pub async fn update(config: &fastn_core::Config) -> fastn_core::Result<()> {
         println!("Updated {} dependencies.", c.package.dependencies.len())
     }
 
+    for package in lock.packages.iter() {
+        println!(
+            "  {} {}",
+            package.name,
+            package.version.as_deref().unwrap_or("(unversioned)")
+        );
+    }
+    println!(
+        "Locked {} packages in {}.",
+        lock.packages.len(),
+        fastn_core::package::lock::LOCK_FILE
+    );
+
     Ok(())
 }
@@ -0,0 +1,184 @@
+/// Copies every dependency, and their dependencies, into `vendor/` and lists them in
+/// `vendor/fastn.vendor.ftd`. Packages are downloaded from their `zip`, the versions in
+/// `FASTN.lock` are used if there is one, and `git` dependencies are cloned. `path` dependencies
+/// are not copied, they are used from where they are, but their dependencies are vendored. Only
+/// the folders of the packages vendored before are replaced, so a `vendor/` not made by
+/// `fastn vendor` is never touched.
+pub async fn vendor(config: &fastn_core::Config) -> fastn_core::Result<()> {
+    use fastn_core::package::vendor;
+
+    let vendor_dir = config.root.join(vendor::VENDOR_DIR);
+    let previous = vendor::Manifest::read(&config.root).await?;
+    if previous.is_none() && vendor_dir.exists() {
+        return Err(fastn_core::Error::PackageError {
+            message: format!(
+                "{}/ exists but has no {}, it was not made by `fastn vendor`, move it away first",
+                vendor::VENDOR_DIR,
+                vendor::MANIFEST_FILE
+            ),
+        });
+    }
+    let previous: Vec<String> = previous
+        .map(|m| m.packages.into_iter().map(|p| p.name).collect())
+        .unwrap_or_default();
+
+    // everything is downloaded in a scratch folder first, so a failed download does not leave a
+    // half written `vendor/` behind
+    let staging: camino::Utf8PathBuf = std::env::temp_dir()
+        .join(format!("fastn-vendor-{}", std::process::id()))
+        .try_into()?;
+    remove_dir(&staging).await?;
+
+    let lock = fastn_core::package::lock::LockFile::read(&config.root).await?;
+    let mut walker =
+        fastn_core::package::dependency::Walker::new(config.package.dependencies.as_slice());
+    let mut downloaded = vec![];
+
+    while let Some((dependency, _)) = walker.next() {
+        let name = dependency.package.name.to_string();
+        let root = if let Some(ref path) = dependency.path {
+            config.root.join(path)
+        } else {
+            let dst = staging.join(name.as_str());
+            let source = download(&dependency, lock.as_ref(), &dst).await?;
+            downloaded.push((name.to_string(), source));
+            dst
+        };
+
+        let fastn_ftd = tokio::fs::read_to_string(root.join("FASTN.ftd")).await?;
+        walker.add(name.as_str(), fastn_ftd.as_str())?;
+    }
+    downloaded.sort_by(|a, b| a.0.cmp(&b.0));
+
+    for (name, _) in downloaded.iter() {
+        if !previous.contains(name) && vendor_dir.join(name.as_str()).exists() {
+            return Err(fastn_core::Error::PackageError {
+                message: format!(
+                    "{}/{}/ was not vendored by `fastn vendor`, move it away first",
+                    vendor::VENDOR_DIR,
+                    name
+                ),
+            });
+        }
+    }
+    for name in previous.iter() {
+        remove_package_dir(&vendor_dir, name.as_str()).await?;
+    }
+
+    let mut manifest = vendor::Manifest::default();
+    for (name, source) in downloaded {
+        let dst = vendor_dir.join(name.as_str());
+        fastn_core::copy_dir_all(staging.join(name.as_str()), dst.clone()).await?;
+        for ignored in [".git", ".packages", ".build"] {
+            remove_dir(&dst.join(ignored)).await?;
+        }
+
+        let fastn_ftd = tokio::fs::read_to_string(dst.join("FASTN.ftd")).await?;
+        let package: fastn_package::old_fastn::PackageTemp =
+            fastn_core::package::dependency::parse_fastn(name.as_str(), fastn_ftd.as_str())?
+                .get("fastn#package")?;
+
+        manifest.packages.push(vendor::VendoredPackage {
+            source,
+            version: package.version,
+            sha256: vendor::files_sha256(&dst)?,
+            name,
+        });
+    }
+    manifest.write(&config.root).await?;
+    remove_dir(&staging).await?;
+
+    for package in manifest.packages.iter() {
+        println!(
+            "  {} {}",
+            package.name,
+            package.version.as_deref().unwrap_or("(unversioned)")
+        );
+    }
+    println!(
+        "Vendored {} packages in {}/.",
+        manifest.packages.len(),
+        vendor::VENDOR_DIR
+    );
+
+    Ok(())
+}
+
+/// Downloads `dependency` into `dst`, returns where it came from.
+async fn download(
+    dependency: &fastn_core::Dependency,
+    lock: Option<&fastn_core::package::lock::LockFile>,
+    dst: &camino::Utf8PathBuf,
+) -> fastn_core::Result<Option<String>> {
+    let name = dependency.package.name.as_str();
+    if let Some(ref git) = dependency.git {
+        dependency.clone_git(dst)?;
+        return Ok(Some(match dependency.rev {
+            Some(ref rev) => format!("{}#{}", git, rev),
+            None => git.to_string(),
+        }));
+    }
+
+    let locked = match lock {
+        Some(lock) => lock.get(&dependency.package, dependency.version.as_deref())?,
+        None => None,
+    };
+    let no_zip = || fastn_core::Error::PackageError {
+        message: format!("{} can not be vendored, its FASTN.ftd has no `zip`", name),
+    };
+
+    let fastn_ftd = match locked {
+        Some(locked) => {
+            let fastn_ftd = locked.fetch_fastn().await?;
+            if !locked.install_archive(fastn_ftd.as_str(), dst).await? {
+                return Err(no_zip());
+            }
+            fastn_ftd
+        }
+        None => {
+            let fastn_ftd = dependency.package.get_fastn().await?;
+            let package: fastn_package::old_fastn::PackageTemp =
+                fastn_core::package::dependency::parse_fastn(name, fastn_ftd.as_str())?
+                    .get("fastn#package")?;
+            let zip = package.zip.ok_or_else(no_zip)?;
+            let archive = crate::http::construct_url_and_get(zip.as_str()).await?;
+            fastn_core::package::dependency::extract_zip(archive.as_slice(), dst)?;
+            fastn_ftd
+        }
+    };
+    // the checked `FASTN.ftd` is the one vendored, whatever the zip has
+    fastn_core::utils::update(dst.join("FASTN.ftd"), fastn_ftd.as_bytes()).await?;
+
+    let package: fastn_package::old_fastn::PackageTemp =
+        fastn_core::package::dependency::parse_fastn(name, fastn_ftd.as_str())?
+            .get("fastn#package")?;
+    Ok(package.zip)
+}
+
+/// Removes the folder of a vendored package, and the folders it was in if they are left empty,
+/// `foo.com/ui` is in `vendor/foo.com/ui/`.
+async fn remove_package_dir(vendor_dir: &camino::Utf8Path, name: &str) -> fastn_core::Result<()> {
+    let mut dir = vendor_dir.join(name);
+    remove_dir(&dir).await?;
+    while let Some(parent) = dir.parent() {
+        if parent == vendor_dir {
+            break;
+        }
+        // fails if it is not empty, that is fine
+        if tokio::fs::remove_dir(parent).await.is_err() {
+            break;
+        }
+        dir = parent.to_path_buf();
+    }
+    Ok(())
+}
+
+async fn remove_dir(path: &camino::Utf8Path) -> fastn_core::Result<()> {
+    if let Err(e) = tokio::fs::remove_dir_all(path).await {
+        match e.kind() {
+            std::io::ErrorKind::NotFound => {}
+            _ => return Err(e.into()),
+        }
+    }
+    Ok(())
+}
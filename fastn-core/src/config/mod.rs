@@ -45,6 +45,12 @@ pub struct Config {
     pub ftd_inline_js: Vec<String>,
     pub ftd_external_css: Vec<String>,
     pub ftd_inline_css: Vec<String>,
+    /// the locale of the `i18n` messages, negotiated from the request if not set
+    pub locale: Option<String>,
+    /// syntaxes and themes shipped by the package and its dependencies, used by `ftd.code`
+    pub code_assets: std::cell::RefCell<Option<std::sync::Arc<ftd::executor::code::CodeAssets>>>,
+    /// `FASTN.lock`, read once, and made by `resolve_package()` if the package does not have one
+    pub lock: std::cell::RefCell<Option<fastn_core::package::lock::LockFile>>,
 }
 
 impl Config {
@@ -112,6 +118,10 @@ impl Config {
         self.cr_path(cr_number).join("-/meta.ftd")
     }
 
+    pub fn cr_reviews_path(&self, cr_number: usize) -> camino::Utf8PathBuf {
+        self.cr_path(cr_number).join("-/reviews.ftd")
+    }
+
     pub(crate) fn package_info_package(&self) -> &str {
         match self
             .package
@@ -473,6 +483,10 @@ impl Config {
             package_fastn_path.parent().unwrap().to_owned()
         } else if package.name.eq(&self.package.name) {
             self.root.clone()
+        } else if let Some(vendored) =
+            fastn_core::package::vendor::package_root(&self.root, package.name.as_str())
+        {
+            vendored
         } else {
             self.packages_root.clone().join(package.name.as_str())
         }
@@ -661,7 +675,7 @@ impl Config {
         &self,
         package: &fastn_core::Package,
     ) -> fastn_core::Result<fastn_core::Package> {
-        let fastn_path = &self.packages_root.join(&package.name).join("FASTN.ftd");
+        let fastn_path = &self.get_root_for_package(package).join("FASTN.ftd");
 
         if !fastn_path.exists() {
             let package = self.resolve_package(package).await?;
@@ -1165,7 +1179,7 @@ impl Config {
         })
     }
 
-    async fn get_root_path(
+    pub(crate) async fn get_root_path(
         directory: &camino::Utf8PathBuf,
     ) -> fastn_core::Result<camino::Utf8PathBuf> {
         if let Some(fastn_ftd_root) = utils::find_root_for_file(directory, "FASTN.ftd") {
@@ -1273,7 +1287,9 @@ impl Config {
             }
         };
         let fastn_doc = utils::fastn_doc(&root.join("FASTN.ftd")).await?;
-        let package = fastn_core::Package::from_fastn_doc(&root, &fastn_doc)?;
+        let mut package = fastn_core::Package::from_fastn_doc(&root, &fastn_doc)?;
+        fastn_core::package::vendor::link(&root, &mut package.dependencies);
+        let lock = fastn_core::package::lock::LockFile::read(&root).await?;
         let mut config = Config {
             package: package.clone(),
             packages_root: root.clone().join(".packages"),
@@ -1291,11 +1307,22 @@ impl Config {
             ftd_inline_js: Default::default(),
             ftd_external_css: Default::default(),
             ftd_inline_css: Default::default(),
+            locale: None,
+            code_assets: Default::default(),
+            lock: std::cell::RefCell::new(lock),
         };
 
         // Update global_ids map from the current package files
         config.update_ids_from_package().await?;
 
+        // the original package of a translation package is read like a dependency, its documents
+        // and sitemap are used by the translation
+        if let Some(original) = package.translation_of.as_ref() {
+            let original = config.resolve_package(original).await?;
+            *package.translation_of = Some(original);
+            config.package.translation_of = package.translation_of.clone();
+        }
+
         // TODO: Major refactor, while parsing sitemap of a package why do we need config in it?
         config.package.sitemap = {
             let sitemap = match package.translation_of.as_ref() {
@@ -1335,6 +1362,8 @@ impl Config {
         };
 
         config.add_package(&package);
+        fastn_core::watcher::add_linked_packages(&config.package);
+        *config.code_assets.borrow_mut() = fastn_core::package::code_assets::load(&config)?;
 
         // fastn installed Apps
         config.package.apps = {
@@ -1366,14 +1395,66 @@ impl Config {
             return Ok(package.clone());
         }
 
-        let package = package
-            .get_and_resolve(&self.get_root_for_package(package))
-            .await?;
+        let dependency = self
+            .package
+            .dependencies
+            .iter()
+            .find(|d| d.package.name == package.name);
+        let root = self.get_root_for_package(dependency.map(|d| &d.package).unwrap_or(package));
+
+        let is_vendored =
+            fastn_core::package::vendor::package_root(&self.root, package.name.as_str()).is_some();
+        let is_local = is_vendored
+            || dependency
+                .map(|d| d.path.is_some() || d.git.is_some())
+                .unwrap_or(false);
+        if is_vendored {
+            fastn_core::package::vendor::verify(&self.root, package.name.as_str()).await?;
+        } else if let Some(dependency) = dependency {
+            dependency.clone_git(&root)?;
+        }
+
+        // `path`, `git` and vendored dependencies are not locked, they are whatever is on the disk
+        if !is_local {
+            self.ensure_lock().await?;
+        }
+        let locked = match *self.lock.borrow() {
+            Some(ref lock) if !is_local => lock
+                .get(package, dependency.and_then(|d| d.version.as_deref()))?
+                .cloned(),
+            _ => None,
+        };
+
+        let mut package = package.get_and_resolve(&root, locked.as_ref()).await?;
+        if is_vendored || dependency.map(|d| d.path.is_some()).unwrap_or(false) {
+            // missing files must not be downloaded into the linked or vendored folder
+            package.download_base_url = None;
+        }
 
         self.add_package(&package);
+        if self
+            .package
+            .dependencies
+            .iter()
+            .any(|d| d.package.name == package.name)
+        {
+            *self.code_assets.borrow_mut() = fastn_core::package::code_assets::load(self)?;
+        }
         Ok(package)
     }
 
+    /// Makes `FASTN.lock` the first time a dependency is downloaded, so every later download gets
+    /// the same versions. Running offline nothing can be resolved, what is on the disk is used.
+    async fn ensure_lock(&self) -> fastn_core::Result<()> {
+        if self.lock.borrow().is_some() || fastn_core::package::cache::is_offline() {
+            return Ok(());
+        }
+        let lock = fastn_core::package::lock::resolve(self).await?;
+        lock.write(&self.root).await?;
+        *self.lock.borrow_mut() = Some(lock);
+        Ok(())
+    }
+
     pub(crate) fn add_package(&self, package: &fastn_core::Package) {
         self.all_packages
             .borrow_mut()
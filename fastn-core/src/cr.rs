@@ -4,6 +4,8 @@ pub struct CRMeta {
     #[serde(rename = "cr-number")]
     pub cr_number: usize,
     pub open: bool,
+    /// The identity the CR was created by, only known for CRs created by a signed in user
+    pub author: Option<String>,
 }
 
 impl CRMeta {
@@ -12,6 +14,7 @@ impl CRMeta {
             title: self.title,
             cr_number: self.cr_number,
             open: false,
+            author: self.author,
         }
     }
 }
@@ -21,11 +24,22 @@ pub(crate) async fn get_cr_meta(
     cr_number: usize,
 ) -> fastn_core::Result<fastn_core::cr::CRMeta> {
     let cr_meta_path = config.cr_meta_path(cr_number);
-    if !cr_meta_path.exists() {
+    let doc = if cr_meta_path.exists() {
+        tokio::fs::read_to_string(&cr_meta_path).await?
+    } else if config.package.history_backend.is_git() {
+        // not on the branch of the CR
+        let cr_meta_file = config.path_without_root(&cr_meta_path)?;
+        match fastn_core::git::read_file_at(
+            &config.root,
+            fastn_core::git::cr_branch(cr_number).as_str(),
+            cr_meta_file.as_str(),
+        )? {
+            Some(doc) => doc,
+            None => return fastn_core::usage_error(format!("CR#{} doesn't exist", cr_number)),
+        }
+    } else {
         return fastn_core::usage_error(format!("CR#{} doesn't exist", cr_number));
-    }
-
-    let doc = tokio::fs::read_to_string(&cr_meta_path).await?;
+    };
     resolve_cr_meta(&doc, cr_number).await
 }
 
@@ -37,6 +51,7 @@ pub(crate) async fn resolve_cr_meta(
     struct CRMetaTemp {
         pub title: String,
         pub open: Option<bool>,
+        pub author: Option<String>,
     }
 
     impl CRMetaTemp {
@@ -45,6 +60,7 @@ pub(crate) async fn resolve_cr_meta(
                 title: self.title,
                 cr_number,
                 open: self.open.unwrap_or(true),
+                author: self.author,
             }
         }
     }
@@ -95,6 +111,9 @@ pub(crate) async fn create_cr_meta(
 
 pub(crate) fn generate_cr_meta_content(cr_meta: &fastn_core::cr::CRMeta) -> String {
     let mut meta_content = format!("-- import: fastn\n\n\n-- fastn.cr-meta: {}", cr_meta.title,);
+    if let Some(ref author) = cr_meta.author {
+        meta_content = format!("{}\nauthor: {}", meta_content, author);
+    }
     if !cr_meta.open {
         meta_content = format!("{}\n{}", meta_content, cr_meta.open);
     }
@@ -428,3 +447,19 @@ pub(crate) async fn cr_remote_file_info(
 
     Ok(file_info)
 }
+
+#[cfg(test)]
+mod test {
+    #[tokio::test]
+    async fn cr_meta_author() {
+        let content = super::generate_cr_meta_content(&super::CRMeta {
+            title: "Fix the typo".to_string(),
+            cr_number: 2,
+            open: true,
+            author: Some("github: alice".to_string()),
+        });
+        let cr_meta = super::resolve_cr_meta(content.as_str(), 2).await.unwrap();
+        assert_eq!(cr_meta.title, "Fix the typo");
+        assert_eq!(cr_meta.author.as_deref(), Some("github: alice"));
+    }
+}
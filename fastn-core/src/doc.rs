@@ -13,12 +13,12 @@ fn cached_parse(
 
     if let Some(c) = get_cached::<C>(id) {
         if c.hash == hash {
-            tracing::debug!("cache hit");
+            tracing::debug!(cache = "hit", "cache hit");
             return Ok(c.doc);
         }
-        tracing::debug!("cached hash mismatch");
+        tracing::debug!(cache = "miss", "cached hash mismatch");
     } else {
-        tracing::debug!("cached miss");
+        tracing::debug!(cache = "miss", "cached miss");
     }
 
     let doc = ftd::interpreter::ParsedDocument::parse_with_line_number(id, source, line_number)?;
@@ -84,7 +84,7 @@ pub async fn interpret_helper<'a>(
         name.trim_matches('/').to_string(),
         lib.config.package.name.to_string(),
     );
-    let document;
+    let mut document;
     loop {
         match s {
             ftd::interpreter::Interpreter::Done { document: doc } => {
@@ -151,6 +151,7 @@ pub async fn interpret_helper<'a>(
             }
         }
     }
+    document.code_assets = lib.config.code_assets.borrow().clone();
     Ok(document)
 }
 
@@ -200,12 +201,14 @@ pub async fn resolve_import<'a>(
 }
 
 // source, foreign_variable, foreign_function
+#[tracing::instrument(name = "fastn_core::resolve-import", skip_all)]
 pub async fn resolve_import_2022<'a>(
     lib: &'a mut fastn_core::Library2022,
     _state: &mut ftd::interpreter::InterpreterState,
     module: &str,
     caller_module: &str,
 ) -> ftd::interpreter::Result<(String, Vec<String>, Vec<String>, usize)> {
+    tracing::info!(module = module, caller = caller_module);
     let current_package = lib.get_current_package(caller_module)?;
     let source = if module.eq("fastn/time") {
         ("".to_string(), vec!["time".to_string()], vec![], 0)
@@ -217,7 +220,9 @@ pub async fn resolve_import_2022<'a>(
                 "figma-typo-token".to_string(),
                 "figma-cs-token".to_string(),
                 "figma-cs-token-old".to_string(),
+                "design-tokens".to_string(),
                 "http".to_string(),
+                "i18n".to_string(),
                 "get-data".to_string(),
                 "toc".to_string(),
                 "sitemap".to_string(),
@@ -281,7 +286,9 @@ pub async fn resolve_import_2022<'a>(
                 "figma-typo-token".to_string(),
                 "figma-cs-token".to_string(),
                 "figma-cs-token-old".to_string(),
+                "design-tokens".to_string(),
                 "http".to_string(),
+                "i18n".to_string(),
                 "package-query".to_string(),
                 "pg".to_string(),
                 "toc".to_string(),
@@ -26,6 +26,15 @@ impl File {
             Self::Image(a) => a.id_with_package(),
         }
     }
+    pub fn get_package_name(&self) -> &str {
+        match self {
+            Self::Ftd(a) => a.package_name.as_str(),
+            Self::Static(a) => a.package_name.as_str(),
+            Self::Markdown(a) => a.package_name.as_str(),
+            Self::Code(a) => a.package_name.as_str(),
+            Self::Image(a) => a.package_name.as_str(),
+        }
+    }
     pub fn set_id(&mut self, new_id: &str) {
         *(match self {
             Self::Ftd(a) => &mut a.id,
@@ -149,6 +158,9 @@ pub fn package_ignores(
         overrides.add("!.history")?;
     }
     overrides.add("!.packages")?;
+    if fastn_core::package::vendor::is_vendored(root_path) {
+        overrides.add(format!("!{}", fastn_core::package::vendor::VENDOR_DIR).as_str())?;
+    }
     overrides.add("!.tracks")?;
     overrides.add("!fastn")?;
     overrides.add("!rust-toolchain")?;
@@ -166,6 +178,9 @@ pub fn ignore_path(
 ) -> Result<ignore::overrides::Override, ignore::Error> {
     let mut overrides = ignore::overrides::OverrideBuilder::new(root_path);
     overrides.add("!.packages")?;
+    if fastn_core::package::vendor::is_vendored(root_path) {
+        overrides.add(format!("!{}", fastn_core::package::vendor::VENDOR_DIR).as_str())?;
+    }
     overrides.add("!.build")?;
     for ignored_path in &package.ignored_paths {
         overrides.add(format!("!{}", ignored_path).as_str())?;
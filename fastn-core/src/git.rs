@@ -0,0 +1,750 @@
+//! With `history-backend: git` in `fastn.package` the history of the package is kept in the git
+//! repository the package is in, instead of `.history`: `fastn sync` and the edits made using
+//! `/-/edit/` are commits, a CR is the branch `cr-<number>` and merging a CR is a git merge. So
+//! the web editor and the people using git directly see the same history.
+
+use fastn_core::commands::status::FileStatus;
+
+/// Folders fastn creates in the package, these are never committed.
+const FASTN_FOLDERS: &[&str] = &[
+    ".build",
+    ".packages",
+    ".history",
+    ".tracks",
+    ".remote-state",
+    ".clone-state",
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum HistoryBackend {
+    /// `.history`, `.remote-state` and `.clone-state`
+    #[default]
+    Fastn,
+    Git,
+}
+
+impl HistoryBackend {
+    pub(crate) fn from_header(value: Option<&str>) -> fastn_core::Result<HistoryBackend> {
+        match value {
+            None | Some("fastn") => Ok(HistoryBackend::Fastn),
+            Some("git") => Ok(HistoryBackend::Git),
+            Some(v) => Err(fastn_core::Error::PackageError {
+                message: format!(
+                    "unknown history-backend `{}`, it can be `fastn` or `git`",
+                    v
+                ),
+            }),
+        }
+    }
+
+    pub fn is_git(&self) -> bool {
+        self.eq(&HistoryBackend::Git)
+    }
+}
+
+fn git(root: &camino::Utf8Path, args: &[&str]) -> fastn_core::Result<String> {
+    let output = std::process::Command::new("git")
+        .args(args)
+        .current_dir(root)
+        .output()
+        .map_err(|e| fastn_core::Error::UsageError {
+            message: format!("failed to run git: {}", e),
+        })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        return fastn_core::usage_error(format!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            if stderr.trim().is_empty() {
+                stdout.trim()
+            } else {
+                stderr.trim()
+            }
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// For git commands that answer using the exit code, like `git diff --quiet`.
+fn git_succeeds(root: &camino::Utf8Path, args: &[&str]) -> bool {
+    std::process::Command::new("git")
+        .args(args)
+        .current_dir(root)
+        .output()
+        .map(|v| v.status.success())
+        .unwrap_or(false)
+}
+
+fn ensure_repository(root: &camino::Utf8Path) -> fastn_core::Result<()> {
+    if !git_succeeds(root, &["rev-parse", "--is-inside-work-tree"]) {
+        return fastn_core::usage_error(format!(
+            "The package uses `history-backend: git`, but {} is not in a git repository, create \
+            one using `git init`",
+            root
+        ));
+    }
+    Ok(())
+}
+
+/// The given files, or the whole package, without the fastn folders.
+fn pathspecs(files: &[String]) -> Vec<String> {
+    let mut pathspecs = if files.is_empty() {
+        vec![".".to_string()]
+    } else {
+        files.to_vec()
+    };
+    pathspecs.extend(
+        FASTN_FOLDERS
+            .iter()
+            .map(|folder| format!(":(exclude){}", folder)),
+    );
+    pathspecs
+}
+
+fn git_with_pathspecs(
+    root: &camino::Utf8Path,
+    args: &[&str],
+    files: &[String],
+) -> fastn_core::Result<String> {
+    let pathspecs = pathspecs(files);
+    let mut args = args.to_vec();
+    args.push("--");
+    args.extend(pathspecs.iter().map(String::as_str));
+    git(root, args.as_slice())
+}
+
+fn has_head(root: &camino::Utf8Path) -> bool {
+    git_succeeds(root, &["rev-parse", "--quiet", "--verify", "HEAD"])
+}
+
+fn is_merging(root: &camino::Utf8Path) -> bool {
+    git_succeeds(root, &["rev-parse", "--quiet", "--verify", "MERGE_HEAD"])
+}
+
+fn current_branch(root: &camino::Utf8Path) -> fastn_core::Result<String> {
+    match git(root, &["symbolic-ref", "--quiet", "--short", "HEAD"]) {
+        Ok(branch) => Ok(branch.trim().to_string()),
+        Err(_) => fastn_core::usage_error("Not on a branch, checkout a branch first".to_string()),
+    }
+}
+
+/// The files changed since the last commit, with paths relative to the package root.
+pub(crate) fn changed_files(
+    root: &camino::Utf8Path,
+    files: &[String],
+) -> fastn_core::Result<Vec<(String, FileStatus)>> {
+    let mut changed_files = std::collections::BTreeMap::new();
+    let untracked = if has_head(root) {
+        let diff = git_with_pathspecs(
+            root,
+            &[
+                "diff",
+                "--name-status",
+                "--no-renames",
+                "--relative",
+                "HEAD",
+            ],
+            files,
+        )?;
+        for line in diff.lines() {
+            if let Some((status, file)) = line.split_once('\t') {
+                let status = match status {
+                    "A" => FileStatus::Added,
+                    "D" => FileStatus::Deleted,
+                    _ => FileStatus::Modified,
+                };
+                changed_files.insert(file.to_string(), status);
+            }
+        }
+        // the files with conflicts are only unmerged in the index, not when compared with `HEAD`,
+        // and they stay unmerged till they are added, so the conflict markers are checked too
+        let unmerged = git_with_pathspecs(
+            root,
+            &["diff", "--name-only", "--relative", "--diff-filter=U"],
+            files,
+        )?;
+        for file in unmerged.lines() {
+            let has_conflict_markers = std::fs::read_to_string(root.join(file))
+                .map(|content| {
+                    content
+                        .lines()
+                        .any(|line| line.starts_with("<<<<<<< ") || line.starts_with(">>>>>>> "))
+                })
+                .unwrap_or(false);
+            if has_conflict_markers {
+                changed_files.insert(file.to_string(), FileStatus::Conflicted);
+            }
+        }
+        git_with_pathspecs(root, &["ls-files", "--others", "--exclude-standard"], files)?
+    } else {
+        // nothing is committed yet
+        git_with_pathspecs(
+            root,
+            &["ls-files", "--cached", "--others", "--exclude-standard"],
+            files,
+        )?
+    };
+    for file in untracked.lines() {
+        changed_files.insert(file.to_string(), FileStatus::Added);
+    }
+    Ok(changed_files.into_iter().collect())
+}
+
+/// Commits the changes to the given files, or to the whole package. Returns the short hash of the
+/// commit, `None` if there was nothing to commit.
+///
+/// `author` is the identity of the signed in user who made the changes, e.g. `github: amitu`, the
+/// commit is by whoever git is set up for otherwise. If git is not set up, like on a server, the
+/// author commits it.
+pub(crate) fn commit(
+    root: &camino::Utf8Path,
+    files: &[String],
+    message: &str,
+    author: Option<&str>,
+) -> fastn_core::Result<Option<String>> {
+    ensure_repository(root)?;
+    git_with_pathspecs(root, &["add", "--all"], files)?;
+
+    let mut args = vec![];
+    let committer;
+    if user(root).is_none() || config_value(root, "user.email").is_none() {
+        committer = format!("user.name={}", author.unwrap_or("fastn"));
+        args.extend(["-c", committer.as_str(), "-c", "user.email="]);
+    }
+    args.extend(["commit", "--quiet"]);
+    // an identity is not an email, git is fine with an empty one
+    let author = author.map(|v| format!("{} <>", v));
+    if let Some(ref author) = author {
+        args.extend(["--author", author.as_str()]);
+    }
+
+    if is_merging(root) {
+        // a merge has to be concluded with all the changes, git does not allow a partial commit
+        args.push("--no-edit");
+        git(root, args.as_slice())?;
+    } else {
+        let pathspecs = pathspecs(files);
+        let mut diff = vec!["diff", "--cached", "--quiet", "--"];
+        diff.extend(pathspecs.iter().map(String::as_str));
+        if git_succeeds(root, diff.as_slice()) {
+            return Ok(None);
+        }
+        args.extend(["--message", message]);
+        git_with_pathspecs(root, args.as_slice(), files)?;
+    }
+    Ok(Some(
+        git(root, &["rev-parse", "--short", "HEAD"])?
+            .trim()
+            .to_string(),
+    ))
+}
+
+/// `commit()` for the request handlers, git runs on the blocking thread pool so it does not hold
+/// up the server.
+pub(crate) async fn commit_async(
+    root: &camino::Utf8Path,
+    files: Vec<String>,
+    message: String,
+    author: Option<String>,
+) -> fastn_core::Result<Option<String>> {
+    let root = root.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        commit(&root, files.as_slice(), message.as_str(), author.as_deref())
+    })
+    .await
+    .map_err(|e| fastn_core::Error::GenericError(format!("failed to run git: {}", e)))?
+}
+
+/// The `user.name` git uses in `root`, the identity of whoever runs fastn there, if git is set up
+pub(crate) fn user(root: &camino::Utf8Path) -> Option<String> {
+    config_value(root, "user.name")
+}
+
+fn config_value(root: &camino::Utf8Path, key: &str) -> Option<String> {
+    git(root, &["config", key])
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// The content of a file at a commit or branch, `None` if the file is not there.
+pub(crate) fn read_file_at(
+    root: &camino::Utf8Path,
+    rev: &str,
+    file: &str,
+) -> fastn_core::Result<Option<String>> {
+    // `./` makes the path relative to the package root instead of the repository root
+    let object = format!("{}:./{}", rev, file);
+    if !git_succeeds(root, &["cat-file", "-e", object.as_str()]) {
+        return Ok(None);
+    }
+    git(root, &["show", object.as_str()]).map(Some)
+}
+
+pub(crate) fn cr_branch(cr_number: usize) -> String {
+    format!("cr-{}", cr_number)
+}
+
+pub(crate) fn ensure_on_cr_branch(
+    root: &camino::Utf8Path,
+    cr_number: usize,
+) -> fastn_core::Result<()> {
+    let branch = cr_branch(cr_number);
+    if !current_branch(root)?.eq(&branch) {
+        return fastn_core::usage_error(format!(
+            "CR#{} is the branch `{}`, switch to it using `git checkout {}`",
+            cr_number, branch, branch
+        ));
+    }
+    Ok(())
+}
+
+/// The branch the CR was created from, it is what `main` means for the CR.
+fn base_branch(root: &camino::Utf8Path, cr_number: usize) -> fastn_core::Result<String> {
+    let key = format!("branch.{}.fastn-base", cr_branch(cr_number));
+    match git(root, &["config", "--get", key.as_str()]) {
+        Ok(branch) => Ok(branch.trim().to_string()),
+        Err(_) => fastn_core::usage_error(format!("CR#{} doesn't exist", cr_number)),
+    }
+}
+
+/// The files changed in the CR branch since it was created.
+pub(crate) fn cr_files(
+    root: &camino::Utf8Path,
+    cr_number: usize,
+) -> fastn_core::Result<Vec<String>> {
+    let range = format!(
+        "{}...{}",
+        base_branch(root, cr_number)?,
+        cr_branch(cr_number)
+    );
+    Ok(git_with_pathspecs(
+        root,
+        &[
+            "diff",
+            "--name-only",
+            "--no-renames",
+            "--relative",
+            range.as_str(),
+        ],
+        &[],
+    )?
+    .lines()
+    // `-/` has the meta files of the CR
+    .filter(|v| !v.starts_with("-/"))
+    .map(ToString::to_string)
+    .collect())
+}
+
+pub(crate) async fn sync(
+    config: &fastn_core::Config,
+    files: Option<Vec<String>>,
+) -> fastn_core::Result<()> {
+    let root = config.root.as_path();
+    ensure_repository(root)?;
+    let files = files.unwrap_or_default();
+
+    let changed_files = changed_files(root, files.as_slice())?;
+    let conflicted = changed_files
+        .iter()
+        .filter(|(_, status)| status.eq(&FileStatus::Conflicted))
+        .map(|(file, _)| file.as_str())
+        .collect::<Vec<_>>();
+    if !conflicted.is_empty() {
+        return fastn_core::usage_error(format!(
+            "Resolve the conflicts in {} before syncing",
+            conflicted.join(", ")
+        ));
+    }
+
+    let message = match changed_files.as_slice() {
+        [(file, FileStatus::Added)] => format!("Add {}", file),
+        [(file, FileStatus::Deleted)] => format!("Delete {}", file),
+        [(file, _)] => format!("Update {}", file),
+        _ => format!(
+            "Sync {} files\n\n{}",
+            changed_files.len(),
+            changed_files
+                .iter()
+                .map(|(file, status)| format!("{:?}: {}", status, file))
+                .collect::<Vec<_>>()
+                .join("\n")
+        ),
+    };
+    for (file, status) in changed_files.iter() {
+        println!("{:?}: {}", status, file);
+    }
+    let commit = commit(root, files.as_slice(), message.as_str(), None)?;
+    if let Some(ref commit) = commit {
+        println!("Committed {}", commit);
+    }
+
+    // only when the branch tracks a remote branch, otherwise the history is local
+    match git(
+        root,
+        &[
+            "rev-parse",
+            "--abbrev-ref",
+            "--symbolic-full-name",
+            "@{upstream}",
+        ],
+    ) {
+        Ok(upstream) => {
+            git(root, &["pull", "--quiet", "--no-rebase", "--no-edit"])?;
+            git(root, &["push", "--quiet"])?;
+            println!("Synced with {}", upstream.trim());
+        }
+        Err(_) if commit.is_none() => println!("Nothing to sync, clean working tree"),
+        Err(_) => {}
+    }
+    Ok(())
+}
+
+pub(crate) async fn status(
+    config: &fastn_core::Config,
+    source: Option<&str>,
+) -> fastn_core::Result<()> {
+    ensure_repository(&config.root)?;
+    let files = source.map(|v| vec![v.to_string()]).unwrap_or_default();
+    let changed_files = changed_files(&config.root, files.as_slice())?;
+    if changed_files.is_empty() {
+        println!("Nothing to sync, clean working tree");
+    }
+    for (file, status) in changed_files {
+        println!("{:?}: {}", status, file);
+    }
+    Ok(())
+}
+
+pub(crate) async fn diff(
+    config: &fastn_core::Config,
+    files: Option<Vec<String>>,
+) -> fastn_core::Result<()> {
+    ensure_repository(&config.root)?;
+    if !has_head(&config.root) {
+        // nothing is committed yet, so everything is new
+        return Ok(());
+    }
+    // git prints the diff itself, so it can use colors and the pager settings of the user
+    let pathspecs = pathspecs(files.unwrap_or_default().as_slice());
+    let status = std::process::Command::new("git")
+        .args(["diff", "--relative", "HEAD", "--"])
+        .args(pathspecs)
+        .current_dir(&config.root)
+        .status()?;
+    if !status.success() {
+        return fastn_core::usage_error("`git diff` failed".to_string());
+    }
+    Ok(())
+}
+
+/// Creates the branch `cr-<number>` from the current branch and switches to it. The meta files of
+/// the CR are the first commit on it.
+pub(crate) async fn create_cr(
+    config: &fastn_core::Config,
+    title: Option<&str>,
+) -> fastn_core::Result<()> {
+    let root = config.root.as_path();
+    ensure_repository(root)?;
+    if !has_head(root) {
+        return fastn_core::usage_error(
+            "Nothing is synced yet, sync the package before creating a CR".to_string(),
+        );
+    }
+    let base = current_branch(root)?;
+
+    let cr_number = git(
+        root,
+        &[
+            "for-each-ref",
+            "--format=%(refname:short)",
+            "refs/heads/cr-*",
+        ],
+    )?
+    .lines()
+    .filter_map(|v| v.strip_prefix("cr-").and_then(|v| v.parse::<usize>().ok()))
+    .max()
+    .unwrap_or_default()
+        + 1;
+    let branch = cr_branch(cr_number);
+
+    git(root, &["checkout", "--quiet", "-b", branch.as_str()])?;
+    git(
+        root,
+        &[
+            "config",
+            format!("branch.{}.fastn-base", branch).as_str(),
+            base.as_str(),
+        ],
+    )?;
+
+    let cr_meta = fastn_core::cr::CRMeta {
+        title: title
+            .map(ToString::to_string)
+            .unwrap_or(format!("CR#{cr_number}")),
+        cr_number,
+        open: true,
+        author: None,
+    };
+    fastn_core::cr::create_cr_meta(config, &cr_meta).await?;
+    fastn_core::cr::create_cr_about(config, &cr_meta).await?;
+    commit(
+        root,
+        &[format!("{}/-", fastn_core::cr::cr_path(cr_number))],
+        format!("Create CR#{}: {}", cr_number, cr_meta.title).as_str(),
+        None,
+    )?;
+
+    println!(
+        "Created CR#{} on the branch `{}`, from `{}`",
+        cr_number, branch, base
+    );
+    Ok(())
+}
+
+/// `main` is the branch the CR was created from. With a file only that file is taken from `src`,
+/// otherwise the branches are merged.
+pub(crate) async fn merge(
+    config: &fastn_core::Config,
+    src: &str,
+    dest: &str,
+    file: Option<&str>,
+) -> fastn_core::Result<()> {
+    let root = config.root.as_path();
+    ensure_repository(root)?;
+
+    let (src_branch, dest_branch) = match (src, dest) {
+        ("main", dest) => {
+            let dest = dest.parse::<usize>()?;
+            (base_branch(root, dest)?, cr_branch(dest))
+        }
+        (src, "main") => {
+            let src = src.parse::<usize>()?;
+            fastn_core::review::check_approvals(config, src).await?;
+            (cr_branch(src), base_branch(root, src)?)
+        }
+        (src, dest) => (
+            cr_branch(src.parse::<usize>()?),
+            cr_branch(dest.parse::<usize>()?),
+        ),
+    };
+
+    if !changed_files(root, &[])?.is_empty() {
+        return fastn_core::usage_error(
+            "There are un-synced changes, sync them before merging".to_string(),
+        );
+    }
+    git(root, &["checkout", "--quiet", dest_branch.as_str()])?;
+
+    match file {
+        Some(file) => {
+            git(
+                root,
+                &[
+                    "checkout",
+                    src_branch.as_str(),
+                    "--",
+                    format!("./{}", file).as_str(),
+                ],
+            )?;
+            commit(
+                root,
+                &[file.to_string()],
+                format!("Merge {} from {} into {}", file, src_branch, dest_branch).as_str(),
+                None,
+            )?;
+        }
+        None => {
+            let message = format!("Merge {} into {}", src_branch, dest_branch);
+            if let Err(e) = git(
+                root,
+                &[
+                    "merge",
+                    "--quiet",
+                    "--no-ff",
+                    "--message",
+                    message.as_str(),
+                    src_branch.as_str(),
+                ],
+            ) {
+                if is_merging(root) {
+                    return fastn_core::usage_error(format!(
+                        "Merging {} into {} has conflicts, resolve them and run `fastn sync`",
+                        src_branch, dest_branch
+                    ));
+                }
+                return Err(e);
+            }
+        }
+    }
+
+    println!("Merged {} into {}", src_branch, dest_branch);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::FileStatus;
+
+    fn git(dir: &camino::Utf8Path, args: &[&str]) -> String {
+        super::git(dir, args).unwrap().trim().to_string()
+    }
+
+    fn write(root: &camino::Utf8Path, file: &str, content: &str) {
+        let path = root.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    /// A repository with the package in `package/`, so paths relative to the package root are
+    /// tested too.
+    fn repository(name: &str) -> (camino::Utf8PathBuf, camino::Utf8PathBuf) {
+        let base = camino::Utf8PathBuf::from_path_buf(std::env::temp_dir())
+            .unwrap()
+            .join(format!("fastn-git-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&base);
+        std::fs::create_dir_all(&base).unwrap();
+        git(&base, &["init", "--quiet"]);
+        git(&base, &["symbolic-ref", "HEAD", "refs/heads/main"]);
+        git(&base, &["config", "user.name", "test"]);
+        git(&base, &["config", "user.email", "test@example.com"]);
+        let root = base.join("package");
+        std::fs::create_dir_all(&root).unwrap();
+        (base, root)
+    }
+
+    #[test]
+    fn history_backend() {
+        assert_eq!(
+            super::HistoryBackend::from_header(None).unwrap(),
+            super::HistoryBackend::Fastn
+        );
+        assert!(super::HistoryBackend::from_header(Some("git"))
+            .unwrap()
+            .is_git());
+        assert!(super::HistoryBackend::from_header(Some("svn")).is_err());
+    }
+
+    #[test]
+    fn user() {
+        let (base, root) = repository("user");
+        assert_eq!(super::user(&root).as_deref(), Some("test"));
+        std::fs::remove_dir_all(base).unwrap();
+    }
+
+    #[test]
+    fn changed_files_and_commit() {
+        let (base, root) = repository("commit");
+        write(&base, "outside.txt", "not in the package");
+        write(&root, "FASTN.ftd", "-- import: fastn");
+        write(&root, "index.ftd", "v1");
+        write(&root, ".build/index.html", "built");
+        write(&root, ".history/index.1.ftd", "v1");
+
+        // nothing is committed yet
+        assert_eq!(
+            super::changed_files(&root, &[]).unwrap(),
+            vec![
+                ("FASTN.ftd".to_string(), FileStatus::Added),
+                ("index.ftd".to_string(), FileStatus::Added),
+            ]
+        );
+        assert!(super::commit(&root, &[], "first", None).unwrap().is_some());
+        assert!(super::changed_files(&root, &[]).unwrap().is_empty());
+        assert_eq!(super::commit(&root, &[], "nothing", None).unwrap(), None);
+        // the fastn folders and the files outside the package are never committed
+        assert_eq!(
+            git(&base, &["ls-files"]),
+            "package/FASTN.ftd\npackage/index.ftd"
+        );
+
+        write(&root, "index.ftd", "v2");
+        write(&root, "new.ftd", "new");
+        std::fs::remove_file(root.join("FASTN.ftd")).unwrap();
+        assert_eq!(
+            super::changed_files(&root, &[]).unwrap(),
+            vec![
+                ("FASTN.ftd".to_string(), FileStatus::Deleted),
+                ("index.ftd".to_string(), FileStatus::Modified),
+                ("new.ftd".to_string(), FileStatus::Added),
+            ]
+        );
+
+        // only the given files are committed
+        super::commit(&root, &["index.ftd".to_string()], "second", None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            super::read_file_at(&root, "HEAD", "index.ftd").unwrap(),
+            Some("v2".to_string())
+        );
+        assert_eq!(
+            super::read_file_at(&root, "HEAD~1", "index.ftd").unwrap(),
+            Some("v1".to_string())
+        );
+        assert_eq!(super::read_file_at(&root, "HEAD", "new.ftd").unwrap(), None);
+        assert_eq!(
+            super::changed_files(&root, &[]).unwrap(),
+            vec![
+                ("FASTN.ftd".to_string(), FileStatus::Deleted),
+                ("new.ftd".to_string(), FileStatus::Added),
+            ]
+        );
+
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn commit_author() {
+        let (base, root) = repository("author");
+        write(&root, "index.ftd", "v1");
+        super::commit(&root, &[], "first", Some("github: alice")).unwrap();
+        assert_eq!(
+            git(&root, &["log", "--format=%an|%cn"]),
+            "github: alice|test"
+        );
+
+        // git is not set up, the author commits it
+        git(&base, &["config", "user.name", ""]);
+        write(&root, "index.ftd", "v2");
+        super::commit(&root, &[], "second", Some("github: bob")).unwrap();
+        assert_eq!(
+            git(&root, &["log", "-1", "--format=%an|%cn"]),
+            "github: bob|github: bob"
+        );
+
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn cr_branch_files() {
+        let (base, root) = repository("cr");
+        write(&root, "index.ftd", "v1");
+        write(&root, "other.ftd", "v1");
+        super::commit(&root, &[], "first", None).unwrap();
+
+        // what `create_cr` does
+        git(&root, &["checkout", "--quiet", "-b", "cr-1"]);
+        git(&root, &["config", "branch.cr-1.fastn-base", "main"]);
+        super::ensure_on_cr_branch(&root, 1).unwrap();
+        assert!(super::ensure_on_cr_branch(&root, 2).is_err());
+        assert!(super::cr_files(&root, 2).is_err());
+
+        write(&root, "-/1/-/about.ftd", "about");
+        write(&root, "index.ftd", "v2");
+        super::commit(&root, &[], "edit", None).unwrap();
+        // changes made to the base later are not changes of the CR
+        git(&root, &["checkout", "--quiet", "main"]);
+        write(&root, "other.ftd", "v2");
+        super::commit(&root, &[], "other", None).unwrap();
+
+        assert_eq!(
+            super::cr_files(&root, 1).unwrap(),
+            vec!["index.ftd".to_string()]
+        );
+        assert_eq!(super::current_branch(&root).unwrap(), "main");
+
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+}
@@ -73,6 +73,37 @@ impl fastn_core::Config {
         FileHistory::from_ftd(history_content.as_str())
     }
 
+    /// The edits of `file`, newest first.
+    pub async fn get_file_history(&self, file: &str) -> fastn_core::Result<FileHistory> {
+        let history = if self.history_file().exists() {
+            self.get_history().await?
+        } else {
+            vec![]
+        };
+        history
+            .into_iter()
+            .find(|v| v.filename.eq(file))
+            .ok_or_else(|| fastn_core::Error::UsageError {
+                message: format!("No history found for `{}`", file),
+            })
+    }
+
+    /// The content of `file` as it was at `version`.
+    pub async fn get_file_at_version(
+        &self,
+        file: &str,
+        version: i32,
+    ) -> fastn_core::Result<Vec<u8>> {
+        let file_history = self.get_file_history(file).await?;
+        let file_edit = file_history.get_file_edit(version)?;
+        if file_edit.is_deleted() {
+            return Err(fastn_core::Error::UsageError {
+                message: format!("`{}` was deleted in version {}", file, version),
+            });
+        }
+        Ok(tokio::fs::read(self.history_path(file, version)).await?)
+    }
+
     pub async fn get_remote_manifest(
         &self,
         with_deleted: bool,
@@ -144,7 +175,16 @@ impl FileHistory {
             .collect())
     }
 
-    fn get_latest_file_edit(&self, with_deleted: bool) -> Option<FileEdit> {
+    pub(crate) fn get_file_edit(&self, version: i32) -> fastn_core::Result<&FileEdit> {
+        self.file_edit
+            .iter()
+            .find(|v| v.version.eq(&version))
+            .ok_or_else(|| fastn_core::Error::UsageError {
+                message: format!("`{}` has no version {}", self.filename, version),
+            })
+    }
+
+    pub(crate) fn get_latest_file_edit(&self, with_deleted: bool) -> Option<FileEdit> {
         for file_edit in self.file_edit.iter() {
             if file_edit.is_deleted() && !with_deleted {
                 return None;
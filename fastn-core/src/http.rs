@@ -304,19 +304,24 @@ pub(crate) fn url_regex() -> regex::Regex {
 
 #[tracing::instrument]
 pub(crate) async fn construct_url_and_get_str(url: &str) -> fastn_core::Result<String> {
-    construct_url_and_return_response(url.to_string(), |f| async move {
-        http_get_str(f.as_str()).await
+    let data = fastn_core::package::cache::fetch(url, || {
+        construct_url_and_return_response(url.to_string(), |f| async move {
+            http_get_str(f.as_str()).await.map(String::into_bytes)
+        })
     })
-    .await
+    .await?;
+    Ok(String::from_utf8(data)?)
 }
 
 #[tracing::instrument]
 pub(crate) async fn construct_url_and_get(url: &str) -> fastn_core::Result<Vec<u8>> {
-    println!("http_download_by_id: {url}");
-    construct_url_and_return_response(
-        url.to_string(),
-        |f| async move { http_get(f.as_str()).await },
-    )
+    fastn_core::package::cache::fetch(url, || {
+        println!("http_download_by_id: {url}");
+        construct_url_and_return_response(
+            url.to_string(),
+            |f| async move { http_get(f.as_str()).await },
+        )
+    })
     .await
 }
 
@@ -1,3 +1,4 @@
+pub(crate) mod package;
 pub mod translation;
 
 type Bundle = fluent::bundle::FluentBundle<
@@ -0,0 +1,338 @@
+//! The Fluent messages of a package, so its documents can be localized without maintaining
+//! translation packages. The messages of a locale are the `.ftl` files in `i18n/<locale>/` of the
+//! package, and are looked up with the `i18n` processor:
+//!
+//! ```ftd
+//! -- import: fastn/processors as pr
+//!
+//! -- string welcome:
+//! $processor$: pr.i18n
+//! id: welcome-user
+//! name: $user-name
+//! ```
+//!
+//! The default locale is the `language` of the package, `en` if it has none. `fastn serve`
+//! serves `/<locale>/<path>` in that locale, else uses the `fastn-locale` cookie or the
+//! `Accept-Language` of the request. `fastn build` builds every other locale in
+//! `.build/<locale>/`.
+
+pub(crate) const I18N_DIR: &str = "i18n";
+pub(crate) const COOKIE: &str = "fastn-locale";
+
+/// The locales with messages in the package, sorted
+pub(crate) fn locales(config: &fastn_core::Config) -> Vec<String> {
+    let mut locales = std::fs::read_dir(config.root.join(I18N_DIR))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .filter_map(|e| e.file_name().into_string().ok())
+        .collect::<Vec<_>>();
+    locales.sort();
+    locales
+}
+
+pub(crate) fn default_locale(config: &fastn_core::Config) -> String {
+    config
+        .package
+        .language
+        .clone()
+        .unwrap_or_else(|| "en".to_string())
+}
+
+/// The locales `fastn build` builds in `.build/<locale>/`
+pub(crate) fn other_locales(config: &fastn_core::Config) -> Vec<String> {
+    let default = default_locale(config);
+    locales(config)
+        .into_iter()
+        .filter(|l| !l.eq_ignore_ascii_case(default.as_str()))
+        .collect()
+}
+
+/// `hi/blog/` -> `("hi", "blog/")` if the package has messages for `hi`
+pub(crate) fn strip_locale(config: &fastn_core::Config, path: &str) -> Option<(String, String)> {
+    let (first, rest) = path.split_once('/').unwrap_or((path, ""));
+    if first.is_empty() {
+        return None;
+    }
+    locales(config)
+        .into_iter()
+        .find(|l| l.eq(first))
+        .map(|locale| (locale, rest.to_string()))
+}
+
+/// The locale set by `fastn serve` or `fastn build`, else the one the request asks for, else the
+/// default locale.
+pub(crate) fn current_locale(config: &fastn_core::Config) -> String {
+    if let Some(ref locale) = config.locale {
+        return locale.to_string();
+    }
+    config
+        .request
+        .as_ref()
+        .and_then(|req| negotiate(locales(config).as_slice(), req))
+        .unwrap_or_else(|| default_locale(config))
+}
+
+fn negotiate(locales: &[String], req: &fastn_core::http::Request) -> Option<String> {
+    let find = |tag: &str| {
+        let primary = tag.split(['-', '_']).next().unwrap_or(tag);
+        locales
+            .iter()
+            .find(|l| l.eq_ignore_ascii_case(tag))
+            .or_else(|| locales.iter().find(|l| l.eq_ignore_ascii_case(primary)))
+            .or_else(|| {
+                locales.iter().find(|l| {
+                    l.split(['-', '_'])
+                        .next()
+                        .is_some_and(|p| p.eq_ignore_ascii_case(primary))
+                })
+            })
+            .cloned()
+    };
+
+    if let Some(locale) = req.cookie(COOKIE).and_then(|v| find(v.as_str())) {
+        return Some(locale);
+    }
+    let header = req
+        .headers()
+        .get("accept-language")
+        .and_then(|v| v.to_str().ok())?;
+    accept_language(header)
+        .iter()
+        .find_map(|tag| find(tag.as_str()))
+}
+
+/// The language tags of an `Accept-Language` header, most preferred first
+fn accept_language(header: &str) -> Vec<String> {
+    let mut tags = header
+        .split(',')
+        .filter_map(|v| {
+            let mut parts = v.split(';');
+            let tag = parts.next()?.trim();
+            let q = parts
+                .find_map(|p| p.trim().strip_prefix("q="))
+                .and_then(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (!tag.is_empty() && tag != "*" && q > 0.0).then(|| (tag.to_string(), q))
+        })
+        .collect::<Vec<_>>();
+    // stable, so tags with the same weight stay in the order they are given
+    tags.sort_by(|a, b| b.1.total_cmp(&a.1));
+    tags.into_iter().map(|(tag, _)| tag).collect()
+}
+
+/// The message `id` in `locale`, or in the default locale if `locale` does not have it.
+pub(crate) async fn message(
+    config: &fastn_core::Config,
+    locale: &str,
+    id: &str,
+    args: &fluent::FluentArgs<'_>,
+) -> fastn_core::Result<String> {
+    let default = default_locale(config);
+    let mut tried = vec![];
+    for locale in [locale, default.as_str()] {
+        if tried.contains(&locale) {
+            continue;
+        }
+        tried.push(locale);
+        let bundle = match bundle(config, locale).await? {
+            Some(bundle) => bundle,
+            None => continue,
+        };
+        let pattern = match bundle.get_message(id).and_then(|m| m.value()) {
+            Some(pattern) => pattern,
+            None => continue,
+        };
+        let mut errors = vec![];
+        let value = bundle.format_pattern(pattern, Some(args), &mut errors);
+        if !errors.is_empty() {
+            return fastn_core::usage_error(format!(
+                "Failed to format the message `{}` of {}: {:?}",
+                id, locale, errors
+            ));
+        }
+        return Ok(value.to_string());
+    }
+    fastn_core::usage_error(format!(
+        "No message `{}` in {}",
+        id,
+        tried
+            .iter()
+            .map(|l| format!("{}/{}/", I18N_DIR, l))
+            .collect::<Vec<_>>()
+            .join(" or ")
+    ))
+}
+
+/// All the `.ftl` files of `locale`, `None` if the package has no messages for it
+async fn bundle(
+    config: &fastn_core::Config,
+    locale: &str,
+) -> fastn_core::Result<Option<fluent::FluentBundle<fluent::FluentResource>>> {
+    let dir = config.root.join(I18N_DIR).join(locale);
+    if !dir.is_dir() {
+        return Ok(None);
+    }
+    let language = match locale.parse() {
+        Ok(language) => language,
+        Err(e) => {
+            return fastn_core::usage_error(format!(
+                "{} is not a language identifier: {:?}",
+                locale, e
+            ))
+        }
+    };
+    let mut files = std::fs::read_dir(&dir)?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "ftl"))
+        .collect::<Vec<_>>();
+    files.sort();
+
+    let mut bundle = fluent::FluentBundle::new(vec![language]);
+    // the isolation marks around arguments would end up in the html
+    bundle.set_use_isolating(false);
+    for file in files {
+        let content = tokio::fs::read_to_string(&file).await?;
+        let resource = fluent::FluentResource::try_new(content).map_err(|(_, errors)| {
+            fastn_core::Error::UsageError {
+                message: format!("Failed to parse {}: {:?}", file.display(), errors),
+            }
+        })?;
+        if let Err(errors) = bundle.add_resource(resource) {
+            return fastn_core::usage_error(format!(
+                "{} redefines messages: {:?}",
+                file.display(),
+                errors
+            ));
+        }
+    }
+    Ok(Some(bundle))
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    fn request(headers: &[(&str, &str)]) -> fastn_core::http::Request {
+        let mut req = actix_web::test::TestRequest::get();
+        for header in headers {
+            req = req.insert_header(*header);
+        }
+        fastn_core::http::Request::from_actix(req.to_http_request(), Default::default())
+    }
+
+    fn locales(locales: &[&str]) -> Vec<String> {
+        locales.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn accept_language() {
+        assert_eq!(
+            super::accept_language("fr-CH, fr;q=0.9, en;q=0.8, de;q=0.7, *;q=0.5"),
+            vec!["fr-CH", "fr", "en", "de"]
+        );
+        assert_eq!(
+            super::accept_language("en;q=0.5, hi, ta;q=0, mr;q=0.5"),
+            vec!["hi", "en", "mr"]
+        );
+        assert!(super::accept_language("").is_empty());
+    }
+
+    #[test]
+    fn negotiate() {
+        let available = locales(&["en", "hi", "pt-BR"]);
+        let negotiate = |headers: &[(&str, &str)]| super::negotiate(&available, &request(headers));
+
+        assert_eq!(negotiate(&[]), None);
+        assert_eq!(
+            negotiate(&[("accept-language", "ta, hi-IN;q=0.8, en;q=0.5")]),
+            Some("hi".to_string())
+        );
+        // the primary language matches a locale with a region
+        assert_eq!(
+            negotiate(&[("accept-language", "pt-PT")]),
+            Some("pt-BR".to_string())
+        );
+        assert_eq!(negotiate(&[("accept-language", "ta")]), None);
+        // the cookie wins over the header, if the package has messages for it
+        assert_eq!(
+            negotiate(&[("cookie", "fastn-locale=HI"), ("accept-language", "en")]),
+            Some("hi".to_string())
+        );
+        assert_eq!(
+            negotiate(&[("cookie", "fastn-locale=ta"), ("accept-language", "en")]),
+            Some("en".to_string())
+        );
+    }
+
+    async fn package(name: &str) -> (camino::Utf8PathBuf, fastn_core::Config) {
+        let root: camino::Utf8PathBuf = std::env::temp_dir()
+            .join(format!("fastn-i18n-{}-{}", name, std::process::id()))
+            .try_into()
+            .unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+        for (path, content) in [
+            (
+                "FASTN.ftd",
+                "-- import: fastn\n\n-- fastn.package: www.example.com\n",
+            ),
+            (
+                "i18n/en/main.ftl",
+                "welcome = Welcome, { $name }!\nbye = Bye\n",
+            ),
+            ("i18n/hi/main.ftl", "welcome = स्वागत है, { $name }!\n"),
+            ("i18n/hi/notes.txt", "not messages"),
+        ] {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        let config = fastn_core::Config::read(Some(root.to_string()), false, None)
+            .await
+            .unwrap();
+        (root, config)
+    }
+
+    #[tokio::test]
+    async fn package_locales() {
+        let (root, config) = package("locales").await;
+        let strip = |path: &str| super::strip_locale(&config, path);
+
+        assert_eq!(super::locales(&config), locales(&["en", "hi"]));
+        assert_eq!(super::default_locale(&config), "en");
+        assert_eq!(super::other_locales(&config), locales(&["hi"]));
+        assert_eq!(super::current_locale(&config), "en");
+        assert_eq!(
+            strip("hi/blog/"),
+            Some(("hi".to_string(), "blog/".to_string()))
+        );
+        assert_eq!(strip("hi"), Some(("hi".to_string(), "".to_string())));
+        assert_eq!(strip("history/"), None);
+        assert_eq!(strip(""), None);
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn message() {
+        let (root, config) = package("message").await;
+        let mut args = fluent::FluentArgs::new();
+        args.set("name", "Asha");
+
+        let welcome = super::message(&config, "hi", "welcome", &args).await;
+        // `hi` does not have `bye`, the message of the default locale is used
+        let bye = super::message(&config, "hi", "bye", &args).await;
+        let unknown_locale = super::message(&config, "ta", "welcome", &args).await;
+        let missing = super::message(&config, "hi", "missing", &args).await;
+        std::fs::remove_dir_all(root).unwrap();
+
+        assert_eq!(welcome.unwrap(), "स्वागत है, Asha!");
+        assert_eq!(bye.unwrap(), "Bye");
+        assert_eq!(unknown_locale.unwrap(), "Welcome, Asha!");
+        assert_eq!(
+            missing.unwrap_err().to_string(),
+            "UsageError: No message `missing` in i18n/hi/ or i18n/en/"
+        );
+    }
+}
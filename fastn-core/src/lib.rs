@@ -2,6 +2,7 @@ extern crate self as fastn_core;
 
 #[macro_use]
 pub mod utils;
+pub mod access_log;
 mod apis;
 mod auto_import;
 mod cache;
@@ -12,8 +13,13 @@ mod cr;
 mod doc;
 mod file;
 mod font;
+mod git;
 mod history;
+mod merge;
 mod package;
+mod review;
+mod tokens;
+mod translate;
 pub(crate) mod watcher;
 #[macro_use]
 mod http;
@@ -22,6 +28,7 @@ mod ds;
 mod error;
 mod i18n;
 pub mod library;
+pub mod metrics;
 mod proxy;
 pub mod sitemap;
 mod snapshot;
@@ -32,6 +39,7 @@ mod translation;
 mod version;
 // mod wasm;
 mod library2022;
+mod lsp;
 mod workspace;
 
 pub(crate) use auto_import::AutoImport;
@@ -41,7 +49,7 @@ pub use commands::{
     mark_resolved::mark_resolved, mark_upto_date::mark_upto_date, merge::merge, query::query,
     resolve_conflict::resolve_conflict, revert::revert, rm::rm, serve::listen,
     start_tracking::start_tracking, status::status, sync2::sync2,
-    translation_status::translation_status, update::update,
+    translation_status::translation_status, update::update, vendor::vendor,
 };
 pub use config::{Config, FTDEdition};
 pub use error::Error;
@@ -51,8 +59,8 @@ pub(crate) use font::Font;
 pub use library::{FastnLibrary, Library, Library2};
 pub use library2022::Library2022;
 pub(crate) use package::dependency::Dependency;
-pub use package::user_group;
 pub(crate) use package::Package;
+pub use package::{cache as package_cache, publish, user_group};
 pub(crate) use snapshot::Snapshot;
 pub(crate) use tracker::Track;
 pub(crate) use translation::{TranslatedDocument, TranslationData};
@@ -70,6 +78,10 @@ pub fn ftd_html() -> &'static str {
     include_str!("../ftd_2022.html")
 }
 
+fn editor_html() -> &'static str {
+    include_str!("../editor.html")
+}
+
 fn processor_ftd() -> &'static str {
     include_str!("../ftd/processors.ftd")
 }
@@ -143,38 +155,6 @@ fn package_info_about(config: &fastn_core::Config) -> fastn_core::Result<String>
     })
 }
 
-fn package_editor_source(
-    config: &fastn_core::Config,
-    file_name: &str,
-) -> fastn_core::Result<String> {
-    let body_prefix = match config.package.generate_prefix_string(false) {
-        Some(bp) => bp,
-        None => String::new(),
-    };
-    let editor_ftd = indoc::formatdoc! {"
-            {body_prefix}
-    
-            -- import: {package_info_package}/e as pi
-            -- import: fastn/processors as pr
-
-            
-            -- pi.editor:
-            $asts: $asts
-            path: {file_name}
-
-            -- pr.ast list $asts:
-            {processor_marker}: pr.query
-            file: {file_name}
-        ",
-        body_prefix = body_prefix,
-        package_info_package = config.package.name,
-        file_name = file_name,
-        processor_marker = ftd::PROCESSOR_MARKER,
-    };
-
-    Ok(editor_ftd)
-}
-
 fn package_info_editor(
     config: &fastn_core::Config,
     file_name: &str,
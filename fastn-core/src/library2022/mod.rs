@@ -23,6 +23,9 @@ pub struct Library2022 {
     pub translated_data: fastn_core::TranslationData,
     pub base_url: String,
     pub module_package_map: std::collections::BTreeMap<String, String>,
+    /// Set by `fastn check`, processors are not run, they evaluate to an empty value of their
+    /// kind instead, see `placeholder()`.
+    pub skip_processors: bool,
 }
 
 impl Library2022 {
@@ -30,10 +33,12 @@ impl Library2022 {
         &mut self,
         name: &str,
         current_processing_module: &str,
-    ) -> ftd::p1::Result<(String, usize)> {
+    ) -> ftd::interpreter::Result<(String, usize)> {
         match self.get(name, current_processing_module).await {
             Some(v) => Ok(v),
-            None => ftd::p1::utils::parse_error(format!("library not found 1: {}", name), "", 0),
+            None => Err(ftd::interpreter::Error::LibraryNotFound {
+                module: name.to_string(),
+            }),
         }
     }
 
@@ -209,7 +214,12 @@ impl Library2022 {
     }
 
     /// process the $processor$ and return the processor's output
-    #[tracing::instrument(name = "fastn_core::stuck-on-processor", skip_all, err)]
+    #[tracing::instrument(
+        name = "fastn_core::stuck-on-processor",
+        skip_all,
+        fields(processor = processor.as_str()),
+        err
+    )]
     pub async fn process<'a>(
         &'a mut self,
         ast: ftd::ast::AST,
@@ -223,6 +233,9 @@ impl Library2022 {
         );
         let line_number = ast.line_number();
         let (_processor, value, kind) = get_processor_data(ast, doc)?;
+        if self.skip_processors {
+            return placeholder(&kind, doc, line_number);
+        }
         match processor.as_str() {
             "figma-typo-token" => {
                 processor::figma_typography_tokens::process_typography_tokens(value, kind, doc)
@@ -233,8 +246,23 @@ impl Library2022 {
             "figma-cs-token-old" => {
                 processor::figma_tokens::process_figma_tokens_old(value, kind, doc, &self.config)
             }
+            "design-tokens" => {
+                processor::design_tokens::process(value, kind, doc, &self.config).await
+            }
             "http" => processor::http::process(value, kind, doc, &self.config).await,
+            "i18n" => processor::i18n::process(value, kind, doc, &self.config).await,
             "toc" => processor::toc::process(value, kind, doc, &self.config),
+            "heading-outline" => {
+                processor::heading_outline::process(
+                    value,
+                    kind,
+                    doc,
+                    &self.config,
+                    self.document_id.as_str(),
+                    self.markdown.as_ref(),
+                )
+                .await
+            }
             "get-data" => processor::get_data::process(value, kind, doc, &self.config),
             "sitemap" => processor::sitemap::process(value, kind, doc, &self.config),
             "full-sitemap" => {
@@ -302,6 +330,64 @@ impl Library2022 {
     }
 }
 
+/// An empty value of `kind`, optional fields of records are left out and the others are empty too.
+fn placeholder(
+    kind: &ftd::interpreter::Kind,
+    doc: &ftd::interpreter::TDoc,
+    line_number: usize,
+) -> ftd::interpreter::Result<ftd::interpreter::Value> {
+    use ftd::interpreter::{Kind, KindData, PropertyValue, Value};
+
+    Ok(match kind {
+        Kind::String => Value::String {
+            text: Default::default(),
+        },
+        Kind::Integer => Value::Integer { value: 0 },
+        Kind::Decimal => Value::Decimal { value: 0.0 },
+        Kind::Boolean => Value::Boolean { value: false },
+        Kind::Object => Value::Object {
+            values: Default::default(),
+        },
+        Kind::List { kind } => Value::List {
+            data: vec![],
+            kind: KindData::new(kind.as_ref().clone()),
+        },
+        Kind::Optional { kind } => Value::Optional {
+            data: Box::new(None),
+            kind: KindData::new(kind.as_ref().clone()),
+        },
+        Kind::Constant { kind } => return placeholder(kind, doc, line_number),
+        Kind::Record { name } => {
+            let mut fields = ftd::Map::new();
+            for field in doc.get_record(name, line_number)?.fields {
+                let value = match field.value {
+                    Some(value) => value,
+                    None => PropertyValue::Value {
+                        value: placeholder(&field.kind.kind, doc, line_number)?,
+                        is_mutable: false,
+                        line_number,
+                    },
+                };
+                fields.insert(field.name, value);
+            }
+            Value::Record {
+                name: name.to_string(),
+                fields,
+            }
+        }
+        kind => {
+            return Err(ftd::interpreter::Error::ParseError {
+                message: format!(
+                    "a processor returning `{}` can not be checked without running it",
+                    kind.get_name()
+                ),
+                doc_id: doc.name.to_string(),
+                line_number,
+            })
+        }
+    })
+}
+
 fn get_processor_data(
     ast: ftd::ast::AST,
     doc: &mut ftd::interpreter::TDoc,
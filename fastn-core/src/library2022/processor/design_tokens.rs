@@ -0,0 +1,70 @@
+/// `ftd.color-scheme` or `ftd.type-data` from the design tokens in `file`, see `fastn_core::tokens`.
+///
+/// -- ftd.color-scheme my-colors:
+/// $processor$: pr.design-tokens
+/// file: tokens/figma.json
+pub async fn process(
+    value: ftd::ast::VariableValue,
+    kind: ftd::interpreter::Kind,
+    doc: &ftd::interpreter::TDoc<'_>,
+    config: &fastn_core::Config,
+) -> ftd::interpreter::Result<ftd::interpreter::Value> {
+    let line_number = value.line_number();
+    let record_name = match &kind {
+        ftd::interpreter::Kind::Record { name }
+            if name.eq(ftd::interpreter::FTD_COLOR_SCHEME)
+                || name.eq(ftd::interpreter::FTD_TYPE_DATA) =>
+        {
+            name.to_string()
+        }
+        _ => {
+            return ftd::interpreter::utils::e2(
+                format!(
+                    "Expected kind is `ftd.color-scheme` or `ftd.type-data`, found: `{:?}`",
+                    kind
+                ),
+                doc.name,
+                line_number,
+            )
+        }
+    };
+
+    let headers = match value.get_record(doc.name) {
+        Ok(val) => val.2.to_owned(),
+        Err(_e) => ftd::ast::HeaderValues::new(vec![]),
+    };
+    let file = headers
+        .get_optional_string_by_key("file", doc.name, line_number)?
+        .ok_or(ftd::interpreter::Error::ParseError {
+            message: "`file` not found".to_string(),
+            doc_id: doc.name.to_string(),
+            line_number,
+        })?;
+
+    let content = tokio::fs::read_to_string(config.root.join(file.as_str()))
+        .await
+        .map_err(|e| ftd::interpreter::Error::ParseError {
+            message: format!("failed to read {}: {}", file, e),
+            doc_id: doc.name.to_string(),
+            line_number,
+        })?;
+    let json: serde_json::Value = serde_json::from_str(content.as_str()).map_err(|e| {
+        ftd::interpreter::Error::ParseError {
+            message: format!("{} is not json: {}", file, e),
+            doc_id: doc.name.to_string(),
+            line_number,
+        }
+    })?;
+
+    let imported = fastn_core::tokens::import(&json);
+    let (value, what) = if record_name.eq(ftd::interpreter::FTD_COLOR_SCHEME) {
+        (imported.color_scheme, "colors")
+    } else {
+        (imported.type_data, "typography")
+    };
+    value.ok_or(ftd::interpreter::Error::ParseError {
+        message: format!("No {} tokens found in {}", what, file),
+        doc_id: doc.name.to_string(),
+        line_number,
+    })
+}
@@ -0,0 +1,114 @@
+/// Extracts the headings of a document as a `toc-item list`, so the outline of a page can be
+/// shown using the existing toc components.
+///
+/// ```ftd
+/// -- toc-item list outline:
+/// $processor$: heading-outline
+/// document: blog/post.md
+/// ```
+///
+/// `document` is relative to the package root and defaults to the current document. For `.ftd`
+/// documents the markdown bodies of its sections are used, in order.
+pub async fn process(
+    value: ftd::ast::VariableValue,
+    kind: ftd::interpreter::Kind,
+    doc: &ftd::interpreter::TDoc<'_>,
+    config: &fastn_core::Config,
+    document_id: &str,
+    markdown: Option<&(String, String)>,
+) -> ftd::interpreter::Result<ftd::interpreter::Value> {
+    let headers = match value.get_record(doc.name) {
+        Ok(val) => val.2.to_owned(),
+        Err(_e) => ftd::ast::HeaderValues::new(vec![]),
+    };
+    let document = headers
+        .get_optional_string_by_key("document", doc.name, value.line_number())?
+        .unwrap_or_else(|| document_id.to_string());
+
+    let content = match markdown {
+        // the current module is a markdown file, we already have its content
+        Some((file_name, content)) if file_name.eq(document.as_str()) => content.to_string(),
+        _ => tokio::fs::read_to_string(config.root.join(document.trim_start_matches('/')))
+            .await
+            .map_err(|e| ftd::interpreter::Error::ParseError {
+                message: format!("failed to read {}: {}", document, e),
+                doc_id: doc.name.to_string(),
+                line_number: value.line_number(),
+            })?,
+    };
+
+    let headings = if document.ends_with(".ftd") {
+        let sections = ftd::p1::parse(content.as_str(), doc.name).map_err(|e| {
+            ftd::interpreter::Error::ParseError {
+                message: format!("failed to parse {}: {:?}", document, e),
+                doc_id: doc.name.to_string(),
+                line_number: value.line_number(),
+            }
+        })?;
+        let mut bodies = vec![];
+        collect_bodies(sections.as_slice(), &mut bodies);
+        bodies
+            .into_iter()
+            .flat_map(ftd::executor::markup::headings)
+            .collect()
+    } else {
+        ftd::executor::markup::headings(content.as_str())
+    };
+
+    doc.from_json(&outline(headings.as_slice()), &kind, &value)
+}
+
+/// Markdown bodies of `sections`, skipping definitions (`-- component foo:`, `-- string x:`
+/// etc) and code blocks, where a line starting with `#` is not a heading.
+fn collect_bodies<'a>(sections: &'a [ftd::p1::Section], bodies: &mut Vec<&'a str>) {
+    for section in sections {
+        if section.is_commented || section.kind.is_some() || section.name.ends_with("code") {
+            continue;
+        }
+        if let Some(ref body) = section.body {
+            bodies.push(body.value.as_str());
+        }
+        collect_bodies(section.sub_sections.as_slice(), bodies);
+    }
+}
+
+/// Nests every heading under the closest preceding heading of a smaller level.
+fn outline(
+    headings: &[ftd::executor::markup::Heading],
+) -> Vec<fastn_core::library::toc::TocItemCompat> {
+    let mut items = vec![];
+    let mut index = 0;
+    while index < headings.len() {
+        let heading = &headings[index];
+        let end = headings[index + 1..]
+            .iter()
+            .position(|h| h.level <= heading.level)
+            .map(|p| index + 1 + p)
+            .unwrap_or(headings.len());
+        items.push(item(heading, outline(&headings[index + 1..end])));
+        index = end;
+    }
+    items
+}
+
+fn item(
+    heading: &ftd::executor::markup::Heading,
+    children: Vec<fastn_core::library::toc::TocItemCompat>,
+) -> fastn_core::library::toc::TocItemCompat {
+    fastn_core::library::toc::TocItemCompat {
+        url: Some(format!("#{}", heading.id)),
+        number: None,
+        title: Some(heading.title.to_string()),
+        path: None,
+        description: None,
+        bury: false,
+        is_heading: false,
+        font_icon: None,
+        is_disabled: false,
+        is_active: false,
+        is_open: true,
+        image_src: None,
+        children,
+        document: None,
+    }
+}
@@ -0,0 +1,71 @@
+/// The Fluent message `id` of the package in the current locale, see `fastn_core::i18n::package`.
+/// The other headers are the arguments of the message.
+///
+/// -- string welcome:
+/// $processor$: pr.i18n
+/// id: welcome-user
+/// name: $user-name
+pub async fn process(
+    value: ftd::ast::VariableValue,
+    kind: ftd::interpreter::Kind,
+    doc: &ftd::interpreter::TDoc<'_>,
+    config: &fastn_core::Config,
+) -> ftd::interpreter::Result<ftd::interpreter::Value> {
+    let line_number = value.line_number();
+    if !kind.is_string() {
+        return ftd::interpreter::utils::e2(
+            format!("Expected kind is `string`, found: `{:?}`", kind),
+            doc.name,
+            line_number,
+        );
+    }
+    let headers = match value.get_record(doc.name) {
+        Ok(val) => val.2.to_owned(),
+        Err(_e) => ftd::ast::HeaderValues::new(vec![]),
+    };
+    let id = headers
+        .get_optional_string_by_key("id", doc.name, line_number)?
+        .ok_or(ftd::interpreter::Error::ParseError {
+            message: "`id` not found".to_string(),
+            doc_id: doc.name.to_string(),
+            line_number,
+        })?;
+
+    let mut args = fluent::FluentArgs::new();
+    for header in headers.0.iter() {
+        if header.key.eq("id") || header.key.eq(ftd::PROCESSOR_MARKER) {
+            continue;
+        }
+        let value = header.value.string(doc.name)?;
+        let value = if value.starts_with('$') {
+            doc.get_value(header.line_number, value.as_str())?
+                .to_serde_value()
+                .unwrap_or_default()
+        } else {
+            serde_json::Value::String(value)
+        };
+        let value: fluent::FluentValue = match value {
+            serde_json::Value::Number(n) => match n.as_f64() {
+                Some(n) => n.into(),
+                None => n.to_string().into(),
+            },
+            serde_json::Value::String(s) => match s.parse::<f64>() {
+                Ok(n) => n.into(),
+                Err(_) => s.into(),
+            },
+            serde_json::Value::Null => fluent::FluentValue::None,
+            v => v.to_string().into(),
+        };
+        args.set(header.key.to_string(), value);
+    }
+
+    let locale = fastn_core::i18n::package::current_locale(config);
+    let text = fastn_core::i18n::package::message(config, locale.as_str(), id.as_str(), &args)
+        .await
+        .map_err(|e| ftd::interpreter::Error::ParseError {
+            message: e.to_string(),
+            doc_id: doc.name.to_string(),
+            line_number,
+        })?;
+    Ok(ftd::interpreter::Value::String { text })
+}
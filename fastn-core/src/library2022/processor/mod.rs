@@ -1,10 +1,13 @@
 pub(crate) mod apps;
+pub(crate) mod design_tokens;
 pub(crate) mod document;
 pub(crate) mod fetch_file;
 pub(crate) mod figma_tokens;
 pub(crate) mod figma_typography_tokens;
 pub(crate) mod get_data;
+pub(crate) mod heading_outline;
 pub(crate) mod http;
+pub(crate) mod i18n;
 pub(crate) mod package_tree;
 pub(crate) mod pg;
 pub(crate) mod query;
@@ -0,0 +1,549 @@
+/// The definitions of a document, what go-to-definition, hover, completion and document symbols
+/// are computed from. Sections that do not parse are skipped, so a half written section does
+/// not hide the rest of the document.
+#[derive(Debug, Default)]
+pub(super) struct Analysis {
+    pub symbols: Vec<Symbol>,
+    pub imports: Vec<ftd::ast::Import>,
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// `component`, `record`, the kind of a variable or the module of an import
+    pub detail: String,
+    /// 1-based, `0` for the auto imports of the package
+    pub line_number: usize,
+    /// the arguments of components and functions, the fields of records and the variants of
+    /// or-types
+    pub fields: Vec<Field>,
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct Field {
+    pub name: String,
+    pub kind: String,
+    pub line_number: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum SymbolKind {
+    Module,
+    Component,
+    Record,
+    OrType,
+    Variable,
+    Function,
+}
+
+/// Where a name is defined, `path` is `None` for the `ftd` built-ins.
+#[derive(Debug)]
+pub(super) struct Definition {
+    pub path: Option<camino::Utf8PathBuf>,
+    pub line_number: usize,
+    pub symbol: Symbol,
+    pub field: Option<Field>,
+}
+
+/// The keywords a section can start with, besides the names of components and variables.
+const KEYWORDS: &[&str] = &[
+    "import",
+    "component",
+    "record",
+    "or-type",
+    "web-component",
+    "end",
+    "string",
+    "integer",
+    "decimal",
+    "boolean",
+    "optional",
+];
+
+impl Analysis {
+    /// With a `package`, its auto imports are added to the imports of the document, as
+    /// `fastn build` does.
+    pub(super) fn new(content: &str, id: &str, package: Option<&fastn_core::Package>) -> Analysis {
+        let (content, offset) = match package {
+            Some(package) => {
+                let prefixed = package.get_prefixed_body(content, id, true);
+                let offset = prefixed
+                    .lines()
+                    .count()
+                    .saturating_sub(content.lines().count());
+                (prefixed, offset)
+            }
+            None => (content.to_string(), 0),
+        };
+        let line = |line_number: usize| line_number.saturating_sub(offset);
+
+        let mut analysis = Analysis::default();
+        let sections = match ftd::p1::parse(content.as_str(), id) {
+            Ok(sections) => sections,
+            Err(_) => return analysis,
+        };
+        for section in sections.iter().filter(|s| !s.is_commented) {
+            let ast = match ftd::ast::AST::from_section(section, id) {
+                Ok(ast) => ast,
+                Err(_) => continue,
+            };
+            let symbol = match ast {
+                ftd::ast::AST::Import(mut import) => {
+                    import.line_number = line(import.line_number);
+                    let symbol = Symbol {
+                        name: import.alias.clone(),
+                        kind: SymbolKind::Module,
+                        detail: import.module.clone(),
+                        line_number: import.line_number,
+                        fields: vec![],
+                    };
+                    analysis.imports.push(import);
+                    symbol
+                }
+                ftd::ast::AST::Record(r) => Symbol {
+                    name: r.name,
+                    kind: SymbolKind::Record,
+                    detail: "record".to_string(),
+                    line_number: line(r.line_number),
+                    fields: fields(r.fields.as_slice(), line),
+                },
+                ftd::ast::AST::OrType(o) => Symbol {
+                    name: o.name,
+                    kind: SymbolKind::OrType,
+                    detail: "or-type".to_string(),
+                    line_number: line(o.line_number),
+                    fields: o
+                        .variants
+                        .iter()
+                        .map(|v| match v {
+                            ftd::ast::OrTypeVariant::AnonymousRecord(r) => Field {
+                                name: r.name.clone(),
+                                kind: "record".to_string(),
+                                line_number: line(r.line_number),
+                            },
+                            ftd::ast::OrTypeVariant::Regular(f)
+                            | ftd::ast::OrTypeVariant::Constant(f) => field(f, line),
+                        })
+                        .collect(),
+                },
+                ftd::ast::AST::VariableDefinition(v) => Symbol {
+                    name: v.name,
+                    kind: SymbolKind::Variable,
+                    detail: kind_of(&v.kind),
+                    line_number: line(v.line_number),
+                    fields: vec![],
+                },
+                ftd::ast::AST::ComponentDefinition(c) => Symbol {
+                    name: c.name,
+                    kind: SymbolKind::Component,
+                    detail: "component".to_string(),
+                    line_number: line(c.line_number),
+                    fields: fields(c.arguments.as_slice(), line),
+                },
+                ftd::ast::AST::WebComponentDefinition(w) => Symbol {
+                    name: w.name,
+                    kind: SymbolKind::Component,
+                    detail: "web-component".to_string(),
+                    line_number: line(w.line_number),
+                    fields: fields(w.arguments.as_slice(), line),
+                },
+                ftd::ast::AST::FunctionDefinition(f) => Symbol {
+                    name: f.name,
+                    kind: SymbolKind::Function,
+                    detail: kind_of(&f.kind),
+                    line_number: line(f.line_number),
+                    fields: fields(f.arguments.as_slice(), line),
+                },
+                ftd::ast::AST::VariableInvocation(_) | ftd::ast::AST::ComponentInvocation(_) => {
+                    continue
+                }
+            };
+            analysis.symbols.push(symbol);
+        }
+        analysis
+    }
+
+    /// The definitions of the document, without the imports.
+    pub(super) fn definitions(&self) -> impl Iterator<Item = &Symbol> {
+        self.symbols.iter().filter(|s| s.kind != SymbolKind::Module)
+    }
+
+    fn get(&self, name: &str) -> Option<&Symbol> {
+        self.definitions().find(|s| s.name.eq(name))
+    }
+}
+
+/// Resolves names for the document at `path`, using the package, when there is one, to find
+/// the documents imported modules live in.
+pub(super) struct Resolver<'a> {
+    pub config: Option<&'a fastn_core::Config>,
+    pub path: &'a camino::Utf8Path,
+    pub analysis: &'a Analysis,
+}
+
+impl<'a> Resolver<'a> {
+    /// `name` is what is written in the document: `foo`, `$foo.bar`, `lib.foo`, `ftd.text`
+    /// or `ftd#text`. When it names a field, the field of the record or the argument of the
+    /// component is returned.
+    pub(super) fn resolve(&self, name: &str) -> Option<Definition> {
+        let name = name.trim_start_matches('$').replace('#', ".");
+        let mut parts = name.split('.').filter(|p| !p.is_empty());
+        let head = parts.next()?;
+        let rest: Vec<&str> = parts.collect();
+
+        if head.eq("ftd") {
+            let symbol = builtin(rest.first()?)?;
+            return Some(with_field(None, symbol, rest.get(1).copied()));
+        }
+        if let Some(symbol) = self.analysis.get(head) {
+            return Some(with_field(
+                Some(self.path.to_path_buf()),
+                symbol.clone(),
+                rest.first().copied(),
+            ));
+        }
+        for import in self.analysis.imports.iter() {
+            if import.alias.eq(head) {
+                let path = self.module_path(import.module.as_str())?;
+                return match rest.first() {
+                    None => Some(Definition {
+                        path: Some(path),
+                        line_number: 1,
+                        symbol: Symbol {
+                            name: import.alias.clone(),
+                            kind: SymbolKind::Module,
+                            detail: import.module.clone(),
+                            line_number: 1,
+                            fields: vec![],
+                        },
+                        field: None,
+                    }),
+                    Some(name) => {
+                        let symbol = module_analysis(&path)?.get(name)?.clone();
+                        Some(with_field(Some(path), symbol, rest.get(1).copied()))
+                    }
+                };
+            }
+            let exposed = match import.exposing {
+                Some(ftd::ast::Exposing::All) => true,
+                Some(ftd::ast::Exposing::Things(ref things)) => things.iter().any(|t| t.eq(head)),
+                None => false,
+            };
+            if exposed {
+                if let Some(path) = self.module_path(import.module.as_str()) {
+                    if let Some(symbol) = module_analysis(&path).and_then(|a| a.get(head).cloned())
+                    {
+                        return Some(with_field(Some(path), symbol, rest.first().copied()));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// The names that can follow `prefix`, the part of the word before the cursor. `section`
+    /// is the name of the section the cursor is in, when it is on a header line.
+    pub(super) fn complete(&self, prefix: &str, section: Option<&str>) -> Vec<Completion> {
+        let prefix = prefix.trim_start_matches('$').replace('#', ".");
+        if let Some((head, _)) = prefix.rsplit_once('.') {
+            if head.eq("ftd") {
+                return ftd::interpreter::default::default_bag()
+                    .keys()
+                    .filter_map(|k| k.strip_prefix("ftd#"))
+                    .filter_map(|name| {
+                        builtin(name).map(|s| Completion {
+                            label: name.to_string(),
+                            ..Completion::from_symbol(&s)
+                        })
+                    })
+                    .collect();
+            }
+            if let Some(import) = self.analysis.imports.iter().find(|i| i.alias.eq(head)) {
+                return self
+                    .module_path(import.module.as_str())
+                    .and_then(|path| module_analysis(&path))
+                    .map(|a| a.definitions().map(Completion::from_symbol).collect())
+                    .unwrap_or_default();
+            }
+            return self
+                .resolve(head)
+                .map(|d| d.symbol.fields.iter().map(Completion::from_field).collect())
+                .unwrap_or_default();
+        }
+
+        if let Some(section) = section {
+            return self
+                .resolve(section)
+                .map(|d| d.symbol.fields.iter().map(Completion::from_field).collect())
+                .unwrap_or_default();
+        }
+
+        let mut completions: Vec<Completion> = self
+            .analysis
+            .symbols
+            .iter()
+            .map(Completion::from_symbol)
+            .collect();
+        completions.push(Completion {
+            label: "ftd".to_string(),
+            kind: SymbolKind::Module,
+            detail: "ftd".to_string(),
+        });
+        completions.extend(KEYWORDS.iter().map(|k| Completion {
+            label: k.to_string(),
+            kind: SymbolKind::Variable,
+            detail: "keyword".to_string(),
+        }));
+        completions
+    }
+
+    /// The document an imported module is in: `<package>/<path>` is `<path>.ftd` or
+    /// `<path>/index.ftd` in the package, or in the dependency, named or aliased, it is in.
+    fn module_path(&self, module: &str) -> Option<camino::Utf8PathBuf> {
+        let config = self.config?;
+        let mut packages = config.package.aliases();
+        packages.insert(config.package.name.as_str(), &config.package);
+        let (prefix, package) = packages
+            .into_iter()
+            .filter(|(name, _)| {
+                module.eq(*name) || module.starts_with(format!("{}/", name).as_str())
+            })
+            .max_by_key(|(name, _)| name.len())?;
+
+        let root = config.get_root_for_package(package);
+        let rest = module[prefix.len()..].trim_matches('/');
+        let candidates = if rest.is_empty() {
+            vec![root.join("index.ftd")]
+        } else {
+            vec![
+                root.join(format!("{}.ftd", rest)),
+                root.join(rest).join("index.ftd"),
+            ]
+        };
+        candidates.into_iter().find(|p| p.is_file())
+    }
+}
+
+pub(super) struct Completion {
+    pub label: String,
+    pub kind: SymbolKind,
+    pub detail: String,
+}
+
+impl Completion {
+    fn from_symbol(symbol: &Symbol) -> Completion {
+        Completion {
+            label: symbol.name.clone(),
+            kind: symbol.kind,
+            detail: symbol.detail.clone(),
+        }
+    }
+
+    fn from_field(field: &Field) -> Completion {
+        Completion {
+            label: field.name.clone(),
+            kind: SymbolKind::Variable,
+            detail: field.kind.clone(),
+        }
+    }
+}
+
+impl Symbol {
+    /// How the symbol is defined, in ftd syntax, shown on hover.
+    pub(super) fn signature(&self) -> String {
+        let mut signature = match self.kind {
+            SymbolKind::Module => format!("-- import: {}\nas: {}", self.detail, self.name),
+            SymbolKind::Function => format!(
+                "-- {} {}({}):",
+                self.detail,
+                self.name,
+                self.fields
+                    .iter()
+                    .map(|f| f.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            _ => format!("-- {} {}:", self.detail, self.name),
+        };
+        for field in self.fields.iter() {
+            signature.push_str(format!("\n{}", field.signature()).as_str());
+        }
+        signature
+    }
+}
+
+impl Field {
+    pub(super) fn signature(&self) -> String {
+        if self.kind.is_empty() {
+            format!("{}:", self.name)
+        } else {
+            format!("{} {}:", self.kind, self.name)
+        }
+    }
+}
+
+impl SymbolKind {
+    /// https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#symbolKind
+    pub(super) fn lsp_symbol_kind(&self) -> u8 {
+        match self {
+            SymbolKind::Module => 2,
+            SymbolKind::Component => 5,
+            SymbolKind::OrType => 10,
+            SymbolKind::Function => 12,
+            SymbolKind::Variable => 13,
+            SymbolKind::Record => 23,
+        }
+    }
+
+    /// https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#completionItemKind
+    pub(super) fn lsp_completion_kind(&self) -> u8 {
+        match self {
+            SymbolKind::Function => 3,
+            SymbolKind::Variable => 6,
+            SymbolKind::Component => 7,
+            SymbolKind::Module => 9,
+            SymbolKind::OrType => 13,
+            SymbolKind::Record => 22,
+        }
+    }
+}
+
+fn with_field(
+    path: Option<camino::Utf8PathBuf>,
+    symbol: Symbol,
+    field: Option<&str>,
+) -> Definition {
+    let field = field.and_then(|name| symbol.fields.iter().find(|f| f.name.eq(name)).cloned());
+    Definition {
+        path,
+        line_number: field.as_ref().map_or(symbol.line_number, |f| f.line_number),
+        symbol,
+        field,
+    }
+}
+
+fn module_analysis(path: &camino::Utf8Path) -> Option<Analysis> {
+    let content = std::fs::read_to_string(path).ok()?;
+    Some(Analysis::new(content.as_str(), path.as_str(), None))
+}
+
+fn fields(fields: &[ftd::ast::Field], line: impl Fn(usize) -> usize) -> Vec<Field> {
+    fields.iter().map(|f| field(f, &line)).collect()
+}
+
+fn field(f: &ftd::ast::Field, line: impl Fn(usize) -> usize) -> Field {
+    Field {
+        name: if f.mutable {
+            format!("${}", f.name)
+        } else {
+            f.name.clone()
+        },
+        kind: kind_of(&f.kind),
+        line_number: line(f.line_number),
+    }
+}
+
+fn kind_of(kind: &ftd::ast::VariableKind) -> String {
+    match kind.modifier {
+        Some(ftd::ast::VariableModifier::List) => format!("{} list", kind.kind),
+        Some(ftd::ast::VariableModifier::Optional) => format!("optional {}", kind.kind),
+        None => kind.kind.clone(),
+    }
+}
+
+/// `ftd.<name>`, from the things the interpreter knows without any document.
+fn builtin(name: &str) -> Option<Symbol> {
+    let bag = ftd::interpreter::default::default_bag();
+    let (kind, detail, line_number, fields) = match bag.get(format!("ftd#{}", name).as_str())? {
+        ftd::interpreter::Thing::Component(c) => (
+            SymbolKind::Component,
+            "component".to_string(),
+            c.line_number,
+            builtin_fields(c.arguments.as_slice()),
+        ),
+        ftd::interpreter::Thing::WebComponent(w) => (
+            SymbolKind::Component,
+            "web-component".to_string(),
+            w.line_number,
+            builtin_fields(w.arguments.as_slice()),
+        ),
+        ftd::interpreter::Thing::Record(r) => (
+            SymbolKind::Record,
+            "record".to_string(),
+            r.line_number,
+            builtin_fields(r.fields.as_slice()),
+        ),
+        ftd::interpreter::Thing::OrType(o) => (
+            SymbolKind::OrType,
+            "or-type".to_string(),
+            o.line_number,
+            o.variants
+                .iter()
+                .map(|v| Field {
+                    name: v
+                        .name()
+                        .trim_start_matches(o.name.as_str())
+                        .trim_start_matches('.')
+                        .to_string(),
+                    kind: String::new(),
+                    line_number: v.line_number(),
+                })
+                .collect(),
+        ),
+        ftd::interpreter::Thing::Variable(v) => (
+            SymbolKind::Variable,
+            kind_data_of(&v.kind),
+            v.line_number,
+            vec![],
+        ),
+        ftd::interpreter::Thing::Function(f) => (
+            SymbolKind::Function,
+            kind_data_of(&f.return_kind),
+            f.line_number,
+            builtin_fields(f.arguments.as_slice()),
+        ),
+        _ => return None,
+    };
+    Some(Symbol {
+        name: format!("ftd.{}", name),
+        kind,
+        detail,
+        line_number,
+        fields,
+    })
+}
+
+fn builtin_fields(fields: &[ftd::interpreter::Field]) -> Vec<Field> {
+    fields
+        .iter()
+        .map(|f| Field {
+            name: if f.mutable {
+                format!("${}", f.name)
+            } else {
+                f.name.clone()
+            },
+            kind: kind_data_of(&f.kind),
+            line_number: f.line_number,
+        })
+        .collect()
+}
+
+fn kind_data_of(kind: &ftd::interpreter::KindData) -> String {
+    let name = kind_name(&kind.kind);
+    match (kind.caption, kind.body) {
+        (true, true) => format!("caption or body {}", name),
+        (true, false) => format!("caption {}", name),
+        (false, true) => format!("body {}", name),
+        (false, false) => name,
+    }
+}
+
+fn kind_name(kind: &ftd::interpreter::Kind) -> String {
+    match kind {
+        ftd::interpreter::Kind::List { kind } => format!("{} list", kind_name(kind)),
+        ftd::interpreter::Kind::Optional { kind } => format!("optional {}", kind_name(kind)),
+        ftd::interpreter::Kind::Constant { kind } => kind_name(kind),
+        kind => kind.get_name().replace('#', "."),
+    }
+}
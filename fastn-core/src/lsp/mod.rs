@@ -0,0 +1,706 @@
+//! `fastn lsp`, a Language Server Protocol server for the ftd documents of a package, speaking
+//! JSON-RPC over stdin and stdout. Diagnostics are what `fastn check` reports for a document,
+//! published when it is opened or saved. Definitions, hover, completion and document symbols
+//! come from the AST of the document and of the modules it imports, see `analysis`.
+
+mod analysis;
+
+// https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#errorCodes
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const SERVER_NOT_INITIALIZED: i64 = -32002;
+
+pub async fn serve() -> fastn_core::Result<()> {
+    use tokio::io::AsyncWriteExt;
+
+    let mut stdin = tokio::io::BufReader::new(tokio::io::stdin());
+    let mut stdout = tokio::io::stdout();
+    let mut server = Server::default();
+    while let Some(message) = read_message(&mut stdin).await? {
+        let messages = match message {
+            Ok(message) => server.handle(message).await,
+            // the next message can still be read, the client gets an error for this one
+            Err(e) => {
+                tracing::error!(lsp = "malformed message", error = e.as_str());
+                vec![serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": null,
+                    "error": {"code": PARSE_ERROR, "message": e},
+                })]
+            }
+        };
+        for message in messages {
+            let body = serde_json::to_string(&message)?;
+            stdout
+                .write_all(format!("Content-Length: {}\r\n\r\n{}", body.len(), body).as_bytes())
+                .await?;
+            stdout.flush().await?;
+        }
+        if server.exit {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Returns `None` once the client closes stdin, and `Some(Err(_))` for a message that is not
+/// JSON, or whose headers can not be read. Such a message is skipped, the one after it is read
+/// next time.
+async fn read_message<R>(
+    reader: &mut R,
+) -> fastn_core::Result<Option<Result<serde_json::Value, String>>>
+where
+    R: tokio::io::AsyncBufRead + Unpin,
+{
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt};
+
+    let mut length = None;
+    let mut error = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim();
+        if line.is_empty() {
+            break;
+        }
+        match line.split_once(':') {
+            Some((name, value)) if name.trim().eq_ignore_ascii_case("content-length") => {
+                match value.trim().parse::<usize>() {
+                    Ok(value) => length = Some(value),
+                    Err(e) => error = Some(format!("invalid Content-Length `{}`: {}", value, e)),
+                }
+            }
+            Some(_) => {}
+            None => error = Some(format!("invalid header `{}`", line)),
+        }
+    }
+    if let Some(error) = error {
+        return Ok(Some(Err(error)));
+    }
+    let length = match length {
+        Some(length) => length,
+        None => {
+            return Ok(Some(Err(
+                "message without a Content-Length header".to_string()
+            )))
+        }
+    };
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await?;
+    Ok(Some(
+        serde_json::from_slice(&body).map_err(|e| format!("invalid JSON: {}", e)),
+    ))
+}
+
+#[derive(Default)]
+struct Server {
+    /// `None` if the workspace is not a fastn package, or its `FASTN.ftd` could not be read,
+    /// only syntax errors are reported then and imports are not resolved.
+    config: Option<fastn_core::Config>,
+    /// The text of the open documents, by uri, as the editor has it.
+    documents: std::collections::HashMap<String, String>,
+    /// The last analysis of each open document that parsed, used while it does not.
+    analyses: std::collections::HashMap<String, analysis::Analysis>,
+    initialized: bool,
+    exit: bool,
+}
+
+impl Server {
+    /// The messages to send back, the response to a request and the notifications it causes.
+    async fn handle(&mut self, message: serde_json::Value) -> Vec<serde_json::Value> {
+        let method = match message.get("method").and_then(|m| m.as_str()) {
+            Some(method) => method.to_string(),
+            // a response, we do not send requests
+            None => return vec![],
+        };
+        let params = message.get("params").cloned().unwrap_or_default();
+        let id = match message.get("id") {
+            Some(id) => id.clone(),
+            None => return self.notification(method.as_str(), &params).await,
+        };
+
+        let mut messages = vec![];
+        let result = match method.as_str() {
+            "initialize" => {
+                let (result, message) = self.initialize(&params).await;
+                messages.extend(message);
+                Ok(result)
+            }
+            "shutdown" => Ok(serde_json::Value::Null),
+            _ if !self.initialized => Err((
+                SERVER_NOT_INITIALIZED,
+                "initialize has not been called".to_string(),
+            )),
+            "textDocument/definition" => Ok(self.definition(&params)),
+            "textDocument/hover" => Ok(self.hover(&params)),
+            "textDocument/completion" => Ok(self.completion(&params)),
+            "textDocument/documentSymbol" => Ok(self.document_symbols(&params)),
+            _ => Err((METHOD_NOT_FOUND, format!("{} is not supported", method))),
+        };
+        let response = match result {
+            Ok(result) => serde_json::json!({"jsonrpc": "2.0", "id": id, "result": result}),
+            Err((code, message)) => serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": {"code": code, "message": message},
+            }),
+        };
+        messages.insert(0, response);
+        messages
+    }
+
+    async fn notification(
+        &mut self,
+        method: &str,
+        params: &serde_json::Value,
+    ) -> Vec<serde_json::Value> {
+        let uri = params["textDocument"]["uri"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        match method {
+            "exit" => {
+                self.exit = true;
+                vec![]
+            }
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                self.update(uri.as_str(), text.to_string());
+                vec![self.diagnostics(uri.as_str()).await]
+            }
+            "textDocument/didChange" => {
+                // the server asks for full document sync, the last change is the whole text
+                let changes = params["contentChanges"].as_array();
+                if let Some(text) = changes
+                    .and_then(|c| c.last())
+                    .and_then(|c| c["text"].as_str())
+                {
+                    self.update(uri.as_str(), text.to_string());
+                }
+                vec![]
+            }
+            "textDocument/didSave" => {
+                if let Some(text) = params["text"].as_str() {
+                    self.update(uri.as_str(), text.to_string());
+                }
+                vec![self.diagnostics(uri.as_str()).await]
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(&uri);
+                self.analyses.remove(&uri);
+                vec![publish_diagnostics(uri.as_str(), vec![])]
+            }
+            _ => vec![],
+        }
+    }
+
+    async fn initialize(
+        &mut self,
+        params: &serde_json::Value,
+    ) -> (serde_json::Value, Option<serde_json::Value>) {
+        self.initialized = true;
+        let capabilities = serde_json::json!({
+            "capabilities": {
+                "textDocumentSync": {
+                    "openClose": true,
+                    "change": 1,
+                    "save": {"includeText": true},
+                },
+                "definitionProvider": true,
+                "hoverProvider": true,
+                "completionProvider": {"triggerCharacters": [".", "$", "#"]},
+                "documentSymbolProvider": true,
+            },
+            "serverInfo": {"name": "fastn", "version": env!("CARGO_PKG_VERSION")},
+        });
+
+        let workspace = params["rootUri"]
+            .as_str()
+            .and_then(uri_to_path)
+            .or_else(|| params["rootPath"].as_str().map(camino::Utf8PathBuf::from));
+        let message = match self.read_config(workspace).await {
+            Ok(config) => {
+                self.config = Some(config);
+                None
+            }
+            Err(e) => Some(serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "window/showMessage",
+                "params": {
+                    "type": 2,
+                    "message": format!("fastn: could not read the package, only syntax errors are reported: {}", e),
+                },
+            })),
+        };
+        (capabilities, message)
+    }
+
+    async fn read_config(
+        &self,
+        workspace: Option<camino::Utf8PathBuf>,
+    ) -> fastn_core::Result<fastn_core::Config> {
+        let workspace = match workspace {
+            Some(workspace) => workspace,
+            None => std::env::current_dir()?.canonicalize()?.try_into()?,
+        };
+        let root = fastn_core::Config::get_root_path(&workspace).await?;
+        fastn_core::Config::read(Some(root.to_string()), true, None).await
+    }
+
+    fn update(&mut self, uri: &str, text: String) {
+        if let Some(path) = uri_to_path(uri) {
+            let analysis = analysis::Analysis::new(
+                text.as_str(),
+                self.document_id(&path).as_str(),
+                self.config.as_ref().map(|c| &c.package),
+            );
+            // a document that does not parse has no symbols, keep the last ones
+            if !analysis.symbols.is_empty() || ftd::p1::parse(text.as_str(), "").is_ok() {
+                self.analyses.insert(uri.to_string(), analysis);
+            }
+        }
+        self.documents.insert(uri.to_string(), text);
+    }
+
+    /// The path of the document relative to the package root, as `fastn check` names it.
+    fn document_id(&self, path: &camino::Utf8Path) -> String {
+        match self.config {
+            Some(ref config) => path
+                .strip_prefix(&config.root)
+                .map(|p| p.to_string())
+                .unwrap_or_else(|_| path.to_string()),
+            None => path.to_string(),
+        }
+    }
+
+    /// What `fastn check` finds in the document. Problems it reports in other documents are
+    /// shown on the first line, with their location.
+    async fn diagnostics(&self, uri: &str) -> serde_json::Value {
+        let (path, text) = match (uri_to_path(uri), self.documents.get(uri)) {
+            (Some(path), Some(text)) => (path, text),
+            _ => return publish_diagnostics(uri, vec![]),
+        };
+        let id = self.document_id(&path);
+
+        let diagnostics = match self.config {
+            Some(ref config) if path.starts_with(&config.root) => {
+                match check(config, id.as_str(), text.as_str()).await {
+                    Ok(diagnostics) => diagnostics,
+                    Err(e) => vec![fastn_core::commands::check::Diagnostic {
+                        file: id.clone(),
+                        line: 0,
+                        severity: fastn_core::commands::check::Severity::Error,
+                        code: "check-failed",
+                        message: e.to_string(),
+                    }],
+                }
+            }
+            _ => syntax_errors(id.as_str(), text.as_str()),
+        };
+
+        let lines: Vec<&str> = text.lines().collect();
+        let diagnostics = diagnostics
+            .into_iter()
+            .map(|d| {
+                let (line, message) = if d.file.eq(&id) {
+                    (d.line.saturating_sub(1), d.message)
+                } else {
+                    (0, format!("{}:{}: {}", d.file, d.line, d.message))
+                };
+                serde_json::json!({
+                    "range": line_range(&lines, line),
+                    "severity": match d.severity {
+                        fastn_core::commands::check::Severity::Error => 1,
+                        fastn_core::commands::check::Severity::Warning => 2,
+                    },
+                    "code": d.code,
+                    "source": "fastn",
+                    "message": message,
+                })
+            })
+            .collect();
+        publish_diagnostics(uri, diagnostics)
+    }
+
+    fn definition(&self, params: &serde_json::Value) -> serde_json::Value {
+        let definition = match self.at_position(params, resolve) {
+            Some(definition) => definition,
+            None => return serde_json::Value::Null,
+        };
+        match definition.path {
+            Some(path) => serde_json::json!({
+                "uri": path_to_uri(&path),
+                "range": line_range(&[], definition.line_number.saturating_sub(1)),
+            }),
+            None => serde_json::Value::Null,
+        }
+    }
+
+    fn hover(&self, params: &serde_json::Value) -> serde_json::Value {
+        let definition = match self.at_position(params, resolve) {
+            Some(definition) => definition,
+            None => return serde_json::Value::Null,
+        };
+        let signature = match definition.field {
+            Some(ref field) => format!(
+                "-- {} {}:\n{}",
+                definition.symbol.detail,
+                definition.symbol.name,
+                field.signature()
+            ),
+            None => definition.symbol.signature(),
+        };
+        serde_json::json!({
+            "contents": {
+                "kind": "markdown",
+                "value": format!("```ftd\n{}\n```", signature),
+            },
+        })
+    }
+
+    fn completion(&self, params: &serde_json::Value) -> serde_json::Value {
+        let completions = self
+            .at_position(params, |resolver, prefix, line| {
+                let section = if line.starts_with("-- ") {
+                    None
+                } else {
+                    line.section.as_deref()
+                };
+                Some(resolver.complete(prefix, section))
+            })
+            .unwrap_or_default();
+        serde_json::Value::Array(
+            completions
+                .into_iter()
+                .map(|c| {
+                    serde_json::json!({
+                        "label": c.label,
+                        "kind": c.kind.lsp_completion_kind(),
+                        "detail": c.detail,
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    fn document_symbols(&self, params: &serde_json::Value) -> serde_json::Value {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        let (analysis, text) = match (self.analyses.get(uri), self.documents.get(uri)) {
+            (Some(analysis), Some(text)) => (analysis, text),
+            _ => return serde_json::Value::Array(vec![]),
+        };
+        let lines: Vec<&str> = text.lines().collect();
+        let range = |line_number: usize| line_range(&lines, line_number.saturating_sub(1));
+        serde_json::Value::Array(
+            analysis
+                .symbols
+                .iter()
+                // the auto imports of the package
+                .filter(|s| s.line_number > 0)
+                .map(|s| {
+                    serde_json::json!({
+                        "name": s.name,
+                        "detail": s.detail,
+                        "kind": s.kind.lsp_symbol_kind(),
+                        "range": range(s.line_number),
+                        "selectionRange": range(s.line_number),
+                        "children": s.fields.iter().map(|f| serde_json::json!({
+                            "name": f.name,
+                            "detail": f.kind,
+                            "kind": 8,
+                            "range": range(f.line_number),
+                            "selectionRange": range(f.line_number),
+                        })).collect::<Vec<_>>(),
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    /// Calls `f` with the word under, or before, the cursor of a `TextDocumentPositionParams`.
+    fn at_position<T>(
+        &self,
+        params: &serde_json::Value,
+        f: impl FnOnce(&analysis::Resolver, &str, &Line) -> Option<T>,
+    ) -> Option<T> {
+        let uri = params["textDocument"]["uri"].as_str()?;
+        let text = self.documents.get(uri)?;
+        let analysis = self.analyses.get(uri)?;
+        let path = uri_to_path(uri)?;
+        let line_number = params["position"]["line"].as_u64()? as usize;
+        let character = params["position"]["character"].as_u64()? as usize;
+
+        let lines: Vec<&str> = text.lines().collect();
+        let line = Line::new(&lines, line_number)?;
+        let word = line.word_at(character);
+        let resolver = analysis::Resolver {
+            config: self.config.as_ref(),
+            path: &path,
+            analysis,
+        };
+        f(&resolver, word.as_str(), &line)
+    }
+}
+
+/// A header name resolves to the argument of the component, or the field of the record, of its
+/// section.
+fn resolve(resolver: &analysis::Resolver, word: &str, line: &Line) -> Option<analysis::Definition> {
+    resolver.resolve(word).or_else(|| match line.section {
+        Some(ref section) if !line.starts_with("-- ") => {
+            resolver.resolve(format!("{}.{}", section, word).as_str())
+        }
+        _ => None,
+    })
+}
+
+/// A line of a document, with the name of the section it is in.
+struct Line {
+    text: String,
+    /// For a header line, the section it belongs to: `foo` for the headers of `-- foo:`, of
+    /// `-- component foo:` and of `-- foo.bar:`, with the latter's `.bar`.
+    section: Option<String>,
+}
+
+impl Line {
+    fn new(lines: &[&str], line_number: usize) -> Option<Line> {
+        let text = lines.get(line_number).copied().unwrap_or_default();
+        let section = lines[..line_number.min(lines.len())]
+            .iter()
+            .rev()
+            .find(|l| l.starts_with("-- ") && !l.starts_with("-- end:"))
+            .and_then(|l| l.trim_start_matches("-- ").split(':').next())
+            .and_then(|l| l.split_whitespace().last())
+            .map(|s| s.trim_start_matches('$').to_string());
+        Some(Line {
+            text: text.to_string(),
+            section,
+        })
+    }
+
+    fn starts_with(&self, prefix: &str) -> bool {
+        self.text.starts_with(prefix)
+    }
+
+    /// The name the cursor is on, up to the cursor if it is at its end. `character` is in UTF-16
+    /// code units, as LSP positions are.
+    fn word_at(&self, character: usize) -> String {
+        let is_word = |c: &char| c.is_alphanumeric() || "-_.#$".contains(*c);
+        let chars: Vec<char> = self.text.chars().collect();
+        let character = char_index(self.text.as_str(), character);
+        let start = chars[..character]
+            .iter()
+            .rposition(|c| !is_word(c))
+            .map_or(0, |p| p + 1);
+        let end = chars[character..]
+            .iter()
+            .position(|c| !is_word(c))
+            .map_or(chars.len(), |p| p + character);
+        chars[start..end]
+            .iter()
+            .collect::<String>()
+            .trim_start_matches('-')
+            .to_string()
+    }
+}
+
+pub(crate) async fn check(
+    config: &fastn_core::Config,
+    id: &str,
+    text: &str,
+) -> fastn_core::Result<Vec<fastn_core::commands::check::Diagnostic>> {
+    let mut files = config.get_files(&config.package).await?;
+    // the editor's text, which is not saved yet when the document is opened
+    for file in files.iter_mut() {
+        if let fastn_core::File::Ftd(doc) = file {
+            if doc.id.eq(id) {
+                doc.content = text.to_string();
+            }
+        }
+    }
+    let document = fastn_core::Document {
+        package_name: config.package.name.clone(),
+        id: id.to_string(),
+        content: text.to_string(),
+        parent_path: config.root.to_string(),
+    };
+    let documents: Vec<&fastn_core::Document> = files
+        .iter()
+        .filter_map(|f| match f {
+            fastn_core::File::Ftd(doc) if !doc.id.eq("FASTN.ftd") => Some(doc),
+            _ => None,
+        })
+        .collect();
+    let mut references = std::collections::HashMap::new();
+    for document in documents.iter() {
+        references.insert(
+            document.id.as_str(),
+            fastn_core::commands::check::references_of(document),
+        );
+    }
+    Ok(fastn_core::commands::check::check_document(
+        config,
+        &document,
+        documents.as_slice(),
+        files.as_slice(),
+        &references,
+    )
+    .await)
+}
+
+/// Outside of a package only the syntax can be checked.
+fn syntax_errors(id: &str, text: &str) -> Vec<fastn_core::commands::check::Diagnostic> {
+    match ftd::p1::parse(text, id)
+        .map_err(ftd::ast::Error::from)
+        .and_then(|sections| ftd::ast::AST::from_sections(sections.as_slice(), id))
+    {
+        Ok(_) => vec![],
+        Err(e) => vec![fastn_core::commands::check::Diagnostic {
+            file: id.to_string(),
+            line: e.location().map(|(_, line)| line).unwrap_or_default(),
+            severity: fastn_core::commands::check::Severity::Error,
+            code: "syntax-error",
+            message: e.to_string(),
+        }],
+    }
+}
+
+fn publish_diagnostics(uri: &str, diagnostics: Vec<serde_json::Value>) -> serde_json::Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": {"uri": uri, "diagnostics": diagnostics},
+    })
+}
+
+/// The whole of the 0-based `line`, or its start if `lines` does not have it.
+fn line_range(lines: &[&str], line: usize) -> serde_json::Value {
+    let length = lines.get(line).map_or(0, |l| utf16_length(l));
+    serde_json::json!({
+        "start": {"line": line, "character": 0},
+        "end": {"line": line, "character": length},
+    })
+}
+
+/// LSP positions count UTF-16 code units, these convert them to and from `char`s.
+fn utf16_length(text: &str) -> usize {
+    text.chars().map(char::len_utf16).sum()
+}
+
+/// The index of the `char` at the UTF-16 offset `utf16` of `text`, a position inside a
+/// surrogate pair is the `char` of the pair, and one past the end is the end.
+fn char_index(text: &str, utf16: usize) -> usize {
+    let mut offset = 0;
+    for (index, c) in text.chars().enumerate() {
+        offset += c.len_utf16();
+        if offset > utf16 {
+            return index;
+        }
+    }
+    text.chars().count()
+}
+
+fn uri_to_path(uri: &str) -> Option<camino::Utf8PathBuf> {
+    let path = url::Url::parse(uri).ok()?.to_file_path().ok()?;
+    camino::Utf8PathBuf::from_path_buf(path).ok()
+}
+
+fn path_to_uri(path: &camino::Utf8Path) -> String {
+    url::Url::from_file_path(path)
+        .map(|u| u.to_string())
+        .unwrap_or_else(|_| format!("file://{}", path))
+}
+
+#[cfg(test)]
+mod test {
+    fn frame(body: &str) -> String {
+        format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+    }
+
+    async fn read_all(input: &str) -> Vec<Result<serde_json::Value, String>> {
+        let mut reader = input.as_bytes();
+        let mut messages = vec![];
+        while let Some(message) = super::read_message(&mut reader).await.unwrap() {
+            messages.push(message);
+        }
+        messages
+    }
+
+    #[tokio::test]
+    async fn read_message() {
+        let input = format!(
+            "{}Content-Type: application/vscode-jsonrpc; charset=utf-8\r\n{}",
+            frame(r#"{"id":1}"#),
+            frame(r#"{"id":"é"}"#)
+        );
+        assert_eq!(
+            read_all(input.as_str()).await,
+            vec![
+                Ok(serde_json::json!({"id": 1})),
+                Ok(serde_json::json!({"id": "é"}))
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn read_malformed_message() {
+        let input = format!(
+            "{}Content-Length: ten\r\n\r\nContent-Type: x\r\n\r\n{}",
+            frame("{not json"),
+            frame(r#"{"id":2}"#)
+        );
+        let messages = read_all(input.as_str()).await;
+        assert_eq!(messages.len(), 4, "{:?}", messages);
+        assert!(messages[0]
+            .as_ref()
+            .unwrap_err()
+            .starts_with("invalid JSON"));
+        assert!(messages[1]
+            .as_ref()
+            .unwrap_err()
+            .starts_with("invalid Content-Length"));
+        assert_eq!(
+            messages[2],
+            Err("message without a Content-Length header".to_string())
+        );
+        assert_eq!(messages[3], Ok(serde_json::json!({"id": 2})));
+    }
+
+    fn line(text: &str) -> super::Line {
+        super::Line::new(&[text], 0).unwrap()
+    }
+
+    #[test]
+    fn word_at() {
+        let l = line("-- ftd.text: $name");
+        assert_eq!(l.word_at(14), "$name");
+        // at the end of the word
+        assert_eq!(l.word_at(18), "$name");
+        assert_eq!(l.word_at(5), "ftd.text");
+        assert_eq!(l.word_at(100), "$name");
+        // 🎉 is two UTF-16 code units, é one
+        let l = line("🎉 é $who");
+        assert_eq!(l.word_at(5), "$who");
+        assert_eq!(l.word_at(3), "é");
+    }
+
+    #[test]
+    fn utf16() {
+        assert_eq!(super::utf16_length("a🎉é"), 4);
+        assert_eq!(super::char_index("a🎉é", 0), 0);
+        assert_eq!(super::char_index("a🎉é", 1), 1);
+        // inside the surrogate pair
+        assert_eq!(super::char_index("a🎉é", 2), 1);
+        assert_eq!(super::char_index("a🎉é", 3), 2);
+        assert_eq!(super::char_index("a🎉é", 4), 3);
+        assert_eq!(super::char_index("a🎉é", 9), 3);
+        assert_eq!(
+            super::line_range(&["a🎉é"], 0),
+            serde_json::json!({
+                "start": {"line": 0, "character": 0},
+                "end": {"line": 0, "character": 4},
+            })
+        );
+    }
+}
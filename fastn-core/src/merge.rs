@@ -0,0 +1,401 @@
+/// Merges `ours` and `theirs` with their common `ancestor`, line by line. Conflict markers are
+/// only added for hunks changed on both sides, and the content with the markers is returned as
+/// the error.
+///
+/// `.ftd` files are merged section by section, so edits to different sections never conflict
+/// and both sides can add sections. Files that don't parse are merged line by line.
+pub(crate) fn merge(
+    path: &str,
+    ancestor: &str,
+    ours: &str,
+    theirs: &str,
+) -> std::result::Result<String, String> {
+    if path.ends_with(".ftd") {
+        if let Some(merged) = merge_ftd(path, ancestor, ours, theirs) {
+            return merged;
+        }
+    }
+    merge_lines(ancestor, ours, theirs)
+}
+
+fn merge_lines(ancestor: &str, ours: &str, theirs: &str) -> std::result::Result<String, String> {
+    diffy::MergeOptions::new()
+        .set_conflict_style(diffy::ConflictStyle::Merge)
+        .merge(ancestor, ours, theirs)
+}
+
+/// A top level section of a `.ftd` file, or the comments before the first section.
+#[derive(Debug)]
+struct Section {
+    /// The kind and name of the section, `component foo` or ` ftd.text`.
+    key: String,
+    /// The source of the section, ending with a newline.
+    body: String,
+    /// The blank lines after the section.
+    trailing: String,
+}
+
+fn sections(path: &str, source: &str) -> Option<Vec<Section>> {
+    let parsed = ftd::p1::parse(source, path).ok()?;
+
+    let mut offsets = vec![0];
+    offsets.extend(source.match_indices('\n').map(|(index, _)| index + 1));
+    let offset = |line_number: usize| {
+        offsets
+            .get(line_number.saturating_sub(1))
+            .copied()
+            .unwrap_or(source.len())
+    };
+
+    let mut starts = vec![(0, "".to_string())];
+    for section in parsed.iter() {
+        let start = offset(section.line_number);
+        let key = format!(
+            "{} {}",
+            section.kind.clone().unwrap_or_default(),
+            section.name
+        );
+        // comments before a section belong to the previous one
+        if source[..start].trim().is_empty() {
+            starts[0].1 = key;
+            continue;
+        }
+        starts.push((start, key));
+    }
+
+    let mut sections = vec![];
+    for (index, (start, key)) in starts.iter().enumerate() {
+        let end = starts
+            .get(index + 1)
+            .map(|(start, _)| *start)
+            .unwrap_or(source.len());
+        let text = &source[*start..end];
+        if text.trim().is_empty() {
+            continue;
+        }
+        // trailing whitespace of the last line is kept, it can be part of a value
+        let content = text.trim_end().len();
+        let body_end = text[content..]
+            .find('\n')
+            .map(|index| content + index + 1)
+            .unwrap_or(text.len());
+        let mut body = text[..body_end].to_string();
+        if !body.ends_with('\n') {
+            body.push('\n');
+        }
+        sections.push(Section {
+            key: key.to_string(),
+            body,
+            trailing: text[body_end..].to_string(),
+        });
+    }
+    Some(sections)
+}
+
+/// Matches the sections of `from` to the sections of `to`, keeping their order. Identical
+/// sections are preferred over sections that only have the same key, and among those the ones
+/// sharing the most lines, so repeated sections like `-- ftd.text:` pair with their own edit.
+fn align(from: &[Section], to: &[Section]) -> Vec<Option<usize>> {
+    let scores = from
+        .iter()
+        .map(|a| {
+            to.iter()
+                .map(|b| {
+                    if a.key != b.key {
+                        0
+                    } else if a.body == b.body {
+                        2 * a.body.lines().count() + 2
+                    } else {
+                        common_lines(&a.body, &b.body) + 1
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let score = |i: usize, j: usize| scores[i][j];
+
+    let mut table = vec![vec![0; to.len() + 1]; from.len() + 1];
+    for i in (0..from.len()).rev() {
+        for j in (0..to.len()).rev() {
+            table[i][j] = table[i + 1][j].max(table[i][j + 1]);
+            if score(i, j) > 0 {
+                table[i][j] = table[i][j].max(score(i, j) + table[i + 1][j + 1]);
+            }
+        }
+    }
+
+    let mut matches = vec![None; from.len()];
+    let (mut i, mut j) = (0, 0);
+    while i < from.len() && j < to.len() {
+        if score(i, j) > 0 && table[i][j] == score(i, j) + table[i + 1][j + 1] {
+            matches[i] = Some(j);
+            i += 1;
+            j += 1;
+        } else if table[i][j] == table[i + 1][j] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    matches
+}
+
+/// The number of lines `a` and `b` have in common, in any order.
+fn common_lines(a: &str, b: &str) -> usize {
+    let mut lines: std::collections::HashMap<&str, usize> = Default::default();
+    for line in a.lines() {
+        *lines.entry(line).or_default() += 1;
+    }
+    b.lines()
+        .filter(|line| match lines.get_mut(line) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                true
+            }
+            _ => false,
+        })
+        .count()
+}
+
+fn conflict(ours: &str, theirs: &str) -> String {
+    format!("<<<<<<< ours\n{}=======\n{}>>>>>>> theirs\n", ours, theirs)
+}
+
+fn merge_ftd(
+    path: &str,
+    ancestor: &str,
+    ours: &str,
+    theirs: &str,
+) -> Option<std::result::Result<String, String>> {
+    let ancestor = sections(path, ancestor)?;
+    let ours = sections(path, ours)?;
+    let theirs = sections(path, theirs)?;
+
+    let to_ours = align(&ancestor, &ours);
+    let to_theirs = align(&ancestor, &theirs);
+    let mut from_ours = vec![None; ours.len()];
+    for (index, matched) in to_ours.iter().enumerate() {
+        if let Some(matched) = matched {
+            from_ours[*matched] = Some(index);
+        }
+    }
+    let mut from_theirs = vec![None; theirs.len()];
+    for (index, matched) in to_theirs.iter().enumerate() {
+        if let Some(matched) = matched {
+            from_theirs[*matched] = Some(index);
+        }
+    }
+
+    let mut merged: Vec<(String, &str)> = vec![];
+    let mut conflicted = false;
+    let (mut next_ours, mut next_theirs) = (0, 0);
+    for index in 0..=ancestor.len() {
+        // sections kept by both sides are anchors, everything in between was added or deleted
+        // by at least one side
+        let (until_ours, until_theirs) = match ancestor.get(index) {
+            Some(_) => match (to_ours[index], to_theirs[index]) {
+                (Some(o), Some(t)) => (o, t),
+                _ => continue,
+            },
+            None => (ours.len(), theirs.len()),
+        };
+
+        let added_by_ours = ours
+            .iter()
+            .zip(from_ours.iter())
+            .take(until_ours)
+            .skip(next_ours)
+            .filter(|(_, base)| base.is_none())
+            .map(|(section, _)| section.body.as_str())
+            .collect::<Vec<_>>();
+        for (section, base) in ours
+            .iter()
+            .zip(from_ours.iter())
+            .take(until_ours)
+            .skip(next_ours)
+        {
+            match base {
+                // deleted by theirs
+                Some(base) if ancestor[*base].body == section.body => {}
+                Some(_) => {
+                    conflicted = true;
+                    merged.push((conflict(&section.body, ""), &section.trailing));
+                }
+                None => merged.push((section.body.clone(), &section.trailing)),
+            }
+        }
+        for (section, base) in theirs
+            .iter()
+            .zip(from_theirs.iter())
+            .take(until_theirs)
+            .skip(next_theirs)
+        {
+            match base {
+                // deleted by ours
+                Some(base) if ancestor[*base].body == section.body => {}
+                Some(_) => {
+                    conflicted = true;
+                    merged.push((conflict("", &section.body), &section.trailing));
+                }
+                // added by both
+                None if added_by_ours.contains(&section.body.as_str()) => {}
+                None => merged.push((section.body.clone(), &section.trailing)),
+            }
+        }
+
+        let Some(base) = ancestor.get(index) else {
+            break;
+        };
+        let (o, t) = (&ours[until_ours], &theirs[until_theirs]);
+        let body = if o.body == t.body || t.body == base.body {
+            o.body.clone()
+        } else if o.body == base.body {
+            t.body.clone()
+        } else {
+            merge_lines(&base.body, &o.body, &t.body).unwrap_or_else(|body| {
+                conflicted = true;
+                body
+            })
+        };
+        merged.push((body, &o.trailing));
+        (next_ours, next_theirs) = (until_ours + 1, until_theirs + 1);
+    }
+
+    let count = merged.len();
+    let mut content = String::new();
+    for (index, (body, trailing)) in merged.into_iter().enumerate() {
+        content.push_str(body.as_str());
+        if trailing.is_empty() && index + 1 < count {
+            content.push('\n');
+        } else {
+            content.push_str(trailing);
+        }
+    }
+
+    Some(if conflicted {
+        Err(content)
+    } else {
+        Ok(content)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    #[track_caller]
+    fn m(ancestor: &str, ours: &str, theirs: &str) -> Result<String, String> {
+        super::merge("index.ftd", ancestor, ours, theirs)
+    }
+
+    #[test]
+    fn section_edits() {
+        let ancestor = "-- ftd.text: Hello\n\n-- ftd.text: World\n";
+        assert_eq!(
+            m(
+                ancestor,
+                "-- ftd.text: Hello!\n\n-- ftd.text: World\n",
+                "-- ftd.text: Hello\n\n-- ftd.text: World!\n",
+            ),
+            Ok("-- ftd.text: Hello!\n\n-- ftd.text: World!\n".to_string())
+        );
+        // the same section edited differently by both sides
+        assert_eq!(
+            m(
+                ancestor,
+                "-- ftd.text: Hi\n\n-- ftd.text: World\n",
+                "-- ftd.text: Hey\n\n-- ftd.text: World\n",
+            ),
+            Err(
+                "<<<<<<< ours\n-- ftd.text: Hi\n=======\n-- ftd.text: Hey\n>>>>>>> theirs\n\n\
+                 -- ftd.text: World\n"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn section_additions() {
+        let ancestor = "-- ftd.text: Hello\n";
+        assert_eq!(
+            m(
+                ancestor,
+                "-- ftd.text: Hello\n\n-- ftd.text: Same\n",
+                "-- ftd.text: Hello\n\n-- ftd.text: Same\n",
+            ),
+            Ok("-- ftd.text: Hello\n\n-- ftd.text: Same\n".to_string())
+        );
+        assert_eq!(
+            m(
+                ancestor,
+                "-- ftd.text: Hello\n\n-- ftd.text: Ours\n",
+                "-- ftd.text: Hello\n\n-- ftd.text: Theirs\n",
+            ),
+            Ok("-- ftd.text: Hello\n\n-- ftd.text: Ours\n\n-- ftd.text: Theirs\n".to_string())
+        );
+    }
+
+    #[test]
+    fn section_edit_and_delete() {
+        let ancestor = "-- ftd.text: Hello\n\n-- ftd.text: World\n";
+        assert_eq!(
+            m(
+                ancestor,
+                "-- ftd.text: World\n",
+                "-- ftd.text: Hello\n\n-- ftd.text: World\n"
+            ),
+            Ok("-- ftd.text: World\n".to_string())
+        );
+        assert_eq!(
+            m(
+                ancestor,
+                "-- ftd.text: World\n",
+                "-- ftd.text: Hello!\n\n-- ftd.text: World\n"
+            ),
+            Err(
+                "<<<<<<< ours\n=======\n-- ftd.text: Hello!\n>>>>>>> theirs\n\n\
+                 -- ftd.text: World\n"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn repeated_sections() {
+        // ours deletes the first text and edits the second, theirs edits the second too
+        let ancestor = indoc::indoc! {"
+            -- ftd.text: Hello
+            padding.px: 4
+            color: red
+
+            -- ftd.text: World
+            padding.px: 4
+            color: blue
+        "};
+        let ours = indoc::indoc! {"
+            -- ftd.text: World
+            padding.px: 4
+            color: green
+        "};
+        let theirs = indoc::indoc! {"
+            -- ftd.text: Hello
+            padding.px: 4
+            color: red
+
+            -- ftd.text: World!
+            padding.px: 4
+            color: blue
+        "};
+        assert_eq!(
+            m(ancestor, ours, theirs),
+            Ok("-- ftd.text: World!\npadding.px: 4\ncolor: green\n".to_string())
+        );
+
+        let sections = |source| super::sections("index.ftd", source).unwrap();
+        assert_eq!(
+            super::align(
+                &sections("-- ftd.text: a\n\n-- ftd.text: b\n\n-- ftd.text: c\n"),
+                &sections("-- ftd.text: b\n\n-- ftd.text: c!\n"),
+            ),
+            vec![None, Some(0), Some(1)]
+        );
+    }
+}
@@ -0,0 +1,337 @@
+// Prometheus text format metrics for `fastn serve`, served on `/-/metrics`.
+// https://prometheus.io/docs/instrumenting/exposition_formats/
+
+pub(crate) const PROCESSOR_SPAN: &str = "fastn_core::stuck-on-processor";
+pub(crate) const SSR_SPAN: &str = "fastn_js::ssr";
+
+/// upper bounds (in seconds) of the latency histogram buckets
+const BUCKETS: &[f64] = &[
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+static METRICS: once_cell::sync::Lazy<antidote::Mutex<Metrics>> =
+    once_cell::sync::Lazy::new(|| antidote::Mutex::new(Metrics::default()));
+
+#[derive(Debug, Default)]
+struct Metrics {
+    /// (method, status) -> count
+    requests: std::collections::BTreeMap<(String, u16), u64>,
+    request_duration: Histogram,
+    processor_duration: std::collections::BTreeMap<String, Histogram>,
+    ssr_duration: Histogram,
+    /// "hit" / "miss" -> count
+    cache: std::collections::BTreeMap<String, u64>,
+}
+
+#[derive(Debug)]
+struct Histogram {
+    /// non cumulative count of observations per bucket, the last one is `+Inf`
+    buckets: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Histogram {
+            buckets: vec![0; BUCKETS.len() + 1],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+}
+
+impl Histogram {
+    fn observe(&mut self, duration: std::time::Duration) {
+        let seconds = duration.as_secs_f64();
+        let index = BUCKETS
+            .iter()
+            .position(|le| seconds <= *le)
+            .unwrap_or(BUCKETS.len());
+        self.buckets[index] += 1;
+        self.sum += seconds;
+        self.count += 1;
+    }
+
+    fn render(&self, name: &str, labels: &str, out: &mut String) {
+        use std::fmt::Write;
+
+        let separator = if labels.is_empty() { "" } else { "," };
+        let mut cumulative = 0;
+        for (index, count) in self.buckets.iter().enumerate() {
+            cumulative += count;
+            let le = BUCKETS
+                .get(index)
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| "+Inf".to_string());
+            writeln!(
+                out,
+                "{name}_bucket{{{labels}{separator}le=\"{le}\"}} {cumulative}"
+            )
+            .unwrap();
+        }
+        let labels = if labels.is_empty() {
+            String::new()
+        } else {
+            format!("{{{labels}}}")
+        };
+        writeln!(out, "{name}_sum{labels} {}", self.sum).unwrap();
+        writeln!(out, "{name}_count{labels} {}", self.count).unwrap();
+    }
+}
+
+pub(crate) fn record_request(method: &str, status: u16, duration: std::time::Duration) {
+    let mut metrics = METRICS.lock();
+    *metrics
+        .requests
+        .entry((method.to_uppercase(), status))
+        .or_default() += 1;
+    metrics.request_duration.observe(duration);
+}
+
+fn record_processor(processor: &str, duration: std::time::Duration) {
+    METRICS
+        .lock()
+        .processor_duration
+        .entry(processor.to_string())
+        .or_default()
+        .observe(duration);
+}
+
+fn record_ssr(duration: std::time::Duration) {
+    METRICS.lock().ssr_duration.observe(duration);
+}
+
+fn record_cache(status: &str) {
+    *METRICS.lock().cache.entry(status.to_string()).or_default() += 1;
+}
+
+pub fn render() -> String {
+    METRICS.lock().render()
+}
+
+impl Metrics {
+    fn render(&self) -> String {
+        use std::fmt::Write;
+
+        let mut out = String::new();
+
+        out.push_str("# HELP fastn_http_requests_total Number of HTTP requests served.\n");
+        out.push_str("# TYPE fastn_http_requests_total counter\n");
+        for ((method, status), count) in self.requests.iter() {
+            writeln!(
+                out,
+                "fastn_http_requests_total{{method=\"{method}\",status=\"{status}\"}} {count}"
+            )
+            .unwrap();
+        }
+
+        out.push_str("# HELP fastn_http_request_duration_seconds Time taken to serve a request.\n");
+        out.push_str("# TYPE fastn_http_request_duration_seconds histogram\n");
+        self.request_duration
+            .render("fastn_http_request_duration_seconds", "", &mut out);
+
+        out.push_str("# HELP fastn_processor_duration_seconds Time taken by ftd processors.\n");
+        out.push_str("# TYPE fastn_processor_duration_seconds histogram\n");
+        for (processor, histogram) in self.processor_duration.iter() {
+            histogram.render(
+                "fastn_processor_duration_seconds",
+                format!(
+                    "processor=\"{}\"",
+                    fastn_core::utils::escape_quoted(processor)
+                )
+                .as_str(),
+                &mut out,
+            );
+        }
+
+        out.push_str("# HELP fastn_ssr_duration_seconds Time taken by server side rendering.\n");
+        out.push_str("# TYPE fastn_ssr_duration_seconds histogram\n");
+        self.ssr_duration
+            .render("fastn_ssr_duration_seconds", "", &mut out);
+
+        out.push_str(
+            "# HELP fastn_ast_cache_requests_total Lookups in the parsed document cache.\n",
+        );
+        out.push_str("# TYPE fastn_ast_cache_requests_total counter\n");
+        for (status, count) in self.cache.iter() {
+            writeln!(
+                out,
+                "fastn_ast_cache_requests_total{{result=\"{}\"}} {count}",
+                fastn_core::utils::escape_quoted(status)
+            )
+            .unwrap();
+        }
+
+        out
+    }
+}
+
+// route: /-/metrics/
+pub(crate) async fn serve() -> fastn_core::Result<fastn_core::http::Response> {
+    Ok(actix_web::HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(render()))
+}
+
+/// Collects processor, ssr and cache metrics from the existing `tracing` instrumentation.
+///
+/// Spans named [`PROCESSOR_SPAN`] (with a `processor` field) and [`SSR_SPAN`] are timed, and
+/// events with a `cache` field are counted.
+#[derive(Debug, Default)]
+pub struct Layer {}
+
+struct Timing {
+    start: std::time::Instant,
+    processor: Option<String>,
+}
+
+#[derive(Default)]
+struct Visitor {
+    processor: Option<String>,
+    cache: Option<String>,
+}
+
+impl tracing::field::Visit for Visitor {
+    fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+        match field.name() {
+            "processor" => self.processor = Some(value.to_string()),
+            "cache" => self.cache = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        self.record_str(field, format!("{:?}", value).trim_matches('"'))
+    }
+}
+
+impl<S> tracing_subscriber::Layer<S> for Layer
+where
+    S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+{
+    fn on_new_span(
+        &self,
+        attrs: &tracing::span::Attributes<'_>,
+        id: &tracing::span::Id,
+        ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        let name = attrs.metadata().name();
+        if name != PROCESSOR_SPAN && name != SSR_SPAN {
+            return;
+        }
+
+        let mut visitor = Visitor::default();
+        attrs.record(&mut visitor);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(Timing {
+                start: std::time::Instant::now(),
+                processor: visitor.processor,
+            });
+        }
+    }
+
+    fn on_event(
+        &self,
+        event: &tracing::Event<'_>,
+        _ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        if !event.fields().any(|f| f.name() == "cache") {
+            return;
+        }
+
+        let mut visitor = Visitor::default();
+        event.record(&mut visitor);
+        if let Some(cache) = visitor.cache {
+            record_cache(cache.as_str());
+        }
+    }
+
+    fn on_close(&self, id: tracing::span::Id, ctx: tracing_subscriber::layer::Context<'_, S>) {
+        let span = match ctx.span(&id) {
+            Some(span) => span,
+            None => return,
+        };
+        let timing = match span.extensions_mut().remove::<Timing>() {
+            Some(timing) => timing,
+            None => return,
+        };
+
+        match span.name() {
+            PROCESSOR_SPAN => record_processor(
+                timing.processor.as_deref().unwrap_or("unknown"),
+                timing.start.elapsed(),
+            ),
+            SSR_SPAN => record_ssr(timing.start.elapsed()),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn histogram() {
+        let mut histogram = super::Histogram::default();
+        histogram.observe(std::time::Duration::from_millis(3));
+        histogram.observe(std::time::Duration::from_millis(3));
+        histogram.observe(std::time::Duration::from_secs(20));
+
+        let mut out = String::new();
+        histogram.render("latency", "", &mut out);
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "latency_bucket{le=\"0.001\"} 0");
+        // the buckets are cumulative
+        assert_eq!(lines[1], "latency_bucket{le=\"0.005\"} 2");
+        assert_eq!(lines[11], "latency_bucket{le=\"10\"} 2");
+        assert_eq!(lines[12], "latency_bucket{le=\"+Inf\"} 3");
+        assert_eq!(lines[13], "latency_sum 20.006");
+        assert_eq!(lines[14], "latency_count 3");
+
+        let mut out = String::new();
+        histogram.render("latency", "processor=\"http\"", &mut out);
+        assert!(out.starts_with("latency_bucket{processor=\"http\",le=\"0.001\"} 0\n"));
+        assert!(out.ends_with("latency_count{processor=\"http\"} 3\n"));
+    }
+
+    #[test]
+    fn render() {
+        let mut metrics = super::Metrics::default();
+        metrics.requests.insert(("GET".to_string(), 200), 3);
+        metrics.requests.insert(("POST".to_string(), 404), 1);
+        metrics
+            .processor_duration
+            .entry("pr.\"odd\"".to_string())
+            .or_default()
+            .observe(std::time::Duration::from_millis(1));
+        metrics.cache.insert("hit".to_string(), 5);
+
+        let out = metrics.render();
+        assert!(out.contains("\nfastn_http_requests_total{method=\"GET\",status=\"200\"} 3\n"));
+        assert!(out.contains("\nfastn_http_requests_total{method=\"POST\",status=\"404\"} 1\n"));
+        assert!(out.contains(
+            "\nfastn_processor_duration_seconds_count{processor=\"pr.\\\"odd\\\"\"} 1\n"
+        ));
+        assert!(out.contains("\nfastn_ssr_duration_seconds_count 0\n"));
+        assert!(out.contains("\nfastn_ast_cache_requests_total{result=\"hit\"} 5\n"));
+    }
+
+    #[test]
+    fn layer() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let subscriber = tracing_subscriber::registry().with(super::Layer::default());
+        tracing::subscriber::with_default(subscriber, || {
+            let span =
+                tracing::info_span!("fastn_core::stuck-on-processor", processor = "metrics-test");
+            span.in_scope(|| tracing::info!(cache = "metrics-test"));
+            // package cache hits are not lookups in the parsed document cache
+            tracing::info!(package_cache = "metrics-test-package");
+        });
+
+        let metrics = super::METRICS.lock();
+        assert_eq!(metrics.processor_duration["metrics-test"].count, 1);
+        assert_eq!(metrics.cache["metrics-test"], 1);
+        assert!(!metrics.cache.contains_key("metrics-test-package"));
+    }
+}
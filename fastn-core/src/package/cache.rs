@@ -0,0 +1,183 @@
+// Package cache shared by all projects of a user, so a locked dependency is downloaded once
+// instead of once per project, and `--offline` builds can work without network access.
+//
+// Files are stored by the SHA-256 of their content in `objects/`, and `urls/` maps the url a file
+// was downloaded from to its hash. Only lookups by hash, of the `FASTN.ftd` and zip checksums in
+// `FASTN.lock`, are used while online, what a url serves can change at any time. The url map is
+// only used with `--offline`. Files in `.packages` are copies, never links to the objects.
+//
+// The cache is in `$FASTN_CACHE_DIR` if set, else in `fastn.com/packages` in the user cache dir.
+
+pub const CACHE_DIR_ENV: &str = "FASTN_CACHE_DIR";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// always download urls, and update the cache
+    Default,
+    /// never download, use the last cached copy of a url, fail if it is not in the cache
+    Offline,
+}
+
+static MODE: once_cell::sync::Lazy<antidote::RwLock<Mode>> =
+    once_cell::sync::Lazy::new(|| antidote::RwLock::new(Mode::Default));
+
+pub fn set_mode(mode: Mode) {
+    *MODE.write() = mode;
+}
+
+pub fn mode() -> Mode {
+    *MODE.read()
+}
+
+pub fn is_offline() -> bool {
+    mode() == Mode::Offline
+}
+
+pub fn root() -> Option<camino::Utf8PathBuf> {
+    if let Ok(dir) = std::env::var(CACHE_DIR_ENV) {
+        return Some(camino::Utf8PathBuf::from(dir));
+    }
+    let dir = dirs::cache_dir()?.join("fastn.com").join("packages");
+    camino::Utf8PathBuf::from_path_buf(dir).ok()
+}
+
+fn object_path(root: &camino::Utf8Path, hash: &str) -> camino::Utf8PathBuf {
+    let hash = hash.to_uppercase();
+    root.join("objects").join(&hash[..2]).join(hash)
+}
+
+fn url_path(root: &camino::Utf8Path, url: &str) -> camino::Utf8PathBuf {
+    root.join("urls")
+        .join(fastn_core::utils::generate_hash(url))
+}
+
+/// The cached file with the SHA-256 `hash`, if there is one.
+pub(crate) async fn get_object(hash: &str) -> Option<Vec<u8>> {
+    let root = root()?;
+    tokio::fs::read(object_path(&root, hash)).await.ok()
+}
+
+/// The cached copy of what was last downloaded from `url`, if there is one.
+pub(crate) async fn get_url(url: &str) -> Option<Vec<u8>> {
+    let root = root()?;
+    let hash = tokio::fs::read_to_string(url_path(&root, url)).await.ok()?;
+    tokio::fs::read(object_path(&root, hash.trim())).await.ok()
+}
+
+/// Downloads `url` using `download`, and keeps a copy for `--offline`, see `Mode`.
+#[tracing::instrument(skip(download))]
+pub(crate) async fn fetch<F, T>(url: &str, download: F) -> fastn_core::Result<Vec<u8>>
+where
+    F: FnOnce() -> T,
+    T: std::future::Future<Output = fastn_core::Result<Vec<u8>>>,
+{
+    if is_offline() {
+        return match get_url(url).await {
+            Some(data) => {
+                tracing::info!(package_cache = "hit");
+                Ok(data)
+            }
+            None => Err(fastn_core::Error::PackageError {
+                message: format!("running offline, and {} is not in the package cache", url),
+            }),
+        };
+    }
+
+    let data = download().await?;
+    if let Err(e) = store(url, data.as_slice()).await {
+        fastn_core::warning!("failed to add {} to the package cache: {:?}", url, e);
+    }
+    Ok(data)
+}
+
+async fn store(url: &str, data: &[u8]) -> fastn_core::Result<()> {
+    let root = match root() {
+        Some(root) => root,
+        None => return Ok(()),
+    };
+    let hash = store_object(&root, data).await?;
+    fastn_core::utils::update(url_path(&root, url), hash.as_bytes()).await
+}
+
+async fn store_object(root: &camino::Utf8Path, data: &[u8]) -> fastn_core::Result<String> {
+    let hash = fastn_core::utils::generate_hash(data);
+    let path = object_path(root, hash.as_str());
+    if !path.exists() {
+        // written next to it and renamed, so a half written object is never seen
+        let temp = path.with_extension(format!("{}.tmp", std::process::id()));
+        fastn_core::utils::update(&temp, data).await?;
+        tokio::fs::rename(&temp, &path).await?;
+    }
+    Ok(hash)
+}
+
+/// With `--offline`, checks that every dependency, and their dependencies, can be used without
+/// downloading anything, and errors with the list of the ones that can not.
+#[tracing::instrument(skip_all)]
+pub async fn check_offline(config: &fastn_core::Config) -> fastn_core::Result<()> {
+    if !is_offline() {
+        return Ok(());
+    }
+
+    let lock = fastn_core::package::lock::LockFile::read(&config.root).await?;
+    let mut walker =
+        fastn_core::package::dependency::Walker::new(config.package.dependencies.as_slice());
+    let mut missing = vec![];
+
+    while let Some((dependency, _)) = walker.next() {
+        let root = config.get_root_for_package(&dependency.package);
+        let locked = lock.as_ref().and_then(|l| {
+            l.packages
+                .iter()
+                .find(|p| p.name == dependency.package.name)
+        });
+        let content = if root.join("FASTN.ftd").exists() {
+            tokio::fs::read(root.join("FASTN.ftd")).await.ok()
+        } else if dependency.path.is_some() || dependency.git.is_some() {
+            None
+        } else if let Some(locked) = locked {
+            get_object(locked.sha256.as_str()).await
+        } else {
+            get_url(format!("{}/FASTN.ftd", dependency.package.name).as_str()).await
+        };
+
+        let content = match content.map(String::from_utf8) {
+            Some(Ok(content)) => content,
+            _ => {
+                missing.push(dependency.package.name.to_string());
+                continue;
+            }
+        };
+        // a locked zip is installed along with `FASTN.ftd`, so it has to be in the cache too
+        if let Some(archive) = locked.and_then(|l| l.archive_sha256.as_ref()) {
+            if !root.join("FASTN.ftd").exists() && get_object(archive.as_str()).await.is_none() {
+                missing.push(dependency.package.name.to_string());
+                continue;
+            }
+        }
+
+        if walker
+            .add(dependency.package.name.as_str(), content.as_str())
+            .is_err()
+        {
+            missing.push(dependency.package.name.to_string());
+        }
+    }
+
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    Err(fastn_core::Error::PackageError {
+        message: format!(
+            "running offline, but these packages are neither in .packages nor in the package \
+            cache ({}):\n{}\nrun `fastn update` with network access to download them",
+            root().map(|r| r.to_string()).unwrap_or_default(),
+            missing
+                .iter()
+                .map(|name| format!("  {}", name))
+                .collect::<Vec<_>>()
+                .join("\n")
+        ),
+    })
+}
@@ -0,0 +1,133 @@
+// Syntax definitions (`.sublime-syntax`) and themes (`.tmTheme`) shipped by packages, used by
+// `ftd.code` in addition to the ones built into ftd.
+
+const SYNTAX_FOLDER: &str = "syntax";
+const THEME_FOLDER: &str = "theme";
+const SYNTAX_EXTENSION: &str = "sublime-syntax";
+const THEME_EXTENSION: &str = "tmTheme";
+
+/// The last assets built by `load()`, reused while the source files are unchanged since
+/// `Config` is read again for every request.
+static LOADED: once_cell::sync::Lazy<
+    antidote::Mutex<Option<std::sync::Arc<ftd::executor::code::CodeAssets>>>,
+> = once_cell::sync::Lazy::new(|| antidote::Mutex::new(None));
+
+/// Returns the syntaxes and themes of the current package and its dependencies, they are used
+/// in the documents of any of them. `None` if none of the packages ship any.
+///
+/// Dependencies that have not been downloaded yet are skipped, `Config::resolve_package()`
+/// loads them again when one gets downloaded.
+#[tracing::instrument(skip_all)]
+pub(crate) fn load(
+    config: &fastn_core::Config,
+) -> fastn_core::Result<Option<std::sync::Arc<ftd::executor::code::CodeAssets>>> {
+    let mut syntaxes = vec![];
+    let mut themes = vec![];
+
+    let (s, t) = files(&config.package, &config.root)?;
+    syntaxes.extend(s);
+    themes.extend(t);
+
+    for dependency in config.package.dependencies.iter() {
+        let package = config
+            .all_packages
+            .borrow()
+            .get(dependency.package.name.as_str())
+            .cloned()
+            .unwrap_or_else(|| dependency.package.clone());
+        let root = config.get_root_for_package(&package);
+        if !root.exists() {
+            continue;
+        }
+
+        let (s, t) = files(&package, &root)?;
+        syntaxes.extend(s);
+        themes.extend(t);
+    }
+
+    tracing::info!(syntaxes = syntaxes.len(), themes = themes.len());
+    if syntaxes.is_empty() && themes.is_empty() {
+        return Ok(None);
+    }
+
+    let fingerprint = ftd::executor::code::CodeAssets::fingerprint_of(&syntaxes, &themes);
+    let mut loaded = LOADED.lock();
+    if let Some(assets) = loaded.as_ref().filter(|v| v.fingerprint() == fingerprint) {
+        return Ok(Some(assets.clone()));
+    }
+
+    let assets = std::sync::Arc::new(ftd::executor::code::CodeAssets::new(
+        syntaxes.as_slice(),
+        themes.as_slice(),
+    )?);
+    *loaded = Some(assets.clone());
+    Ok(Some(assets))
+}
+
+/// Returns the syntaxes and themes declared in `FASTN.ftd` of `package`, followed by the ones
+/// found in its `syntax` and `theme` folders.
+fn files(
+    package: &fastn_core::Package,
+    root: &camino::Utf8Path,
+) -> fastn_core::Result<(
+    Vec<ftd::executor::code::AssetFile>,
+    Vec<ftd::executor::code::AssetFile>,
+)> {
+    let syntaxes = declared_and_discovered(
+        root,
+        package.code_syntaxes.as_slice(),
+        SYNTAX_FOLDER,
+        SYNTAX_EXTENSION,
+    )?;
+    let themes = declared_and_discovered(
+        root,
+        package.code_themes.as_slice(),
+        THEME_FOLDER,
+        THEME_EXTENSION,
+    )?;
+    Ok((syntaxes, themes))
+}
+
+fn declared_and_discovered(
+    root: &camino::Utf8Path,
+    declared: &[String],
+    folder: &str,
+    extension: &str,
+) -> fastn_core::Result<Vec<ftd::executor::code::AssetFile>> {
+    let mut paths: Vec<camino::Utf8PathBuf> = declared
+        .iter()
+        .map(|path| root.join(path.trim_start_matches('/')))
+        .collect();
+
+    let folder = root.join(folder);
+    if folder.is_dir() {
+        let mut discovered = vec![];
+        for entry in std::fs::read_dir(&folder)? {
+            let path = match camino::Utf8PathBuf::from_path_buf(entry?.path()) {
+                Ok(path) => path,
+                Err(_) => continue,
+            };
+            if path.extension() == Some(extension) && !paths.contains(&path) {
+                discovered.push(path);
+            }
+        }
+        // read_dir() order is platform dependent, and the order decides which syntax wins
+        // when two of them claim the same file extension
+        discovered.sort();
+        paths.extend(discovered);
+    }
+
+    let mut files = vec![];
+    for path in paths {
+        let content =
+            std::fs::read_to_string(&path).map_err(|e| fastn_core::Error::PackageError {
+                message: format!("failed to read {}: {}", path, e),
+            })?;
+        files.push(ftd::executor::code::AssetFile {
+            name: path.file_stem().unwrap_or_default().to_string(),
+            path: path.to_string(),
+            content,
+        });
+    }
+    Ok(files)
+}
@@ -9,9 +9,146 @@ pub struct Dependency {
     pub implements: Vec<String>,
     pub endpoint: Option<String>,
     pub mountpoint: Option<String>,
+    /// `path` is the folder of a package used straight from the file system, relative to the
+    /// `FASTN.ftd` declaring the dependency. Nothing is downloaded or copied for it. It is only
+    /// used in the `FASTN.ftd` of the package being built, see `Dependency::unlink()`.
+    pub path: Option<camino::Utf8PathBuf>,
+    /// `git` is the repository the package is cloned from, and `rev` the branch, tag or commit
+    /// checked out.
+    pub git: Option<String>,
+    pub rev: Option<String>,
 }
 
 impl Dependency {
+    /// Points the package of a `path` dependency to the linked folder, `root` is the folder of the
+    /// `FASTN.ftd` declaring the dependency. Only call this for the dependencies of the package
+    /// being built, use `unlink()` for the ones of its dependencies.
+    pub(crate) fn link(&mut self, root: &camino::Utf8Path) {
+        if let Some(ref path) = self.path {
+            self.package.fastn_path = Some(root.join(path).join("FASTN.ftd"));
+        }
+    }
+
+    /// A `path` is only used in the `FASTN.ftd` of the package being built, it means nothing on the
+    /// machine of someone depending on `declared_in`, so the dependency is downloaded instead.
+    pub(crate) fn unlink(&mut self, declared_in: &str) {
+        if let Some(path) = self.path.take() {
+            fastn_core::warning!(
+                "ignoring `path: {}` of the dependency {} of {}, `path` is only used in the \
+                FASTN.ftd of the package being built",
+                path,
+                self.package.name,
+                declared_in
+            );
+        }
+    }
+
+    /// Clones a `git` dependency into `package_root`, or if it was cloned already, checks out
+    /// `rev` if that is not what is checked out.
+    #[tracing::instrument(skip(self))]
+    pub(crate) fn clone_git(&self, package_root: &camino::Utf8Path) -> fastn_core::Result<()> {
+        let url = match self.git {
+            Some(ref url) => url,
+            None => return Ok(()),
+        };
+        // both are passed to git, which would take them for options
+        for value in std::iter::once(url).chain(self.rev.iter()) {
+            if value.starts_with('-') {
+                return Err(fastn_core::Error::PackageError {
+                    message: format!(
+                        "invalid git dependency {}: `{}` can not start with `-`",
+                        self.package.name, value
+                    ),
+                });
+            }
+        }
+
+        let cloned = package_root.join("FASTN.ftd").exists();
+        if cloned {
+            let rev = match self.rev {
+                Some(ref rev) => rev,
+                None => return Ok(()),
+            };
+            let head = git(&["rev-parse", "HEAD"], Some(package_root))?;
+            let wanted = git(
+                &[
+                    "rev-parse",
+                    "--verify",
+                    "--quiet",
+                    format!("{}^{{commit}}", rev).as_str(),
+                ],
+                Some(package_root),
+            );
+            if wanted.map(|wanted| wanted == head).unwrap_or(false) {
+                return Ok(());
+            }
+        }
+        if fastn_core::package::cache::is_offline() {
+            return Err(fastn_core::Error::PackageError {
+                message: format!("running offline, can not clone {}", url),
+            });
+        }
+
+        let start = std::time::Instant::now();
+        if cloned {
+            print!("Fetching {} ... ", url);
+        } else {
+            print!("Cloning {} ... ", url);
+        }
+        std::io::Write::flush(&mut std::io::stdout())?;
+
+        if cloned {
+            git(
+                &["fetch", "--quiet", "--tags", "origin"],
+                Some(package_root),
+            )?;
+        } else {
+            if let Some(parent) = package_root.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            git(
+                &["clone", "--quiet", "--", url, package_root.as_str()],
+                None,
+            )?;
+        }
+        if let Some(ref rev) = self.rev {
+            // `--` after `rev`, so it is never taken for a path either
+            git(&["checkout", "--quiet", rev, "--"], Some(package_root))?;
+        }
+
+        fastn_core::utils::print_end(
+            format!("Cloned {}", self.package.name.as_str()).as_str(),
+            start,
+        );
+        return Ok(());
+
+        fn git(args: &[&str], dir: Option<&camino::Utf8Path>) -> fastn_core::Result<String> {
+            let mut command = std::process::Command::new("git");
+            if let Some(dir) = dir {
+                command.current_dir(dir);
+            }
+            let output =
+                command
+                    .args(args)
+                    .output()
+                    .map_err(|e| fastn_core::Error::PackageError {
+                        message: format!("failed to run git: {}", e),
+                    })?;
+            if !output.status.success() {
+                return Err(fastn_core::Error::PackageError {
+                    message: format!(
+                        "`git {}` failed: {}",
+                        args.join(" "),
+                        String::from_utf8_lossy(output.stderr.as_slice()).trim()
+                    ),
+                });
+            }
+            Ok(String::from_utf8_lossy(output.stdout.as_slice())
+                .trim()
+                .to_string())
+        }
+    }
+
     pub fn unaliased_name(&self, name: &str) -> Option<String> {
         if name.starts_with(self.package.name.as_str()) {
             Some(name.to_string())
@@ -72,6 +209,9 @@ pub(crate) struct DependencyTemp {
     pub endpoint: Option<String>,
     #[serde(rename = "mount-point")]
     pub mountpoint: Option<String>,
+    pub path: Option<String>,
+    pub git: Option<String>,
+    pub rev: Option<String>,
 }
 
 impl DependencyTemp {
@@ -80,6 +220,19 @@ impl DependencyTemp {
             Some((package, alias)) => (package, Some(alias.to_string())),
             _ => (self.name.as_str(), None),
         };
+        if self.path.is_some() && self.git.is_some() {
+            return Err(fastn_core::Error::PackageError {
+                message: format!(
+                    "dependency {} can have either `path` or `git`, not both",
+                    package_name
+                ),
+            });
+        }
+        if self.rev.is_some() && self.git.is_none() {
+            return Err(fastn_core::Error::PackageError {
+                message: format!("dependency {}: `rev` needs `git`", package_name),
+            });
+        }
         Ok(fastn_core::Dependency {
             package: fastn_core::Package::new(package_name),
             version: self.version,
@@ -94,13 +247,16 @@ impl DependencyTemp {
                 }
                 None => None,
             },
+            path: self.path.map(camino::Utf8PathBuf::from),
+            git: self.git,
+            rev: self.rev,
         })
     }
 }
 
 impl fastn_core::Package {
-    /// `process()` checks the package exists in `.packages` or `fastn_HOME` folder (`fastn_HOME` not
-    /// yet implemented), and if not downloads and unpacks the method.
+    /// `process()` checks the package exists in `.packages`, and if not downloads and unpacks the
+    /// method. Downloads go through the shared package cache, see `fastn_core::package::cache`.
     ///
     /// This is done in following way:
     /// Download the FASTN.ftd file first for the package to download.
@@ -186,56 +342,12 @@ impl fastn_core::Package {
                     })?
             };
 
-            let path = std::env::temp_dir().join(format!("{}.zip", self.name.replace('/', "__")));
-
             let start = std::time::Instant::now();
             print!("Downloading {} ... ", self.name.as_str());
             std::io::stdout().flush()?;
             // Download the zip folder
-            {
-                let response =
-                    if download_url[1..].contains("://") || download_url.starts_with("//") {
-                        crate::http::http_get(download_url.as_str()).await?
-                    } else if let Ok(response) =
-                        crate::http::http_get(format!("https://{}", download_url).as_str()).await
-                    {
-                        response
-                    } else {
-                        crate::http::http_get(format!("http://{}", download_url).as_str()).await?
-                    };
-                let mut file = std::fs::File::create(&path)?;
-                // TODO: instead of reading the whole thing in memory use tokio::io::copy() somehow?
-                file.write_all(&response)?;
-                // file.write_all(response.text().await?.as_bytes())?;
-            }
-
-            let file = std::fs::File::open(&path)?;
-            // TODO: switch to async_zip crate
-            let mut archive = zip::ZipArchive::new(file)?;
-            for i in 0..archive.len() {
-                let mut c_file = archive.by_index(i).unwrap();
-                let out_path = match c_file.enclosed_name() {
-                    Some(path) => path.to_owned(),
-                    None => continue,
-                };
-                let out_path_without_folder = out_path.to_str().unwrap().split_once('/').unwrap().1;
-                let file_extract_path = base_dir
-                    .join(".packages")
-                    .join(self.name.as_str())
-                    .join(out_path_without_folder);
-                if c_file.name().ends_with('/') {
-                    std::fs::create_dir_all(&file_extract_path)?;
-                } else {
-                    if let Some(p) = file_extract_path.parent() {
-                        if !p.exists() {
-                            std::fs::create_dir_all(p)?;
-                        }
-                    }
-                    // Note: we will be able to use tokio::io::copy() with async_zip
-                    let mut outfile = std::fs::File::create(file_extract_path)?;
-                    std::io::copy(&mut c_file, &mut outfile)?;
-                }
-            }
+            let response = crate::http::construct_url_and_get(download_url.as_str()).await?;
+            extract_zip(response.as_slice(), &root)?;
             fastn_core::utils::print_end(
                 format!("Downloaded {}", self.name.as_str()).as_str(),
                 start,
@@ -284,11 +396,7 @@ impl fastn_core::Package {
 
         async fn get_fastn(name: &str) -> fastn_core::Result<String> {
             if let Ok(response_fastn) =
-                crate::http::http_get_str(format!("https://{}/FASTN.ftd", name).as_str()).await
-            {
-                Ok(response_fastn)
-            } else if let Ok(response_fastn) =
-                crate::http::http_get_str(format!("http://{}/FASTN.ftd", name).as_str()).await
+                crate::http::construct_url_and_get_str(format!("{}/FASTN.ftd", name).as_str()).await
             {
                 Ok(response_fastn)
             } else {
@@ -405,11 +513,7 @@ impl fastn_core::Package {
 
         async fn get_fastn(name: &str) -> fastn_core::Result<String> {
             if let Ok(response_fastn) =
-                crate::http::http_get_str(format!("https://{}/FASTN.ftd", name).as_str()).await
-            {
-                Ok(response_fastn)
-            } else if let Ok(response_fastn) =
-                crate::http::http_get_str(format!("http://{}/FASTN.ftd", name).as_str()).await
+                crate::http::construct_url_and_get_str(format!("{}/FASTN.ftd", name).as_str()).await
             {
                 Ok(response_fastn)
             } else {
@@ -439,15 +543,7 @@ impl fastn_core::Package {
         std::io::stdout().flush()?;
         // Download the zip folder
         {
-            let response = if download_url[1..].contains("://") || download_url.starts_with("//") {
-                crate::http::http_get(download_url.as_str()).await?
-            } else if let Ok(response) =
-                crate::http::http_get(format!("https://{}", download_url).as_str()).await
-            {
-                response
-            } else {
-                crate::http::http_get(format!("http://{}", download_url).as_str()).await?
-            };
+            let response = crate::http::construct_url_and_get(download_url.as_str()).await?;
             let mut file = std::fs::File::create(&path)?;
             // TODO: instead of reading the whole thing in memory use tokio::io::copy() somehow?
             file.write_all(&response)?;
@@ -496,7 +592,7 @@ impl fastn_core::Package {
     /// `process_fastn()`, together with `process()`, recursively make dependency packages available inside
     /// `.packages` directory
     #[async_recursion::async_recursion(?Send)]
-    async fn process_fastn(
+    pub(crate) async fn process_fastn(
         root: &camino::Utf8PathBuf,
         base_path: &camino::Utf8PathBuf,
         downloaded_package: &mut Vec<String>,
@@ -550,6 +646,23 @@ impl fastn_core::Package {
 
         if download_dependencies {
             for dep in package.dependencies.iter_mut() {
+                dep.unlink(package.name.as_str());
+                if dep.git.is_some() {
+                    let dst = base_path.join(".packages").join(dep.package.name.as_str());
+                    dep.clone_git(&dst)?;
+                    fastn_core::Package::process_fastn(
+                        &dst,
+                        base_path,
+                        downloaded_package,
+                        &mut dep.package,
+                        false,
+                        true,
+                        &dst.join("FASTN.ftd"),
+                    )
+                    .await?;
+                    continue;
+                }
+
                 let dep_path = root.join(".packages").join(dep.package.name.as_str());
 
                 if dep_path.exists() {
@@ -669,6 +782,23 @@ impl fastn_core::Package {
 
         if download_dependencies {
             for dep in package.dependencies.iter_mut() {
+                dep.unlink(package.name.as_str());
+                if dep.git.is_some() {
+                    let dst = base_path.join(".packages").join(dep.package.name.as_str());
+                    dep.clone_git(&dst)?;
+                    fastn_core::Package::process_fastn2(
+                        &dst,
+                        base_path,
+                        downloaded_package,
+                        &mut dep.package,
+                        false,
+                        true,
+                        &dst.join("FASTN.ftd"),
+                    )
+                    .await?;
+                    continue;
+                }
+
                 let dep_path = root.join(".packages").join(dep.package.name.as_str());
 
                 if dep_path.exists() {
@@ -739,3 +869,225 @@ impl fastn_core::Package {
         Ok(())
     }
 }
+
+/// Parses the `FASTN.ftd` of the package `name`.
+pub(crate) fn parse_fastn(
+    name: &str,
+    content: &str,
+) -> fastn_core::Result<ftd::ftd2021::p2::Document> {
+    let lib = fastn_core::FastnLibrary::default();
+    fastn_core::doc::parse_ftd("fastn", content, &lib).map_err(|e| {
+        fastn_core::Error::PackageError {
+            message: format!("failed to parse FASTN.ftd of {}: {:?}", name, e),
+        }
+    })
+}
+
+/// Walks the dependencies of a package, and their dependencies, breadth first, each package once.
+/// `next()` gives a package and if it is a direct dependency, `add()` walks the dependencies
+/// listed in its `FASTN.ftd` too.
+pub(crate) struct Walker {
+    queue: std::collections::VecDeque<(Dependency, bool)>,
+    seen: std::collections::HashSet<String>,
+    repeated: Vec<(Dependency, bool)>,
+}
+
+impl Walker {
+    pub(crate) fn new(dependencies: &[Dependency]) -> Walker {
+        Walker {
+            queue: dependencies.iter().map(|d| (d.clone(), true)).collect(),
+            seen: Default::default(),
+            repeated: vec![],
+        }
+    }
+
+    pub(crate) fn add(&mut self, name: &str, fastn_ftd: &str) -> fastn_core::Result<()> {
+        let dependencies: Vec<DependencyTemp> =
+            parse_fastn(name, fastn_ftd)?.get("fastn#dependency")?;
+        for dependency in dependencies {
+            let mut dependency = dependency.into_dependency()?;
+            dependency.unlink(name);
+            self.queue.push_back((dependency, false));
+        }
+        Ok(())
+    }
+
+    /// The dependencies on packages `next()` already gave, for another dependent
+    pub(crate) fn repeated(&self) -> &[(Dependency, bool)] {
+        self.repeated.as_slice()
+    }
+}
+
+impl Iterator for Walker {
+    type Item = (Dependency, bool);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((dependency, direct)) = self.queue.pop_front() {
+            if self.seen.insert(dependency.package.name.to_string()) {
+                return Some((dependency, direct));
+            }
+            self.repeated.push((dependency, direct));
+        }
+        None
+    }
+}
+
+/// Unpacks a package zip into `root`, leaving out the top level folder every file in the zip is
+/// in.
+pub(crate) fn extract_zip(archive: &[u8], root: &camino::Utf8Path) -> fastn_core::Result<()> {
+    // TODO: switch to async_zip crate
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(archive))?;
+    for i in 0..archive.len() {
+        let mut c_file = archive.by_index(i)?;
+        let out_path = match c_file.enclosed_name() {
+            Some(path) => path.to_owned(),
+            None => continue,
+        };
+        let out_path_without_folder = match out_path.to_str().and_then(|p| p.split_once('/')) {
+            Some((_, path)) => path.to_string(),
+            None => continue,
+        };
+        let file_extract_path = root.join(out_path_without_folder);
+        if c_file.name().ends_with('/') {
+            std::fs::create_dir_all(&file_extract_path)?;
+        } else {
+            if let Some(p) = file_extract_path.parent() {
+                if !p.exists() {
+                    std::fs::create_dir_all(p)?;
+                }
+            }
+            // Note: we will be able to use tokio::io::copy() with async_zip
+            let mut outfile = std::fs::File::create(file_extract_path)?;
+            std::io::copy(&mut c_file, &mut outfile)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    fn dependency(git: &str, rev: Option<&str>) -> super::Dependency {
+        super::Dependency {
+            package: fastn_core::Package::new("example.com/ui"),
+            version: None,
+            notes: None,
+            alias: None,
+            implements: vec![],
+            endpoint: None,
+            mountpoint: None,
+            path: None,
+            git: Some(git.to_string()),
+            rev: rev.map(str::to_string),
+        }
+    }
+
+    fn git(dir: &camino::Utf8Path, args: &[&str]) -> String {
+        let output = std::process::Command::new("git")
+            .current_dir(dir)
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+        String::from_utf8(output.stdout).unwrap().trim().to_string()
+    }
+
+    #[test]
+    fn walker() {
+        let mut walker = super::Walker::new(&[
+            super::Dependency {
+                git: None,
+                ..dependency("", None)
+            },
+            super::Dependency {
+                package: fastn_core::Package::new("example.com/icons"),
+                git: None,
+                ..dependency("", None)
+            },
+        ]);
+
+        let (ui, direct) = walker.next().unwrap();
+        assert_eq!((ui.package.name.as_str(), direct), ("example.com/ui", true));
+        walker
+            .add(
+                "example.com/ui",
+                "-- import: fastn\n\n-- fastn.package: example.com/ui\n\n\
+                -- fastn.dependency: example.com/icons\nversion: 1.2\n\n\
+                -- fastn.dependency: example.com/theme\n",
+            )
+            .unwrap();
+
+        let next: Vec<_> = walker
+            .by_ref()
+            .map(|(d, direct)| (d.package.name, direct))
+            .collect();
+        assert_eq!(
+            next,
+            vec![
+                ("example.com/icons".to_string(), true),
+                ("example.com/theme".to_string(), false)
+            ]
+        );
+        // the `icons` of `ui` is not given again, but is kept to check its version
+        assert_eq!(walker.repeated().len(), 1);
+        assert_eq!(walker.repeated()[0].0.version.as_deref(), Some("1.2"));
+        assert!(!walker.repeated()[0].1);
+
+        assert!(walker.add("example.com/theme", "-- fastn.package").is_err());
+    }
+
+    #[test]
+    fn clone_git_rejects_options() {
+        let root = camino::Utf8PathBuf::from("/nonexistent/fastn-clone-git-options");
+        let e = dependency("--upload-pack=touch /tmp/x", None)
+            .clone_git(&root)
+            .unwrap_err();
+        assert!(e.to_string().contains("can not start with `-`"), "{}", e);
+        let e = dependency("https://example.com/ui.git", Some("-b"))
+            .clone_git(&root)
+            .unwrap_err();
+        assert!(e.to_string().contains("can not start with `-`"), "{}", e);
+    }
+
+    #[test]
+    fn clone_git_checks_out_changed_rev() {
+        let base = camino::Utf8PathBuf::from_path_buf(std::env::temp_dir())
+            .unwrap()
+            .join(format!("fastn-clone-git-{}", std::process::id()));
+        let remote = base.join("remote");
+        let root = base.join("clone");
+        std::fs::create_dir_all(&remote).unwrap();
+        git(&remote, &["init", "--quiet"]);
+        std::fs::write(remote.join("FASTN.ftd"), "v1").unwrap();
+        git(&remote, &["add", "FASTN.ftd"]);
+        git(&remote, &["commit", "--quiet", "-m", "v1"]);
+        git(&remote, &["tag", "v1"]);
+        std::fs::write(remote.join("FASTN.ftd"), "v2").unwrap();
+        git(&remote, &["commit", "--quiet", "-am", "v2"]);
+        git(&remote, &["tag", "v2"]);
+
+        dependency(remote.as_str(), Some("v1"))
+            .clone_git(&root)
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(root.join("FASTN.ftd")).unwrap(),
+            "v1"
+        );
+
+        // a tag made after the clone has to be fetched
+        std::fs::write(remote.join("FASTN.ftd"), "v3").unwrap();
+        git(&remote, &["commit", "--quiet", "-am", "v3"]);
+        git(&remote, &["tag", "v3"]);
+        for rev in ["v2", "v3", "v1"] {
+            dependency(remote.as_str(), Some(rev))
+                .clone_git(&root)
+                .unwrap();
+            assert_eq!(
+                std::fs::read_to_string(root.join("FASTN.ftd")).unwrap(),
+                rev
+            );
+        }
+
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+}
@@ -0,0 +1,598 @@
+// `FASTN.lock` pins every (transitive) dependency of a package to the version that was resolved
+// for it by `fastn update`, along with where it was downloaded from, the SHA-256 of its
+// `FASTN.ftd`, and of its `zip` if it publishes one.
+//
+// A locked package with a zip is installed from the zip, and the zip is checked against the lock,
+// so no file of it is downloaded unchecked. Packages without a zip are served a file at a time from
+// their `download-base-url`, for those only `FASTN.ftd` is checked.
+//
+// A package publishes versions by setting `version` in `fastn.package`, and listing the older
+// ones, each with the url they are served from, using `fastn.release`:
+//
+// -- fastn.package: example.com/ui
+// version: 1.2.0
+//
+// -- fastn.release: 1.1.0
+// download-base-url: https://example.com/ui/v1.1.0/
+
+pub const LOCK_FILE: &str = "FASTN.lock";
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LockFile {
+    pub packages: Vec<LockedPackage>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: Option<String>,
+    /// the `version` of the dependency in `FASTN.ftd` when this was resolved, only set for direct
+    /// dependencies
+    pub requirement: Option<String>,
+    #[serde(rename = "download-base-url")]
+    pub download_base_url: String,
+    /// of the `FASTN.ftd` served from `download_base_url`
+    pub sha256: String,
+    /// of the `zip` given in that `FASTN.ftd`
+    #[serde(rename = "archive-sha256")]
+    pub archive_sha256: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct Release {
+    version: String,
+    #[serde(rename = "download-base-url")]
+    download_base_url: String,
+}
+
+impl LockFile {
+    /// Returns `None` if the package does not have a `FASTN.lock`.
+    pub async fn read(root: &camino::Utf8Path) -> fastn_core::Result<Option<LockFile>> {
+        let path = root.join(LOCK_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = tokio::fs::read_to_string(&path).await?;
+        let lib = fastn_core::FastnLibrary::default();
+        let doc = fastn_core::doc::parse_ftd(LOCK_FILE, content.as_str(), &lib).map_err(|e| {
+            fastn_core::Error::PackageError {
+                message: format!("failed to parse {}: {:?}", path, e),
+            }
+        })?;
+        Ok(Some(LockFile {
+            packages: doc.get("fastn#lock")?,
+        }))
+    }
+
+    pub async fn write(&self, root: &camino::Utf8Path) -> fastn_core::Result<()> {
+        let mut content =
+            ";; This file is generated by `fastn update`, do not edit it by hand.\n\n\
+            -- import: fastn"
+                .to_string();
+
+        for package in self.packages.iter() {
+            content.push_str(format!("\n\n-- fastn.lock: {}", package.name).as_str());
+            if let Some(ref version) = package.version {
+                content.push_str(format!("\nversion: {}", version).as_str());
+            }
+            if let Some(ref requirement) = package.requirement {
+                content.push_str(format!("\nrequirement: {}", requirement).as_str());
+            }
+            content.push_str(
+                format!(
+                    "\ndownload-base-url: {}\nsha256: {}",
+                    package.download_base_url, package.sha256
+                )
+                .as_str(),
+            );
+            if let Some(ref archive_sha256) = package.archive_sha256 {
+                content.push_str(format!("\narchive-sha256: {}", archive_sha256).as_str());
+            }
+        }
+        content.push('\n');
+
+        fastn_core::utils::update(&root.join(LOCK_FILE), content.as_bytes()).await
+    }
+
+    /// The locked entry for `dependency`, errors if the version in `FASTN.ftd` was changed to one
+    /// the locked version does not satisfy.
+    pub(crate) fn get(
+        &self,
+        dependency: &fastn_core::Package,
+        requirement: Option<&str>,
+    ) -> fastn_core::Result<Option<&LockedPackage>> {
+        let locked = match self.packages.iter().find(|p| p.name.eq(&dependency.name)) {
+            Some(locked) => locked,
+            None => return Ok(None),
+        };
+
+        if let (Some(requirement), Some(version)) = (requirement, locked.version.as_ref()) {
+            if !matches(requirement, version)? {
+                return Err(fastn_core::Error::PackageError {
+                    message: format!(
+                        "{} is locked to version {}, which does not match `{}`, run `fastn update` \
+                        to update {}",
+                        dependency.name, version, requirement, LOCK_FILE
+                    ),
+                });
+            }
+        }
+
+        Ok(Some(locked))
+    }
+}
+
+impl LockedPackage {
+    /// If the installed `FASTN.ftd` is the locked one, of the locked version.
+    pub(crate) async fn is_installed(
+        &self,
+        fastn_ftd: &camino::Utf8Path,
+    ) -> fastn_core::Result<bool> {
+        let content = tokio::fs::read_to_string(fastn_ftd).await?;
+        if !fastn_core::utils::generate_hash(content.as_str())
+            .eq_ignore_ascii_case(self.sha256.as_str())
+        {
+            return Ok(false);
+        }
+        let package: Option<fastn_package::old_fastn::PackageTemp> =
+            fastn_core::package::dependency::parse_fastn(self.name.as_str(), content.as_str())?
+                .get("fastn#package")?;
+        Ok(package.and_then(|p| p.version).eq(&self.version))
+    }
+
+    /// Downloads the `FASTN.ftd` of the locked version, and checks it is the same one that was
+    /// locked.
+    pub(crate) async fn fetch_fastn(&self) -> fastn_core::Result<String> {
+        if let Some(content) = fastn_core::package::cache::get_object(self.sha256.as_str()).await {
+            return Ok(String::from_utf8(content)?);
+        }
+
+        let content = crate::http::construct_url_and_get_str(
+            format!("{}/FASTN.ftd", self.download_base_url.trim_end_matches('/')).as_str(),
+        )
+        .await?;
+
+        let sha256 = fastn_core::utils::generate_hash(content.as_str());
+        if !sha256.eq_ignore_ascii_case(self.sha256.as_str()) {
+            return Err(fastn_core::Error::PackageError {
+                message: format!(
+                    "checksum mismatch for {} ({}): {} has {}, but {} was downloaded",
+                    self.name, self.download_base_url, LOCK_FILE, self.sha256, sha256
+                ),
+            });
+        }
+
+        Ok(content)
+    }
+
+    /// Downloads the `zip` of the locked version, checks it is the same one that was locked, and
+    /// unpacks it into `package_root`. `fastn` is the `FASTN.ftd` returned by `fetch_fastn()`.
+    ///
+    /// Returns `false` if no zip was locked, the files of the package have to be downloaded one
+    /// at a time in that case.
+    pub(crate) async fn install_archive(
+        &self,
+        fastn: &str,
+        package_root: &camino::Utf8PathBuf,
+    ) -> fastn_core::Result<bool> {
+        let expected = match self.archive_sha256 {
+            Some(ref expected) => expected,
+            None => return Ok(false),
+        };
+
+        let archive = match fastn_core::package::cache::get_object(expected.as_str()).await {
+            Some(archive) => archive,
+            None => {
+                let url = zip_url(self.name.as_str(), fastn)?.ok_or_else(|| {
+                    fastn_core::Error::PackageError {
+                        message: format!(
+                            "{} has a zip checksum for {}, but its FASTN.ftd has no `zip`",
+                            LOCK_FILE, self.name
+                        ),
+                    }
+                })?;
+                crate::http::construct_url_and_get(url.as_str()).await?
+            }
+        };
+
+        let sha256 = fastn_core::utils::generate_hash(archive.as_slice());
+        if !sha256.eq_ignore_ascii_case(expected.as_str()) {
+            return Err(fastn_core::Error::PackageError {
+                message: format!(
+                    "checksum mismatch for the zip of {}: {} has {}, but {} was downloaded",
+                    self.name, LOCK_FILE, expected, sha256
+                ),
+            });
+        }
+
+        fastn_core::package::dependency::extract_zip(archive.as_slice(), package_root)?;
+        Ok(true)
+    }
+}
+
+/// Resolves the versions of all dependencies of the package, and of their dependencies, picking
+/// the latest published version that matches the `version` given for the dependency.
+///
+/// `path` and `git` dependencies are not locked, but their dependencies are.
+#[tracing::instrument(skip_all)]
+pub async fn resolve(config: &fastn_core::Config) -> fastn_core::Result<LockFile> {
+    let mut walker =
+        fastn_core::package::dependency::Walker::new(config.package.dependencies.as_slice());
+    let mut packages: Vec<LockedPackage> = vec![];
+
+    while let Some((dependency, direct)) = walker.next() {
+        let name = dependency.package.name.to_string();
+        let content = if dependency.path.is_some() || dependency.git.is_some() {
+            let root = config.get_root_for_package(&dependency.package);
+            dependency.clone_git(&root)?;
+            tokio::fs::read_to_string(root.join("FASTN.ftd")).await?
+        } else {
+            let requirement = dependency.version.clone();
+            let (locked, content) = resolve_one(name.as_str(), requirement.as_deref()).await?;
+            tracing::info!(package = name, version = ?locked.version);
+            packages.push(LockedPackage {
+                requirement: if direct { requirement } else { None },
+                ..locked
+            });
+            content
+        };
+        walker.add(name.as_str(), content.as_str())?;
+    }
+
+    // the version picked for a package has to be one all its dependents can use
+    for (dependency, direct) in walker.repeated() {
+        let locked = match packages
+            .iter_mut()
+            .find(|p| p.name.eq(&dependency.package.name))
+        {
+            Some(locked) => locked,
+            None => continue,
+        };
+        match (dependency.version.as_ref(), locked.version.as_ref()) {
+            (Some(requirement), Some(version)) if !matches(requirement, version)? => {
+                return Err(fastn_core::Error::PackageError {
+                    message: format!(
+                        "conflicting versions of {}: {} was picked, but `{}` is also required",
+                        locked.name, version, requirement
+                    ),
+                });
+            }
+            _ => {}
+        }
+        if *direct {
+            locked.requirement = dependency.version.clone();
+        }
+    }
+
+    packages.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(LockFile { packages })
+}
+
+/// Returns the locked entry for the version of `name` picked for `requirement`, and its
+/// `FASTN.ftd`.
+async fn resolve_one(
+    name: &str,
+    requirement: Option<&str>,
+) -> fastn_core::Result<(LockedPackage, String)> {
+    let latest = fastn_core::Package::new(name).get_fastn().await?;
+    let doc = fastn_core::package::dependency::parse_fastn(name, latest.as_str())?;
+    let package: Option<fastn_package::old_fastn::PackageTemp> = doc.get("fastn#package")?;
+    let package = package.ok_or_else(|| fastn_core::Error::PackageError {
+        message: format!("FASTN.ftd of {} does not contain package definition", name),
+    })?;
+    let releases: Vec<Release> = doc.get("fastn#release")?;
+
+    let latest_base_url = package
+        .download_base_url
+        .clone()
+        .unwrap_or_else(|| name.to_string());
+
+    let (version, download_base_url) = match requirement {
+        None => (package.version.clone(), latest_base_url.clone()),
+        Some(requirement) => {
+            let mut candidates = releases
+                .into_iter()
+                .map(|r| (r.version, r.download_base_url))
+                .collect::<Vec<_>>();
+            if let Some(version) = package.version.clone() {
+                candidates.push((version, latest_base_url.clone()));
+            }
+
+            let mut best: Option<(Vec<u64>, String, String)> = None;
+            for (version, base_url) in candidates {
+                let parsed = match parse_version(version.as_str()) {
+                    Some(parsed) => parsed,
+                    None => continue,
+                };
+                if !matches(requirement, version.as_str())? {
+                    continue;
+                }
+                if best
+                    .as_ref()
+                    .map(|(b, _, _)| compare(&parsed, b).is_gt())
+                    .unwrap_or(true)
+                {
+                    best = Some((parsed, version, base_url));
+                }
+            }
+
+            match best {
+                Some((_, version, base_url)) => (Some(version), base_url),
+                None => {
+                    return Err(fastn_core::Error::PackageError {
+                        message: format!(
+                            "no published version of {} matches `{}`",
+                            name, requirement
+                        ),
+                    })
+                }
+            }
+        }
+    };
+
+    let content = if download_base_url.eq(&latest_base_url) {
+        latest
+    } else {
+        crate::http::construct_url_and_get_str(
+            format!("{}/FASTN.ftd", download_base_url.trim_end_matches('/')).as_str(),
+        )
+        .await?
+    };
+
+    let archive_sha256 = match zip_url(name, content.as_str())? {
+        Some(url) => {
+            let archive = crate::http::construct_url_and_get(url.as_str()).await?;
+            Some(fastn_core::utils::generate_hash(archive.as_slice()))
+        }
+        None => None,
+    };
+
+    Ok((
+        LockedPackage {
+            name: name.to_string(),
+            version,
+            requirement: None,
+            download_base_url,
+            sha256: fastn_core::utils::generate_hash(content.as_str()),
+            archive_sha256,
+        },
+        content,
+    ))
+}
+
+/// The `zip` given in the `FASTN.ftd` of `name`.
+fn zip_url(name: &str, fastn: &str) -> fastn_core::Result<Option<String>> {
+    let package: Option<fastn_package::old_fastn::PackageTemp> =
+        fastn_core::package::dependency::parse_fastn(name, fastn)?.get("fastn#package")?;
+    Ok(package.and_then(|p| p.zip))
+}
+
+/// `1.2.3`, `v1.2`, returns `None` for anything that is not dot separated numbers.
+fn parse_version(version: &str) -> Option<Vec<u64>> {
+    version
+        .trim()
+        .trim_start_matches(['v', 'V'])
+        .split('.')
+        .map(|v| v.parse::<u64>().ok())
+        .collect()
+}
+
+/// Compares versions as if the missing components were `0`.
+fn compare(a: &[u64], b: &[u64]) -> std::cmp::Ordering {
+    let len = std::cmp::max(a.len(), b.len());
+    (0..len)
+        .map(|i| a.get(i).unwrap_or(&0).cmp(b.get(i).unwrap_or(&0)))
+        .find(|o| o.is_ne())
+        .unwrap_or(std::cmp::Ordering::Equal)
+}
+
+/// Checks `version` against a comma separated list of cargo style comparators: `1.2` (same as
+/// `^1.2`), `^1.2`, `~1.2`, `=1.2.3`, `>=1.0`, `<2`, `1.*` and `*`.
+fn matches(requirement: &str, version: &str) -> fastn_core::Result<bool> {
+    let version = match parse_version(version) {
+        Some(version) => version,
+        None => return Ok(false),
+    };
+
+    for comparator in requirement.split(',').map(str::trim) {
+        if comparator.is_empty() || comparator.eq("*") {
+            continue;
+        }
+
+        let (operator, operand) = match comparator.find(|c: char| c.is_ascii_digit()) {
+            Some(index) => (comparator[..index].trim(), &comparator[index..]),
+            None => ("", comparator),
+        };
+        // `1.2.*`, `1.x` are the same as `=1.2`, `=1`
+        let (operator, operand) = match operand.split_once(['*', 'x', 'X']) {
+            Some((prefix, _)) if operator.is_empty() => ("=", prefix.trim_end_matches('.')),
+            _ => (operator, operand),
+        };
+        let operand = parse_version(operand).ok_or_else(|| fastn_core::Error::UsageError {
+            message: format!("invalid version requirement: `{}`", comparator),
+        })?;
+
+        let ok = match operator {
+            "=" => operand
+                .iter()
+                .enumerate()
+                .all(|(i, v)| version.get(i).unwrap_or(&0) == v),
+            ">" => compare(&version, &operand).is_gt(),
+            ">=" => compare(&version, &operand).is_ge(),
+            "<" => compare(&version, &operand).is_lt(),
+            "<=" => compare(&version, &operand).is_le(),
+            "~" => {
+                // ~1.2.3 is >=1.2.3, <1.3.0 and ~1 is >=1.0.0, <2.0.0
+                let upper = bump(&operand, std::cmp::min(1, operand.len() - 1));
+                compare(&version, &operand).is_ge() && compare(&version, &upper).is_lt()
+            }
+            "^" | "" => {
+                // the left most non zero component can not change
+                let index = operand
+                    .iter()
+                    .position(|v| *v != 0)
+                    .unwrap_or(operand.len() - 1);
+                let upper = bump(&operand, index);
+                compare(&version, &operand).is_ge() && compare(&version, &upper).is_lt()
+            }
+            _ => {
+                return Err(fastn_core::Error::UsageError {
+                    message: format!("invalid version requirement: `{}`", comparator),
+                })
+            }
+        };
+
+        if !ok {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// `bump([1, 2, 3], 1)` is `[1, 3]`
+fn bump(version: &[u64], index: usize) -> Vec<u64> {
+    let mut bumped = version[..=index].to_vec();
+    bumped[index] += 1;
+    bumped
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn parse_version() {
+        assert_eq!(super::parse_version("1.2.3"), Some(vec![1, 2, 3]));
+        assert_eq!(super::parse_version(" v1.2 "), Some(vec![1, 2]));
+        assert_eq!(super::parse_version("V0"), Some(vec![0]));
+        assert_eq!(super::parse_version("1.2.3-beta"), None);
+        assert_eq!(super::parse_version("1..2"), None);
+        assert_eq!(super::parse_version(""), None);
+    }
+
+    #[test]
+    fn compare() {
+        use std::cmp::Ordering;
+
+        assert_eq!(super::compare(&[1, 2], &[1, 2, 0]), Ordering::Equal);
+        assert_eq!(super::compare(&[1, 2, 1], &[1, 2]), Ordering::Greater);
+        assert_eq!(super::compare(&[1, 10], &[1, 9, 9]), Ordering::Greater);
+        assert_eq!(super::compare(&[0, 9], &[1]), Ordering::Less);
+        assert_eq!(super::compare(&[], &[0, 0]), Ordering::Equal);
+    }
+
+    #[test]
+    fn bump() {
+        assert_eq!(super::bump(&[1, 2, 3], 0), vec![2]);
+        assert_eq!(super::bump(&[1, 2, 3], 1), vec![1, 3]);
+        assert_eq!(super::bump(&[0, 0, 3], 2), vec![0, 0, 4]);
+    }
+
+    #[track_caller]
+    fn m(requirement: &str, version: &str) -> bool {
+        super::matches(requirement, version).unwrap()
+    }
+
+    #[test]
+    fn matches_caret() {
+        assert!(m("1.2", "1.2.0"));
+        assert!(m("1.2", "1.9.9"));
+        assert!(!m("1.2", "2.0.0"));
+        assert!(!m("1.2", "1.1.9"));
+        assert!(m("^1.2.3", "1.2.3"));
+        assert!(!m("^1.2.3", "1.2.2"));
+        // the left most non zero component can not change
+        assert!(m("^0.2.3", "0.2.9"));
+        assert!(!m("^0.2.3", "0.3.0"));
+        assert!(m("^0.0.3", "0.0.3"));
+        assert!(!m("^0.0.3", "0.0.4"));
+        assert!(m("^0", "0.9"));
+        assert!(!m("^0", "1.0"));
+    }
+
+    #[test]
+    fn matches_tilde() {
+        assert!(m("~1.2.3", "1.2.9"));
+        assert!(!m("~1.2.3", "1.3.0"));
+        assert!(!m("~1.2.3", "1.2.2"));
+        assert!(m("~1", "1.9"));
+        assert!(!m("~1", "2.0"));
+    }
+
+    #[test]
+    fn matches_comparators() {
+        assert!(m("=1.2.3", "1.2.3"));
+        assert!(!m("=1.2.3", "1.2.4"));
+        assert!(m("=1.2", "1.2.7"));
+        assert!(m(">=1.0", "1.0.0"));
+        assert!(!m(">1.0", "1.0.0"));
+        assert!(m("<2", "1.99"));
+        assert!(!m("<2", "2.0"));
+        assert!(m("<=2", "2.0.0"));
+        assert!(m(">=1.2, <1.5", "1.4.9"));
+        assert!(!m(">=1.2, <1.5", "1.5.0"));
+    }
+
+    #[test]
+    fn matches_wildcards() {
+        assert!(m("*", "0.0.1"));
+        assert!(m("", "3.0"));
+        assert!(m("1.*", "1.7.2"));
+        assert!(!m("1.*", "2.0.0"));
+        assert!(m("1.2.x", "1.2.5"));
+        assert!(!m("1.2.x", "1.3.0"));
+    }
+
+    #[test]
+    fn matches_invalid() {
+        // versions that can not be parsed never match
+        assert!(!m("*", "latest"));
+        assert!(super::matches("!1.0", "1.0").is_err());
+        assert!(super::matches(">=one", "1.0").is_err());
+    }
+
+    #[tokio::test]
+    async fn is_installed() {
+        let root = camino::Utf8PathBuf::from_path_buf(std::env::temp_dir())
+            .unwrap()
+            .join(format!("fastn-lock-installed-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let fastn_ftd = root.join("FASTN.ftd");
+        let v1 = "-- import: fastn\n\n-- fastn.package: example.com/ui\nversion: 1.0.0\n";
+        std::fs::write(&fastn_ftd, v1).unwrap();
+
+        let locked = super::LockedPackage {
+            name: "example.com/ui".to_string(),
+            version: Some("1.0.0".to_string()),
+            requirement: None,
+            download_base_url: "https://example.com/ui/".to_string(),
+            sha256: fastn_core::utils::generate_hash(v1),
+            archive_sha256: None,
+        };
+        assert!(locked.is_installed(&fastn_ftd).await.unwrap());
+
+        // installed before the lock was updated
+        let v2 = super::LockedPackage {
+            version: Some("2.0.0".to_string()),
+            sha256: fastn_core::utils::generate_hash(v1.replace("1.0.0", "2.0.0")),
+            ..locked.clone()
+        };
+        assert!(!v2.is_installed(&fastn_ftd).await.unwrap());
+        std::fs::write(&fastn_ftd, format!("{}\n", v1)).unwrap();
+        assert!(!locked.is_installed(&fastn_ftd).await.unwrap());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn zip_url() {
+        let fastn = "-- import: fastn\n\n-- fastn.package: example.com/ui\nzip: \
+            https://example.com/ui.zip\n";
+        assert_eq!(
+            super::zip_url("example.com/ui", fastn).unwrap().as_deref(),
+            Some("https://example.com/ui.zip")
+        );
+        let fastn = "-- import: fastn\n\n-- fastn.package: example.com/ui\n";
+        assert_eq!(super::zip_url("example.com/ui", fastn).unwrap(), None);
+    }
+}
@@ -1,8 +1,13 @@
 pub mod app;
+pub mod cache;
+pub mod code_assets;
 pub mod dependency;
+pub mod lock;
 pub mod package_doc;
+pub mod publish;
 pub mod redirects;
 pub mod user_group;
+pub mod vendor;
 
 #[derive(Debug, Clone)]
 pub struct Package {
@@ -27,6 +32,13 @@ pub struct Package {
     pub fastn_path: Option<camino::Utf8PathBuf>,
     /// `ignored` keeps track of files that are to be ignored by `fastn build`, `fastn sync` etc.
     pub ignored_paths: Vec<String>,
+    /// `code_syntaxes` and `code_themes` are the `.sublime-syntax` and `.tmTheme` files, relative
+    /// to the package root, declared using `fastn.code-syntax` and `fastn.code-theme`. Files in
+    /// the `syntax` and `theme` folders are picked up even if not declared.
+    pub code_syntaxes: Vec<String>,
+    pub code_themes: Vec<String>,
+    /// `markdown` are the markdown extensions enabled using `fastn.markdown`.
+    pub markdown: Option<ftd::executor::markup::MarkdownOptions>,
     /// `fonts` keeps track of the fonts used by the package.
     ///
     /// Note that this too is kind of bad design, we will move fonts to `fastn_core::Package` struct soon.
@@ -70,6 +82,17 @@ pub struct Package {
 
     /// Redirect URLs
     pub redirects: Option<ftd::Map<String>>,
+
+    /// `required_approvals` is the number of approvals a CR needs before it can be merged, set
+    /// using `fastn.cr-rules`.
+    pub required_approvals: usize,
+
+    /// `history_backend` is where the history of the package is kept, set using
+    /// `history-backend` in `fastn.package`.
+    pub history_backend: fastn_core::git::HistoryBackend,
+
+    /// `publish` are the destinations of `fastn publish-static`, set using `fastn.publish`.
+    pub publish: Vec<publish::Target>,
 }
 
 impl Package {
@@ -89,6 +112,9 @@ impl Package {
             auto_import: vec![],
             fastn_path: None,
             ignored_paths: vec![],
+            code_syntaxes: vec![],
+            code_themes: vec![],
+            markdown: None,
             fonts: vec![],
             import_auto_imports_from_original: true,
             groups: std::collections::BTreeMap::new(),
@@ -103,6 +129,9 @@ impl Package {
             apps: vec![],
             icon: None,
             redirects: None,
+            required_approvals: 0,
+            history_backend: Default::default(),
+            publish: vec![],
         }
     }
 
@@ -508,6 +537,9 @@ impl Package {
             .collect::<Vec<fastn_core::Result<fastn_core::Dependency>>>()
             .into_iter()
             .collect::<fastn_core::Result<Vec<fastn_core::Dependency>>>()?;
+        for dep in package.dependencies.iter_mut() {
+            dep.unlink(package.name.as_str());
+        }
 
         let user_groups: Vec<crate::user_group::UserGroupTemp> =
             fastn_document.get("fastn#user-group")?;
@@ -519,30 +551,56 @@ impl Package {
             .map(|f| f.into_auto_import())
             .collect();
         package.fonts = fastn_document.get("fastn#font")?;
+        package.code_syntaxes = fastn_document.get("fastn#code-syntax")?;
+        package.code_themes = fastn_document.get("fastn#code-theme")?;
+        package.markdown = fastn_document.get("fastn#markdown")?;
         package.sitemap_temp = fastn_document.get("fastn#sitemap")?;
         *self = package;
         Ok(())
     }
 
+    /// `locked` is the entry of this package in `FASTN.lock`, if there is one, the locked version
+    /// is downloaded instead of the latest one.
     #[tracing::instrument(skip(self))]
     pub(crate) async fn get_and_resolve(
         &self,
         package_root: &camino::Utf8PathBuf,
+        locked: Option<&fastn_core::package::lock::LockedPackage>,
     ) -> fastn_core::Result<fastn_core::Package> {
-        use tokio::io::AsyncWriteExt;
-
         let file_extract_path = package_root.join("FASTN.ftd");
+        if let Some(locked) = locked {
+            if file_extract_path.exists() && !locked.is_installed(&file_extract_path).await? {
+                // another version is in `.packages`, from before the lock was made or changed
+                tracing::info!(package = self.name, version = ?locked.version, "reinstalling");
+                tokio::fs::remove_dir_all(package_root).await?;
+            }
+        }
         if !file_extract_path.exists() {
             std::fs::create_dir_all(package_root)?;
-            let fastn_string = self.get_fastn().await?;
-            tokio::fs::File::create(&file_extract_path)
-                .await?
-                .write_all(fastn_string.as_bytes())
-                .await?;
+            let fastn_string = match locked {
+                Some(locked) => {
+                    let fastn_string = locked.fetch_fastn().await?;
+                    locked
+                        .install_archive(fastn_string.as_str(), package_root)
+                        .await?;
+                    fastn_string
+                }
+                None => self.get_fastn().await?,
+            };
+            fastn_core::utils::write(package_root, "FASTN.ftd", fastn_string.as_bytes()).await?;
         }
 
         let mut package = self.clone();
         package.resolve(&file_extract_path).await?;
+        if let Some(locked) = locked {
+            package.download_base_url = if locked.archive_sha256.is_some() {
+                // every file came from the checked zip, nothing else is downloaded
+                None
+            } else {
+                // the rest of the files have to come from the same version
+                Some(locked.download_base_url.to_string())
+            };
+        }
         Ok(package)
     }
 
@@ -554,7 +612,13 @@ impl Package {
             fastn_doc.get("fastn#package")?;
 
         let mut package = match temp_package {
-            Some(v) => v.into_package(),
+            Some(v) => {
+                let history_backend =
+                    fastn_core::git::HistoryBackend::from_header(v.history_backend.as_deref())?;
+                let mut package = v.into_package();
+                package.history_backend = history_backend;
+                package
+            }
             None => {
                 return Err(fastn_core::Error::PackageError {
                     message: "FASTN.ftd does not contain package definition".to_string(),
@@ -588,8 +652,14 @@ impl Package {
                 implements: Vec::new(),
                 endpoint: None,
                 mountpoint: None,
+                path: None,
+                git: None,
+                rev: None,
             });
         };
+        for dep in deps.iter_mut() {
+            dep.link(root);
+        }
         // setting dependencies
         package.dependencies = deps;
         package.fastn_path = Some(root.join("FASTN.ftd"));
@@ -607,9 +677,17 @@ impl Package {
             .collect();
 
         package.ignored_paths = fastn_doc.get::<Vec<String>>("fastn#ignore")?;
+        package.code_syntaxes = fastn_doc.get::<Vec<String>>("fastn#code-syntax")?;
+        package.code_themes = fastn_doc.get::<Vec<String>>("fastn#code-theme")?;
+        package.markdown = fastn_doc.get("fastn#markdown")?;
         package.fonts = fastn_doc.get("fastn#font")?;
         package.sitemap_temp = fastn_doc.get("fastn#sitemap")?;
         package.dynamic_urls_temp = fastn_doc.get("fastn#dynamic-urls")?;
+        package.required_approvals = fastn_doc
+            .get::<Option<fastn_core::review::CRRulesTemp>>("fastn#cr-rules")?
+            .map(|v| v.required_approvals)
+            .unwrap_or_default();
+        package.publish = fastn_doc.get("fastn#publish")?;
 
         // TODO: resolve group dependent packages, there may be imported group from foreign package
         //   We need to make sure to resolve that package as well before moving ahead
@@ -713,6 +791,9 @@ impl PackageTempIntoPackage for fastn_package::old_fastn::PackageTemp {
             auto_import: vec![],
             fastn_path: None,
             ignored_paths: vec![],
+            code_syntaxes: vec![],
+            code_themes: vec![],
+            markdown: None,
             fonts: vec![],
             import_auto_imports_from_original: self.import_auto_imports_from_original,
             groups: std::collections::BTreeMap::new(),
@@ -727,6 +808,9 @@ impl PackageTempIntoPackage for fastn_package::old_fastn::PackageTemp {
             apps: vec![],
             icon: self.icon,
             redirects: None,
+            required_approvals: 0,
+            history_backend: Default::default(),
+            publish: vec![],
         }
     }
 }
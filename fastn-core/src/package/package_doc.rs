@@ -402,6 +402,7 @@ pub(crate) async fn read_ftd_2022(
         translated_data: Default::default(),
         base_url: base_url.to_string(),
         module_package_map: Default::default(),
+        skip_processors: false,
     };
 
     // Get Prefix Body => [AutoImports + Actual Doc content]
@@ -421,7 +422,10 @@ pub(crate) async fn read_ftd_2022(
     )
     .await
     {
-        Ok(v) => v,
+        Ok(mut v) => {
+            v.markdown_options = current_package.markdown.clone();
+            v
+        }
         Err(e) => {
             tracing::error!(msg = "failed to parse", doc = main.id.as_str());
             return Err(fastn_core::Error::PackageError {
@@ -477,6 +481,7 @@ pub(crate) async fn read_ftd_2023(
         translated_data: Default::default(),
         base_url: base_url.to_string(),
         module_package_map: Default::default(),
+        skip_processors: false,
     };
 
     // Get Prefix Body => [AutoImports + Actual Doc content]
@@ -496,7 +501,10 @@ pub(crate) async fn read_ftd_2023(
     )
     .await
     {
-        Ok(v) => v,
+        Ok(mut v) => {
+            v.markdown_options = current_package.markdown.clone();
+            v
+        }
         Err(e) => {
             tracing::error!(msg = "failed to parse", doc = main.id.as_str());
             return Err(fastn_core::Error::PackageError {
@@ -512,8 +520,9 @@ pub(crate) async fn read_ftd_2023(
     let js_ast_data = ftd::js::document_into_js_ast(main_ftd_doc);
     let js_document_script = fastn_js::to_js(js_ast_data.asts.as_slice(), true);
     let js_ftd_script = fastn_js::to_js(ftd::js::default_bag_into_js_ast().as_slice(), false);
-    let ssr_body =
-        fastn_js::ssr_with_js_string(format!("{js_ftd_script}\n{js_document_script}").as_str());
+    let ssr_body = tracing::info_span!("fastn_js::ssr", doc = main.id.as_str()).in_scope(|| {
+        fastn_js::ssr_with_js_string(format!("{js_ftd_script}\n{js_document_script}").as_str())
+    });
 
     all_packages.extend(lib.config.all_packages.into_inner());
     drop(all_packages);
@@ -575,6 +584,10 @@ pub(crate) async fn process_ftd(
     } else {
         main.id.replace(".ftd", "/index.html")
     };
+    let file_rel_path = match config.locale {
+        Some(ref locale) => format!("{}/{}", locale, file_rel_path),
+        None => file_rel_path,
+    };
 
     let response = read_ftd(config, &main, base_url, !no_static, test).await?;
     fastn_core::utils::write(
@@ -0,0 +1,15 @@
+/// A destination of `fastn publish-static`, `-- fastn.publish: <name>` in FASTN.ftd. `kind` is
+/// `fastn-cloud`, `s3`, `directory` or `rsync`; which of the other fields are needed depends on it.
+#[derive(serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct Target {
+    pub name: String,
+    pub kind: String,
+    /// the folder of `directory`, or the `[user@]host:path` of `rsync`
+    pub path: Option<String>,
+    pub bucket: Option<String>,
+    /// of an S3-compatible service, AWS if not given
+    pub endpoint: Option<String>,
+    pub region: Option<String>,
+    /// the files are published under this path of the bucket
+    pub prefix: Option<String>,
+}
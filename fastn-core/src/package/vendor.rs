@@ -0,0 +1,214 @@
+// `fastn vendor` copies every (transitive) dependency of a package into `vendor/`, so they can be
+// committed along with it. `vendor/fastn.vendor.ftd` lists the vendored packages:
+//
+// -- fastn.vendored: example.com/ui
+// version: 1.2.0
+// source: https://example.com/ui.zip
+// sha256: 2C26B46B68FFC68FF99B453C1D30413413422D706483BFA0F98A5E886266E7AE
+//
+// `sha256` is of all the vendored files of the package, see `files_sha256()`, using a vendored
+// package whose files do not match is an error. Vendored packages are used instead of the ones in
+// `.packages`, and nothing is ever downloaded for them. `path` dependencies are not vendored, only
+// their dependencies are. `fastn vendor` only replaces the folders of the packages it vendored
+// before, anything else in `vendor/` is left alone.
+
+pub const VENDOR_DIR: &str = "vendor";
+pub const MANIFEST_FILE: &str = "fastn.vendor.ftd";
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Manifest {
+    pub packages: Vec<VendoredPackage>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+pub struct VendoredPackage {
+    pub name: String,
+    pub version: Option<String>,
+    /// the zip the package was downloaded from, or the `git` repository it was cloned from
+    pub source: Option<String>,
+    pub sha256: String,
+}
+
+impl Manifest {
+    /// Returns `None` if the package has not been vendored.
+    pub async fn read(root: &camino::Utf8Path) -> fastn_core::Result<Option<Manifest>> {
+        let path = root.join(VENDOR_DIR).join(MANIFEST_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = tokio::fs::read_to_string(&path).await?;
+        let lib = fastn_core::FastnLibrary::default();
+        let doc =
+            fastn_core::doc::parse_ftd(MANIFEST_FILE, content.as_str(), &lib).map_err(|e| {
+                fastn_core::Error::PackageError {
+                    message: format!("failed to parse {}: {:?}", path, e),
+                }
+            })?;
+        Ok(Some(Manifest {
+            packages: doc.get("fastn#vendored")?,
+        }))
+    }
+
+    pub async fn write(&self, root: &camino::Utf8Path) -> fastn_core::Result<()> {
+        let mut content =
+            ";; This file is generated by `fastn vendor`, do not edit it by hand.\n\n\
+            -- import: fastn"
+                .to_string();
+
+        for package in self.packages.iter() {
+            content.push_str(format!("\n\n-- fastn.vendored: {}", package.name).as_str());
+            if let Some(ref version) = package.version {
+                content.push_str(format!("\nversion: {}", version).as_str());
+            }
+            if let Some(ref source) = package.source {
+                content.push_str(format!("\nsource: {}", source).as_str());
+            }
+            content.push_str(format!("\nsha256: {}", package.sha256).as_str());
+        }
+        content.push('\n');
+
+        fastn_core::utils::update(
+            &root.join(VENDOR_DIR).join(MANIFEST_FILE),
+            content.as_bytes(),
+        )
+        .await
+    }
+}
+
+/// `true` if `root` has a `vendor/` folder created by `fastn vendor`.
+pub(crate) fn is_vendored(root: &camino::Utf8Path) -> bool {
+    root.join(VENDOR_DIR).join(MANIFEST_FILE).exists()
+}
+
+/// The folder of `package_name` in `vendor/`, if it has been vendored.
+pub(crate) fn package_root(
+    root: &camino::Utf8Path,
+    package_name: &str,
+) -> Option<camino::Utf8PathBuf> {
+    if !is_vendored(root) {
+        return None;
+    }
+    let package_root = root.join(VENDOR_DIR).join(package_name);
+    if package_root.join("FASTN.ftd").exists() {
+        Some(package_root)
+    } else {
+        None
+    }
+}
+
+/// Errors if the vendored files of `package_name` are not the ones `fastn vendor` copied, as
+/// vendored packages are not supposed to be edited by hand.
+pub(crate) async fn verify(root: &camino::Utf8Path, package_name: &str) -> fastn_core::Result<()> {
+    let manifest = match Manifest::read(root).await? {
+        Some(manifest) => manifest,
+        None => return Ok(()),
+    };
+    let vendored = match manifest.packages.iter().find(|p| p.name.eq(package_name)) {
+        Some(vendored) => vendored,
+        None => return Ok(()),
+    };
+
+    let sha256 = files_sha256(&root.join(VENDOR_DIR).join(package_name))?;
+    if !sha256.eq_ignore_ascii_case(vendored.sha256.as_str()) {
+        return Err(fastn_core::Error::PackageError {
+            message: format!(
+                "{}/{}/ has been modified since it was vendored, run `fastn vendor` again",
+                VENDOR_DIR, package_name
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// The SHA-256 of the list of the files in `package_root`, a line with the SHA-256 and the path of
+/// each, sorted by path. So adding, removing, renaming or editing any file changes it.
+pub(crate) fn files_sha256(package_root: &camino::Utf8Path) -> fastn_core::Result<String> {
+    fn files(
+        dir: &camino::Utf8Path,
+        found: &mut Vec<camino::Utf8PathBuf>,
+    ) -> fastn_core::Result<()> {
+        for entry in dir.read_dir_utf8()? {
+            let path = entry?.into_path();
+            if path.is_dir() {
+                files(&path, found)?;
+            } else {
+                found.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    let mut paths = vec![];
+    files(package_root, &mut paths)?;
+    let mut paths = paths
+        .into_iter()
+        .map(|path| {
+            let relative = path.strip_prefix(package_root).unwrap_or(&path).as_str();
+            (relative.replace('\\', "/"), path)
+        })
+        .collect::<Vec<_>>();
+    paths.sort();
+    let mut list = String::new();
+    for (relative, path) in paths {
+        let sha256 = fastn_core::utils::generate_hash(std::fs::read(path)?);
+        list.push_str(format!("{} {}\n", sha256, relative).as_str());
+    }
+    Ok(fastn_core::utils::generate_hash(list))
+}
+
+/// Points the vendored `dependencies` to their folder in `vendor/`, `path` dependencies are never
+/// vendored, they are used from where they are.
+pub(crate) fn link(root: &camino::Utf8Path, dependencies: &mut [fastn_core::Dependency]) {
+    for dependency in dependencies.iter_mut() {
+        if dependency.path.is_some() {
+            continue;
+        }
+        if let Some(package_root) = package_root(root, dependency.package.name.as_str()) {
+            dependency.package.fastn_path = Some(package_root.join("FASTN.ftd"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[tokio::test]
+    async fn verify() {
+        let root = camino::Utf8PathBuf::from_path_buf(std::env::temp_dir())
+            .unwrap()
+            .join(format!("fastn-vendor-verify-{}", std::process::id()));
+        let fastn_ftd = "-- import: fastn\n\n-- fastn.package: example.com/ui\n";
+        let package_root = root.join(super::VENDOR_DIR).join("example.com/ui");
+        std::fs::create_dir_all(&package_root).unwrap();
+        std::fs::write(package_root.join("FASTN.ftd"), fastn_ftd).unwrap();
+        std::fs::create_dir_all(package_root.join("ui")).unwrap();
+        std::fs::write(package_root.join("ui/button.ftd"), "-- ftd.text: hello\n").unwrap();
+        super::Manifest {
+            packages: vec![super::VendoredPackage {
+                name: "example.com/ui".to_string(),
+                version: None,
+                source: None,
+                sha256: super::files_sha256(&package_root).unwrap(),
+            }],
+        }
+        .write(&root)
+        .await
+        .unwrap();
+
+        super::verify(&root, "example.com/ui").await.unwrap();
+        // packages that are not vendored are not checked
+        super::verify(&root, "example.com/other").await.unwrap();
+
+        // any file, not just `FASTN.ftd`
+        std::fs::write(package_root.join("ui/button.ftd"), "-- ftd.text: bye\n").unwrap();
+        let e = super::verify(&root, "example.com/ui").await.unwrap_err();
+        assert!(e.to_string().contains("has been modified"), "{}", e);
+
+        std::fs::write(package_root.join("ui/button.ftd"), "-- ftd.text: hello\n").unwrap();
+        super::verify(&root, "example.com/ui").await.unwrap();
+        std::fs::write(package_root.join("ui/extra.ftd"), "").unwrap();
+        assert!(super::verify(&root, "example.com/ui").await.is_err());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
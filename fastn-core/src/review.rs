@@ -0,0 +1,675 @@
+//! Reviews of a CR are stored in `-/<cr-number>/-/reviews.ftd`, along with the other files of
+//! the CR, so they are versioned and synced like everything else.
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReviewStatus {
+    Approved,
+    ChangesRequested,
+}
+
+impl ReviewStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ReviewStatus::Approved => "approved",
+            ReviewStatus::ChangesRequested => "changes-requested",
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct CRReview {
+    pub reviewer: String,
+    pub status: ReviewStatus,
+    pub timestamp: u128,
+    /// The `cr_version()` reviewed, an approval only counts for the version it was given to
+    pub version: Option<String>,
+    pub message: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct CRComment {
+    pub id: usize,
+    pub author: String,
+    /// The file and line the comment is about, `None` for comments about the whole CR
+    pub file: Option<String>,
+    pub line: Option<usize>,
+    /// The comment this is a reply to
+    #[serde(rename = "reply-to")]
+    pub reply_to: Option<usize>,
+    pub timestamp: u128,
+    pub message: String,
+}
+
+#[derive(Debug, Default)]
+pub struct CRReviews {
+    pub reviewers: Vec<String>,
+    /// All reviews, oldest first, the latest review of a reviewer is their current state
+    pub reviews: Vec<CRReview>,
+    pub comments: Vec<CRComment>,
+}
+
+/// `-- fastn.cr-rules:` in FASTN.ftd
+#[derive(serde::Deserialize, Debug)]
+pub(crate) struct CRRulesTemp {
+    #[serde(rename = "required-approvals")]
+    pub required_approvals: usize,
+}
+
+impl CRReviews {
+    pub(crate) fn is_reviewer(&self, identity: &str) -> bool {
+        self.reviewers.iter().any(|v| v.eq(identity))
+    }
+
+    /// The latest review of each reviewer
+    pub(crate) fn latest_reviews(&self) -> std::collections::BTreeMap<&str, &CRReview> {
+        self.reviews
+            .iter()
+            .filter(|v| self.is_reviewer(v.reviewer.as_str()))
+            .map(|v| (v.reviewer.as_str(), v))
+            .collect()
+    }
+
+    /// The reviewers who approved `version` of the CR, approvals of older versions don't count
+    pub(crate) fn approvals(&self, version: &str) -> usize {
+        self.latest_reviews()
+            .values()
+            .filter(|v| {
+                v.status.eq(&ReviewStatus::Approved) && v.version.as_deref().eq(&Some(version))
+            })
+            .count()
+    }
+
+    pub(crate) fn changes_requested_by(&self) -> Vec<&str> {
+        self.latest_reviews()
+            .into_iter()
+            .filter(|(_, v)| v.status.eq(&ReviewStatus::ChangesRequested))
+            .map(|(k, _)| k)
+            .collect()
+    }
+
+    pub(crate) fn add_comment(
+        &mut self,
+        author: &str,
+        message: &str,
+        file: Option<&str>,
+        line: Option<usize>,
+        reply_to: Option<usize>,
+    ) -> fastn_core::Result<usize> {
+        if message.trim().is_empty() {
+            return fastn_core::usage_error("The comment is empty".to_string());
+        }
+        if line.is_some() && file.is_none() {
+            return fastn_core::usage_error("`--line` needs `--file`".to_string());
+        }
+        if let Some(reply_to) = reply_to {
+            if !self.comments.iter().any(|v| v.id.eq(&reply_to)) {
+                return fastn_core::usage_error(format!("No comment with id {}", reply_to));
+            }
+        }
+        let id = self.comments.iter().map(|v| v.id).max().unwrap_or_default() + 1;
+        self.comments.push(CRComment {
+            id,
+            author: author.to_string(),
+            file: file.map(ToString::to_string),
+            line,
+            reply_to,
+            timestamp: fastn_core::timestamp_nanosecond(),
+            message: message.trim().to_string(),
+        });
+        Ok(id)
+    }
+}
+
+/// `github:Amitu` -> `github: amitu`, identities are compared ignoring case.
+pub(crate) fn identity(value: &str) -> fastn_core::Result<String> {
+    match fastn_core::user_group::parse_identities(value).as_slice() {
+        [identity] if !identity.key.is_empty() && !identity.value.is_empty() => Ok(format!(
+            "{}: {}",
+            identity.key.to_lowercase(),
+            identity.value.to_lowercase()
+        )),
+        _ => fastn_core::usage_error(format!(
+            "`{}` is not an identity, use `<key>: <value>`, e.g. `github: amitu`",
+            value
+        )),
+    }
+}
+
+/// The version of the changes of a CR, the SHA-256 of the list of its files and their content.
+/// Editing any file of the CR changes it, adding reviews or comments does not.
+pub(crate) async fn cr_version(
+    config: &fastn_core::Config,
+    cr_number: usize,
+) -> fastn_core::Result<String> {
+    let mut list = String::new();
+    for file in cr_files(config, cr_number).await? {
+        let content = if config.package.history_backend.is_git() {
+            fastn_core::git::read_file_at(
+                &config.root,
+                fastn_core::git::cr_branch(cr_number).as_str(),
+                file.as_str(),
+            )?
+        } else {
+            let path = config.cr_path(cr_number).join(file.as_str());
+            if path.exists() {
+                Some(tokio::fs::read_to_string(path).await?)
+            } else {
+                None
+            }
+        };
+        let sha256 = content
+            .map(fastn_core::utils::generate_hash)
+            .unwrap_or_else(|| "deleted".to_string());
+        list.push_str(format!("{} {}\n", sha256, file).as_str());
+    }
+    Ok(fastn_core::utils::generate_hash(list))
+}
+
+pub(crate) async fn get_cr_reviews(
+    config: &fastn_core::Config,
+    cr_number: usize,
+) -> fastn_core::Result<CRReviews> {
+    let reviews_path = config.cr_reviews_path(cr_number);
+    let content = if reviews_path.exists() {
+        tokio::fs::read_to_string(&reviews_path).await?
+    } else if config.package.history_backend.is_git() {
+        // not on the branch of the CR
+        let reviews_file = config.path_without_root(&reviews_path)?;
+        fastn_core::git::read_file_at(
+            &config.root,
+            fastn_core::git::cr_branch(cr_number).as_str(),
+            reviews_file.as_str(),
+        )?
+        .unwrap_or_default()
+    } else {
+        // not in the clone, the reviews may only be in the history
+        let reviews_file = config.path_without_root(&reviews_path)?;
+        match config.get_cr_manifest(cr_number).await {
+            Ok(manifest) => match manifest.get(reviews_file.as_str()) {
+                Some(file_edit) if !file_edit.is_deleted() => {
+                    tokio::fs::read_to_string(
+                        config.history_path(reviews_file.as_str(), file_edit.version),
+                    )
+                    .await?
+                }
+                _ => String::new(),
+            },
+            Err(_) => String::new(),
+        }
+    };
+    resolve_cr_reviews(content.as_str(), cr_number)
+}
+
+pub(crate) fn resolve_cr_reviews(content: &str, cr_number: usize) -> fastn_core::Result<CRReviews> {
+    if content.trim().is_empty() {
+        return Ok(CRReviews::default());
+    }
+    let lib = fastn_core::FastnLibrary::default();
+    let b = fastn_core::doc::parse_ftd("reviews.ftd", content, &lib).map_err(|e| {
+        fastn_core::Error::UsageError {
+            message: format!("failed to parse reviews.ftd for CR#{}: {:?}", cr_number, e),
+        }
+    })?;
+
+    Ok(CRReviews {
+        reviewers: b.get("fastn#cr-reviewer")?,
+        reviews: b.get("fastn#cr-review")?,
+        comments: b.get("fastn#cr-comment")?,
+    })
+}
+
+pub(crate) fn generate_cr_reviews_content(reviews: &CRReviews) -> String {
+    let mut content = vec!["-- import: fastn".to_string()];
+
+    for reviewer in reviews.reviewers.iter() {
+        content.push(format!("-- fastn.cr-reviewer: {}", reviewer));
+    }
+
+    for review in reviews.reviews.iter() {
+        let mut review_content = format!(
+            "-- fastn.cr-review: {}\nstatus: {}\ntimestamp: {}",
+            review.reviewer,
+            review.status.as_str(),
+            review.timestamp
+        );
+        if let Some(ref version) = review.version {
+            review_content = format!("{}\nversion: {}", review_content, version);
+        }
+        if let Some(ref message) = review.message {
+            review_content = format!(
+                "{}\n\n{}",
+                review_content,
+                fastn_core::utils::escape_ftd(message)
+            );
+        }
+        content.push(review_content);
+    }
+
+    for comment in reviews.comments.iter() {
+        let mut comment_content = format!(
+            "-- fastn.cr-comment: {}\nid: {}",
+            comment.author, comment.id
+        );
+        if let Some(ref file) = comment.file {
+            comment_content = format!("{}\nfile: {}", comment_content, file);
+        }
+        if let Some(line) = comment.line {
+            comment_content = format!("{}\nline: {}", comment_content, line);
+        }
+        if let Some(reply_to) = comment.reply_to {
+            comment_content = format!("{}\nreply-to: {}", comment_content, reply_to);
+        }
+        content.push(format!(
+            "{}\ntimestamp: {}\n\n{}",
+            comment_content,
+            comment.timestamp,
+            fastn_core::utils::escape_ftd(comment.message.as_str())
+        ));
+    }
+
+    format!("{}\n", content.join("\n\n"))
+}
+
+/// Writes the reviews, and adds them to the workspace if this is their first version. With the git
+/// history backend they are committed instead, by `author` if the change was made by a signed in
+/// user.
+pub(crate) async fn create_cr_reviews(
+    config: &fastn_core::Config,
+    cr_number: usize,
+    reviews: &CRReviews,
+    author: Option<&str>,
+) -> fastn_core::Result<()> {
+    use itertools::Itertools;
+
+    let reviews_path = config.cr_reviews_path(cr_number);
+    if config.package.history_backend.is_git() {
+        fastn_core::git::ensure_on_cr_branch(&config.root, cr_number)?;
+    }
+    fastn_core::utils::update(
+        &reviews_path,
+        generate_cr_reviews_content(reviews).as_bytes(),
+    )
+    .await?;
+
+    let reviews_filename = config.path_without_root(&reviews_path)?;
+    if config.package.history_backend.is_git() {
+        fastn_core::git::commit_async(
+            &config.root,
+            vec![reviews_filename],
+            format!("Update the reviews of CR#{}", cr_number),
+            author.map(ToString::to_string),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let mut workspace = config.get_workspace_map().await?;
+    if !workspace.contains_key(reviews_filename.as_str()) {
+        workspace.insert(
+            reviews_filename.to_string(),
+            fastn_core::workspace::WorkspaceEntry {
+                filename: reviews_filename,
+                deleted: None,
+                version: None,
+                cr: Some(cr_number),
+            },
+        );
+        config
+            .write_workspace(workspace.into_values().collect_vec().as_slice())
+            .await?;
+    }
+    Ok(())
+}
+
+/// The files added, edited or deleted in the CR
+pub(crate) async fn cr_files(
+    config: &fastn_core::Config,
+    cr_number: usize,
+) -> fastn_core::Result<Vec<String>> {
+    if config.package.history_backend.is_git() {
+        return fastn_core::git::cr_files(&config.root, cr_number);
+    }
+    let cr_path = format!("{}/", fastn_core::cr::cr_path(cr_number));
+    let mut files = config
+        .get_clone_workspace()
+        .await?
+        .into_keys()
+        .filter_map(|v| v.strip_prefix(cr_path.as_str()).map(ToString::to_string))
+        // `-/` has the meta files of the CR
+        .filter(|v| !v.starts_with("-/"))
+        .collect::<std::collections::BTreeSet<String>>();
+    files.extend(
+        fastn_core::cr::get_deleted_files(config, cr_number)
+            .await?
+            .into_iter()
+            .map(|v| v.filename),
+    );
+    Ok(files.into_iter().collect())
+}
+
+/// The identities of the `writers` of the files in the CR, as per the sitemap.
+pub(crate) async fn cr_writers(
+    config: &fastn_core::Config,
+    cr_number: usize,
+) -> fastn_core::Result<Vec<String>> {
+    let sitemap = match config.package.sitemap.as_ref() {
+        Some(sitemap) => sitemap,
+        None => return Ok(vec![]),
+    };
+
+    let mut writers = std::collections::BTreeSet::new();
+    for file in cr_files(config, cr_number).await? {
+        let document_name = config
+            .document_name_with_default(fastn_core::utils::id_to_path(file.as_str()).as_str());
+        for group in sitemap.writers(document_name.as_str(), &config.package.groups) {
+            for user_identity in group.get_identities(config)? {
+                writers.insert(identity(user_identity.to_string().as_str())?);
+            }
+        }
+    }
+    Ok(writers.into_iter().collect())
+}
+
+/// The reviewers to assign to a CR: the `requested` ones, or all the `writers` of its files if
+/// none are. Only writers can review a CR, and never its `author`.
+pub(crate) fn choose_reviewers(
+    cr_number: usize,
+    requested: Vec<String>,
+    writers: Vec<String>,
+    author: Option<&str>,
+) -> fastn_core::Result<Vec<String>> {
+    let is_author = |v: &String| author.is_some_and(|author| author.eq(v));
+    if requested.is_empty() {
+        let writers = writers
+            .into_iter()
+            .filter(|v| !is_author(v))
+            .collect::<Vec<_>>();
+        if writers.is_empty() {
+            return fastn_core::usage_error(format!(
+                "No writers found for the files of CR#{}, other than its author",
+                cr_number
+            ));
+        }
+        return Ok(writers);
+    }
+    for reviewer in requested.iter() {
+        if is_author(reviewer) {
+            return fastn_core::usage_error(format!(
+                "`{}` created CR#{}, they can't review it",
+                reviewer, cr_number
+            ));
+        }
+        if !writers.contains(reviewer) {
+            return fastn_core::usage_error(format!(
+                "`{}` can't review CR#{}, they are not a writer of its files",
+                reviewer, cr_number
+            ));
+        }
+    }
+    Ok(requested)
+}
+
+/// The reviewer of the CR signed in with `req`.
+pub(crate) async fn signed_in_reviewer(
+    req: &fastn_core::http::Request,
+    reviews: &CRReviews,
+) -> fastn_core::Result<Option<String>> {
+    signed_in(req, reviews.reviewers.as_slice()).await
+}
+
+/// Which of `identities` is signed in with `req`, found the same way `Config::can_write` finds the
+/// writers of a document.
+pub(crate) async fn signed_in(
+    req: &fastn_core::http::Request,
+    identities: &[String],
+) -> fastn_core::Result<Option<String>> {
+    let identities = identities
+        .iter()
+        .flat_map(|v| fastn_core::user_group::parse_identities(v))
+        .collect::<Vec<_>>();
+    let signed_in =
+        match fastn_core::auth::get_auth_identities(req.cookies(), identities.as_slice()).await {
+            Ok(identities) => identities,
+            Err(fastn_core::Error::GenericError(_)) => vec![],
+            Err(e) => return Err(e),
+        };
+    signed_in
+        .first()
+        .map(|v| identity(v.to_string().as_str()))
+        .transpose()
+}
+
+/// The identities of all the user groups of the package, anyone who can sign in as one of them
+/// can create a CR.
+pub(crate) fn package_identities(config: &fastn_core::Config) -> fastn_core::Result<Vec<String>> {
+    let mut identities = std::collections::BTreeSet::new();
+    for group in config.package.groups.values() {
+        for user_identity in group.get_identities(config)? {
+            identities.insert(identity(user_identity.to_string().as_str())?);
+        }
+    }
+    Ok(identities.into_iter().collect())
+}
+
+/// Who can comment on a CR: its author, its reviewers and the writers of its files.
+pub(crate) async fn commenters(
+    config: &fastn_core::Config,
+    cr_number: usize,
+    reviews: &CRReviews,
+) -> fastn_core::Result<Vec<String>> {
+    let mut commenters = std::collections::BTreeSet::new();
+    commenters.extend(fastn_core::cr::get_cr_meta(config, cr_number).await?.author);
+    commenters.extend(reviews.reviewers.iter().cloned());
+    commenters.extend(cr_writers(config, cr_number).await?);
+    Ok(commenters.into_iter().collect())
+}
+
+/// Adds the review of `reviewer`, who must be a reviewer of the CR, and writes the reviews.
+pub(crate) async fn add_review(
+    config: &fastn_core::Config,
+    cr_number: usize,
+    reviews: &mut CRReviews,
+    reviewer: &str,
+    status: ReviewStatus,
+    message: Option<&str>,
+) -> fastn_core::Result<()> {
+    if !fastn_core::cr::get_cr_meta(config, cr_number).await?.open {
+        return fastn_core::usage_error(format!("CR#{} is closed", cr_number));
+    }
+    if reviews.reviewers.is_empty() {
+        return fastn_core::usage_error(format!(
+            "CR#{} has no reviewers, assign them using `fastn assign-reviewers {}`",
+            cr_number, cr_number
+        ));
+    }
+    if !reviews.is_reviewer(reviewer) {
+        return fastn_core::usage_error(format!(
+            "`{}` is not a reviewer of CR#{}",
+            reviewer, cr_number
+        ));
+    }
+    reviews.reviews.push(CRReview {
+        reviewer: reviewer.to_string(),
+        status,
+        timestamp: fastn_core::timestamp_nanosecond(),
+        version: Some(cr_version(config, cr_number).await?),
+        message: message.map(|v| v.trim().to_string()),
+    });
+    create_cr_reviews(config, cr_number, reviews, Some(reviewer)).await
+}
+
+/// Errors unless the current version of the CR has the approvals required by `fastn.cr-rules` and
+/// no reviewer has requested changes.
+pub(crate) async fn check_approvals(
+    config: &fastn_core::Config,
+    cr_number: usize,
+) -> fastn_core::Result<()> {
+    let reviews = get_cr_reviews(config, cr_number).await?;
+    let version = cr_version(config, cr_number).await?;
+    ensure_approved(
+        &reviews,
+        cr_number,
+        config.package.required_approvals,
+        version.as_str(),
+    )
+}
+
+fn ensure_approved(
+    reviews: &CRReviews,
+    cr_number: usize,
+    required: usize,
+    version: &str,
+) -> fastn_core::Result<()> {
+    let changes_requested_by = reviews.changes_requested_by();
+    if !changes_requested_by.is_empty() {
+        return fastn_core::usage_error(format!(
+            "CR#{} can't be merged, changes are requested by {}",
+            cr_number,
+            changes_requested_by.join(", ")
+        ));
+    }
+
+    let approvals = reviews.approvals(version);
+    if approvals < required {
+        return fastn_core::usage_error(format!(
+            "CR#{} can't be merged, it needs {} approvals of its latest changes and has {}",
+            cr_number, required, approvals
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CRReview, CRReviews, ReviewStatus};
+
+    fn review(reviewer: &str, status: ReviewStatus) -> CRReview {
+        CRReview {
+            reviewer: reviewer.to_string(),
+            status,
+            timestamp: 0,
+            version: Some("v1".to_string()),
+            message: None,
+        }
+    }
+
+    fn reviews() -> CRReviews {
+        CRReviews {
+            reviewers: vec![
+                "github: a".to_string(),
+                "github: b".to_string(),
+                "github: c".to_string(),
+            ],
+            reviews: vec![
+                review("github: a", ReviewStatus::Approved),
+                review("github: b", ReviewStatus::ChangesRequested),
+                review("github: b", ReviewStatus::Approved),
+                // not a reviewer
+                review("github: x", ReviewStatus::Approved),
+            ],
+            comments: vec![],
+        }
+    }
+
+    #[test]
+    fn approvals() {
+        let mut reviews = reviews();
+        assert_eq!(reviews.approvals("v1"), 2);
+        // approvals of an older version of the CR don't count
+        assert_eq!(reviews.approvals("v2"), 0);
+        assert!(reviews.changes_requested_by().is_empty());
+
+        reviews
+            .reviews
+            .push(review("github: a", ReviewStatus::ChangesRequested));
+        reviews
+            .reviews
+            .push(review("github: c", ReviewStatus::ChangesRequested));
+        assert_eq!(reviews.approvals("v1"), 1);
+        assert_eq!(
+            reviews.changes_requested_by(),
+            vec!["github: a", "github: c"]
+        );
+    }
+
+    #[test]
+    fn ensure_approved() {
+        let mut reviews = reviews();
+        assert!(super::ensure_approved(&reviews, 1, 2, "v1").is_ok());
+        assert_eq!(
+            super::ensure_approved(&reviews, 1, 3, "v1")
+                .unwrap_err()
+                .to_string(),
+            "UsageError: CR#1 can't be merged, it needs 3 approvals of its latest changes and has 2"
+        );
+        // the CR changed after it was approved
+        assert!(super::ensure_approved(&reviews, 1, 1, "v2").is_err());
+        reviews.reviews.push(super::CRReview {
+            version: Some("v2".to_string()),
+            ..review("github: c", ReviewStatus::Approved)
+        });
+        assert!(super::ensure_approved(&reviews, 1, 1, "v2").is_ok());
+
+        reviews
+            .reviews
+            .push(review("github: c", ReviewStatus::ChangesRequested));
+        assert_eq!(
+            super::ensure_approved(&reviews, 1, 0, "v1")
+                .unwrap_err()
+                .to_string(),
+            "UsageError: CR#1 can't be merged, changes are requested by github: c"
+        );
+    }
+
+    #[test]
+    fn choose_reviewers() {
+        let writers = || vec!["github: a".to_string(), "github: b".to_string()];
+        assert_eq!(
+            super::choose_reviewers(1, vec![], writers(), Some("github: a")).unwrap(),
+            vec!["github: b"]
+        );
+        assert_eq!(
+            super::choose_reviewers(1, vec!["github: b".to_string()], writers(), None).unwrap(),
+            vec!["github: b"]
+        );
+        assert_eq!(
+            super::choose_reviewers(1, vec!["github: x".to_string()], writers(), None)
+                .unwrap_err()
+                .to_string(),
+            "UsageError: `github: x` can't review CR#1, they are not a writer of its files"
+        );
+        assert_eq!(
+            super::choose_reviewers(
+                1,
+                vec!["github: a".to_string()],
+                writers(),
+                Some("github: a")
+            )
+            .unwrap_err()
+            .to_string(),
+            "UsageError: `github: a` created CR#1, they can't review it"
+        );
+        assert!(super::choose_reviewers(1, vec![], vec![], None).is_err());
+    }
+
+    #[test]
+    fn reviews_content() {
+        let mut reviews = reviews();
+        reviews.reviews[0].message = Some("looks good\n-- ftd.text: hello".to_string());
+        reviews
+            .add_comment("github: c", "why?\n--- x", Some("a.ftd"), Some(3), None)
+            .unwrap();
+
+        let content = super::generate_cr_reviews_content(&reviews);
+        let resolved = super::resolve_cr_reviews(content.as_str(), 1).unwrap();
+        assert_eq!(resolved.reviewers, reviews.reviewers);
+        assert_eq!(resolved.approvals("v1"), 2);
+        assert_eq!(
+            resolved.reviews[0].message.as_deref(),
+            Some("looks good\n-- ftd.text: hello")
+        );
+        assert_eq!(resolved.comments[0].message, "why?\n--- x");
+        assert_eq!(resolved.comments[0].line, Some(3));
+    }
+}
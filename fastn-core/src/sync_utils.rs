@@ -338,10 +338,12 @@ impl fastn_core::Config {
                     .await?;
                     let ours_content = String::from_utf8(content.clone())?;
 
-                    match diffy::MergeOptions::new()
-                        .set_conflict_style(diffy::ConflictStyle::Merge)
-                        .merge(&ancestor_content, &ours_content, &theirs_content)
-                    {
+                    match fastn_core::merge::merge(
+                        path,
+                        &ancestor_content,
+                        &ours_content,
+                        &theirs_content,
+                    ) {
                         Ok(data) => {
                             fastn_core::utils::update(self.root.join(filename), data.as_bytes())
                                 .await?;
@@ -0,0 +1,751 @@
+//! Design tokens, in the W3C Design Tokens format, as exported by Tokens Studio for Figma, or as
+//! exported by the `figma-cs-token` and `figma-typo-token` processors, imported into an
+//! `ftd.color-scheme` and an `ftd.type-data`. The tokens override `ftd.default-colors` and
+//! `ftd.default-types`, so they need not cover every color and type.
+//!
+//! A token is matched to a field using the groups it is in: `{"cta-primary": {"hover": ...}}`
+//! is `cta-primary.hover` of the color scheme, and `{"heading-large": {"$type": "typography",
+//! ...}}` is `heading-large` of the type data. A group (or a token set) named `light`, `dark`,
+//! `desktop` or `mobile`, or ending with `-light` etc, makes the tokens in it apply only to that
+//! mode, the other tokens apply to both the modes.
+
+#[derive(Debug, Clone)]
+struct Token {
+    /// The keys of the groups the token is in, followed by its name
+    path: Vec<String>,
+    value: serde_json::Value,
+    type_: Option<String>,
+}
+
+#[derive(Debug)]
+pub(crate) struct Imported {
+    /// `None` if no token is a color of `ftd.color-scheme`
+    pub color_scheme: Option<ftd::interpreter::Value>,
+    /// `None` if no token is a type of `ftd.type-data`
+    pub type_data: Option<ftd::interpreter::Value>,
+    /// The tokens that are neither, like the palette the other tokens refer to
+    pub skipped: Vec<String>,
+}
+
+pub(crate) fn import(json: &serde_json::Value) -> Imported {
+    let mut tokens = vec![];
+    collect_tokens(json, &mut vec![], None, &mut tokens);
+    resolve_aliases(&mut tokens);
+
+    let mut color_scheme = default_value("ftd#default-colors");
+    let mut type_data = default_value("ftd#default-types");
+    let color_fields = color_fields(&color_scheme);
+    let type_fields = match &type_data {
+        ftd::interpreter::Value::Record { fields, .. } => fields.keys().cloned().collect(),
+        _ => vec![],
+    };
+
+    let (mut colors, mut types) = (0, 0);
+    let mut skipped = vec![];
+    // the tokens for both the modes first, so the tokens for a mode override them
+    tokens.sort_by_key(|token| {
+        split_mode(&token.path, "light", "dark").1.is_some()
+            || split_mode(&token.path, "desktop", "mobile").1.is_some()
+    });
+    for token in tokens {
+        if apply_color(&mut color_scheme, color_fields.as_slice(), &token) {
+            colors += 1;
+        } else if apply_type(&mut type_data, type_fields.as_slice(), &token) {
+            types += 1;
+        } else {
+            skipped.push(token.path.join("."));
+        }
+    }
+
+    Imported {
+        color_scheme: (colors > 0).then_some(color_scheme),
+        type_data: (types > 0).then_some(type_data),
+        skipped,
+    }
+}
+
+fn collect_tokens(
+    json: &serde_json::Value,
+    path: &mut Vec<String>,
+    group_type: Option<&str>,
+    tokens: &mut Vec<Token>,
+) {
+    let object = match json {
+        serde_json::Value::Object(object) => object,
+        _ => return,
+    };
+    // the `$type` of a group is the type of the tokens in it
+    let group_type = object.get("$type").and_then(|v| v.as_str()).or(group_type);
+    // `$value` in the W3C format, `value` in the older formats
+    if let Some(value) = object.get("$value").or_else(|| object.get("value")) {
+        tokens.push(Token {
+            path: path.clone(),
+            value: value.clone(),
+            type_: object
+                .get("type")
+                .and_then(|v| v.as_str())
+                .or(group_type)
+                .map(ToString::to_string),
+        });
+        return;
+    }
+    for (key, value) in object.iter() {
+        if key.starts_with('$') {
+            continue;
+        }
+        path.push(key.to_string());
+        collect_tokens(value, path, group_type, tokens);
+        path.pop();
+    }
+}
+
+/// `{colors.blue.500}` is the value of that token. Tokens Studio leaves out the token set in
+/// the references, so the tokens are also found without it.
+fn resolve_aliases(tokens: &mut [Token]) {
+    let mut values = std::collections::HashMap::new();
+    for token in tokens.iter() {
+        values.insert(token.path.join("."), token.value.clone());
+    }
+    for token in tokens.iter() {
+        if token.path.len() > 1 {
+            values
+                .entry(token.path[1..].join("."))
+                .or_insert_with(|| token.value.clone());
+        }
+    }
+    for token in tokens.iter_mut() {
+        token.value = resolve_alias(&token.value, &values, 0);
+    }
+}
+
+fn resolve_alias(
+    value: &serde_json::Value,
+    values: &std::collections::HashMap<String, serde_json::Value>,
+    depth: usize,
+) -> serde_json::Value {
+    // references to references, but not forever
+    if depth > 10 {
+        return value.clone();
+    }
+    match value {
+        serde_json::Value::String(s) => {
+            match s
+                .trim()
+                .strip_prefix('{')
+                .and_then(|v| v.strip_suffix('}'))
+                .and_then(|name| values.get(name))
+            {
+                Some(v) => resolve_alias(v, values, depth + 1),
+                None => value.clone(),
+            }
+        }
+        serde_json::Value::Object(object) => serde_json::Value::Object(
+            object
+                .iter()
+                .map(|(k, v)| (k.to_string(), resolve_alias(v, values, depth)))
+                .collect(),
+        ),
+        _ => value.clone(),
+    }
+}
+
+/// `Accent Colors` -> `accent`, `CTA Primary Colors` -> `cta-primary`, as exported by
+/// `figma-cs-token`.
+fn normalize(key: &str) -> String {
+    let key = key.trim().to_lowercase().replace([' ', '_'], "-");
+    match key.strip_suffix("-colors") {
+        Some(key) => key.to_string(),
+        None => key,
+    }
+}
+
+/// The path without the mode, and the mode.
+fn split_mode(path: &[String], first: &str, second: &str) -> (Vec<String>, Option<String>) {
+    let mut mode = None;
+    let mut rest = vec![];
+    for key in path.iter().map(|v| normalize(v)) {
+        let key_mode = [first, second].into_iter().find(|m| {
+            key.eq(m) || key.ends_with(format!("-{}", m).as_str()) || key.eq(&format!("{}-mode", m))
+        });
+        match key_mode {
+            Some(m) if mode.is_none() => mode = Some(m.to_string()),
+            _ => rest.push(key),
+        }
+    }
+    (rest, mode)
+}
+
+fn default_value(name: &str) -> ftd::interpreter::Value {
+    match ftd::interpreter::default::default_bag().get(name) {
+        Some(ftd::interpreter::Thing::Variable(ftd::interpreter::Variable {
+            value: ftd::interpreter::PropertyValue::Value { value, .. },
+            ..
+        })) => value.clone(),
+        t => unreachable!("`{}` is a variable with a value, found: {:?}", name, t),
+    }
+}
+
+/// The paths to the `ftd.color`s in the color scheme, like `["cta-primary", "hover"]`.
+fn color_fields(color_scheme: &ftd::interpreter::Value) -> Vec<Vec<String>> {
+    fn collect(
+        value: &ftd::interpreter::Value,
+        path: &mut Vec<String>,
+        paths: &mut Vec<Vec<String>>,
+    ) {
+        if let ftd::interpreter::Value::Record { name, fields } = value {
+            if name.eq(ftd::interpreter::FTD_COLOR) {
+                paths.push(path.clone());
+                return;
+            }
+            for (key, field) in fields.iter() {
+                if let ftd::interpreter::PropertyValue::Value { value, .. } = field {
+                    path.push(key.to_string());
+                    collect(value, path, paths);
+                    path.pop();
+                }
+            }
+        }
+    }
+
+    let mut paths = vec![];
+    collect(color_scheme, &mut vec![], &mut paths);
+    paths
+}
+
+fn field_mut<'a>(
+    value: &'a mut ftd::interpreter::Value,
+    path: &[String],
+) -> Option<&'a mut ftd::Map<ftd::interpreter::PropertyValue>> {
+    let mut value = value;
+    for key in path {
+        value = match value {
+            ftd::interpreter::Value::Record { fields, .. } => match fields.get_mut(key)? {
+                ftd::interpreter::PropertyValue::Value { value, .. } => value,
+                _ => return None,
+            },
+            _ => return None,
+        };
+    }
+    match value {
+        ftd::interpreter::Value::Record { fields, .. } => Some(fields),
+        _ => None,
+    }
+}
+
+fn property_value(value: ftd::interpreter::Value) -> ftd::interpreter::PropertyValue {
+    ftd::interpreter::PropertyValue::Value {
+        value,
+        is_mutable: false,
+        line_number: 0,
+    }
+}
+
+fn apply_color(
+    color_scheme: &mut ftd::interpreter::Value,
+    color_fields: &[Vec<String>],
+    token: &Token,
+) -> bool {
+    if !matches!(token.type_.as_deref(), None | Some("color")) {
+        return false;
+    }
+    // ftd colors are in lower case, `#2563EB` is not a color
+    let color = match &token.value {
+        serde_json::Value::String(color) => color.trim().to_lowercase(),
+        // the newer W3C format has the color space and the components, and the hex
+        serde_json::Value::Object(object) => match object.get("hex").and_then(|v| v.as_str()) {
+            Some(hex) => hex.to_lowercase(),
+            None => return false,
+        },
+        _ => return false,
+    };
+    let (path, mode) = split_mode(&token.path, "light", "dark");
+    // the longest match, so `cta-primary.text` is not taken as `text`
+    let field = match color_fields
+        .iter()
+        .filter(|field| path.ends_with(field))
+        .max_by_key(|field| field.len())
+    {
+        Some(field) => field,
+        None => return false,
+    };
+    let fields = match field_mut(color_scheme, field) {
+        Some(fields) => fields,
+        None => return false,
+    };
+    let modes = match mode.as_deref() {
+        Some(mode) => vec![mode],
+        None => vec!["light", "dark"],
+    };
+    for mode in modes {
+        fields.insert(
+            mode.to_string(),
+            property_value(ftd::interpreter::Value::String {
+                text: color.to_string(),
+            }),
+        );
+    }
+    true
+}
+
+/// `fontFamily`, `font-family` and the like, as the fields of `ftd.type`.
+fn type_property(key: &str) -> Option<&'static str> {
+    match key.to_lowercase().replace(['-', '_', ' '], "").as_str() {
+        "fontfamily" => Some("font-family"),
+        "size" | "fontsize" => Some("size"),
+        "lineheight" => Some("line-height"),
+        "letterspacing" => Some("letter-spacing"),
+        "weight" | "fontweight" => Some("weight"),
+        _ => None,
+    }
+}
+
+fn apply_type(
+    type_data: &mut ftd::interpreter::Value,
+    type_fields: &[String],
+    token: &Token,
+) -> bool {
+    let (path, mode) = split_mode(&token.path, "desktop", "mobile");
+    let position = match path.iter().rposition(|key| type_fields.contains(key)) {
+        Some(position) => position,
+        None => return false,
+    };
+
+    // either a typography token, or a token for one of the properties, as `figma-typo-token`
+    // exports them
+    let mut properties = vec![];
+    match (&path[position + 1..], &token.value) {
+        ([], serde_json::Value::Object(object)) => {
+            for (key, value) in object.iter() {
+                if let Some(property) = type_property(key) {
+                    properties.push((property, value.clone(), None));
+                }
+            }
+        }
+        ([key], value) => match type_property(key) {
+            Some(property) => properties.push((property, value.clone(), token.type_.as_deref())),
+            None => return false,
+        },
+        _ => return false,
+    }
+
+    let modes = match mode.as_deref() {
+        Some(mode) => vec![mode],
+        None => vec!["desktop", "mobile"],
+    };
+    let mut applied = false;
+    for mode in modes {
+        let type_path = vec![path[position].to_string(), mode.to_string()];
+        let fields = match field_mut(type_data, type_path.as_slice()) {
+            Some(fields) => fields,
+            None => return false,
+        };
+        for (property, value, type_) in properties.iter() {
+            if let Some(value) = type_value(property, value, *type_) {
+                fields.insert(property.to_string(), value);
+                applied = true;
+            }
+        }
+    }
+    applied
+}
+
+fn type_value(
+    property: &str,
+    value: &serde_json::Value,
+    type_: Option<&str>,
+) -> Option<ftd::interpreter::PropertyValue> {
+    match property {
+        "font-family" => match (value, type_) {
+            // `figma-typo-token` exports the references to `ftd.font-display` etc as is
+            (serde_json::Value::String(name), Some("reference")) => {
+                Some(ftd::interpreter::PropertyValue::Reference {
+                    name: name.to_string(),
+                    kind: ftd::interpreter::Kind::string().into_kind_data(),
+                    source: ftd::interpreter::PropertyValueSource::Global,
+                    is_mutable: false,
+                    line_number: 0,
+                })
+            }
+            (serde_json::Value::String(family), _) => {
+                Some(property_value(ftd::interpreter::Value::String {
+                    text: family.to_string(),
+                }))
+            }
+            (serde_json::Value::Array(families), _) => {
+                Some(property_value(ftd::interpreter::Value::String {
+                    text: families
+                        .iter()
+                        .filter_map(|v| v.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                }))
+            }
+            _ => None,
+        },
+        "weight" => font_weight(value).map(|value| {
+            property_value(ftd::interpreter::Value::Integer {
+                value: value as i64,
+            })
+        }),
+        _ => font_size(property, value, type_).map(property_value),
+    }
+}
+
+/// `400`, `"400"` or `"Regular"`
+fn font_weight(value: &serde_json::Value) -> Option<u32> {
+    match value {
+        serde_json::Value::Number(n) => n.as_u64().map(|v| v as u32),
+        serde_json::Value::String(s) => s.trim().parse::<u32>().ok().or_else(|| {
+            let name = s.to_lowercase().replace([' ', '-', '_'], "");
+            let name = name.trim_end_matches("italic");
+            Some(match name {
+                "thin" | "hairline" => 100,
+                "extralight" | "ultralight" => 200,
+                "light" => 300,
+                "regular" | "normal" | "" => 400,
+                "medium" => 500,
+                "semibold" | "demibold" => 600,
+                "bold" => 700,
+                "extrabold" | "ultrabold" => 800,
+                "black" | "heavy" => 900,
+                _ => return None,
+            })
+        }),
+        _ => None,
+    }
+}
+
+/// `ftd.font-size` from `16`, `"16px"`, `"1.5rem"`, `"150%"` or `{"value": 16, "unit": "px"}`.
+/// A number without a unit is in `px`, but a line height like `1.5` is a multiple of the size,
+/// as in CSS, so it is in `em`.
+fn font_size(
+    property: &str,
+    value: &serde_json::Value,
+    type_: Option<&str>,
+) -> Option<ftd::interpreter::Value> {
+    let (number, unit) = match value {
+        serde_json::Value::Number(n) => (n.as_f64()?, None),
+        serde_json::Value::String(s) => {
+            let s = s.trim();
+            let unit_start = s
+                .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-'))
+                .unwrap_or(s.len());
+            let (number, unit) = s.split_at(unit_start);
+            (
+                number.parse::<f64>().ok()?,
+                Some(unit.trim().to_lowercase()).filter(|v| !v.is_empty()),
+            )
+        }
+        serde_json::Value::Object(object) => (
+            object.get("value")?.as_f64()?,
+            object
+                .get("unit")
+                .and_then(|v| v.as_str())
+                .map(ToString::to_string),
+        ),
+        _ => return None,
+    };
+    // `figma-typo-token` exports the unit as the type
+    let unit = unit.or_else(|| {
+        type_
+            .filter(|v| ["px", "em", "rem"].contains(v))
+            .map(ToString::to_string)
+    });
+
+    let (variant, value) = match unit.as_deref() {
+        Some("px") => ("px", number),
+        Some("em") => ("em", number),
+        Some("rem") => ("rem", number),
+        Some("%") => ("em", number / 100.0),
+        None if property.eq("line-height") && number <= 3.0 => ("em", number),
+        None => ("px", number),
+        Some(_) => return None,
+    };
+    let value = if variant.eq("px") {
+        ftd::interpreter::Value::Integer {
+            value: value.round() as i64,
+        }
+    } else {
+        ftd::interpreter::Value::Decimal { value }
+    };
+    let variant = format!("{}.{}", ftd::interpreter::FTD_FONT_SIZE, variant);
+    Some(ftd::interpreter::Value::OrType {
+        name: ftd::interpreter::FTD_FONT_SIZE.to_string(),
+        variant: variant.clone(),
+        full_variant: variant,
+        value: Box::new(property_value(value)),
+    })
+}
+
+/// The record as ftd, the records in it are variables of their own, named after the field they
+/// are in: `my-colors-cta-primary` for the `cta-primary` of `my-colors`.
+pub(crate) fn to_ftd(name: &str, value: &ftd::interpreter::Value) -> String {
+    let mut sections = vec![];
+    write_record(name, value, &mut sections);
+    sections.join("\n\n")
+}
+
+fn write_record(name: &str, value: &ftd::interpreter::Value, sections: &mut Vec<String>) {
+    let (record_name, fields) = match value {
+        ftd::interpreter::Value::Record { name, fields } => (name, fields),
+        _ => return,
+    };
+    let mut headers = vec![];
+    for (key, field) in fields.iter() {
+        let value = match field {
+            ftd::interpreter::PropertyValue::Reference { name, .. } => {
+                headers.push(format!("{}: ${}", key, name.replace('#', ".")));
+                continue;
+            }
+            ftd::interpreter::PropertyValue::Value { value, .. } => value,
+            _ => continue,
+        };
+        let value = match value {
+            ftd::interpreter::Value::Optional { data, .. } => match data.as_ref() {
+                Some(value) => value,
+                None => continue,
+            },
+            value => value,
+        };
+        match value {
+            ftd::interpreter::Value::Record { .. } => {
+                let variable = format!("{}-{}", name, key);
+                write_record(variable.as_str(), value, sections);
+                headers.push(format!("{}: ${}", key, variable));
+            }
+            ftd::interpreter::Value::OrType { variant, value, .. } => {
+                if let ftd::interpreter::PropertyValue::Value { value, .. } = value.as_ref() {
+                    if let Some(text) = scalar(value) {
+                        let variant = variant.rsplit_once('.').map(|v| v.1).unwrap_or(variant);
+                        headers.push(format!("{}.{}: {}", key, variant, text));
+                    }
+                }
+            }
+            value => {
+                if let Some(text) = scalar(value) {
+                    headers.push(format!("{}: {}", key, text));
+                }
+            }
+        }
+    }
+    sections.push(format!(
+        "-- {} {}:\n{}\n",
+        record_name.replace('#', "."),
+        name,
+        headers.join("\n")
+    ));
+}
+
+fn scalar(value: &ftd::interpreter::Value) -> Option<String> {
+    match value {
+        ftd::interpreter::Value::String { text } => Some(text.to_string()),
+        ftd::interpreter::Value::Integer { value } => Some(value.to_string()),
+        ftd::interpreter::Value::Decimal { value } => Some(value.to_string()),
+        ftd::interpreter::Value::Boolean { value } => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    /// The value at `path` in the record
+    fn get(value: &ftd::interpreter::Value, path: &[&str]) -> ftd::interpreter::Value {
+        let mut value = value.clone();
+        for key in path {
+            value = match value {
+                ftd::interpreter::Value::Record { mut fields, .. } => match fields.remove(*key) {
+                    Some(ftd::interpreter::PropertyValue::Value { value, .. }) => value,
+                    t => panic!("`{}` is not a value: {:?}", key, t),
+                },
+                ftd::interpreter::Value::Optional { data, .. } => match *data {
+                    Some(ftd::interpreter::Value::Record { mut fields, .. }) => {
+                        match fields.remove(*key) {
+                            Some(ftd::interpreter::PropertyValue::Value { value, .. }) => value,
+                            t => panic!("`{}` is not a value: {:?}", key, t),
+                        }
+                    }
+                    t => panic!("`{}` is not in a record: {:?}", key, t),
+                },
+                t => panic!("`{}` is not in a record: {:?}", key, t),
+            };
+        }
+        value
+    }
+
+    fn text(value: ftd::interpreter::Value) -> String {
+        match value {
+            ftd::interpreter::Value::String { text } => text,
+            t => panic!("not a string: {:?}", t),
+        }
+    }
+
+    #[test]
+    fn split_mode() {
+        let path = |v: &[&str]| v.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            super::split_mode(
+                &path(&["Brand Dark", "CTA Primary Colors", "hover"]),
+                "light",
+                "dark"
+            ),
+            (path(&["cta-primary", "hover"]), Some("dark".to_string()))
+        );
+        assert_eq!(
+            super::split_mode(&path(&["accent", "primary"]), "light", "dark"),
+            (path(&["accent", "primary"]), None)
+        );
+    }
+
+    #[test]
+    fn font_weight_and_size() {
+        let weight = |v: serde_json::Value| super::font_weight(&v);
+        assert_eq!(weight(serde_json::json!(500)), Some(500));
+        assert_eq!(weight(serde_json::json!("600")), Some(600));
+        assert_eq!(weight(serde_json::json!("Semi Bold Italic")), Some(600));
+        assert_eq!(weight(serde_json::json!("Wide")), None);
+
+        let size =
+            |property: &str, v: serde_json::Value, type_: Option<&str>| match super::font_size(
+                property, &v, type_,
+            ) {
+                Some(ftd::interpreter::Value::OrType { variant, value, .. }) => {
+                    let value = match *value {
+                        ftd::interpreter::PropertyValue::Value { value, .. } => value,
+                        t => panic!("not a value: {:?}", t),
+                    };
+                    Some((variant, super::scalar(&value).unwrap()))
+                }
+                _ => None,
+            };
+        let px = |v: &str| Some(("ftd#font-size.px".to_string(), v.to_string()));
+        let em = |v: &str| Some(("ftd#font-size.em".to_string(), v.to_string()));
+        assert_eq!(size("size", serde_json::json!(15.6), None), px("16"));
+        assert_eq!(
+            size("size", serde_json::json!("1.5rem"), None),
+            Some(("ftd#font-size.rem".to_string(), "1.5".to_string()))
+        );
+        assert_eq!(
+            size("size", serde_json::json!({"value": 20, "unit": "px"}), None),
+            px("20")
+        );
+        assert_eq!(size("size", serde_json::json!("12"), Some("px")), px("12"));
+        assert_eq!(size("line-height", serde_json::json!(1.5), None), em("1.5"));
+        assert_eq!(
+            size("line-height", serde_json::json!("150%"), None),
+            em("1.5")
+        );
+        assert_eq!(size("line-height", serde_json::json!(24), None), px("24"));
+        assert_eq!(size("size", serde_json::json!("1vw"), None), None);
+    }
+
+    #[test]
+    fn import() {
+        let json = serde_json::json!({
+            "palette": {
+                "blue": {"500": {"$value": "#2563EB", "$type": "color"}}
+            },
+            "light": {
+                "CTA Primary Colors": {"hover": {"$value": "{palette.blue.500}", "$type": "color"}}
+            },
+            "dark": {
+                "cta-primary": {
+                    "hover": {"$value": {"colorSpace": "srgb", "hex": "#1E40AF"}, "$type": "color"}
+                }
+            },
+            "background": {"base": {"value": "#ffffff", "type": "color"}},
+            "heading-large": {
+                "$type": "typography",
+                "$value": {
+                    "fontFamily": ["Inter", "sans-serif"],
+                    "fontSize": "40px",
+                    "fontWeight": "Bold",
+                    "lineHeight": 1.2
+                }
+            },
+            "mobile": {"heading-large": {"size": {"value": "32", "type": "px"}}},
+            "spacing": {"small": {"$value": "4px", "$type": "dimension"}}
+        });
+        let imported = super::import(&json);
+        let color_scheme = imported.color_scheme.unwrap();
+        let type_data = imported.type_data.unwrap();
+
+        assert_eq!(
+            text(get(&color_scheme, &["cta-primary", "hover", "light"])),
+            "#2563eb"
+        );
+        assert_eq!(
+            text(get(&color_scheme, &["cta-primary", "hover", "dark"])),
+            "#1e40af"
+        );
+        assert_eq!(
+            text(get(&color_scheme, &["background", "base", "light"])),
+            "#ffffff"
+        );
+        assert_eq!(
+            text(get(&color_scheme, &["background", "base", "dark"])),
+            "#ffffff"
+        );
+
+        assert_eq!(
+            text(get(
+                &type_data,
+                &["heading-large", "desktop", "font-family"]
+            )),
+            "Inter, sans-serif"
+        );
+        assert_eq!(
+            get(&type_data, &["heading-large", "mobile", "weight"]),
+            ftd::interpreter::Value::Integer { value: 700 }
+        );
+        // the mobile token overrides the size for both the modes
+        let size = |mode| match get(&type_data, &["heading-large", mode, "size"]) {
+            ftd::interpreter::Value::OrType { value, .. } => value,
+            t => panic!("not a font size: {:?}", t),
+        };
+        assert_eq!(
+            size("desktop"),
+            Box::new(super::property_value(ftd::interpreter::Value::Integer {
+                value: 40
+            }))
+        );
+        assert_eq!(
+            size("mobile"),
+            Box::new(super::property_value(ftd::interpreter::Value::Integer {
+                value: 32
+            }))
+        );
+
+        // the palette is a color token, but not of the color scheme
+        assert_eq!(imported.skipped, vec!["palette.blue.500", "spacing.small"]);
+    }
+
+    #[test]
+    fn import_without_tokens() {
+        let imported = super::import(&serde_json::json!({"spacing": {"$value": "4px"}}));
+        assert!(imported.color_scheme.is_none());
+        assert!(imported.type_data.is_none());
+        assert_eq!(imported.skipped, vec!["spacing"]);
+    }
+
+    #[test]
+    fn to_ftd() {
+        let imported = super::import(&serde_json::json!({
+            "cta-primary": {"hover": {"$value": "#2563eb", "$type": "color"}}
+        }));
+        let ftd = super::to_ftd("my-colors", &imported.color_scheme.unwrap());
+        let sections = ftd::p1::parse(ftd.as_str(), "colors.ftd").unwrap();
+        assert_eq!(
+            sections
+                .last()
+                .map(|v| (v.kind.as_deref(), v.name.as_str())),
+            Some((Some("ftd.color-scheme"), "my-colors"))
+        );
+        assert!(ftd.contains(
+            "-- ftd.color my-colors-cta-primary-hover:\ndark: #2563eb\nlight: #2563eb\n"
+        ));
+        assert!(ftd.contains("cta-primary: $my-colors-cta-primary\n"));
+        assert!(ftd.contains("hover: $my-colors-cta-primary-hover\n"));
+    }
+}
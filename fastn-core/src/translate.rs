@@ -0,0 +1,931 @@
+//! The translatable strings of ftd documents, and the XLIFF 1.2 and PO files translators work on.
+//!
+//! The strings are the captions, bodies and text headers of the sections, as `ftd::p1` parses
+//! them. Each string has an id made of the path to it in the document, e.g.
+//! `s2:ftd.column/s0:ftd.text/caption` is the caption of the first child of the third section. As
+//! a translation package starts as a copy of the original, the strings of a translated document
+//! have the same ids as the ones they translate.
+
+/// Headers that are text, other headers only are when their kind is `string`.
+const TEXT_HEADERS: &[&str] = &[
+    "title",
+    "subtitle",
+    "description",
+    "alt",
+    "placeholder",
+    "label",
+    "text",
+    "tooltip",
+    "summary",
+    "heading",
+    "caption",
+];
+
+/// Sections whose caption and body are not text.
+const NON_TEXT_SECTIONS: &[&str] = &[
+    "import",
+    "ftd.code",
+    "ftd.iframe",
+    "ftd.integer",
+    "ftd.decimal",
+    "ftd.boolean",
+];
+
+#[derive(Debug, Clone)]
+pub(crate) struct Unit {
+    /// the id of the document in the package, e.g. `blog/index.ftd`
+    pub document: String,
+    pub id: String,
+    pub line_number: usize,
+    pub source: String,
+    pub target: Option<String>,
+    /// the target is only a suggestion, and has to be reviewed before it is applied
+    pub fuzzy: bool,
+    pub note: Option<String>,
+}
+
+/// Calls `f` with the id, line number and value of each translatable string of `sections`, the
+/// value can be changed in place.
+pub(crate) fn visit(
+    sections: &mut [ftd::p1::Section],
+    f: &mut dyn FnMut(&str, usize, &mut String),
+) {
+    visit_sections(sections, "", f)
+}
+
+fn visit_sections(
+    sections: &mut [ftd::p1::Section],
+    prefix: &str,
+    f: &mut dyn FnMut(&str, usize, &mut String),
+) {
+    for (index, section) in sections.iter_mut().enumerate() {
+        if section.is_commented {
+            continue;
+        }
+        let id = format!("{}s{}:{}", prefix, index, section.name);
+        let is_processed = section
+            .headers
+            .0
+            .iter()
+            .any(|h| matches!(h, ftd::p1::Header::KV(kv) if kv.key.eq(ftd::PROCESSOR_MARKER)));
+        let has_text = !is_processed
+            && !NON_TEXT_SECTIONS.contains(&section.name.as_str())
+            && section.kind.as_deref().map_or(true, is_text_kind);
+
+        if has_text {
+            if let Some(ftd::p1::Header::KV(ref mut kv)) = section.caption {
+                if let Some(ref mut value) = kv.value {
+                    if is_text(value) {
+                        f(format!("{}/caption", id).as_str(), kv.line_number, value);
+                    }
+                }
+            }
+        }
+        visit_headers(&mut section.headers.0, id.as_str(), f);
+        if has_text {
+            if let Some(ref mut body) = section.body {
+                if is_text(body.value.as_str()) {
+                    f(
+                        format!("{}/body", id).as_str(),
+                        body.line_number,
+                        &mut body.value,
+                    );
+                }
+            }
+        }
+        visit_sections(&mut section.sub_sections, format!("{}/", id).as_str(), f);
+    }
+}
+
+fn visit_headers(
+    headers: &mut [ftd::p1::Header],
+    prefix: &str,
+    f: &mut dyn FnMut(&str, usize, &mut String),
+) {
+    for (index, header) in headers.iter_mut().enumerate() {
+        match header {
+            ftd::p1::Header::KV(kv) => {
+                let is_text_header = TEXT_HEADERS.contains(&kv.key.as_str())
+                    || kv.kind.as_deref().is_some_and(is_text_kind);
+                if kv.key.starts_with('/') || !is_text_header {
+                    continue;
+                }
+                if let Some(ref mut value) = kv.value {
+                    if is_text(value) {
+                        f(
+                            format!("{}/h{}:{}", prefix, index, kv.key).as_str(),
+                            kv.line_number,
+                            value,
+                        );
+                    }
+                }
+            }
+            ftd::p1::Header::Section(section) => {
+                if section.key.starts_with('/') {
+                    continue;
+                }
+                visit_sections(
+                    &mut section.section,
+                    format!("{}/h{}:{}/", prefix, index, section.key).as_str(),
+                    f,
+                );
+            }
+            ftd::p1::Header::BlockRecordHeader(record) => {
+                if record.key.starts_with('/') {
+                    continue;
+                }
+                let id = format!("{}/h{}:{}", prefix, index, record.key);
+                if let Some(ref mut caption) = record.caption {
+                    if is_text(caption) {
+                        f(
+                            format!("{}/caption", id).as_str(),
+                            record.line_number,
+                            caption,
+                        );
+                    }
+                }
+                visit_headers(&mut record.fields, id.as_str(), f);
+                if let (Some(ref mut body), line_number) = record.body {
+                    if is_text(body) {
+                        f(
+                            format!("{}/body", id).as_str(),
+                            line_number.unwrap_or(record.line_number),
+                            body,
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// `string`, `optional string list`, `caption or body` ...
+fn is_text_kind(kind: &str) -> bool {
+    let kind = kind.trim();
+    let kind = kind.strip_prefix("optional ").unwrap_or(kind);
+    let kind = kind.strip_suffix(" list").unwrap_or(kind);
+    matches!(
+        kind,
+        "string" | "caption" | "body" | "caption or body" | "body or caption"
+    )
+}
+
+/// Leaves out references, expressions, numbers, booleans, urls and paths.
+fn is_text(value: &str) -> bool {
+    let value = value.trim();
+    if value.is_empty()
+        || value.starts_with('$')
+        || (value.starts_with('{') && value.ends_with('}'))
+        || value.parse::<f64>().is_ok()
+        || value.eq("true")
+        || value.eq("false")
+        || !value.chars().any(char::is_alphabetic)
+    {
+        return false;
+    }
+    let is_path = !value.contains(char::is_whitespace)
+        && (value.starts_with('/')
+            || value.starts_with('#')
+            || value.starts_with("http://")
+            || value.starts_with("https://")
+            || value.starts_with("mailto:"));
+    !is_path
+}
+
+/// The translatable strings of the document, with their ids.
+pub(crate) fn strings(
+    content: &str,
+    document: &str,
+) -> fastn_core::Result<Vec<(String, usize, String)>> {
+    let mut sections = ftd::p1::parse(content, document)?;
+    let mut strings = vec![];
+    visit(&mut sections, &mut |id, line_number, value| {
+        strings.push((id.to_string(), line_number, value.to_string()))
+    });
+    Ok(strings)
+}
+
+/// The document with `translations` of its strings, by id.
+pub(crate) fn translate(
+    content: &str,
+    document: &str,
+    translations: &std::collections::HashMap<String, String>,
+) -> fastn_core::Result<String> {
+    let mut sections = ftd::p1::parse(content, document)?;
+    visit(&mut sections, &mut |id, _, value| {
+        if let Some(translation) = translations.get(id) {
+            *value = translation.to_string();
+        }
+    });
+    Ok(ftd::p1::to_string(&sections))
+}
+
+/// Matches the translated `units` with `strings`, the strings of the original document, gives
+/// the ids they translate with their target, and the number of units whose text the original no
+/// longer has. The ids are positions, adding a section to the original moves the ids of the ones
+/// after it, so a unit whose id now has another text is matched by its text.
+pub(crate) fn match_units(
+    strings: &[(String, usize, String)],
+    units: Vec<Unit>,
+) -> (Vec<(String, String)>, usize) {
+    let units = units
+        .into_iter()
+        .filter_map(|unit| unit.target.map(|target| (unit.id, unit.source, target)))
+        .collect::<Vec<_>>();
+    let (same, moved): (Vec<_>, Vec<_>) = units.into_iter().partition(|(id, source, _)| {
+        strings
+            .iter()
+            .any(|(string_id, _, value)| string_id.eq(id) && value.eq(source))
+    });
+
+    let mut taken: std::collections::HashSet<String> =
+        same.iter().map(|(id, _, _)| id.to_string()).collect();
+    let mut matched = same
+        .into_iter()
+        .map(|(id, _, target)| (id, target))
+        .collect::<Vec<_>>();
+    let mut changed = 0;
+    for (_, source, target) in moved {
+        match strings
+            .iter()
+            .find(|(id, _, value)| value.eq(&source) && !taken.contains(id))
+        {
+            Some((id, _, _)) => {
+                taken.insert(id.to_string());
+                matched.push((id.to_string(), target));
+            }
+            None => changed += 1,
+        }
+    }
+    (matched, changed)
+}
+
+/// `text` translated by the backend at `url`. The backend is sent
+/// `{"source-language": "en", "target-language": "hi", "texts": ["..."]}` and answers with
+/// `{"translations": ["..."]}`, in the same order.
+pub(crate) async fn backend_translate(
+    url: &str,
+    source_language: &str,
+    target_language: &str,
+    texts: &[String],
+) -> fastn_core::Result<Vec<String>> {
+    #[derive(serde::Deserialize)]
+    struct BackendResponse {
+        translations: Vec<String>,
+    }
+
+    let body = serde_json::json!({
+        "source-language": source_language,
+        "target-language": target_language,
+        "texts": texts,
+    });
+    let response: BackendResponse =
+        fastn_core::http::post_json(url, serde_json::to_string(&body)?).await?;
+    if response.translations.len() != texts.len() {
+        return fastn_core::usage_error(format!(
+            "The translation backend {} sent {} translations for {} texts",
+            url,
+            response.translations.len(),
+            texts.len()
+        ));
+    }
+    Ok(response.translations)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Format {
+    Xliff,
+    Po,
+}
+
+impl Format {
+    pub(crate) fn from_str(format: &str) -> fastn_core::Result<Format> {
+        match format {
+            "xliff" | "xlf" => Ok(Format::Xliff),
+            "po" => Ok(Format::Po),
+            _ => {
+                fastn_core::usage_error(format!("Unknown format `{}`, use `xliff` or `po`", format))
+            }
+        }
+    }
+
+    /// `.po` files are PO, `.xlf` and `.xliff` files are XLIFF
+    pub(crate) fn from_path(path: &str) -> Option<Format> {
+        match camino::Utf8Path::new(path).extension() {
+            Some("po") => Some(Format::Po),
+            Some("xlf") | Some("xliff") => Some(Format::Xliff),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn detect(content: &str) -> Format {
+        if content.trim_start().starts_with('<') {
+            Format::Xliff
+        } else {
+            Format::Po
+        }
+    }
+}
+
+pub(crate) fn write(
+    format: Format,
+    units: &[Unit],
+    source_language: &str,
+    target_language: &str,
+) -> String {
+    match format {
+        Format::Xliff => write_xliff(units, source_language, target_language),
+        Format::Po => write_po(units, source_language, target_language),
+    }
+}
+
+pub(crate) fn read(format: Format, content: &str) -> fastn_core::Result<Vec<Unit>> {
+    match format {
+        Format::Xliff => read_xliff(content),
+        Format::Po => read_po(content),
+    }
+}
+
+fn write_xliff(units: &[Unit], source_language: &str, target_language: &str) -> String {
+    let mut xliff = vec![
+        r#"<?xml version="1.0" encoding="UTF-8"?>"#.to_string(),
+        r#"<xliff version="1.2" xmlns="urn:oasis:names:tc:xliff:document:1.2">"#.to_string(),
+    ];
+    for (document, units) in
+        units
+            .iter()
+            .fold(Vec::<(&str, Vec<&Unit>)>::new(), |mut documents, unit| {
+                match documents.last_mut() {
+                    Some((document, units)) if unit.document.eq(document) => units.push(unit),
+                    _ => documents.push((unit.document.as_str(), vec![unit])),
+                }
+                documents
+            })
+    {
+        xliff.push(format!(
+            r#"  <file original="{}" source-language="{}" target-language="{}" datatype="plaintext">"#,
+            xml_escape(document),
+            xml_escape(source_language),
+            xml_escape(target_language)
+        ));
+        xliff.push("    <body>".to_string());
+        for unit in units {
+            xliff.push(format!(
+                r#"      <trans-unit id="{}">"#,
+                xml_escape(unit.id.as_str())
+            ));
+            xliff.push(format!(
+                "        <source>{}</source>",
+                xml_escape(unit.source.as_str())
+            ));
+            if let Some(ref target) = unit.target {
+                let state = if unit.fuzzy {
+                    "needs-review-translation"
+                } else {
+                    "translated"
+                };
+                xliff.push(format!(
+                    r#"        <target state="{}">{}</target>"#,
+                    state,
+                    xml_escape(target.as_str())
+                ));
+            }
+            xliff.push(format!(
+                "        <note>line {}{}</note>",
+                unit.line_number,
+                unit.note
+                    .as_ref()
+                    .map(|v| format!(", {}", xml_escape(v)))
+                    .unwrap_or_default()
+            ));
+            xliff.push("      </trans-unit>".to_string());
+        }
+        xliff.push("    </body>".to_string());
+        xliff.push("  </file>".to_string());
+    }
+    xliff.push("</xliff>".to_string());
+    format!("{}\n", xliff.join("\n"))
+}
+
+fn read_xliff(content: &str) -> fastn_core::Result<Vec<Unit>> {
+    let file_re = regex::Regex::new(r"(?s)<file\b([^>]*)>(.*?)</file>").unwrap();
+    let unit_re = regex::Regex::new(r"(?s)<trans-unit\b([^>]*)>(.*?)</trans-unit>").unwrap();
+    let source_re = regex::Regex::new(r"(?s)<source\b[^>]*>(.*?)</source>").unwrap();
+    let target_re = regex::Regex::new(r"(?s)<target\b([^>]*?)(?:/>|>(.*?)</target>)").unwrap();
+
+    let mut units = vec![];
+    for file in file_re.captures_iter(content) {
+        let document = match xml_attribute(&file[1], "original") {
+            Some(document) => document,
+            None => return fastn_core::usage_error("A <file> has no `original`".to_string()),
+        };
+        for unit in unit_re.captures_iter(&file[2]) {
+            let id = match xml_attribute(&unit[1], "id") {
+                Some(id) => id,
+                None => {
+                    return fastn_core::usage_error(format!(
+                        "A <trans-unit> of {} has no `id`",
+                        document
+                    ))
+                }
+            };
+            let source = match source_re.captures(&unit[2]) {
+                Some(source) => xml_unescape(&source[1]),
+                None => {
+                    return fastn_core::usage_error(format!(
+                        "The <trans-unit> {} of {} has no <source>",
+                        id, document
+                    ))
+                }
+            };
+            let (target, fuzzy) = match target_re.captures(&unit[2]) {
+                Some(target) => {
+                    let state = xml_attribute(&target[1], "state");
+                    let approved = xml_attribute(&unit[1], "approved").as_deref() == Some("yes");
+                    (
+                        target.get(2).map(|v| xml_unescape(v.as_str())),
+                        !approved
+                            && !matches!(
+                                state.as_deref(),
+                                None | Some("translated") | Some("final") | Some("signed-off")
+                            ),
+                    )
+                }
+                None => (None, false),
+            };
+            units.push(Unit {
+                document: document.clone(),
+                id,
+                line_number: 0,
+                source,
+                target: target.filter(|v| !v.is_empty()),
+                fuzzy,
+                note: None,
+            });
+        }
+    }
+    Ok(units)
+}
+
+fn xml_attribute(attributes: &str, name: &str) -> Option<String> {
+    regex::Regex::new(format!(r#"\b{}\s*=\s*(?:"([^"]*)"|'([^']*)')"#, name).as_str())
+        .unwrap()
+        .captures(attributes)
+        .and_then(|c| c.get(1).or_else(|| c.get(2)))
+        .map(|v| xml_unescape(v.as_str()))
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn xml_unescape(value: &str) -> String {
+    if let Some(value) = value
+        .strip_prefix("<![CDATA[")
+        .and_then(|v| v.strip_suffix("]]>"))
+    {
+        return value.to_string();
+    }
+    regex::Regex::new(r"&(#x[0-9a-fA-F]+|#[0-9]+|[a-z]+);")
+        .unwrap()
+        .replace_all(value, |c: &regex::Captures| {
+            let entity = &c[1];
+            let ch = if let Some(hex) = entity.strip_prefix("#x") {
+                u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+            } else if let Some(decimal) = entity.strip_prefix('#') {
+                decimal.parse::<u32>().ok().and_then(char::from_u32)
+            } else {
+                match entity {
+                    "amp" => Some('&'),
+                    "lt" => Some('<'),
+                    "gt" => Some('>'),
+                    "quot" => Some('"'),
+                    "apos" => Some('\''),
+                    _ => None,
+                }
+            };
+            ch.map(String::from).unwrap_or_else(|| c[0].to_string())
+        })
+        .to_string()
+}
+
+fn write_po(units: &[Unit], source_language: &str, target_language: &str) -> String {
+    let mut po = vec![format!(
+        "msgid \"\"\nmsgstr \"\"\n\"Language: {}\\n\"\n\"Content-Type: text/plain; charset=UTF-8\\n\"\n\"X-Source-Language: {}\\n\"",
+        po_escape(target_language),
+        po_escape(source_language)
+    )];
+    for unit in units {
+        let mut entry = vec![];
+        if let Some(ref note) = unit.note {
+            entry.push(format!("#. {}", note));
+        }
+        entry.push(format!("#: {}:{}", unit.document, unit.line_number));
+        if unit.fuzzy {
+            entry.push("#, fuzzy".to_string());
+        }
+        entry.push(format!(
+            "msgctxt {}",
+            po_string(format!("{} {}", unit.document, unit.id).as_str())
+        ));
+        entry.push(format!("msgid {}", po_string(unit.source.as_str())));
+        entry.push(format!(
+            "msgstr {}",
+            po_string(unit.target.as_deref().unwrap_or_default())
+        ));
+        po.push(entry.join("\n"));
+    }
+    format!("{}\n", po.join("\n\n"))
+}
+
+fn read_po(content: &str) -> fastn_core::Result<Vec<Unit>> {
+    #[derive(Clone, Copy)]
+    enum Field {
+        Context,
+        Source,
+        Target,
+    }
+
+    let mut units = vec![];
+    for (index, entry) in content.split("\n\n").enumerate() {
+        let mut fuzzy = false;
+        let mut values: [Option<String>; 3] = [None, None, None];
+        // the field the `"..."` lines continue
+        let mut current = None;
+        for line in entry.lines().map(str::trim) {
+            if let Some(flags) = line.strip_prefix("#,") {
+                fuzzy = flags.split(',').any(|v| v.trim().eq("fuzzy"));
+                continue;
+            }
+            if line.starts_with('#') || line.is_empty() {
+                continue;
+            }
+            let (field, value) = if let Some(value) = line.strip_prefix("msgctxt ") {
+                (Field::Context, value)
+            } else if let Some(value) = line.strip_prefix("msgid ") {
+                (Field::Source, value)
+            } else if let Some(value) = line.strip_prefix("msgstr ") {
+                (Field::Target, value)
+            } else if line.starts_with('"') {
+                match current {
+                    Some(field) => (field, line),
+                    None => {
+                        return fastn_core::usage_error(format!(
+                            "`{}` in the entry {} of the PO file continues nothing",
+                            line,
+                            index + 1
+                        ))
+                    }
+                }
+            } else {
+                return fastn_core::usage_error(format!(
+                    "Can not read `{}` in the entry {} of the PO file",
+                    line,
+                    index + 1
+                ));
+            };
+            let value = po_unescape(value.trim().trim_matches('"'));
+            values[field as usize]
+                .get_or_insert_with(String::new)
+                .push_str(value.as_str());
+            current = Some(field);
+        }
+
+        let [context, source, target] = values;
+        // the header has no context
+        let (context, source) = match (context, source) {
+            (Some(context), Some(source)) => (context, source),
+            _ => continue,
+        };
+        let (document, id) = match context.rsplit_once(' ') {
+            Some((document, id)) => (document.to_string(), id.to_string()),
+            None => {
+                return fastn_core::usage_error(format!(
+                    "`{}` is not a `<document> <id>` context",
+                    context
+                ))
+            }
+        };
+        units.push(Unit {
+            document,
+            id,
+            line_number: 0,
+            source,
+            target: target.filter(|v| !v.is_empty()),
+            fuzzy,
+            note: None,
+        });
+    }
+    Ok(units)
+}
+
+/// A PO string, on more than one line if it is
+fn po_string(value: &str) -> String {
+    if !value.strip_suffix('\n').unwrap_or(value).contains('\n') {
+        return format!("\"{}\"", po_escape(value));
+    }
+    let mut lines = vec!["\"\"".to_string()];
+    for line in value.split_inclusive('\n') {
+        lines.push(format!("\"{}\"", po_escape(line)));
+    }
+    lines.join("\n")
+}
+
+fn po_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t")
+}
+
+fn po_unescape(value: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some(c) => unescaped.push(c),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    fn unit(
+        document: &str,
+        id: &str,
+        source: &str,
+        target: Option<&str>,
+        fuzzy: bool,
+    ) -> super::Unit {
+        super::Unit {
+            document: document.to_string(),
+            id: id.to_string(),
+            line_number: 0,
+            source: source.to_string(),
+            target: target.map(ToString::to_string),
+            fuzzy,
+            note: None,
+        }
+    }
+
+    /// What `read` gives back of the units, it has no line numbers and notes
+    fn summary(units: &[super::Unit]) -> Vec<(String, String, String, Option<String>, bool)> {
+        units
+            .iter()
+            .map(|v| {
+                (
+                    v.document.clone(),
+                    v.id.clone(),
+                    v.source.clone(),
+                    v.target.clone(),
+                    v.fuzzy,
+                )
+            })
+            .collect()
+    }
+
+    fn units() -> Vec<super::Unit> {
+        vec![
+            unit(
+                "index.ftd",
+                "s0:ftd.text/caption",
+                "Hello <b>\"world\"</b> & co",
+                Some("नमस्ते"),
+                false,
+            ),
+            unit(
+                "index.ftd",
+                "s1:ftd.text/body",
+                "two\nlines\twith \\ tab",
+                Some("दो\nपंक्तियाँ"),
+                true,
+            ),
+            unit(
+                "blog/index.ftd",
+                "s0:ftd.column/h0:title",
+                "Blog",
+                None,
+                false,
+            ),
+        ]
+    }
+
+    #[test]
+    fn strings() {
+        let document = indoc::indoc! {r#"
+            -- import: foo
+
+            -- ftd.text: Hello world
+            link: /about/
+            color: $inherited.colors.text
+
+            -- ftd.code:
+            lang: rs
+
+            let x = "not text";
+
+            -- ftd.column:
+            title: Blog
+
+            -- ftd.text:
+
+            The body
+
+            -- end: ftd.column
+
+            -- string name: $processor$: pr.foo
+
+            -- integer count: 1
+
+            /-- ftd.text: commented
+        "#};
+        let strings = super::strings(document, "index.ftd")
+            .unwrap()
+            .into_iter()
+            .map(|(id, _, value)| (id, value))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            strings,
+            vec![
+                ("s1:ftd.text/caption".to_string(), "Hello world".to_string()),
+                ("s3:ftd.column/h0:title".to_string(), "Blog".to_string()),
+                (
+                    "s3:ftd.column/s0:ftd.text/body".to_string(),
+                    "The body".to_string()
+                ),
+            ]
+        );
+
+        let translations = strings
+            .into_iter()
+            .map(|(id, value)| (id, value.to_uppercase()))
+            .collect();
+        let translated = super::translate(document, "index.ftd", &translations).unwrap();
+        assert_eq!(
+            super::strings(translated.as_str(), "index.ftd")
+                .unwrap()
+                .into_iter()
+                .map(|(_, _, value)| value)
+                .collect::<Vec<_>>(),
+            vec!["HELLO WORLD", "BLOG", "THE BODY"]
+        );
+        assert!(translated.contains("let x = \"not text\";"));
+    }
+
+    #[test]
+    fn match_units() {
+        let before = "-- ftd.text: Hello\n\n-- ftd.text: Bye\n\n-- ftd.text: Hello\n";
+        let units = super::strings(before, "index.ftd")
+            .unwrap()
+            .into_iter()
+            .map(|(id, _, source)| {
+                let target = format!("{} in hindi", source);
+                unit(
+                    "index.ftd",
+                    id.as_str(),
+                    source.as_str(),
+                    Some(target.as_str()),
+                    false,
+                )
+            })
+            .chain([unit(
+                "index.ftd",
+                "s3:ftd.text/caption",
+                "Gone",
+                None,
+                false,
+            )])
+            .collect();
+
+        // a section added to the original moves the ids of the ones after it, and one changed
+        let after =
+            "-- ftd.text: New\n\n-- ftd.text: Hello\n\n-- ftd.text: Bye!\n\n-- ftd.text: Hello\n";
+        let strings = super::strings(after, "index.ftd").unwrap();
+        let (mut matched, changed) = super::match_units(strings.as_slice(), units);
+        matched.sort();
+        assert_eq!(
+            matched,
+            vec![
+                (
+                    "s1:ftd.text/caption".to_string(),
+                    "Hello in hindi".to_string()
+                ),
+                (
+                    "s3:ftd.text/caption".to_string(),
+                    "Hello in hindi".to_string()
+                ),
+            ]
+        );
+        assert_eq!(changed, 1);
+    }
+
+    #[test]
+    fn xliff() {
+        let units = units();
+        let xliff = super::write(super::Format::Xliff, &units, "en", "hi");
+        assert_eq!(super::Format::detect(xliff.as_str()), super::Format::Xliff);
+        assert!(xliff
+            .contains(r#"<source>Hello &lt;b&gt;&quot;world&quot;&lt;/b&gt; &amp; co</source>"#));
+        assert!(xliff.contains(r#"<target state="needs-review-translation">दो"#));
+        assert_eq!(
+            summary(&super::read(super::Format::Xliff, xliff.as_str()).unwrap()),
+            summary(&units)
+        );
+    }
+
+    #[test]
+    fn xliff_from_tools() {
+        let xliff = indoc::indoc! {r#"
+            <xliff version="1.2">
+              <file original='a.ftd' source-language="en">
+                <body>
+                  <trans-unit id="s0:ftd.text/caption" approved="yes">
+                    <source>Hi</source>
+                    <target state="new">&#x928;&#2350;</target>
+                  </trans-unit>
+                  <trans-unit id="s1:ftd.text/caption">
+                    <source><![CDATA[a < b]]></source>
+                    <target state="final"><![CDATA[a < b!]]></target>
+                  </trans-unit>
+                  <trans-unit id="s2:ftd.text/caption">
+                    <source>empty</source>
+                    <target/>
+                  </trans-unit>
+                </body>
+              </file>
+            </xliff>
+        "#};
+        assert_eq!(
+            summary(&super::read(super::Format::Xliff, xliff).unwrap()),
+            summary(&[
+                unit("a.ftd", "s0:ftd.text/caption", "Hi", Some("नम"), false),
+                unit(
+                    "a.ftd",
+                    "s1:ftd.text/caption",
+                    "a < b",
+                    Some("a < b!"),
+                    false
+                ),
+                unit("a.ftd", "s2:ftd.text/caption", "empty", None, false),
+            ])
+        );
+        assert!(super::read(
+            super::Format::Xliff,
+            "<file><trans-unit id='x'></trans-unit></file>"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn po() {
+        let units = units();
+        let po = super::write(super::Format::Po, &units, "en", "hi");
+        assert_eq!(super::Format::detect(po.as_str()), super::Format::Po);
+        assert!(po.contains(concat!(
+            "#, fuzzy\n",
+            "msgctxt \"index.ftd s1:ftd.text/body\"\n",
+            "msgid \"\"\n",
+            "\"two\\n\"\n",
+            "\"lines\\twith \\\\ tab\"\n",
+        )));
+        assert_eq!(
+            summary(&super::read(super::Format::Po, po.as_str()).unwrap()),
+            summary(&units)
+        );
+
+        assert!(super::read(super::Format::Po, "msgctxt \"a.ftd x\"\nmsgid \"a\"\nfoo").is_err());
+        assert!(super::read(super::Format::Po, "\"continues nothing\"").is_err());
+        assert!(super::read(super::Format::Po, "msgctxt \"no-id\"\nmsgid \"a\"").is_err());
+    }
+
+    #[test]
+    fn format() {
+        assert_eq!(super::Format::from_path("hi.po"), Some(super::Format::Po));
+        assert_eq!(
+            super::Format::from_path("out/hi.xlf"),
+            Some(super::Format::Xliff)
+        );
+        assert_eq!(super::Format::from_path("hi.txt"), None);
+        assert_eq!(
+            super::Format::from_str("xliff").unwrap(),
+            super::Format::Xliff
+        );
+        assert!(super::Format::from_str("json").is_err());
+    }
+}
@@ -250,7 +250,7 @@ pub(crate) fn get_translation_status_counts(
     Ok(translation_status_count)
 }
 
-#[derive(serde::Deserialize, Debug, Clone)]
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
 pub struct TranslationStatusSummary {
     #[serde(rename = "never-marked")]
     pub never_marked: i32,
@@ -263,6 +263,20 @@ pub struct TranslationStatusSummary {
     pub last_modified_on: Option<String>,
 }
 
+impl TranslationStatusSummary {
+    pub fn total(&self) -> i32 {
+        self.never_marked + self.missing + self.out_dated + self.upto_date
+    }
+
+    /// The percentage of the documents that are up to date, 100 if there are none
+    pub fn coverage(&self) -> f64 {
+        match self.total() {
+            0 => 100.0,
+            total => f64::from(self.upto_date) * 100.0 / f64::from(total),
+        }
+    }
+}
+
 impl ToString for TranslationStatusSummary {
     fn to_string(&self) -> String {
         format!(
@@ -201,8 +201,32 @@ pub(crate) fn language_to_human(language: &str) -> String {
         .unwrap_or_else(|_| language.to_string())
 }
 
-pub(crate) fn nanos_to_rfc3339(nanos: &u128) -> String {
-    nanos.to_string() // TODO
+/// Formats nanoseconds since the unix epoch as a UTC time, `2023-10-05T14:30:00Z`.
+pub fn nanos_to_rfc3339(nanos: &u128) -> String {
+    let seconds = (nanos / 1_000_000_000) as i64;
+    let (days, seconds) = (seconds.div_euclid(86400), seconds.rem_euclid(86400));
+
+    // source: http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
 }
 
 pub(crate) fn history_path(id: &str, base_path: &str, timestamp: &u128) -> camino::Utf8PathBuf {
@@ -384,7 +408,17 @@ pub(crate) fn validate_base_url(package: &fastn_core::Package) -> fastn_core::Re
     Ok(())
 }
 
-#[allow(dead_code)]
+/// Escapes `\`, `"` and newlines of a value written between double quotes, like the label
+/// values of the `/-/metrics` endpoint.
+pub(crate) fn escape_quoted(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Escapes the lines of a body in a `FASTN.ftd` style document, like `.reviews/`, that would
+/// start a section or a sub-section.
 pub fn escape_ftd(file: &str) -> String {
     use itertools::Itertools;
 
@@ -399,6 +433,22 @@ pub fn escape_ftd(file: &str) -> String {
         .join("\n")
 }
 
+/// Escapes text written in a generated page, like `/-/history/`, so lines starting with `-- ` do
+/// not start a section and `;;` does not start a comment.
+pub(crate) fn escape_ftd_page(text: &str) -> String {
+    use itertools::Itertools;
+
+    text.split('\n')
+        .map(|v| {
+            if v.starts_with("-- ") {
+                format!("\\{}", v)
+            } else {
+                v.replace(";;", "\\;;")
+            }
+        })
+        .join("\n")
+}
+
 pub fn id_to_path(id: &str) -> String {
     id.replace("/index.ftd", "/")
         .replace("index.ftd", "/")
@@ -827,7 +877,7 @@ pub fn query(uri: &str) -> fastn_core::Result<Vec<(String, String)>> {
             .collect_vec(),
     )
 }
-pub fn generate_hash(content: &str) -> String {
+pub fn generate_hash(content: impl AsRef<[u8]>) -> String {
     use sha2::digest::FixedOutput;
     use sha2::Digest;
     let mut hasher = sha2::Sha256::new();
@@ -922,6 +972,14 @@ mod tests {
             ]
         )
     }
+
+    #[test]
+    fn escape_ftd_page() {
+        assert_eq!(
+            super::escape_ftd_page("-- ftd.text: a;;b\n;; note\nx ;; y\n--- z"),
+            "\\-- ftd.text: a;;b\n\\;; note\nx \\;; y\n--- z"
+        );
+    }
 }
 
 pub fn ignore_headers() -> Vec<&'static str> {
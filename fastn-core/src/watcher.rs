@@ -5,6 +5,28 @@ static WATCHER: once_cell::sync::Lazy<(
 )> = once_cell::sync::Lazy::new(watcher);
 const POLL_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(30 * 1000); // 30 seconds
 static GLOBAL_POLL_COUNT: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+/// folders of the `path` dependencies, watched along with the package
+static LINKED_PACKAGES: once_cell::sync::Lazy<antidote::Mutex<Vec<std::path::PathBuf>>> =
+    once_cell::sync::Lazy::new(|| antidote::Mutex::new(vec![]));
+
+/// Makes the watcher also watch the folders of the `path` dependencies of `package`, so edits in
+/// them reload the pages too. Has to be called before the first poll request.
+pub(crate) fn add_linked_packages(package: &fastn_core::Package) {
+    let mut linked = LINKED_PACKAGES.lock();
+    for dependency in package.dependencies.iter().filter(|d| d.path.is_some()) {
+        if let Some(root) = dependency
+            .package
+            .fastn_path
+            .as_ref()
+            .and_then(|p| p.parent())
+        {
+            let root = root.as_std_path().to_path_buf();
+            if !linked.contains(&root) {
+                linked.push(root);
+            }
+        }
+    }
+}
 
 fn watcher() -> (
     tokio::sync::mpsc::Sender<WatcherSender>,
@@ -74,6 +96,12 @@ fn create_watcher(f_tx: tokio::sync::mpsc::Sender<()>) -> notify::RecommendedWat
         )
         .expect("watcher: failed to watch");
 
+    for path in LINKED_PACKAGES.lock().iter() {
+        if let Err(e) = watcher.watch(path, notify::RecursiveMode::Recursive) {
+            eprintln!("watcher: failed to watch {}: {}", path.display(), e);
+        }
+    }
+
     watcher
 }
 
@@ -30,6 +30,11 @@ pub trait ContextWithMutableVariables: Context {
     fn set_value(&mut self, _identifier: String, _value: Value) -> EvalexprResult<()> {
         Err(EvalexprError::ContextNotMutable)
     }
+
+    /// Removes the variable with the given identifier, returning its value if it was set.
+    fn remove_value(&mut self, _identifier: &str) -> EvalexprResult<Option<Value>> {
+        Err(EvalexprError::ContextNotMutable)
+    }
 }
 
 /// A context that allows to assign to function identifiers.
@@ -145,6 +150,10 @@ impl ContextWithMutableVariables for HashMapContext {
         self.variables.insert(identifier, value);
         Ok(())
     }
+
+    fn remove_value(&mut self, identifier: &str) -> EvalexprResult<Option<Value>> {
+        Ok(self.variables.remove(identifier))
+    }
 }
 
 impl ContextWithMutableFunctions for HashMapContext {
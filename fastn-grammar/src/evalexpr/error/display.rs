@@ -44,6 +44,9 @@ impl fmt::Display for EvalexprError {
                 expected_len, actual
             ),
             ExpectedEmpty { actual } => write!(f, "Expected a Value::Empty, but got {:?}.", actual),
+            ExpectedRecord { actual } => {
+                write!(f, "Expected a Value::Record, but got {:?}.", actual)
+            }
             AppendedToLeafNode => write!(f, "Tried to append a node to a leaf node."),
             PrecedenceViolation => write!(
                 f,
@@ -69,6 +72,14 @@ impl fmt::Display for EvalexprError {
             ),
             UnmatchedLBrace => write!(f, "Found an unmatched opening parenthesis '('."),
             UnmatchedRBrace => write!(f, "Found an unmatched closing parenthesis ')'."),
+            UnmatchedLBracket => write!(f, "Found an unmatched opening bracket '['."),
+            UnmatchedRBracket => write!(f, "Found an unmatched closing bracket ']'."),
+            UnterminatedTemplateExpression => write!(
+                f,
+                "Found a '${{' within a template string without a matching '}}'."
+            ),
+            UnmatchedLCurlyBrace => write!(f, "Found an unmatched opening curly brace '{{'."),
+            UnmatchedRCurlyBrace => write!(f, "Found an unmatched closing curly brace '}}'."),
             MissingOperatorOutsideOfBrace { .. } => write!(
                 f,
                 "Found an opening parenthesis that is preceded by something that does not take \
@@ -91,6 +91,12 @@ pub enum EvalexprError {
         actual: Value,
     },
 
+    /// A record value was expected.
+    ExpectedRecord {
+        /// The actual value.
+        actual: Value,
+    },
+
     /// Tried to append a child to a leaf node.
     /// Leaf nodes cannot have children.
     AppendedToLeafNode,
@@ -129,6 +135,21 @@ pub enum EvalexprError {
     /// A closing brace without a matching opening brace was found.
     UnmatchedRBrace,
 
+    /// An opening bracket without a matching closing bracket was found.
+    UnmatchedLBracket,
+
+    /// A closing bracket without a matching opening bracket was found.
+    UnmatchedRBracket,
+
+    /// A `${` within a template string without a matching `}` was found.
+    UnterminatedTemplateExpression,
+
+    /// A `{` opening a statement block without a matching `}` was found.
+    UnmatchedLCurlyBrace,
+
+    /// A `}` without a matching `{` opening a statement block was found.
+    UnmatchedRCurlyBrace,
+
     /// Left of an opening brace or right of a closing brace is a token that does not expect the brace next to it.
     /// For example, writing `4(5)` would yield this error, as the `4` does not have any operands.
     MissingOperatorOutsideOfBrace,
@@ -274,6 +295,11 @@ impl EvalexprError {
         EvalexprError::ExpectedEmpty { actual }
     }
 
+    /// Constructs `EvalexprError::ExpectedRecord{actual}`.
+    pub fn expected_record(actual: Value) -> Self {
+        EvalexprError::ExpectedRecord { actual }
+    }
+
     /// Constructs an error that expresses that the type of `expected` was expected, but `actual` was found.
     pub(crate) fn expected_type(expected: &Value, actual: Value) -> Self {
         match ValueType::from(expected) {
@@ -283,6 +309,7 @@ impl EvalexprError {
             ValueType::Boolean => Self::expected_boolean(actual),
             ValueType::Tuple => Self::expected_tuple(actual),
             ValueType::Empty => Self::expected_empty(actual),
+            ValueType::Record => Self::expected_record(actual),
         }
     }
 
@@ -393,6 +420,10 @@ mod tests {
             EvalexprError::expected_type(&Value::Tuple(vec![]), Value::Empty),
             EvalexprError::expected_tuple(Value::Empty)
         );
+        assert_eq!(
+            EvalexprError::expected_type(&Value::Record(Default::default()), Value::Empty),
+            EvalexprError::expected_record(Value::Empty)
+        );
         assert_eq!(
             EvalexprError::expected_type(&Value::Empty, Value::String("abc".to_string())),
             EvalexprError::expected_empty(Value::String("abc".to_string()))
@@ -95,6 +95,7 @@ pub fn builtin_function(identifier: &str) -> Option<Function> {
                 Value::Int(_) => "int",
                 Value::Boolean(_) => "boolean",
                 Value::Tuple(_) => "tuple",
+                Value::Record(_) => "record",
                 Value::Empty => "empty",
             }
             .into())
@@ -1,6 +1,7 @@
 use fastn_grammar::evalexpr::{
-    token, tree, value::TupleType, Context, ContextWithMutableVariables, EmptyType, EvalexprError,
-    EvalexprResult, ExprNode, FloatType, HashMapContext, IntType, Value, EMPTY_VALUE,
+    statement, token, tree, value::TupleType, Context, ContextWithMutableVariables, EmptyType,
+    EvalexprError, EvalexprResult, ExprNode, FloatType, HashMapContext, IntType, Statement, Value,
+    EMPTY_VALUE,
 };
 
 /// Evaluate the given expression string.
@@ -87,6 +88,47 @@ pub fn build_operator_tree(string: &str) -> EvalexprResult<ExprNode> {
     tree::tokens_to_operator_tree(token::tokenize(string)?)
 }
 
+/// Build the statements of a function body.
+///
+/// Besides expressions, a body can contain `let` bindings, `if`/`else` blocks, `for` loops over
+/// tuples and `return` statements.
+///
+/// # Examples
+///
+/// ```rust
+/// use fastn_grammar::evalexpr::*;
+///
+/// let statements = build_statements("let total = 0; for n in (1, 2, 3) { total = total + n; } total").unwrap();
+/// assert_eq!(statements.len(), 3);
+/// ```
+///
+/// *See the [crate doc](index.html) for more examples and explanations of the statement format.*
+pub fn build_statements(string: &str) -> EvalexprResult<Vec<Statement>> {
+    statement::parse_statements(string)
+}
+
+/// Evaluate the given statements with the given mutable context.
+///
+/// The result is the value of the first `return` reached, or else the value of the last
+/// statement if it is an expression, or else `Value::Empty`.
+///
+/// # Examples
+///
+/// ```rust
+/// use fastn_grammar::evalexpr::*;
+///
+/// let statements = build_statements("if n > 2 { return \"many\"; } \"few\"").unwrap();
+/// let mut context = HashMapContext::new();
+/// context.set_value("n".into(), 3.into()).unwrap(); // Do proper error handling here
+/// assert_eq!(eval_statements_with_context_mut(&statements, &mut context), Ok(Value::from("many")));
+/// ```
+pub fn eval_statements_with_context_mut<C: ContextWithMutableVariables>(
+    statements: &[Statement],
+    context: &mut C,
+) -> EvalexprResult<Value> {
+    statement::eval_statements_with_context_mut(statements, context)
+}
+
 /// Evaluate the given expression string into a string.
 ///
 /// *See the [crate doc](index.html) for more examples and explanations of the expression format.*
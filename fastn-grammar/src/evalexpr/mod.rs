@@ -113,6 +113,7 @@
 //!
 //! | Operator | Precedence | Description |
 //! |----------|------------|-------------|
+//! | [] | 150 | Index |
 //! | ^ | 120 | Exponentiation |
 //! | * | 100 | Product |
 //! | / | 100 | Division (integer if both arguments are integers, otherwise float) |
@@ -127,6 +128,9 @@
 //! | != | 80 | Not equal |
 //! | && | 75 | Logical and |
 //! | &#124;&#124; | 70 | Logical or |
+//! | ?? | 65 | Coalesce |
+//! | ? | 60 | Conditional |
+//! | : | 55 | Else |
 //! | = | 50 | Assignment |
 //! | += | 50 | Sum-Assignment or String-Concatenation-Assignment |
 //! | -= | 50 | Difference-Assignment |
@@ -143,6 +147,7 @@
 //!
 //! | Operator | Precedence | Description |
 //! |----------|------------|-------------|
+//! | .field | 150 | Member access |
 //! | - | 110 | Negation |
 //! | ! | 110 | Logical not |
 //!
@@ -189,6 +194,72 @@
 //! ])));
 //! ```
 //!
+//! #### Template Strings
+//!
+//! Template strings are enclosed in backticks and may contain expressions in `${...}`.
+//! Their value is the text with the string form of each expression inserted, where strings are inserted without quotes and the empty value as an empty string.
+//! Backticks, `$` and backslashes within the text can be escaped with a backslash.
+//!
+//! ```rust
+//! use fastn_grammar::evalexpr::*;
+//!
+//! let context = fastn_grammar::context_map! {
+//!     "name" => "World",
+//!     "count" => 3
+//! }.unwrap(); // Do proper error handling here
+//!
+//! assert_eq!(eval_with_context("`Hello ${name}! ${count + 1} new`", &context),
+//!            Ok(Value::from("Hello World! 4 new")));
+//! ```
+//!
+//! #### Member Access and Indexing
+//!
+//! Fields of records and elements of tuples can be accessed with `.field` and `[index]`.
+//! A dotted identifier like `person.name` reads the field `name` of the variable `person`, unless `person.name` itself is a variable in the context.
+//! After a closing parenthesis or bracket, `.field` accesses a member of the preceding value.
+//! Strings can be indexed as well, resulting in the character at that position.
+//! Accessing a missing member or a member of the empty value results in the empty value.
+//!
+//! ```rust
+//! use fastn_grammar::evalexpr::*;
+//!
+//! let mut person = RecordType::new();
+//! person.insert("name".to_string(), Value::from("Alice"));
+//! let context = fastn_grammar::context_map! {
+//!     "person" => Value::from(person),
+//!     "names" => Value::from(vec![Value::from("a"), Value::from("b")])
+//! }.unwrap(); // Do proper error handling here
+//!
+//! assert_eq!(eval_with_context("person.name", &context), Ok(Value::from("Alice")));
+//! assert_eq!(eval_with_context("person[\"name\"]", &context), Ok(Value::from("Alice")));
+//! assert_eq!(eval_with_context("names[1]", &context), Ok(Value::from("b")));
+//! assert_eq!(eval_with_context("(1, (2, 3))[1].0", &context), Ok(Value::from(2)));
+//! assert_eq!(eval_with_context("names[5]", &context), Ok(Value::Empty));
+//! ```
+//!
+//! #### Conditional Expressions
+//!
+//! The conditional expression `condition ? a : b` evaluates to `a` if the condition is true, and to `b` otherwise.
+//! The coalesce operator `a ?? b` evaluates to `b` if `a` is the empty value, and to `a` otherwise.
+//! Both only evaluate the operand they result in.
+//! Conditionals chain in the else branch, but nesting one in the first branch requires parentheses.
+//!
+//! ```rust
+//! use fastn_grammar::evalexpr::*;
+//!
+//! let context = fastn_grammar::context_map! {
+//!     "count" => 1,
+//!     "nickname" => ()
+//! }.unwrap(); // Do proper error handling here
+//!
+//! assert_eq!(eval_with_context("count == 1 ? \"item\" : \"items\"", &context),
+//!            Ok(Value::from("item")));
+//! assert_eq!(eval_with_context("count > 5 ? \"many\" : count > 0 ? \"some\" : \"none\"", &context),
+//!            Ok(Value::from("some")));
+//! assert_eq!(eval_with_context("nickname ?? \"anonymous\"", &context),
+//!            Ok(Value::from("anonymous")));
+//! ```
+//!
 //! #### The Assignment Operator
 //!
 //! This crate features the assignment operator, that allows expressions to store their result in a variable in the expression context.
@@ -245,6 +316,35 @@
 //! assert_eq!(healing_script.eval_int_with_context_mut(&mut context), Ok(5));
 //! ```
 //!
+//! #### Statements
+//!
+//! Function bodies can use statements on top of expression chains, built with `build_statements`.
+//! `let name = value;` binds a local, optionally with a kind annotation like `let total: integer = 0;` that is left to the caller to check.
+//! `if condition { ... } else { ... }` and `else if` run a block conditionally, `for item in list { ... }` runs a block for every element of a tuple,
+//! and `return value;` ends the body with a value.
+//! Without a `return`, a body evaluates to its last statement if that is an expression.
+//!
+//! ```rust
+//! use fastn_grammar::evalexpr::*;
+//!
+//! let statements = build_statements("
+//!     let total = 0;
+//!     for price in prices {
+//!         if price > 100 { return -1; }
+//!         total = total + price;
+//!     }
+//!     total
+//! ").unwrap(); // Do proper error handling here
+//!
+//! let mut context = fastn_grammar::context_map! {
+//!     "prices" => Value::from(vec![Value::from(20), Value::from(30)])
+//! }.unwrap(); // Do proper error handling here
+//! assert_eq!(eval_statements_with_context_mut(&statements, &mut context), Ok(Value::from(50)));
+//!
+//! context.set_value("prices".into(), Value::from(vec![Value::from(200)])).unwrap();
+//! assert_eq!(eval_statements_with_context_mut(&statements, &mut context), Ok(Value::from(-1)));
+//! ```
+//!
 //! ### Contexts
 //!
 //! An expression evaluator that just evaluates expressions would be useful already, but this crate can do more.
@@ -379,7 +479,7 @@
 //! ### Values
 //!
 //! Operators take values as arguments and produce values as results.
-//! Values can be booleans, integer or floating point numbers, strings, tuples or the empty type.
+//! Values can be booleans, integer or floating point numbers, strings, tuples, records or the empty type.
 //! Values are denoted as displayed in the following table.
 //!
 //! | Value type | Example |
@@ -389,13 +489,14 @@
 //! | `Value::Int` | `3`, `-9`, `0`, `135412` |
 //! | `Value::Float` | `3.`, `.35`, `1.00`, `0.5`, `123.554`, `23e4`, `-2e-3`, `3.54e+2` |
 //! | `Value::Tuple` | `(3, 55.0, false, ())`, `(1, 2)` |
+//! | `Value::Record` | (only from the context) |
 //! | `Value::Empty` | `()` |
 //!
 //! Integers are internally represented as `i64`, and floating point numbers are represented as `f64`.
-//! Tuples are represented as `Vec<Value>` and empty values are not stored, but represented by Rust's unit type `()` where necessary.
+//! Tuples are represented as `Vec<Value>`, records as `BTreeMap<String, Value>` and empty values are not stored, but represented by Rust's unit type `()` where necessary.
 //!
 //! There exist type aliases for some of the types.
-//! They include `IntType`, `FloatType`, `TupleType` and `EmptyType`.
+//! They include `IntType`, `FloatType`, `TupleType`, `RecordType` and `EmptyType`.
 //!
 //! Values can be constructed either directly or using the `From` trait.
 //! They can be decomposed using the `Value::as_[type]` methods.
@@ -542,9 +643,13 @@ pub use fastn_grammar::evalexpr::{
     function::Function,
     interface::*,
     operator::Operator,
+    statement::Statement,
     token::PartialToken,
     tree::ExprNode,
-    value::{value_type::ValueType, EmptyType, FloatType, IntType, TupleType, Value, EMPTY_VALUE},
+    value::{
+        value_type::ValueType, EmptyType, FloatType, IntType, RecordType, TupleType, Value,
+        EMPTY_VALUE,
+    },
 };
 
 mod context;
@@ -554,6 +659,7 @@ mod feature_serde;
 mod function;
 mod interface;
 mod operator;
+mod statement;
 mod token;
 mod tree;
 mod value;
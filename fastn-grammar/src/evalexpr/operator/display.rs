@@ -37,6 +37,14 @@ impl Display for Operator {
             AndAssign => write!(f, " &&= "),
             OrAssign => write!(f, " ||= "),
 
+            Index => write!(f, "[]"),
+            Member { field } => write!(f, ".{}", field),
+            Template => write!(f, "``"),
+
+            Conditional => write!(f, " ? "),
+            Else => write!(f, " : "),
+            Coalesce => write!(f, " ?? "),
+
             Tuple => write!(f, ", "),
             Chain => write!(f, "; "),
 
@@ -66,6 +66,25 @@ pub enum Operator {
     /// A binary or-assign operator.
     OrAssign,
 
+    /// A binary index operator, as in `a[0]` or `a["name"]`.
+    Index,
+    /// A unary member access operator, as in `(a, b).0` or `people[0].name`.
+    Member {
+        /// The name of the accessed field, or the position in a tuple.
+        field: String,
+    },
+    /// An n-ary template string, concatenating the string form of its arguments.
+    Template,
+
+    /// A binary conditional operator, `condition ? value`.
+    /// It evaluates to `value` if `condition` is true, and to the empty value otherwise.
+    Conditional,
+    /// A binary else operator, `condition ? value : otherwise`.
+    /// Its first argument must be a `Conditional`.
+    Else,
+    /// A binary coalesce operator, evaluating to its second argument if the first one is empty.
+    Coalesce,
+
     /// An n-ary tuple constructor.
     Tuple,
     /// An n-ary subexpression chain.
@@ -135,6 +154,13 @@ impl Operator {
             Assign | AddAssign | SubAssign | MulAssign | DivAssign | ModAssign | ExpAssign
             | AndAssign | OrAssign => 50,
 
+            Index | Member { .. } => 150,
+            Template => 200,
+
+            Coalesce => 65,
+            Conditional => 60,
+            Else => 55,
+
             Tuple => 40,
             Chain => 0,
 
@@ -149,7 +175,7 @@ impl Operator {
     /// Left-to-right chaining has priority if operators with different order but same precedence are chained.
     pub(crate) const fn is_left_to_right(&self) -> bool {
         use fastn_grammar::evalexpr::operator::Operator::*;
-        !matches!(self, Assign | FunctionIdentifier { .. })
+        !matches!(self, Assign | Else | FunctionIdentifier { .. })
     }
 
     /// Returns true if chains of this operator should be flattened into one operator with many arguments.
@@ -170,9 +196,9 @@ impl Operator {
         match self {
             Add | Sub | Mul | Div | Mod | Exp | Eq | Neq | Gt | Lt | Geq | Leq | And | Or
             | Assign | AddAssign | SubAssign | MulAssign | DivAssign | ModAssign | ExpAssign
-            | AndAssign | OrAssign => Some(2),
-            Tuple | Chain => None,
-            Not | Neg | RootNode => Some(1),
+            | AndAssign | OrAssign | Index | Conditional | Else | Coalesce => Some(2),
+            Tuple | Chain | Template => None,
+            Not | Neg | RootNode | Member { .. } => Some(1),
             Const { .. } => Some(0),
             VariableIdentifierWrite { .. } | VariableIdentifierRead { .. } => Some(0),
             FunctionIdentifier { .. } => Some(1),
@@ -424,6 +450,56 @@ impl Operator {
             }
             Assign | AddAssign | SubAssign | MulAssign | DivAssign | ModAssign | ExpAssign
             | AndAssign | OrAssign => Err(EvalexprError::ContextNotMutable),
+            Index => {
+                expect_operator_argument_amount(arguments.len(), 2)?;
+
+                match (&arguments[0], &arguments[1]) {
+                    (Value::Tuple(tuple), Value::Int(index)) => Ok(usize::try_from(*index)
+                        .ok()
+                        .and_then(|index| tuple.get(index).cloned())
+                        .unwrap_or(Value::Empty)),
+                    (Value::Record(record), Value::String(key)) => {
+                        Ok(record.get(key).cloned().unwrap_or(Value::Empty))
+                    }
+                    (Value::String(string), Value::Int(index)) => Ok(usize::try_from(*index)
+                        .ok()
+                        .and_then(|index| string.chars().nth(index))
+                        .map_or(Value::Empty, |c| Value::String(c.to_string()))),
+                    (Value::Empty, _) => Ok(Value::Empty),
+                    (a, b) => Err(EvalexprError::wrong_type_combination(
+                        self.clone(),
+                        vec![a.into(), b.into()],
+                    )),
+                }
+            }
+            Member { field } => {
+                expect_operator_argument_amount(arguments.len(), 1)?;
+
+                member(&arguments[0], field)
+            }
+            Template => Ok(Value::String(
+                arguments.iter().map(template_string).collect(),
+            )),
+            // `Conditional`, `Else` and `Coalesce` only evaluate the branch they pick when
+            // evaluated through an `ExprNode`; these arms cover direct evaluation.
+            Conditional => {
+                expect_operator_argument_amount(arguments.len(), 2)?;
+
+                if arguments[0].as_boolean()? {
+                    Ok(arguments[1].clone())
+                } else {
+                    Ok(Value::Empty)
+                }
+            }
+            Else | Coalesce => {
+                expect_operator_argument_amount(arguments.len(), 2)?;
+
+                if arguments[0].is_empty() {
+                    Ok(arguments[1].clone())
+                } else {
+                    Ok(arguments[0].clone())
+                }
+            }
             Tuple => Ok(Value::Tuple(arguments.into())),
             Chain => {
                 if arguments.is_empty() {
@@ -447,6 +523,8 @@ impl Operator {
 
                 if let Some(value) = context.get_value(identifier).cloned() {
                     Ok(value)
+                } else if let Some(value) = read_dotted_identifier(identifier, context) {
+                    value
                 } else {
                     Err(EvalexprError::VariableIdentifierNotFound(
                         identifier.clone(),
@@ -539,3 +617,62 @@ impl Operator {
         }
     }
 }
+
+/// Reads an identifier like `person.name` or `names.2` whose longest dotted prefix is bound
+/// in the context, by accessing the remaining segments as members of its value.
+fn read_dotted_identifier<C: Context>(
+    identifier: &str,
+    context: &C,
+) -> Option<EvalexprResult<Value>> {
+    let mut end = identifier.len();
+    while let Some(dot) = identifier[..end].rfind('.') {
+        end = dot;
+        if let Some(value) = context.get_value(&identifier[..end]) {
+            return Some(
+                identifier[end + 1..]
+                    .split('.')
+                    .try_fold(value.clone(), |value, field| member(&value, field)),
+            );
+        }
+    }
+    None
+}
+
+/// Accesses the field of a record, or the element of a tuple at the position given by `field`.
+/// Accessing a member of the empty value or a missing member results in the empty value.
+fn member(value: &Value, field: &str) -> EvalexprResult<Value> {
+    match value {
+        Value::Record(record) => Ok(record.get(field).cloned().unwrap_or(Value::Empty)),
+        Value::Tuple(tuple) => match field.parse::<usize>() {
+            Ok(index) => Ok(tuple.get(index).cloned().unwrap_or(Value::Empty)),
+            Err(_) => Err(EvalexprError::wrong_type_combination(
+                Operator::Member {
+                    field: field.to_string(),
+                },
+                vec![value.into()],
+            )),
+        },
+        Value::Empty => Ok(Value::Empty),
+        value => Err(EvalexprError::wrong_type_combination(
+            Operator::Member {
+                field: field.to_string(),
+            },
+            vec![value.into()],
+        )),
+    }
+}
+
+/// Returns the string form of a value as inserted into a template string.
+/// Strings are inserted without quotes, and the empty value as an empty string.
+fn template_string(value: &Value) -> String {
+    match value {
+        Value::String(string) => string.clone(),
+        Value::Empty => String::new(),
+        Value::Tuple(tuple) => tuple
+            .iter()
+            .map(template_string)
+            .collect::<Vec<_>>()
+            .join(","),
+        value => value.to_string(),
+    }
+}
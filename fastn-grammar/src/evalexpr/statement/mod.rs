@@ -0,0 +1,588 @@
+use fastn_grammar::evalexpr::{
+    ContextWithMutableVariables, EvalexprError, EvalexprResult, ExprNode, Value,
+};
+
+/// A statement of a function body.
+///
+/// Function bodies are sequences of statements. Everything that is not a `let`, `if`, `for` or
+/// `return` statement is an ordinary expression, possibly a chain of expressions separated by
+/// `;`, and is kept as a single [`Statement::Expression`].
+#[derive(Debug, PartialEq, Clone)]
+pub enum Statement {
+    /// An expression, `a = a + 1;`. If it is the last statement of the body, its value is the
+    /// value of the body.
+    Expression(ExprNode),
+    /// A local binding, `let total: integer = 0;`. The kind annotation is optional and is kept
+    /// verbatim for the host language to check.
+    Let {
+        /// The name of the local.
+        name: String,
+        /// The kind annotation, if any.
+        kind: Option<String>,
+        /// The initial value.
+        value: ExprNode,
+        /// The line of the statement, counted from 0 at the start of the body.
+        line: usize,
+    },
+    /// `if condition { ... } else { ... }`. An `else if` is an `If` as the only statement of
+    /// `otherwise`.
+    If {
+        /// The condition, which must evaluate to a boolean.
+        condition: ExprNode,
+        /// The statements run if the condition is true.
+        then: Vec<Statement>,
+        /// The statements run otherwise, empty if there is no `else`.
+        otherwise: Vec<Statement>,
+    },
+    /// `for item in list { ... }`, where `list` evaluates to a tuple.
+    For {
+        /// The name each element is bound to.
+        item: String,
+        /// The list to loop over.
+        list: ExprNode,
+        /// The statements run for every element.
+        body: Vec<Statement>,
+        /// The line of the statement, counted from 0 at the start of the body.
+        line: usize,
+    },
+    /// `return value;` or `return;`.
+    Return {
+        /// The value returned, if any.
+        value: Option<ExprNode>,
+        /// The line of the statement, counted from 0 at the start of the body.
+        line: usize,
+    },
+}
+
+enum Flow {
+    Next(Value),
+    Return(Value),
+}
+
+/// The bindings of a block. A `let` or `for` binding is only visible until the end of its block,
+/// like `let` in JS, so the values it shadows are put back when the block ends.
+#[derive(Default)]
+struct Scope {
+    shadowed: Vec<(String, Option<Value>)>,
+}
+
+impl Scope {
+    /// Binds `name` to a new variable, which can hold a value of another type than the variable it
+    /// shadows.
+    fn bind<C: ContextWithMutableVariables>(
+        &mut self,
+        context: &mut C,
+        name: &str,
+        value: Value,
+    ) -> EvalexprResult<()> {
+        let previous = context.remove_value(name)?;
+        if !self.shadowed.iter().any(|(shadowed, _)| shadowed == name) {
+            self.shadowed.push((name.to_string(), previous));
+        }
+        context.set_value(name.to_string(), value)
+    }
+
+    fn end<C: ContextWithMutableVariables>(self, context: &mut C) -> EvalexprResult<()> {
+        for (name, previous) in self.shadowed.into_iter().rev() {
+            context.remove_value(name.as_str())?;
+            if let Some(previous) = previous {
+                context.set_value(name, previous)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Statement {
+    fn eval_with_context_mut<C: ContextWithMutableVariables>(
+        &self,
+        context: &mut C,
+        scope: &mut Scope,
+    ) -> EvalexprResult<Flow> {
+        match self {
+            Statement::Expression(node) => Ok(Flow::Next(node.eval_with_context_mut(context)?)),
+            Statement::Let { name, value, .. } => {
+                let value = value.eval_with_context_mut(context)?;
+                scope.bind(context, name, value)?;
+                Ok(Flow::Next(Value::Empty))
+            }
+            Statement::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                let block = if condition.eval_boolean_with_context_mut(context)? {
+                    then
+                } else {
+                    otherwise
+                };
+                Ok(match eval_block(block, context)? {
+                    Flow::Return(value) => Flow::Return(value),
+                    Flow::Next(_) => Flow::Next(Value::Empty),
+                })
+            }
+            Statement::For {
+                item, list, body, ..
+            } => {
+                let items = match list.eval_with_context_mut(context)? {
+                    Value::Tuple(items) => items,
+                    Value::Empty => vec![],
+                    value => return Err(EvalexprError::expected_tuple(value)),
+                };
+                let mut item_scope = Scope::default();
+                let mut flow = Flow::Next(Value::Empty);
+                for value in items {
+                    item_scope.bind(context, item, value)?;
+                    if let Flow::Return(value) = eval_block(body, context)? {
+                        flow = Flow::Return(value);
+                        break;
+                    }
+                }
+                item_scope.end(context)?;
+                Ok(flow)
+            }
+            Statement::Return { value, .. } => Ok(Flow::Return(match value {
+                Some(value) => value.eval_with_context_mut(context)?,
+                None => Value::Empty,
+            })),
+        }
+    }
+}
+
+fn eval_block<C: ContextWithMutableVariables>(
+    statements: &[Statement],
+    context: &mut C,
+) -> EvalexprResult<Flow> {
+    let mut scope = Scope::default();
+    let mut flow = Flow::Next(Value::Empty);
+    for statement in statements {
+        flow = statement.eval_with_context_mut(context, &mut scope)?;
+        if let Flow::Return(_) = flow {
+            break;
+        }
+    }
+    scope.end(context)?;
+    Ok(flow)
+}
+
+/// Evaluates the statements of a function body. The result is the value of the first `return`
+/// reached, or else the value of the last statement if it is an expression.
+pub(crate) fn eval_statements_with_context_mut<C: ContextWithMutableVariables>(
+    statements: &[Statement],
+    context: &mut C,
+) -> EvalexprResult<Value> {
+    match eval_block(statements, context)? {
+        Flow::Return(value) | Flow::Next(value) => Ok(value),
+    }
+}
+
+/// Parses the statements of a function body.
+pub(crate) fn parse_statements(string: &str) -> EvalexprResult<Vec<Statement>> {
+    let mut parser = Parser {
+        chars: string.chars().collect(),
+        position: 0,
+    };
+    parser.statements(false)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    /// Returns true if the keyword starts at the current position and is not the prefix of a
+    /// longer identifier.
+    fn at_keyword(&self, keyword: &str) -> bool {
+        let end = self.position + keyword.chars().count();
+        end <= self.chars.len()
+            && self.chars[self.position..end]
+                .iter()
+                .copied()
+                .eq(keyword.chars())
+            && !self.chars.get(end).is_some_and(|c| is_identifier_char(*c))
+    }
+
+    fn at_statement_keyword(&mut self) -> bool {
+        ["let", "for", "return"]
+            .iter()
+            .any(|keyword| self.at_keyword(keyword))
+            || self.at_if_statement()
+    }
+
+    /// An `if` not followed by a block is a call to the builtin `if(condition, a, b)`.
+    fn at_if_statement(&mut self) -> bool {
+        if !self.at_keyword("if") {
+            return false;
+        }
+        let position = self.position;
+        self.source_until(&['{', ';', '}']);
+        let is_statement = self.peek() == Some('{');
+        self.position = position;
+        is_statement
+    }
+
+    fn consume_keyword(&mut self, keyword: &str) -> EvalexprResult<()> {
+        self.skip_whitespace();
+        if !self.at_keyword(keyword) {
+            return Err(EvalexprError::CustomMessage(format!(
+                "Expected `{}`, found `{}`",
+                keyword,
+                self.rest()
+            )));
+        }
+        self.position += keyword.chars().count();
+        Ok(())
+    }
+
+    fn consume_char(&mut self, expected: char) -> EvalexprResult<()> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.position += 1;
+                Ok(())
+            }
+            _ => Err(EvalexprError::CustomMessage(format!(
+                "Expected `{}`, found `{}`",
+                expected,
+                self.rest()
+            ))),
+        }
+    }
+
+    /// The line of the current position, counted from 0.
+    fn line(&self) -> usize {
+        self.chars[..self.position]
+            .iter()
+            .filter(|c| **c == '\n')
+            .count()
+    }
+
+    fn rest(&self) -> String {
+        self.chars[self.position..]
+            .iter()
+            .take_while(|c| **c != '\n')
+            .collect()
+    }
+
+    fn identifier(&mut self) -> EvalexprResult<String> {
+        self.skip_whitespace();
+        let start = self.position;
+        while self.peek().is_some_and(is_identifier_char) {
+            self.position += 1;
+        }
+        if start == self.position {
+            return Err(EvalexprError::CustomMessage(format!(
+                "Expected an identifier, found `{}`",
+                self.rest()
+            )));
+        }
+        Ok(self.chars[start..self.position].iter().collect())
+    }
+
+    /// Collects source up to the first of `stops` outside of parentheses, brackets and string
+    /// literals. The stop character itself is not consumed.
+    fn source_until(&mut self, stops: &[char]) -> String {
+        let start = self.position;
+        let mut depth = 0usize;
+        let mut quote = None;
+
+        while let Some(c) = self.peek() {
+            match (quote, c) {
+                (Some(_), '\\') => self.position += 1,
+                (Some(q), c) if q == c => quote = None,
+                (Some(_), _) => {}
+                (None, '"' | '`') => quote = Some(c),
+                (None, '(' | '[') => depth += 1,
+                (None, ')' | ']') => depth = depth.saturating_sub(1),
+                (None, c) if depth == 0 && stops.contains(&c) => break,
+                (None, _) => {}
+            }
+            self.position += 1;
+        }
+
+        self.position = self.position.min(self.chars.len());
+        self.chars[start..self.position].iter().collect()
+    }
+
+    fn expression(&mut self, stops: &[char]) -> EvalexprResult<ExprNode> {
+        let source = self.source_until(stops);
+        if source.trim().is_empty() {
+            return Err(EvalexprError::CustomMessage(format!(
+                "Expected an expression, found `{}`",
+                self.rest()
+            )));
+        }
+        fastn_grammar::evalexpr::build_operator_tree(source.as_str())
+    }
+
+    /// Parses statements up to the end of the input, or up to the `}` closing the current
+    /// block if `in_block` is set.
+    fn statements(&mut self, in_block: bool) -> EvalexprResult<Vec<Statement>> {
+        let mut statements = vec![];
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None if in_block => return Err(EvalexprError::UnmatchedLCurlyBrace),
+                None => return Ok(statements),
+                Some('}') if in_block => {
+                    self.position += 1;
+                    return Ok(statements);
+                }
+                Some('}') => return Err(EvalexprError::UnmatchedRCurlyBrace),
+                Some(';') => self.position += 1,
+                Some(_) if self.at_keyword("let") => statements.push(self.let_statement()?),
+                Some(_) if self.at_if_statement() => statements.push(self.if_statement()?),
+                Some(_) if self.at_keyword("for") => statements.push(self.for_statement()?),
+                Some(_) if self.at_keyword("return") => statements.push(self.return_statement()?),
+                Some(_) => statements.push(self.expression_statement()?),
+            }
+        }
+    }
+
+    /// Expressions separated by `;` are kept together up to the next statement keyword, so a
+    /// body without statements parses to the same expression chain as before.
+    fn expression_statement(&mut self) -> EvalexprResult<Statement> {
+        let mut source = String::new();
+        loop {
+            source.push_str(self.source_until(&[';', '{', '}']).as_str());
+            match self.peek() {
+                Some(';') => {
+                    self.position += 1;
+                    source.push(';');
+                    self.skip_whitespace();
+                    if matches!(self.peek(), None | Some('}')) || self.at_statement_keyword() {
+                        break;
+                    }
+                }
+                Some('{') => {
+                    return Err(EvalexprError::CustomMessage(format!(
+                        "Unexpected `{{` in expression `{}`",
+                        source.trim()
+                    )))
+                }
+                _ => break,
+            }
+        }
+        Ok(Statement::Expression(
+            fastn_grammar::evalexpr::build_operator_tree(source.as_str())?,
+        ))
+    }
+
+    fn let_statement(&mut self) -> EvalexprResult<Statement> {
+        let line = self.line();
+        self.consume_keyword("let")?;
+        let name = self.identifier()?;
+        self.skip_whitespace();
+        let kind = if self.peek() == Some(':') {
+            self.position += 1;
+            let kind = self.source_until(&['=']).trim().to_string();
+            if kind.is_empty() {
+                return Err(EvalexprError::CustomMessage(format!(
+                    "Expected a kind for `{}`",
+                    name
+                )));
+            }
+            Some(kind)
+        } else {
+            None
+        };
+        self.consume_char('=')?;
+        let value = self.expression(&[';', '}'])?;
+        Ok(Statement::Let {
+            name,
+            kind,
+            value,
+            line,
+        })
+    }
+
+    fn if_statement(&mut self) -> EvalexprResult<Statement> {
+        self.consume_keyword("if")?;
+        let condition = self.expression(&['{', ';', '}'])?;
+        self.consume_char('{')?;
+        let then = self.statements(true)?;
+        self.skip_whitespace();
+        let otherwise = if self.at_keyword("else") {
+            self.consume_keyword("else")?;
+            self.skip_whitespace();
+            if self.at_if_statement() {
+                vec![self.if_statement()?]
+            } else {
+                self.consume_char('{')?;
+                self.statements(true)?
+            }
+        } else {
+            vec![]
+        };
+        Ok(Statement::If {
+            condition,
+            then,
+            otherwise,
+        })
+    }
+
+    fn for_statement(&mut self) -> EvalexprResult<Statement> {
+        let line = self.line();
+        self.consume_keyword("for")?;
+        let item = self.identifier()?;
+        self.consume_keyword("in")?;
+        let list = self.expression(&['{', ';', '}'])?;
+        self.consume_char('{')?;
+        let body = self.statements(true)?;
+        Ok(Statement::For {
+            item,
+            list,
+            body,
+            line,
+        })
+    }
+
+    fn return_statement(&mut self) -> EvalexprResult<Statement> {
+        let line = self.line();
+        self.consume_keyword("return")?;
+        let source = self.source_until(&[';', '}']);
+        let value = if source.trim().is_empty() {
+            None
+        } else {
+            Some(fastn_grammar::evalexpr::build_operator_tree(
+                source.as_str(),
+            )?)
+        };
+        Ok(Statement::Return { value, line })
+    }
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use fastn_grammar::evalexpr::{
+        build_operator_tree, build_statements, eval_statements_with_context_mut, EvalexprError,
+        HashMapContext, Statement, Value,
+    };
+
+    fn eval(string: &str) -> Result<Value, EvalexprError> {
+        eval_statements_with_context_mut(&build_statements(string)?, &mut HashMapContext::new())
+    }
+
+    #[test]
+    fn expressions_without_statements_stay_one_chain() {
+        assert_eq!(
+            build_statements("a = 1; b = a + 1;\n b"),
+            Ok(vec![Statement::Expression(
+                build_operator_tree("a = 1; b = a + 1;\n b").unwrap()
+            )])
+        );
+        assert_eq!(eval("a = 1; b = a + 1;\n b"), Ok(Value::Int(2)));
+        assert_eq!(eval("a = 1;"), Ok(Value::Empty));
+    }
+
+    #[test]
+    fn let_if_for_return() {
+        assert_eq!(
+            build_statements("let total: integer = 0;\nreturn total;"),
+            Ok(vec![
+                Statement::Let {
+                    name: "total".to_string(),
+                    kind: Some("integer".to_string()),
+                    value: build_operator_tree("0").unwrap(),
+                    line: 0,
+                },
+                Statement::Return {
+                    value: Some(build_operator_tree("total").unwrap()),
+                    line: 1,
+                },
+            ])
+        );
+        assert_eq!(
+            eval(
+                "let total = 0;
+                 for item in (1, 2, 3, 4) {
+                     if item % 2 == 0 { total = total + item; }
+                 }
+                 total"
+            ),
+            Ok(Value::Int(6))
+        );
+        assert_eq!(
+            eval("let x = 5; if x > 10 { return \"big\"; } else if x > 3 { return \"medium\"; } \"small\""),
+            Ok(Value::String("medium".to_string()))
+        );
+        assert_eq!(
+            eval("for item in (1, 2, 3) { if item == 2 { return item * 10; } } 0"),
+            Ok(Value::Int(20))
+        );
+        assert_eq!(
+            eval("letter = \"a\"; letter"),
+            Ok(Value::String("a".to_string()))
+        );
+        assert_eq!(eval("return;"), Ok(Value::Empty));
+        assert_eq!(
+            eval("x = 1; if(x > 0, \"a\", \"b\")"),
+            Ok(Value::String("a".to_string()))
+        );
+        assert_eq!(eval("s = \"{;}\"; s"), Ok(Value::String("{;}".to_string())));
+    }
+
+    #[test]
+    fn block_scope() {
+        // a `let` in a block is not visible after it, like in JS
+        assert_eq!(
+            eval("let x = 1; if true { let x = 2; x = x + 1; } x"),
+            Ok(Value::Int(1))
+        );
+        assert!(matches!(
+            eval("if true { let y = 2; } y"),
+            Err(EvalexprError::VariableIdentifierNotFound(_))
+        ));
+        assert!(matches!(
+            eval("for item in (1, 2) { let last = item; } last"),
+            Err(EvalexprError::VariableIdentifierNotFound(_))
+        ));
+        // assigning to a variable of an outer block changes it
+        assert_eq!(eval("let x = 1; if true { x = 2; } x"), Ok(Value::Int(2)));
+        // a new binding can have another type than the one it shadows
+        assert_eq!(
+            eval("let x = 1; for x in (\"a\", 2.5) { let x = true; } let x = \"b\"; x"),
+            Ok(Value::String("b".to_string()))
+        );
+        assert_eq!(
+            eval("let item = 0; for item in (1, 2) { item } item"),
+            Ok(Value::Int(0))
+        );
+    }
+
+    #[test]
+    fn statement_errors() {
+        assert_eq!(
+            eval("if true { 1"),
+            Err(EvalexprError::UnmatchedLCurlyBrace)
+        );
+        assert_eq!(eval("1 }"), Err(EvalexprError::UnmatchedRCurlyBrace));
+        assert_eq!(
+            eval("for item in 3 { item }"),
+            Err(EvalexprError::expected_tuple(Value::Int(3)))
+        );
+        assert!(matches!(
+            eval("for item of (1, 2) { item }"),
+            Err(EvalexprError::CustomMessage(_))
+        ));
+        assert!(matches!(
+            eval("let = 1;"),
+            Err(EvalexprError::CustomMessage(_))
+        ));
+    }
+}
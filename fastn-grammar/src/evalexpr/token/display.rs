@@ -1,6 +1,6 @@
 use std::fmt;
 
-use fastn_grammar::evalexpr::token::{PartialToken, Token};
+use fastn_grammar::evalexpr::token::{PartialToken, TemplatePart, Token};
 
 impl fmt::Display for Token {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
@@ -28,6 +28,16 @@ impl fmt::Display for Token {
             LBrace => write!(f, "("),
             RBrace => write!(f, ")"),
 
+            // Access
+            LBracket => write!(f, "["),
+            RBracket => write!(f, "]"),
+            Member(field) => write!(f, ".{}", field),
+
+            // Conditional
+            QuestionMark => write!(f, "?"),
+            Colon => write!(f, ":"),
+            Coalesce => write!(f, "??"),
+
             // Assignment
             Assign => write!(f, "="),
             PlusAssign => write!(f, "+="),
@@ -49,6 +59,32 @@ impl fmt::Display for Token {
             Int(int) => int.fmt(f),
             Boolean(boolean) => boolean.fmt(f),
             String(string) => fmt::Debug::fmt(string, f),
+            Template(parts) => {
+                write!(f, "`")?;
+                for part in parts {
+                    match part {
+                        TemplatePart::Text(text) => {
+                            for c in text.chars() {
+                                if matches!(c, '`' | '$' | '\\') {
+                                    write!(f, "\\")?;
+                                }
+                                write!(f, "{}", c)?;
+                            }
+                        }
+                        TemplatePart::Expression(tokens) => {
+                            write!(f, "${{")?;
+                            for (index, token) in tokens.iter().enumerate() {
+                                if index > 0 {
+                                    write!(f, " ")?;
+                                }
+                                token.fmt(f)?;
+                            }
+                            write!(f, "}}")?;
+                        }
+                    }
+                }
+                write!(f, "`")
+            }
         }
     }
 }
@@ -72,6 +108,7 @@ impl fmt::Display for PartialToken {
             Lt => write!(f, "<"),
             Ampersand => write!(f, "&"),
             VerticalBar => write!(f, "|"),
+            QuestionMark => write!(f, "?"),
         }
     }
 }
@@ -30,6 +30,16 @@ pub enum Token {
     LBrace,
     RBrace,
 
+    // Access
+    LBracket,
+    RBracket,
+    Member(String),
+
+    // Conditional
+    QuestionMark,
+    Colon,
+    Coalesce,
+
     // Assignment
     Assign,
     PlusAssign,
@@ -51,6 +61,16 @@ pub enum Token {
     Int(IntType),
     Boolean(bool),
     String(String),
+    Template(Vec<TemplatePart>),
+}
+
+/// A part of a template string literal, e.g. `` `Hello ${name}!` ``.
+#[derive(Clone, PartialEq, Debug)]
+pub enum TemplatePart {
+    /// Text that is copied into the result as is.
+    Text(String),
+    /// The tokens of an expression inside `${...}`, whose value is inserted into the result.
+    Expression(Vec<Token>),
 }
 
 /// A partial token is an input character whose meaning depends on the characters around it.
@@ -86,6 +106,8 @@ pub enum PartialToken {
     Ampersand,
     /// A vertical bar character '|'.
     VerticalBar,
+    /// A question mark character '?'.
+    QuestionMark,
 }
 
 // Make this a const fn as soon as is_whitespace and to_string get stable (issue #57563)
@@ -101,6 +123,12 @@ fn char_to_partial_token(c: char) -> PartialToken {
         '(' => PartialToken::Token(Token::LBrace),
         ')' => PartialToken::Token(Token::RBrace),
 
+        '[' => PartialToken::Token(Token::LBracket),
+        ']' => PartialToken::Token(Token::RBracket),
+
+        '?' => PartialToken::QuestionMark,
+        ':' => PartialToken::Token(Token::Colon),
+
         ',' => PartialToken::Token(Token::Comma),
         ';' => PartialToken::Token(Token::Semicolon),
 
@@ -145,6 +173,14 @@ impl Token {
             Token::LBrace => true,
             Token::RBrace => false,
 
+            Token::LBracket => false,
+            Token::RBracket => false,
+            Token::Member(_) => false,
+
+            Token::QuestionMark => false,
+            Token::Colon => false,
+            Token::Coalesce => false,
+
             Token::Comma => false,
             Token::Semicolon => false,
 
@@ -163,6 +199,7 @@ impl Token {
             Token::Int(_) => true,
             Token::Boolean(_) => true,
             Token::String(_) => true,
+            Token::Template(_) => true,
         }
     }
 
@@ -189,6 +226,14 @@ impl Token {
             Token::LBrace => false,
             Token::RBrace => true,
 
+            Token::LBracket => false,
+            Token::RBracket => true,
+            Token::Member(_) => true,
+
+            Token::QuestionMark => false,
+            Token::Colon => false,
+            Token::Coalesce => false,
+
             Token::Comma => false,
             Token::Semicolon => false,
 
@@ -207,6 +252,7 @@ impl Token {
             Token::Int(_) => true,
             Token::Boolean(_) => true,
             Token::String(_) => true,
+            Token::Template(_) => true,
         }
     }
 
@@ -260,6 +306,77 @@ fn parse_string_literal<Iter: Iterator<Item = char>>(
     Ok(PartialToken::Token(Token::String(result)))
 }
 
+/// Parses a template string value from the given character iterator.
+///
+/// The first character from the iterator is interpreted as first character of the template.
+/// The template is terminated by a backtick `` ` `` and may contain expressions in `${...}`.
+/// Occurrences of `` ` ``, `$` and `\` within the text can be escaped with `\`.
+fn parse_template_literal<Iter: Iterator<Item = char>>(
+    iter: &mut std::iter::Peekable<Iter>,
+) -> EvalexprResult<PartialToken> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+
+    while let Some(c) = iter.next() {
+        match c {
+            '`' => break,
+            '\\' => match iter.next() {
+                Some(c @ ('`' | '$' | '\\' | '"')) => text.push(c),
+                Some(c) => return Err(EvalexprError::IllegalEscapeSequence(format!("\\{}", c))),
+                None => return Err(EvalexprError::IllegalEscapeSequence("\\".to_string())),
+            },
+            '$' if iter.peek() == Some(&'{') => {
+                iter.next();
+                if !text.is_empty() {
+                    parts.push(TemplatePart::Text(std::mem::take(&mut text)));
+                }
+                let expression = template_expression_source(iter)?;
+                parts.push(TemplatePart::Expression(tokenize(&expression)?));
+            }
+            c => text.push(c),
+        }
+    }
+
+    if !text.is_empty() {
+        parts.push(TemplatePart::Text(text));
+    }
+
+    Ok(PartialToken::Token(Token::Template(parts)))
+}
+
+/// Collects the source of an expression inside `${...}` up to the matching `}`.
+///
+/// Braces inside string literals and nested templates do not count towards the nesting.
+fn template_expression_source<Iter: Iterator<Item = char>>(
+    iter: &mut Iter,
+) -> EvalexprResult<String> {
+    let mut source = String::new();
+    let mut depth = 0;
+    let mut quote = None;
+
+    while let Some(c) = iter.next() {
+        match (quote, c) {
+            (Some(_), '\\') => {
+                source.push(c);
+                if let Some(c) = iter.next() {
+                    source.push(c);
+                }
+                continue;
+            }
+            (Some(q), c) if q == c => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '`') => quote = Some(c),
+            (None, '{') => depth += 1,
+            (None, '}') if depth == 0 => return Ok(source),
+            (None, '}') => depth -= 1,
+            (None, _) => {}
+        }
+        source.push(c);
+    }
+
+    Err(EvalexprError::UnterminatedTemplateExpression)
+}
+
 /// Converts a string to a vector of partial tokens.
 fn str_to_partial_tokens(string: &str) -> EvalexprResult<Vec<PartialToken>> {
     let mut result = Vec::new();
@@ -268,6 +385,8 @@ fn str_to_partial_tokens(string: &str) -> EvalexprResult<Vec<PartialToken>> {
     while let Some(c) = iter.next() {
         if c == '"' {
             result.push(parse_string_literal(&mut iter)?);
+        } else if c == '`' {
+            result.push(parse_template_literal(&mut iter)?);
         } else {
             let mut partial_token = char_to_partial_token(c);
             if let Some(PartialToken::Literal(..)) = result.last() {
@@ -303,12 +422,36 @@ fn partial_tokens_to_tokens(mut tokens: &[PartialToken]) -> EvalexprResult<Vec<T
         let third = tokens.get(2).cloned();
         let mut cutoff = 2;
 
+        // A literal like `.name` right after a closing brace or bracket accesses a member of
+        // the preceding value, as in `(a, b).0` or `people[0].name`.
+        if let PartialToken::Literal(literal) = &first {
+            if literal.starts_with('.')
+                && matches!(result.last(), Some(Token::RBrace | Token::RBracket))
+            {
+                for field in literal.split('.').skip(1) {
+                    if field.is_empty() {
+                        return Err(EvalexprError::unmatched_partial_token(first, second));
+                    }
+                    result.push(Token::Member(field.to_string()));
+                }
+                tokens = &tokens[1..];
+                continue;
+            }
+        }
+
         result.extend(
             match first {
                 PartialToken::Token(token) => {
                     cutoff = 1;
                     Some(token)
                 }
+                PartialToken::QuestionMark => match second {
+                    Some(PartialToken::QuestionMark) => Some(Token::Coalesce),
+                    _ => {
+                        cutoff = 1;
+                        Some(Token::QuestionMark)
+                    }
+                },
                 PartialToken::Plus => match second {
                     Some(PartialToken::Eq) => Some(Token::PlusAssign),
                     _ => {
@@ -449,13 +592,14 @@ pub(crate) fn tokenize(string: &str) -> EvalexprResult<Vec<Token>> {
 
 #[cfg(test)]
 mod tests {
-    use fastn_grammar::evalexpr::token::{char_to_partial_token, tokenize, Token};
+    use fastn_grammar::evalexpr::token::{char_to_partial_token, tokenize, TemplatePart, Token};
     use std::fmt::Write;
 
     #[test]
     fn test_partial_token_display() {
         let chars = vec![
-            '+', '-', '*', '/', '%', '^', '(', ')', ',', ';', '=', '!', '>', '<', '&', '|', ' ',
+            '+', '-', '*', '/', '%', '^', '(', ')', '[', ']', '?', ':', ',', ';', '=', '!', '>',
+            '<', '&', '|', ' ',
         ];
 
         for char in chars {
@@ -469,7 +613,7 @@ mod tests {
     #[test]
     fn test_token_display() {
         let token_string =
-            "+ - * / % ^ == != > < >= <= && || ! ( ) = += -= *= /= %= ^= &&= ||= , ; ";
+            "+ - * / % ^ == != > < >= <= && || ! ( ) [ ] ? : ?? = += -= *= /= %= ^= &&= ||= , ; ";
         let tokens = tokenize(token_string).unwrap();
         let mut result_string = String::new();
 
@@ -492,4 +636,36 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn template_parts() {
+        let tokens = tokenize("`Hi ${user.name}, \\`${\"}\"}\\``").unwrap();
+        assert_eq!(
+            tokens.as_slice(),
+            [Token::Template(vec![
+                TemplatePart::Text("Hi ".to_string()),
+                TemplatePart::Expression(vec![Token::Identifier("user.name".to_string())]),
+                TemplatePart::Text(", `".to_string()),
+                TemplatePart::Expression(vec![Token::String("}".to_string())]),
+                TemplatePart::Text("`".to_string()),
+            ])]
+        );
+        assert!(tokenize("`${a`").is_err());
+    }
+
+    #[test]
+    fn member_after_closing_bracket() {
+        let tokens = tokenize("a[0].name.first").unwrap();
+        assert_eq!(
+            tokens.as_slice(),
+            [
+                Token::Identifier("a".to_string()),
+                Token::LBracket,
+                Token::Int(0),
+                Token::RBracket,
+                Token::Member("name".to_string()),
+                Token::Member("first".to_string()),
+            ]
+        );
+    }
 }
@@ -1,5 +1,5 @@
 use fastn_grammar::evalexpr::{
-    token::Token,
+    token::{TemplatePart, Token},
     value::{TupleType, EMPTY_VALUE},
     Context, ContextWithMutableVariables, EmptyType, FloatType, HashMapContext, IntType,
 };
@@ -176,6 +176,11 @@ impl ExprNode {
     ///
     /// Fails, if one of the operators in the expression tree fails.
     pub fn eval_with_context<C: Context>(&self, context: &C) -> EvalexprResult<Value> {
+        if let Some(result) = self.eval_short_circuit(&mut |child| child.eval_with_context(context))
+        {
+            return result;
+        }
+
         let mut arguments = Vec::new();
         for child in self.children() {
             arguments.push(child.eval_with_context(context)?);
@@ -190,6 +195,12 @@ impl ExprNode {
         &self,
         context: &mut C,
     ) -> EvalexprResult<Value> {
+        if let Some(result) =
+            self.eval_short_circuit(&mut |child| child.eval_with_context_mut(context))
+        {
+            return result;
+        }
+
         let mut arguments = Vec::new();
         for child in self.children() {
             arguments.push(child.eval_with_context_mut(context)?);
@@ -197,6 +208,45 @@ impl ExprNode {
         self.operator().eval_mut(&arguments, context)
     }
 
+    /// Evaluates the operators that only evaluate some of their children, i.e. `?`, `:` and `??`.
+    ///
+    /// Returns `None` for all other operators, whose children are evaluated eagerly.
+    fn eval_short_circuit(
+        &self,
+        eval: &mut dyn FnMut(&ExprNode) -> EvalexprResult<Value>,
+    ) -> Option<EvalexprResult<Value>> {
+        let children = self.children();
+        let result = match self.operator() {
+            Operator::Conditional if children.len() == 2 => {
+                match eval(&children[0]).and_then(|condition| condition.as_boolean()) {
+                    Ok(true) => eval(&children[1]),
+                    Ok(false) => Ok(Value::Empty),
+                    Err(error) => Err(error),
+                }
+            }
+            Operator::Else if children.len() == 2 => {
+                match (children[0].operator(), children[0].children()) {
+                    (Operator::Conditional, [condition, value]) => {
+                        match eval(condition).and_then(|condition| condition.as_boolean()) {
+                            Ok(true) => eval(value),
+                            Ok(false) => eval(&children[1]),
+                            Err(error) => Err(error),
+                        }
+                    }
+                    _ => Err(EvalexprError::CustomMessage(
+                        "Found a ':' without a preceding '?'.".to_string(),
+                    )),
+                }
+            }
+            Operator::Coalesce if children.len() == 2 => match eval(&children[0]) {
+                Ok(Value::Empty) => eval(&children[1]),
+                result => result,
+            },
+            _ => return None,
+        };
+        Some(result)
+    }
+
     /// Evaluates the operator tree rooted at this node.
     ///
     /// Fails, if one of the operators in the expression tree fails.
@@ -623,6 +673,8 @@ fn collapse_all_sequences(root_stack: &mut Vec<ExprNode>) -> EvalexprResult<()>
 
 pub(crate) fn tokens_to_operator_tree(tokens: Vec<Token>) -> EvalexprResult<ExprNode> {
     let mut root_stack = vec![ExprNode::root_node()];
+    // The opening brace or bracket of every subexpression on the root stack.
+    let mut delimiter_stack = Vec::new();
     let mut last_token_is_rightsided_value = false;
     let mut token_iter = tokens.iter().peekable();
 
@@ -655,10 +707,11 @@ pub(crate) fn tokens_to_operator_tree(tokens: Vec<Token>) -> EvalexprResult<Expr
 
             Token::LBrace => {
                 root_stack.push(ExprNode::root_node());
+                delimiter_stack.push(Token::LBrace);
                 None
             }
             Token::RBrace => {
-                if root_stack.len() <= 1 {
+                if delimiter_stack.pop() != Some(Token::LBrace) {
                     return Err(EvalexprError::UnmatchedRBrace);
                 } else {
                     collapse_all_sequences(&mut root_stack)?;
@@ -666,6 +719,29 @@ pub(crate) fn tokens_to_operator_tree(tokens: Vec<Token>) -> EvalexprResult<Expr
                 }
             }
 
+            // The index itself is pushed as a new root after the `Index` node is inserted below
+            Token::LBracket => {
+                if !last_token_is_rightsided_value {
+                    return Err(EvalexprError::CustomMessage(
+                        "Expected a value before '['.".to_string(),
+                    ));
+                }
+                Some(ExprNode::new(Operator::Index))
+            }
+            Token::RBracket => {
+                if delimiter_stack.pop() != Some(Token::LBracket) {
+                    return Err(EvalexprError::UnmatchedRBracket);
+                } else {
+                    collapse_all_sequences(&mut root_stack)?;
+                    root_stack.pop()
+                }
+            }
+            Token::Member(field) => Some(ExprNode::new(Operator::Member { field })),
+
+            Token::QuestionMark => Some(ExprNode::new(Operator::Conditional)),
+            Token::Colon => Some(ExprNode::new(Operator::Else)),
+            Token::Coalesce => Some(ExprNode::new(Operator::Coalesce)),
+
             Token::Assign => Some(ExprNode::new(Operator::Assign)),
             Token::PlusAssign => Some(ExprNode::new(Operator::AddAssign)),
             Token::MinusAssign => Some(ExprNode::new(Operator::SubAssign)),
@@ -700,6 +776,18 @@ pub(crate) fn tokens_to_operator_tree(tokens: Vec<Token>) -> EvalexprResult<Expr
                 Some(ExprNode::new(Operator::value(Value::Boolean(boolean))))
             }
             Token::String(string) => Some(ExprNode::new(Operator::value(Value::String(string)))),
+            Token::Template(parts) => {
+                let mut children = Vec::new();
+                for part in parts {
+                    children.push(match part {
+                        TemplatePart::Text(text) => {
+                            ExprNode::new(Operator::value(Value::String(text)))
+                        }
+                        TemplatePart::Expression(tokens) => tokens_to_operator_tree(tokens)?,
+                    });
+                }
+                Some(ExprNode::new(Operator::Template).add_children(children))
+            }
         };
 
         if let Some(mut node) = node {
@@ -759,14 +847,23 @@ pub(crate) fn tokens_to_operator_tree(tokens: Vec<Token>) -> EvalexprResult<Expr
             }
         }
 
+        if token == Token::LBracket {
+            root_stack.push(ExprNode::root_node());
+            delimiter_stack.push(Token::LBracket);
+        }
+
         last_token_is_rightsided_value = token.is_rightsided_value();
     }
 
     // In the end, all sequences are implicitly terminated
     collapse_all_sequences(&mut root_stack)?;
 
-    if root_stack.len() > 1 {
-        Err(EvalexprError::UnmatchedLBrace)
+    if let Some(delimiter) = delimiter_stack.pop() {
+        if delimiter == Token::LBracket {
+            Err(EvalexprError::UnmatchedLBracket)
+        } else {
+            Err(EvalexprError::UnmatchedLBrace)
+        }
     } else if let Some(root) = root_stack.pop() {
         Ok(root)
     } else {
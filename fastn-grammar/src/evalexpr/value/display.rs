@@ -22,6 +22,20 @@ impl Display for Value {
                 }
                 write!(f, ")")
             }
+            Value::Record(record) => {
+                write!(f, "{{")?;
+                let mut once = false;
+                for (key, value) in record {
+                    if once {
+                        write!(f, ", ")?;
+                    } else {
+                        once = true;
+                    }
+                    write!(f, "{}: ", key)?;
+                    value.fmt(f)?;
+                }
+                write!(f, "}}")
+            }
             Value::Empty => write!(f, "()"),
         }
     }
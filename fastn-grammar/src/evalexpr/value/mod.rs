@@ -13,6 +13,9 @@ pub type FloatType = f64;
 /// The type used to represent tuples in `Value::Tuple`.
 pub type TupleType = Vec<Value>;
 
+/// The type used to represent records in `Value::Record`.
+pub type RecordType = std::collections::BTreeMap<String, Value>;
+
 /// The type used to represent empty values in `Value::Empty`.
 pub type EmptyType = ();
 
@@ -34,6 +37,8 @@ pub enum Value {
     Boolean(bool),
     /// A tuple value.
     Tuple(TupleType),
+    /// A record value, mapping field names to values.
+    Record(RecordType),
     /// An empty value.
     Empty,
 }
@@ -68,6 +73,11 @@ impl Value {
         matches!(self, Value::Tuple(_))
     }
 
+    /// Returns true if `self` is a `Value::Record`.
+    pub fn is_record(&self) -> bool {
+        matches!(self, Value::Record(_))
+    }
+
     /// Returns true if `self` is a `Value::Empty`.
     pub fn is_empty(&self) -> bool {
         matches!(self, Value::Empty)
@@ -137,6 +147,14 @@ impl Value {
         }
     }
 
+    /// Clones the value stored in `self` as `RecordType`, or returns `Err` if `self` is not a `Value::Record`.
+    pub fn as_record(&self) -> EvalexprResult<RecordType> {
+        match self {
+            Value::Record(record) => Ok(record.clone()),
+            value => Err(EvalexprError::expected_record(value.clone())),
+        }
+    }
+
     /// Returns `()`, or returns`Err` if `self` is not a `Value::Tuple`.
     pub fn as_empty(&self) -> EvalexprResult<()> {
         match self {
@@ -182,6 +200,12 @@ impl From<TupleType> for Value {
     }
 }
 
+impl From<RecordType> for Value {
+    fn from(record: RecordType) -> Self {
+        Value::Record(record)
+    }
+}
+
 impl From<Value> for EvalexprResult<Value> {
     fn from(value: Value) -> Self {
         Ok(value)
@@ -254,6 +278,18 @@ impl TryFrom<Value> for TupleType {
     }
 }
 
+impl TryFrom<Value> for RecordType {
+    type Error = EvalexprError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        if let Value::Record(value) = value {
+            Ok(value)
+        } else {
+            Err(EvalexprError::ExpectedRecord { actual: value })
+        }
+    }
+}
+
 impl TryFrom<Value> for () {
     type Error = EvalexprError;
 
@@ -268,7 +304,7 @@ impl TryFrom<Value> for () {
 
 #[cfg(test)]
 mod tests {
-    use fastn_grammar::evalexpr::value::{TupleType, Value};
+    use fastn_grammar::evalexpr::value::{RecordType, TupleType, Value};
 
     #[test]
     fn test_value_conversions() {
@@ -283,6 +319,10 @@ mod tests {
             Value::from(TupleType::new()).as_tuple(),
             Ok(TupleType::new())
         );
+        assert_eq!(
+            Value::from(RecordType::new()).as_record(),
+            Ok(RecordType::new())
+        );
     }
 
     #[test]
@@ -292,5 +332,6 @@ mod tests {
         assert!(Value::from(3.3).is_float());
         assert!(Value::from(true).is_boolean());
         assert!(Value::from(TupleType::new()).is_tuple());
+        assert!(Value::from(RecordType::new()).is_record());
     }
 }
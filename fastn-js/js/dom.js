@@ -260,6 +260,12 @@ fastn_dom.PropertyKind = {
     Css: 104,
     Js: 105,
     LinkRel: 106,
+    CodeHighlighted: 107,
+    CodeHighlightLines: 108,
+    CodeDiff: 109,
+    CodeCollapse: 110,
+    CodeCopyButton: 111,
+    MarkdownHtml: 112,
 };
 
 
@@ -1516,9 +1522,37 @@ class Node2 {
                 staticValue = modifiedText;
             }
             let codeNode = this.#children[0].getNode();
-            codeNode.innerHTML= staticValue;
             this.#extraData.code = this.#extraData.code ? this.#extraData.code : {};
+            if (this.#extraData.code.highlighted) {
+                // the html is already there, the code is only needed for the decorations
+                this.#extraData.code.text = staticValue;
+            } else {
+                codeNode.innerHTML= staticValue;
+                this.#extraData.code.text = codeNode.textContent;
+            }
             fastn_utils.highlightCode(codeNode, this.#extraData.code);
+        } else if (kind === fastn_dom.PropertyKind.CodeHighlighted) {
+            // highlighted on the server, using a syntax or theme shipped by the package
+            let codeNode = this.#children[0].getNode();
+            codeNode.innerHTML = staticValue;
+            this.#extraData.code = this.#extraData.code ? this.#extraData.code : {};
+            this.#extraData.code.highlighted = staticValue;
+        } else if (kind === fastn_dom.PropertyKind.CodeHighlightLines) {
+            this.#extraData.code = this.#extraData.code ? this.#extraData.code : {};
+            this.#extraData.code.highlightLines = fastn_utils.private.lineRanges(staticValue);
+            fastn_utils.highlightCode(this.#children[0].getNode(), this.#extraData.code);
+        } else if (kind === fastn_dom.PropertyKind.CodeDiff) {
+            this.#extraData.code = this.#extraData.code ? this.#extraData.code : {};
+            this.#extraData.code.diff = !!staticValue;
+            fastn_utils.highlightCode(this.#children[0].getNode(), this.#extraData.code);
+        } else if (kind === fastn_dom.PropertyKind.CodeCollapse) {
+            this.#extraData.code = this.#extraData.code ? this.#extraData.code : {};
+            this.#extraData.code.collapse = fastn_utils.private.lineRanges(staticValue);
+            fastn_utils.highlightCode(this.#children[0].getNode(), this.#extraData.code);
+        } else if (kind === fastn_dom.PropertyKind.CodeCopyButton) {
+            this.#extraData.code = this.#extraData.code ? this.#extraData.code : {};
+            this.#extraData.code.copyButton = !!staticValue;
+            fastn_utils.highlightCode(this.#children[0].getNode(), this.#extraData.code);
         }  else if (kind === fastn_dom.PropertyKind.CodeShowLineNumber) {
             if (staticValue) {
                 this.#node.classList.add("line-numbers");
@@ -1592,6 +1626,9 @@ class Node2 {
                 staticValue = fastn_utils.markdown_inline(staticValue);
             }
             this.#node.innerHTML = staticValue;
+        } else if (kind === fastn_dom.PropertyKind.MarkdownHtml) {
+            // rendered on the server, with the markdown options of the package
+            this.#node.innerHTML = staticValue;
         }else {
             throw ("invalid fastn_dom.PropertyKind: " + kind);
         }
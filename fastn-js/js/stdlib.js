@@ -0,0 +1,437 @@
+// The standard library of string, list, number and date functions. These
+// must behave exactly like their counterparts in
+// `ftd/src/interpreter/things/stdlib.rs`.
+let fastn_stdlib = {
+    MONTHS: [
+        "January", "February", "March", "April", "May", "June", "July",
+        "August", "September", "October", "November", "December",
+    ],
+    WEEKDAYS: [
+        "Sunday", "Monday", "Tuesday", "Wednesday", "Thursday", "Friday",
+        "Saturday",
+    ],
+
+    // Functions are called with positional arguments from expressions, and
+    // with an object of named arguments, `{s: ..., sep: ...}`, when called
+    // as `$ftd.split(s = ..., sep = ...)`.
+    define(name, parameters, implementation) {
+        ftd[name] = function (...args) {
+            if (args.length > 0 && !fastn_utils.isNull(args[0])
+                && args[0].constructor === Object) {
+                args = parameters.map(parameter => args[0][parameter]);
+            }
+            return implementation(...args.map(fastn_stdlib.value));
+        };
+    },
+
+    value(value) {
+        value = fastn_utils.getter(value);
+        if (value instanceof fastn.mutableListClass) {
+            return value.getList().map(item => fastn_utils.getter(item.item));
+        }
+        return value === undefined ? null : value;
+    },
+
+    list(value) {
+        if (fastn_utils.isNull(value)) {
+            return [];
+        }
+        if (!Array.isArray(value)) {
+            throw new Error(`Expected a list, found: ${value}`);
+        }
+        return value;
+    },
+
+    string(value) {
+        if (typeof value !== "string") {
+            throw new Error(`Expected a string, found: ${value}`);
+        }
+        return value;
+    },
+
+    digits(value) {
+        if (!Number.isInteger(value) || value < 0 || value > 20) {
+            throw new Error(`Expected 0 to 20 digits, found: ${value}`);
+        }
+        return value;
+    },
+
+    // A record field by its ftd name, `first-name`, or by its expression
+    // name, `first_name`.
+    field(value, name) {
+        let field;
+        if (value instanceof fastn.recordInstanceClass) {
+            field = value.get(name.replaceAll("-", "_"));
+        } else if (!fastn_utils.isNull(value) && typeof value === "object") {
+            field = value[name];
+            if (field === undefined) {
+                field = value[name.replaceAll("_", "-")];
+            }
+        } else {
+            throw new Error(`Expected a record, found: ${value}`);
+        }
+        return fastn_stdlib.value(field);
+    },
+
+    display(value) {
+        return fastn_utils.isNull(value) ? "" : String(value);
+    },
+
+    sameValue(a, b) {
+        return a === b;
+    },
+
+    // Negative indices count from the end, and indices are clamped to the
+    // length.
+    slice(items, start, end) {
+        return items.slice(start, Math.max(
+            start < 0 ? Math.max(items.length + start, 0) : Math.min(start, items.length),
+            end < 0 ? Math.max(items.length + end, 0) : Math.min(end, items.length),
+        ));
+    },
+
+    // Sorts numbers, strings or booleans, or records by a field holding one
+    // of those. The sort is stable.
+    sort(items, by) {
+        let keyed = items.map(item => [by === undefined ? item : fastn_stdlib.field(item, by), item]);
+        keyed.sort(([a], [b]) => {
+            let types = [typeof a, typeof b];
+            if (types[0] !== types[1] || !["string", "number", "boolean"].includes(types[0])) {
+                throw new Error("Can only sort numbers, strings or booleans of one kind");
+            }
+            return a < b ? -1 : a > b ? 1 : 0;
+        });
+        return fastn.mutableList(keyed.map(([_, item]) => item));
+    },
+
+    // Rounds half away from zero to `digits` decimal places.
+    round(value, digits) {
+        let factor = Math.pow(10, digits);
+        return Math.sign(value) * (Math.round(Math.abs(value) * factor) / factor);
+    },
+
+    // The group separator, decimal separator and whether the locale groups
+    // digits the Indian way, `12,34,567`.
+    separators(locale) {
+        locale = locale.toLowerCase().replaceAll("_", "-");
+        let language = locale.split("-")[0];
+        if (locale === "en-in" || language === "hi") {
+            return [",", ".", true];
+        }
+        if (["de", "es", "it", "nl", "pt", "id", "tr", "da"].includes(language)) {
+            return [".", ",", false];
+        }
+        if (language === "fr") {
+            return ["\u202f", ",", false];
+        }
+        if (["ru", "pl", "sv", "cs", "fi", "nb", "uk"].includes(language)) {
+            return ["\u00a0", ",", false];
+        }
+        return [",", ".", false];
+    },
+
+    formatNumber(value, digits, locale, grouped) {
+        let [group, decimal, indian] = fastn_stdlib.separators(locale);
+        value = fastn_stdlib.round(value, digits);
+        let [integer, fraction] = Math.abs(value).toFixed(digits).split(".");
+
+        let groups = [];
+        let size = 3;
+        while (grouped && integer.length > size) {
+            groups.push(integer.slice(integer.length - size));
+            integer = integer.slice(0, integer.length - size);
+            if (indian) {
+                size = 2;
+            }
+        }
+        groups.push(integer);
+        groups.reverse();
+
+        return (value < 0 ? "-" : "") + groups.join(group)
+            + (fraction === undefined ? "" : decimal + fraction);
+    },
+
+    formatCurrency(value, currency, locale) {
+        currency = currency.toUpperCase();
+        let symbol = {
+            USD: "$", EUR: "€", GBP: "£", INR: "₹", JPY: "¥", CNY: "¥",
+            KRW: "₩", AUD: "A$", CAD: "CA$",
+        }[currency] ?? currency;
+        let digits = ["JPY", "KRW"].includes(currency) ? 0 : 2;
+        let number = fastn_stdlib.formatNumber(Math.abs(value), digits, locale, true);
+        let sign = fastn_stdlib.round(value, digits) < 0 ? "-" : "";
+        if (fastn_stdlib.separators(locale)[1] === ",") {
+            return `${sign}${number}\u00a0${symbol}`;
+        } else if (symbol === currency) {
+            return `${sign}${symbol}\u00a0${number}`;
+        }
+        return `${sign}${symbol}${number}`;
+    },
+
+    isLeapYear(year) {
+        return year % 4 === 0 && (year % 100 !== 0 || year % 400 === 0);
+    },
+
+    daysInMonth(year, month) {
+        if (month === 2) {
+            return fastn_stdlib.isLeapYear(year) ? 29 : 28;
+        }
+        return [4, 6, 9, 11].includes(month) ? 30 : 31;
+    },
+
+    // source: http://howardhinnant.github.io/date_algorithms.html
+    daysFromCivil(year, month, day) {
+        year = month <= 2 ? year - 1 : year;
+        let era = Math.floor(year / 400);
+        let yearOfEra = year - era * 400;
+        let dayOfYear = Math.trunc((153 * ((month + 9) % 12) + 2) / 5) + day - 1;
+        let dayOfEra = yearOfEra * 365 + Math.trunc(yearOfEra / 4)
+            - Math.trunc(yearOfEra / 100) + dayOfYear;
+        return era * 146097 + dayOfEra - 719468;
+    },
+
+    civilFromDays(days) {
+        days = days + 719468;
+        let era = Math.floor(days / 146097);
+        let dayOfEra = days - era * 146097;
+        let yearOfEra = Math.trunc((dayOfEra - Math.trunc(dayOfEra / 1460)
+            + Math.trunc(dayOfEra / 36524) - Math.trunc(dayOfEra / 146096)) / 365);
+        let dayOfYear = dayOfEra - (365 * yearOfEra + Math.trunc(yearOfEra / 4)
+            - Math.trunc(yearOfEra / 100));
+        let month = Math.trunc((5 * dayOfYear + 2) / 153);
+        let day = dayOfYear - Math.trunc((153 * month + 2) / 5) + 1;
+        month = month < 10 ? month + 3 : month - 9;
+        return [yearOfEra + era * 400 + (month <= 2 ? 1 : 0), month, day];
+    },
+
+    pad(value, length) {
+        return String(value).padStart(length, "0");
+    },
+
+    date(year, month, day, hour, minute, second, hasTime) {
+        if (month < 1 || month > 12
+            || day < 1 || day > fastn_stdlib.daysInMonth(year, month)
+            || hour < 0 || hour > 23 || minute < 0 || minute > 59
+            || second < 0 || second > 59) {
+            let pad = fastn_stdlib.pad;
+            throw new Error(`Invalid date: ${pad(year, 4)}-${pad(month, 2)}-${pad(day, 2)}`
+                + `T${pad(hour, 2)}:${pad(minute, 2)}:${pad(second, 2)}`);
+        }
+        return {year, month, day, hour, minute, second, hasTime};
+    },
+
+    days(date) {
+        return fastn_stdlib.daysFromCivil(date.year, date.month, date.day);
+    },
+
+    // Parses `YYYY-MM-DD`, `YYYY-MM-DDTHH:MM` or `YYYY-MM-DDTHH:MM:SS`.
+    parseIso(value) {
+        let match = /^(\d{4})-(\d{2})-(\d{2})(?:[T ](\d{2}):(\d{2})(?::(\d{2}))?)?$/
+            .exec(fastn_stdlib.string(value));
+        if (match === null) {
+            throw new Error(`Expected an ISO 8601 date, found: \`${value}\``);
+        }
+        let [_, year, month, day, hour, minute, second] = match
+            .map(part => part === undefined ? 0 : Number(part));
+        return fastn_stdlib.date(
+            year, month, day, hour, minute, second, match[4] !== undefined,
+        );
+    },
+
+    // Parses a date with the directives of `format`. Fields missing from the
+    // format default to `1970-01-01T00:00:00`.
+    parse(value, format) {
+        let invalid = () => new Error(`\`${value}\` does not match the date format \`${format}\``);
+        let [year, month, day, hour, minute, second] = [1970, 1, 1, 0, 0, 0];
+        let [pm, hasTime] = [null, false];
+        let input = value;
+        let directives = Array.from(format);
+
+        for (let i = 0; i < directives.length; i++) {
+            let c = directives[i];
+            if (c !== "%") {
+                if (!input.startsWith(c)) {
+                    throw invalid();
+                }
+                input = input.slice(c.length);
+                continue;
+            }
+            let directive = directives[++i] ?? "%";
+            let digits = max => {
+                let number = /^\d*/.exec(input.slice(0, max))[0];
+                input = input.slice(number.length);
+                if (number.length === 0) {
+                    throw invalid();
+                }
+                return Number(number);
+            };
+            switch (directive) {
+                case "Y": year = digits(4); break;
+                case "y": year = 2000 + digits(2); break;
+                case "m": month = digits(2); break;
+                case "d": case "e": day = digits(2); break;
+                case "H": case "I": hour = digits(2); hasTime = true; break;
+                case "M": minute = digits(2); hasTime = true; break;
+                case "S": second = digits(2); hasTime = true; break;
+                case "p": {
+                    let marker = input.slice(0, 2).toUpperCase();
+                    if (marker !== "AM" && marker !== "PM") {
+                        throw invalid();
+                    }
+                    pm = marker === "PM";
+                    input = input.slice(2);
+                    break;
+                }
+                case "b": case "B": case "a": case "A": {
+                    let names = "bB".includes(directive)
+                        ? fastn_stdlib.MONTHS : fastn_stdlib.WEEKDAYS;
+                    let index = names.findIndex(name => {
+                        name = "BA".includes(directive) ? name : name.slice(0, 3);
+                        if (input.slice(0, name.length).toLowerCase() !== name.toLowerCase()) {
+                            return false;
+                        }
+                        input = input.slice(name.length);
+                        return true;
+                    });
+                    if (index === -1) {
+                        throw invalid();
+                    }
+                    if ("bB".includes(directive)) {
+                        month = index + 1;
+                    }
+                    break;
+                }
+                case "%":
+                    if (!input.startsWith("%")) {
+                        throw invalid();
+                    }
+                    input = input.slice(1);
+                    break;
+                default:
+                    throw invalid();
+            }
+        }
+
+        if (input.length !== 0) {
+            throw invalid();
+        }
+        if (pm !== null && (hour < 1 || hour > 12)) {
+            throw invalid();
+        } else if (pm === true && hour < 12) {
+            hour += 12;
+        } else if (pm === false && hour === 12) {
+            hour = 0;
+        }
+        return fastn_stdlib.date(year, month, day, hour, minute, second, hasTime);
+    },
+
+    toIso(date) {
+        let pad = fastn_stdlib.pad;
+        let iso = `${pad(date.year, 4)}-${pad(date.month, 2)}-${pad(date.day, 2)}`;
+        if (date.hasTime) {
+            iso += `T${pad(date.hour, 2)}:${pad(date.minute, 2)}:${pad(date.second, 2)}`;
+        }
+        return iso;
+    },
+
+    // Formats with `strftime` like directives: `%Y`, `%y`, `%m`, `%d`, `%e`,
+    // `%H`, `%I`, `%M`, `%S`, `%p`, `%b`, `%B`, `%a`, `%A`, `%j` and `%%`.
+    // Names are in English.
+    format(date, format) {
+        let pad = fastn_stdlib.pad;
+        let month = fastn_stdlib.MONTHS[date.month - 1];
+        let days = fastn_stdlib.days(date);
+        let weekday = fastn_stdlib.WEEKDAYS[(((days + 4) % 7) + 7) % 7];
+        let result = "";
+        let directives = Array.from(format);
+        for (let i = 0; i < directives.length; i++) {
+            if (directives[i] !== "%") {
+                result += directives[i];
+                continue;
+            }
+            let directive = directives[++i];
+            switch (directive) {
+                case "Y": result += pad(date.year, 4); break;
+                case "y": result += pad(((date.year % 100) + 100) % 100, 2); break;
+                case "m": result += pad(date.month, 2); break;
+                case "d": result += pad(date.day, 2); break;
+                case "e": result += date.day; break;
+                case "H": result += pad(date.hour, 2); break;
+                case "I": result += pad((date.hour + 11) % 12 + 1, 2); break;
+                case "M": result += pad(date.minute, 2); break;
+                case "S": result += pad(date.second, 2); break;
+                case "p": result += date.hour < 12 ? "AM" : "PM"; break;
+                case "b": result += month.slice(0, 3); break;
+                case "B": result += month; break;
+                case "a": result += weekday.slice(0, 3); break;
+                case "A": result += weekday; break;
+                case "j":
+                    result += pad(days - fastn_stdlib.daysFromCivil(date.year, 1, 1) + 1, 3);
+                    break;
+                case "%": result += "%"; break;
+                case undefined: result += "%"; break;
+                default: result += "%" + directive;
+            }
+        }
+        return result;
+    },
+};
+
+// Strings
+fastn_stdlib.define("split", ["s", "sep"], (s, sep) => fastn.mutableList(
+    fastn_stdlib.string(sep) === "" ? Array.from(fastn_stdlib.string(s))
+        : fastn_stdlib.string(s).split(sep)
+));
+fastn_stdlib.define("join", ["items", "sep"], (items, sep) =>
+    fastn_stdlib.list(items).map(fastn_stdlib.display).join(fastn_stdlib.string(sep)));
+fastn_stdlib.define("replace", ["s", "from", "to"], (s, from, to) =>
+    fastn_stdlib.string(s).replaceAll(fastn_stdlib.string(from), () => fastn_stdlib.string(to)));
+fastn_stdlib.define("upper", ["s"], s => fastn_stdlib.string(s).toUpperCase());
+fastn_stdlib.define("lower", ["s"], s => fastn_stdlib.string(s).toLowerCase());
+fastn_stdlib.define("trim", ["s"], s => fastn_stdlib.string(s).trim());
+fastn_stdlib.define("contains", ["value", "part"], (value, part) =>
+    typeof value === "string" ? value.includes(fastn_stdlib.string(part))
+        : fastn_stdlib.list(value).some(item => fastn_stdlib.sameValue(item, part)));
+fastn_stdlib.define("starts_with", ["s", "prefix"], (s, prefix) =>
+    fastn_stdlib.string(s).startsWith(fastn_stdlib.string(prefix)));
+fastn_stdlib.define("ends_with", ["s", "suffix"], (s, suffix) =>
+    fastn_stdlib.string(s).endsWith(fastn_stdlib.string(suffix)));
+
+// Lists
+fastn_stdlib.define("slice", ["value", "start", "end"], (value, start, end) =>
+    typeof value === "string" ? fastn_stdlib.slice(Array.from(value), start, end).join("")
+        : fastn.mutableList(fastn_stdlib.slice(fastn_stdlib.list(value), start, end)));
+fastn_stdlib.define("reverse", ["items"], items =>
+    fastn.mutableList([...fastn_stdlib.list(items)].reverse()));
+fastn_stdlib.define("sort", ["items"], items =>
+    fastn_stdlib.sort(fastn_stdlib.list(items)));
+fastn_stdlib.define("sort_by", ["items", "field"], (items, field) =>
+    fastn_stdlib.sort(fastn_stdlib.list(items), fastn_stdlib.string(field)));
+fastn_stdlib.define("pluck", ["items", "field"], (items, field) => fastn.mutableList(
+    fastn_stdlib.list(items).map(item => fastn_stdlib.field(item, fastn_stdlib.string(field)))
+));
+fastn_stdlib.define("filter_by", ["items", "field", "value"], (items, field, value) =>
+    fastn.mutableList(fastn_stdlib.list(items).filter(item => fastn_stdlib.sameValue(
+        fastn_stdlib.field(item, fastn_stdlib.string(field)), value,
+    ))));
+
+// Numbers
+fastn_stdlib.define("format_decimal", ["value", "digits"], (value, digits) =>
+    fastn_stdlib.formatNumber(value, fastn_stdlib.digits(digits), "en", false));
+fastn_stdlib.define("format_number", ["value", "digits", "locale"], (value, digits, locale) =>
+    fastn_stdlib.formatNumber(value, fastn_stdlib.digits(digits), fastn_stdlib.string(locale), true));
+fastn_stdlib.define("format_currency", ["value", "currency", "locale"], (value, currency, locale) =>
+    fastn_stdlib.formatCurrency(value, fastn_stdlib.string(currency), fastn_stdlib.string(locale)));
+
+// Dates
+fastn_stdlib.define("format_date", ["date", "format"], (date, format) =>
+    fastn_stdlib.format(fastn_stdlib.parseIso(date), fastn_stdlib.string(format)));
+fastn_stdlib.define("parse_date", ["value", "format"], (value, format) =>
+    fastn_stdlib.toIso(fastn_stdlib.parse(fastn_stdlib.string(value), fastn_stdlib.string(format))));
+fastn_stdlib.define("add_days", ["date", "days"], (date, days) => {
+    date = fastn_stdlib.parseIso(date);
+    [date.year, date.month, date.day] = fastn_stdlib.civilFromDays(fastn_stdlib.days(date) + days);
+    return fastn_stdlib.toIso(date);
+});
+fastn_stdlib.define("days_between", ["from", "to"], (from, to) =>
+    fastn_stdlib.days(fastn_stdlib.parseIso(to)) - fastn_stdlib.days(fastn_stdlib.parseIso(from)));
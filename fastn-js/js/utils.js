@@ -182,6 +182,43 @@ let fastn_utils = {
         }
         return value;
     },
+    // Member access and indexing in expressions, `a.b` and `a[i]`. Missing
+    // members and members of null are null.
+    getMember(value, key) {
+        value = fastn_utils.getter(value);
+        key = fastn_utils.getter(key);
+        if (fastn_utils.isNull(value) || fastn_utils.isNull(key)) {
+            return null;
+        }
+        let member;
+        if (value instanceof fastn.recordInstanceClass) {
+            member = value.get(typeof key === "string" ? key.replaceAll("-", "_") : key);
+        } else if (value instanceof fastn.mutableListClass) {
+            member = fastn_utils.getListItem(value.get(key));
+        } else {
+            member = value[key];
+        }
+        return member === undefined ? null : member;
+    },
+    // The items of a list for `for` loops in function bodies.
+    listItems(value) {
+        value = fastn_utils.getter(value);
+        if (value instanceof fastn.mutableListClass) {
+            return value.getList().map(item => item.item);
+        }
+        return fastn_utils.isNull(value) ? [] : value;
+    },
+    // Template strings in expressions, `Hello ${name}`. Null values are
+    // inserted as empty strings.
+    interpolate(parts) {
+        return parts.map(part => {
+            part = fastn_utils.getter(part);
+            if (part instanceof fastn.mutableListClass) {
+                part = part.getList().map(item => fastn_utils.getter(item.item));
+            }
+            return fastn_utils.isNull(part) ? "" : String(part);
+        }).join("");
+    },
     getEventKey(event) {
         if (65 <= event.keyCode && event.keyCode <= 90) {
             return String.fromCharCode(event.keyCode).toLowerCase();
@@ -281,6 +318,10 @@ let fastn_utils = {
     addCodeTheme(theme) {
         if (!fastn_dom.codeData.addedCssFile.includes(theme)) {
             let themeCssUrl = fastn_dom.codeData.availableThemes[theme];
+            // themes shipped by a package are highlighted on the server, there is no css file
+            if (fastn_utils.isNull(themeCssUrl)) {
+                return;
+            }
             fastn_utils.addCssFile(themeCssUrl);
             fastn_dom.codeData.addedCssFile.push(theme);
         }
@@ -346,14 +387,184 @@ let fastn_utils = {
         }
     },
     highlightCode(codeElement, extraCodeData) {
-        if (!ssr && !fastn_utils.isNull(extraCodeData.language) && !fastn_utils.isNull(extraCodeData.theme)) {
+        if (ssr) {
+            return;
+        }
+        let decorate = fastn_utils.private.hasCodeDecorations(extraCodeData);
+        // the element whose lines get decorated
+        let linesElement = codeElement;
+        if (extraCodeData.highlighted) {
+            // highlighted on the server, start over from its html which is wrapped in a `<pre>`
+            codeElement.innerHTML = extraCodeData.highlighted;
+            linesElement = codeElement.firstElementChild || codeElement;
+        } else if ((decorate || extraCodeData.decorated) && !fastn_utils.isNull(extraCodeData.text)) {
+            // Start over from the plain code, the previous decorations and the diff markers
+            // must not end up in the code prism highlights.
+            codeElement.textContent = extraCodeData.diff
+                ? fastn_utils.private.codeDiff(extraCodeData.text).text
+                : extraCodeData.text;
+        }
+        // code highlighted on the server must not be highlighted again by prism
+        if (!extraCodeData.highlighted
+            && !fastn_utils.isNull(extraCodeData.language)
+            && !fastn_utils.isNull(extraCodeData.theme)) {
             Prism.highlightElement(codeElement);
         }
+        if (decorate && !fastn_utils.isNull(extraCodeData.text)) {
+            fastn_utils.private.decorateCode(linesElement, extraCodeData);
+        }
+        extraCodeData.decorated = decorate;
     }
 }
 
 
 fastn_utils.private = {
+    codeColors: {
+        highlighted: "hsla(24, 20%, 50%, 0.16)",
+        added: "rgba(46, 160, 67, 0.2)",
+        removed: "rgba(248, 81, 73, 0.2)",
+    },
+    /**
+     * Turns the line numbers of an `integer list` into ranges of consecutive lines,
+     * `[1, 3, 4, 5, 8]` becomes `[[1, 1], [3, 5], [8, 8]]`.
+     */
+    lineRanges(value) {
+        if (fastn_utils.isNull(value)) {
+            return [];
+        }
+        let numbers = value
+            .map(obj => fastn_utils.getStaticValue(obj.item))
+            .filter(number => Number.isInteger(number) && number > 0)
+            .sort((a, b) => a - b);
+        let ranges = [];
+        numbers.forEach(number => {
+            let last = ranges[ranges.length - 1];
+            if (last && number <= last[1] + 1) {
+                last[1] = Math.max(last[1], number);
+            } else {
+                ranges.push([number, number]);
+            }
+        });
+        return ranges;
+    },
+    hasCodeDecorations(extraCodeData) {
+        return !!extraCodeData.diff
+            || !!extraCodeData.copyButton
+            || (extraCodeData.highlightLines || []).length !== 0
+            || (extraCodeData.collapse || []).length !== 0;
+    },
+    /**
+     * Strips the `+` and `-` markers of a diff, and the space before the unchanged lines,
+     * returns the code without them and the marker (`+`, `-` or ` `) of every line.
+     */
+    codeDiff(text) {
+        let markers = [];
+        let lines = text.split("\n").map(line => {
+            if (line.startsWith("+") || line.startsWith("-") || line.startsWith(" ")) {
+                markers.push(line[0]);
+                return line.substring(1);
+            }
+            markers.push(" ");
+            return line;
+        });
+        return { markers, text: lines.join("\n") };
+    },
+    escapeHtml(text) {
+        return text.replace(/&/g, "&amp;")
+            .replace(/</g, "&lt;")
+            .replace(/>/g, "&gt;")
+            .replace(/"/g, "&quot;");
+    },
+    /**
+     * Splits the (highlighted) html of the code element into lines. Elements spanning more
+     * than one line, like prism tokens of multi line comments, are closed at the end of the
+     * line and re-opened on the next one.
+     */
+    codeLines(codeElement) {
+        let lines = [""];
+        let open = [];
+        let walk = (node) => {
+            if (node.nodeType === 3) {
+                node.textContent.split("\n").forEach((part, index) => {
+                    if (index > 0) {
+                        lines[lines.length - 1] += open.slice().reverse().map(v => v.end).join("");
+                        lines.push(open.map(v => v.start).join(""));
+                    }
+                    lines[lines.length - 1] += fastn_utils.private.escapeHtml(part);
+                });
+            } else if (node.nodeType === 1) {
+                let start = node.outerHTML.substring(0, node.outerHTML.indexOf(">") + 1);
+                let end = `</${node.tagName.toLowerCase()}>`;
+                open.push({ start, end });
+                lines[lines.length - 1] += start;
+                node.childNodes.forEach(walk);
+                open.pop();
+                lines[lines.length - 1] += end;
+            }
+        };
+        codeElement.childNodes.forEach(walk);
+        if (lines.length > 1 && lines[lines.length - 1] === "") {
+            lines.pop();
+        }
+        return lines;
+    },
+    /**
+     * Wraps the lines of the code element to show the highlighted lines, the diff gutter,
+     * the collapsed regions and the copy button of `ftd.code`.
+     */
+    decorateCode(codeElement, extraCodeData) {
+        let colors = fastn_utils.private.codeColors;
+        let diff = extraCodeData.diff ? fastn_utils.private.codeDiff(extraCodeData.text) : null;
+        let highlightLines = extraCodeData.highlightLines || [];
+        let collapse = extraCodeData.collapse || [];
+        let lines = fastn_utils.private.codeLines(codeElement);
+        let html = "";
+
+        if (extraCodeData.copyButton) {
+            let code = diff
+                ? diff.text.split("\n").filter((_, index) => diff.markers[index] !== "-").join("\n")
+                : extraCodeData.text;
+            html += `<button data-code="${fastn_utils.private.escapeHtml(code.trimEnd())}" onclick="navigator.clipboard.writeText(this.dataset.code)" style="float: right; cursor: pointer; font-size: 0.8em; opacity: 0.7">Copy</button>`;
+        }
+
+        let collapseEnd = null;
+        lines.forEach((line, index) => {
+            let number = index + 1;
+            if (collapseEnd === null) {
+                let range = collapse.find(([start]) => start === number);
+                if (range) {
+                    let count = Math.min(range[1], lines.length) - number + 1;
+                    html += `<details style="display: block"><summary style="cursor: pointer; opacity: 0.6; user-select: none">${count} line${count === 1 ? "" : "s"}</summary>`;
+                    collapseEnd = range[1];
+                }
+            }
+
+            let marker = diff ? diff.markers[index] : " ";
+            let background = highlightLines.some(([start, end]) => start <= number && number <= end)
+                ? colors.highlighted
+                : marker === "+" ? colors.added : marker === "-" ? colors.removed : null;
+            if (background) {
+                html += `<span style="background-color: ${background}; display: block; margin: 0 -1em; padding: 0 1em">`;
+            }
+            if (diff) {
+                html += `<span style="user-select: none; opacity: 0.6; padding-right: 0.5em">${marker}</span>`;
+            }
+            html += line + "\n";
+            if (background) {
+                html += "</span>";
+            }
+
+            if (collapseEnd === number) {
+                html += "</details>";
+                collapseEnd = null;
+            }
+        });
+        if (collapseEnd !== null) {
+            html += "</details>";
+        }
+
+        codeElement.innerHTML = html;
+    },
     /**
      * Helper function for `fastn_utils.markdown_inline` to find the number of
      * spaces before and after the content.
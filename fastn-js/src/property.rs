@@ -300,6 +300,7 @@ impl Value {
 pub enum PropertyKind {
     Children,
     StringValue,
+    MarkdownHtml,
     IntegerValue,
     DecimalValue,
     BooleanValue,
@@ -396,6 +397,11 @@ pub enum PropertyKind {
     CodeTheme,
     CodeLanguage,
     CodeShowLineNumber,
+    CodeHighlighted,
+    CodeHighlightLines,
+    CodeDiff,
+    CodeCollapse,
+    CodeCopyButton,
     MetaTitle,
     MetaOGTitle,
     MetaTwitterTitle,
@@ -418,6 +424,7 @@ impl PropertyKind {
             PropertyKind::AlignSelf => "fastn_dom.PropertyKind.AlignSelf",
             PropertyKind::Anchor => "fastn_dom.PropertyKind.Anchor",
             PropertyKind::StringValue => "fastn_dom.PropertyKind.StringValue",
+            PropertyKind::MarkdownHtml => "fastn_dom.PropertyKind.MarkdownHtml",
             PropertyKind::IntegerValue => "fastn_dom.PropertyKind.IntegerValue",
             PropertyKind::DecimalValue => "fastn_dom.PropertyKind.DecimalValue",
             PropertyKind::BooleanValue => "fastn_dom.PropertyKind.BooleanValue",
@@ -510,6 +517,11 @@ impl PropertyKind {
             PropertyKind::CodeTheme => "fastn_dom.PropertyKind.CodeTheme",
             PropertyKind::CodeShowLineNumber => "fastn_dom.PropertyKind.CodeShowLineNumber",
             PropertyKind::CodeLanguage => "fastn_dom.PropertyKind.CodeLanguage",
+            PropertyKind::CodeHighlighted => "fastn_dom.PropertyKind.CodeHighlighted",
+            PropertyKind::CodeHighlightLines => "fastn_dom.PropertyKind.CodeHighlightLines",
+            PropertyKind::CodeDiff => "fastn_dom.PropertyKind.CodeDiff",
+            PropertyKind::CodeCollapse => "fastn_dom.PropertyKind.CodeCollapse",
+            PropertyKind::CodeCopyButton => "fastn_dom.PropertyKind.CodeCopyButton",
             PropertyKind::MetaTitle => "fastn_dom.PropertyKind.DocumentProperties.MetaTitle",
             PropertyKind::MetaOGTitle => "fastn_dom.PropertyKind.DocumentProperties.MetaOGTitle",
             PropertyKind::MetaTwitterTitle => {
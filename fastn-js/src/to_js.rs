@@ -679,25 +679,21 @@ impl fastn_js::UDF {
                     .append(text("};"))
             })
             .append(pretty::RcDoc::intersperse(
-                self.body.iter().map(|f| {
-                    pretty::RcDoc::text(
-                        fastn_js::to_js::ExpressionGenerator.to_js_(
-                            f,
-                            true,
-                            self.args
-                                .iter()
-                                .map(|v| {
-                                    (
-                                        v.0.to_string(),
-                                        Some(fastn_js::LOCAL_VARIABLE_MAP.to_string()),
-                                    )
-                                })
-                                .collect_vec()
-                                .as_slice(),
-                            false,
-                        ),
-                    )
-                }),
+                udf_statements_to_js(
+                    &self.body,
+                    true,
+                    self.args
+                        .iter()
+                        .map(|v| {
+                            (
+                                v.0.to_string(),
+                                Some(fastn_js::LOCAL_VARIABLE_MAP.to_string()),
+                            )
+                        })
+                        .collect_vec(),
+                )
+                .into_iter()
+                .map(pretty::RcDoc::text),
                 pretty::RcDoc::softline(),
             ));
 
@@ -705,6 +701,95 @@ impl fastn_js::UDF {
     }
 }
 
+/// Lowers the statements of a function body. The last expression of the body is returned, and
+/// `let` and `for` bindings are visible until the end of the enclosing block, as in JS.
+fn udf_statements_to_js(
+    statements: &[fastn_grammar::evalexpr::Statement],
+    root: bool,
+    mut arguments: Vec<(String, Option<String>)>,
+) -> Vec<String> {
+    use fastn_grammar::evalexpr::Statement;
+
+    let generator = fastn_js::to_js::ExpressionGenerator;
+    let block = |statements: &[Statement], arguments: &[(String, Option<String>)]| {
+        udf_statements_to_js(statements, false, arguments.to_vec()).join("\n")
+    };
+    let mut result = vec![];
+    for (index, statement) in statements.iter().enumerate() {
+        let js = match statement {
+            Statement::Expression(node) if root && index == statements.len() - 1 => {
+                generator.to_js_(node, true, arguments.as_slice(), false)
+            }
+            Statement::Expression(node) => {
+                let js = generator.to_js_(node, false, arguments.as_slice(), false);
+                if js.is_empty() || js.ends_with(';') || js.ends_with('}') {
+                    js
+                } else {
+                    format!("{};", js)
+                }
+            }
+            Statement::Let { name, value, .. } => {
+                let js = format!(
+                    "let {} = {};",
+                    name,
+                    generator.to_js_(value, false, arguments.as_slice(), false)
+                );
+                // the local shadows an argument or an outer local of the same name
+                arguments.retain(|(argument, _)| argument != name);
+                arguments.push((name.to_string(), None));
+                js
+            }
+            Statement::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                let mut js = format!(
+                    "if ({}) {{\n{}\n}}",
+                    generator.to_js_(condition, false, arguments.as_slice(), false),
+                    block(then, arguments.as_slice())
+                );
+                match otherwise.as_slice() {
+                    [] => {}
+                    [Statement::If { .. }] => {
+                        js = format!("{} else {}", js, block(otherwise, arguments.as_slice()))
+                    }
+                    _ => {
+                        js = format!(
+                            "{} else {{\n{}\n}}",
+                            js,
+                            block(otherwise, arguments.as_slice())
+                        )
+                    }
+                }
+                js
+            }
+            Statement::For {
+                item, list, body, ..
+            } => {
+                let mut body_arguments = arguments.clone();
+                body_arguments.retain(|(argument, _)| argument != item);
+                body_arguments.push((item.to_string(), None));
+                format!(
+                    "for (let {} of fastn_utils.listItems({})) {{\n{}\n}}",
+                    item,
+                    generator.to_js_(list, false, arguments.as_slice(), true),
+                    block(body, body_arguments.as_slice())
+                )
+            }
+            Statement::Return {
+                value: Some(value), ..
+            } => format!(
+                "return {};",
+                generator.to_js_(value, false, arguments.as_slice(), false)
+            ),
+            Statement::Return { value: None, .. } => "return;".to_string(),
+        };
+        result.push(js);
+    }
+    result
+}
+
 /*fn binary(op: &str, left: &UDFStatement, right: &UDFStatement) -> pretty::RcDoc<'static> {
     left.to_js()
         .append(space())
@@ -895,6 +980,17 @@ impl ExpressionGenerator {
                     self.to_js_(second, false, arguments, false),
                 ]
                 .join("");
+            } else if arguments
+                .iter()
+                .any(|v| first.to_string().eq(&v.0) && v.1.is_none())
+            {
+                // Locals bound with `let` or `for` hold plain values
+                return [
+                    self.to_js_(first, false, arguments, false),
+                    node.operator().to_string(),
+                    self.to_js_(second, false, arguments, false),
+                ]
+                .join("");
             } else if first.operator().get_variable_identifier_write().is_some() {
                 let var = self.to_js_(first, false, arguments, false);
                 let val = self.to_js_(second, false, arguments, true);
@@ -918,6 +1014,14 @@ impl ExpressionGenerator {
             .join("");
         }
 
+        if let Some(value) = self.access_or_conditional(node, arguments) {
+            return if no_getter || !self.is_access(node.operator()) {
+                value
+            } else {
+                format!("fastn_utils.getter({})", value)
+            };
+        }
+
         if let Some(mut operator) = self.has_operator(node.operator()) {
             // Todo: if node.children().len() != 2 {throw error}
             let first = node.children().first().unwrap(); //todo remove unwrap()
@@ -947,6 +1051,14 @@ impl ExpressionGenerator {
             return format!("{}{}", operator.trim(), result.join(" "));
         }
 
+        if let Some(value) = self.argument_member(node.operator(), arguments) {
+            return if no_getter {
+                value
+            } else {
+                format!("fastn_utils.getter({})", value)
+            };
+        }
+
         let value = if self.is_null(node.operator()) {
             "null".to_string()
         } else {
@@ -973,6 +1085,89 @@ impl ExpressionGenerator {
         }
     }
 
+    /// Converts template strings, member access, indexing, `?:` and `??`.
+    fn access_or_conditional(
+        &self,
+        node: &fastn_grammar::evalexpr::ExprNode,
+        arguments: &[(String, Option<String>)],
+    ) -> Option<String> {
+        use fastn_grammar::evalexpr::Operator;
+        use itertools::Itertools;
+
+        let children = node.children();
+        let child = |index: usize| {
+            children
+                .get(index)
+                .map(|child| self.to_js_(child, false, arguments, false))
+                .unwrap_or_else(|| "null".to_string())
+        };
+        Some(match node.operator() {
+            Operator::Template => {
+                let parts = children
+                    .iter()
+                    .map(|child| match child.operator() {
+                        Operator::Const {
+                            value: fastn_grammar::evalexpr::Value::String(text),
+                        } => format!("{:?}", text),
+                        _ => self.to_js_(child, false, arguments, false),
+                    })
+                    .join(", ");
+                format!("fastn_utils.interpolate([{}])", parts)
+            }
+            Operator::Index => format!("fastn_utils.getMember({}, {})", child(0), child(1)),
+            Operator::Member { field } => {
+                format!("fastn_utils.getMember({}, {:?})", child(0), field)
+            }
+            Operator::Else => match children
+                .first()
+                .map(|first| (first.operator(), first.children()))
+            {
+                Some((Operator::Conditional, [condition, value])) => format!(
+                    "({} ? {} : {})",
+                    self.to_js_(condition, false, arguments, false),
+                    self.to_js_(value, false, arguments, false),
+                    child(1)
+                ),
+                _ => format!("({} ?? {})", child(0), child(1)),
+            },
+            Operator::Conditional => format!("({} ? {} : null)", child(0), child(1)),
+            Operator::Coalesce => format!("({} ?? {})", child(0), child(1)),
+            _ => return None,
+        })
+    }
+
+    /// Converts a read of a field of an argument, like `person.name`, into member access on
+    /// the argument.
+    fn argument_member(
+        &self,
+        operator: &fastn_grammar::evalexpr::Operator,
+        arguments: &[(String, Option<String>)],
+    ) -> Option<String> {
+        let identifier = operator.get_variable_identifier_read()?;
+        arguments.iter().find_map(|(name, prefix)| {
+            let fields = identifier.strip_prefix(format!("{}.", name).as_str())?;
+            let argument = format!(
+                "{}{}",
+                prefix
+                    .as_ref()
+                    .map(|v| format!("{}.", v))
+                    .unwrap_or_default(),
+                name
+            );
+            Some(fields.split('.').fold(argument, |value, field| {
+                format!("fastn_utils.getMember({}, {:?})", value, field)
+            }))
+        })
+    }
+
+    fn is_access(&self, operator: &fastn_grammar::evalexpr::Operator) -> bool {
+        matches!(
+            operator,
+            fastn_grammar::evalexpr::Operator::Index
+                | fastn_grammar::evalexpr::Operator::Member { .. }
+        )
+    }
+
     pub fn has_value(&self, operator: &fastn_grammar::evalexpr::Operator) -> Option<String> {
         match operator {
             fastn_grammar::evalexpr::Operator::Const { .. }
@@ -1216,4 +1411,178 @@ mod tests {
         // )
         // .unwrap();
     }
+
+    /// Evaluates each expression with the interpreter and with the JS generated for it, and
+    /// checks that both give the same value.
+    #[test]
+    fn expressions_match_interpreter() {
+        use fastn_grammar::evalexpr::{ContextWithMutableVariables, Value};
+
+        fn plain(value: &Value) -> String {
+            match value {
+                Value::Empty => "null".to_string(),
+                Value::String(string) => format!("{:?}", string),
+                Value::Tuple(tuple) => format!(
+                    "[{}]",
+                    tuple.iter().map(plain).collect::<Vec<_>>().join(",")
+                ),
+                value => value.to_string(),
+            }
+        }
+
+        let expressions = [
+            // `?:`
+            "age > 18 ? \"adult\" : \"minor\"",
+            "age < 18 ? \"minor\" : age < 65 ? \"adult\" : \"senior\"",
+            "age < 18 ? \"minor\"",
+            // `??` replaces null, but not the empty string or zero
+            "nothing ?? \"default\"",
+            "person.nick ?? person.name",
+            "empty ?? \"default\"",
+            "zero ?? 1",
+            "person.name ?? \"default\"",
+            // `a.b`
+            "person.name",
+            "person.address.city",
+            "person.missing",
+            "person.missing.city",
+            "nothing.name",
+            // `a[i]`
+            "names[0]",
+            "names[1]",
+            "names[5]",
+            "person[\"name\"]",
+            "person[\"missing\"]",
+            "word[1]",
+            "word[10]",
+            "nothing[0]",
+            "names[age - 29]",
+            // template strings
+            "`Hi ${person.name}, you are ${age}`",
+            "`${person.nick}!`",
+            "`${names}`",
+            "`${age > 18 ? \"adult\" : \"minor\"} ${person.address.city ?? \"nowhere\"}`",
+        ];
+
+        let mut context = fastn_grammar::evalexpr::HashMapContext::new();
+        let record = |fields: Vec<(&str, Value)>| {
+            Value::Record(
+                fields
+                    .into_iter()
+                    .map(|(k, v)| (k.to_string(), v))
+                    .collect(),
+            )
+        };
+        let person = record(vec![
+            ("name", Value::String("Ann".to_string())),
+            ("nick", Value::Empty),
+            (
+                "address",
+                record(vec![("city", Value::String("Pune".to_string()))]),
+            ),
+        ]);
+        for (name, value) in [
+            ("person", person),
+            ("age", Value::Int(30)),
+            ("zero", Value::Int(0)),
+            ("nothing", Value::Empty),
+            ("empty", Value::String("".to_string())),
+            ("word", Value::String("hey".to_string())),
+            (
+                "names",
+                Value::Tuple(vec![
+                    Value::String("a".to_string()),
+                    Value::String("b".to_string()),
+                ]),
+            ),
+        ] {
+            context.set_value(name.to_string(), value).unwrap();
+        }
+        let arguments = ["person", "age", "zero", "nothing", "empty", "word", "names"]
+            .map(|name| (name.to_string(), None));
+
+        let mut expected = vec![];
+        let mut js = vec![];
+        for expression in expressions {
+            let node = fastn_grammar::evalexpr::build_operator_tree(expression).unwrap();
+            expected.push(plain(&node.eval_with_context_mut(&mut context).unwrap()));
+            js.push(fastn_js::to_js::ExpressionGenerator.to_js_(
+                &node,
+                false,
+                arguments.as_slice(),
+                false,
+            ));
+        }
+
+        let output = fastn_js::ssr_str(&format!(
+            indoc::indoc!(
+                r#"
+                (function () {{
+                    let person = fastn.recordInstance({{
+                        name: "Ann",
+                        nick: null,
+                        address: fastn.recordInstance({{city: "Pune"}}),
+                    }});
+                    let age = fastn.mutable(30);
+                    let zero = 0;
+                    let nothing = null;
+                    let empty = "";
+                    let word = "hey";
+                    let names = fastn.mutableList(["a", "b"]);
+                    let plain = function (value) {{
+                        value = fastn_utils.getter(value);
+                        if (value instanceof fastn.mutableListClass) {{
+                            return value.getList().map(item => plain(item.item));
+                        }}
+                        return value === undefined ? null : value;
+                    }};
+                    return JSON.stringify([{}].map(plain));
+                }})()
+                "#
+            ),
+            js.join(",\n")
+        ));
+        let output = output
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_string();
+        assert_eq!(
+            expressions
+                .iter()
+                .zip(expected.iter())
+                .map(|(expression, value)| format!("{}: {}", expression, value))
+                .collect::<Vec<_>>(),
+            expressions
+                .iter()
+                .zip(split_values(output.as_str()))
+                .map(|(expression, value)| format!("{}: {}", expression, value))
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    /// Splits a JSON array body into its top level values.
+    fn split_values(json: &str) -> Vec<String> {
+        let mut values = vec![];
+        let mut current = String::new();
+        let mut depth = 0;
+        let mut in_string = false;
+        let mut escaped = false;
+        for c in json.chars() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' if in_string => escaped = true,
+                '"' => in_string = !in_string,
+                '[' if !in_string => depth += 1,
+                ']' if !in_string => depth -= 1,
+                ',' if !in_string && depth == 0 => {
+                    values.push(std::mem::take(&mut current));
+                    continue;
+                }
+                _ => {}
+            }
+            current.push(c);
+        }
+        values.push(current);
+        values
+    }
 }
@@ -3,10 +3,10 @@ pub struct UDF {
     pub name: String,
     pub params: Vec<String>,
     pub args: Vec<(String, fastn_js::SetPropertyValue)>,
-    pub body: Vec<fastn_grammar::evalexpr::ExprNode>,
+    pub body: Vec<fastn_grammar::evalexpr::Statement>,
 }
 
-pub fn udf0(name: &str, body: Vec<fastn_grammar::evalexpr::ExprNode>) -> fastn_js::Ast {
+pub fn udf0(name: &str, body: Vec<fastn_grammar::evalexpr::Statement>) -> fastn_js::Ast {
     fastn_js::Ast::UDF(UDF {
         name: name.to_string(),
         params: vec!["args".to_string()],
@@ -17,7 +17,7 @@ pub fn udf0(name: &str, body: Vec<fastn_grammar::evalexpr::ExprNode>) -> fastn_j
 
 pub fn udf_with_arguments(
     name: &str,
-    body: Vec<fastn_grammar::evalexpr::ExprNode>,
+    body: Vec<fastn_grammar::evalexpr::Statement>,
     args: Vec<(String, fastn_js::SetPropertyValue)>,
 ) -> fastn_js::Ast {
     use itertools::Itertools;
@@ -33,7 +33,11 @@ pub fn udf_with_arguments(
     })
 }
 
-pub fn udf1(name: &str, arg1: &str, body: Vec<fastn_grammar::evalexpr::ExprNode>) -> fastn_js::Ast {
+pub fn udf1(
+    name: &str,
+    arg1: &str,
+    body: Vec<fastn_grammar::evalexpr::Statement>,
+) -> fastn_js::Ast {
     fastn_js::Ast::UDF(UDF {
         name: name.to_string(),
         params: vec![arg1.to_string()],
@@ -46,7 +50,7 @@ pub fn udf2(
     name: &str,
     arg1: &str,
     arg2: &str,
-    body: Vec<fastn_grammar::evalexpr::ExprNode>,
+    body: Vec<fastn_grammar::evalexpr::Statement>,
 ) -> fastn_js::Ast {
     fastn_js::Ast::UDF(UDF {
         name: name.to_string(),
@@ -0,0 +1,84 @@
+// https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU
+
+/// Flattens a span tree into Chrome Trace Event objects: a complete (`"ph": "X"`) event for
+/// every span and an instant (`"ph": "i"`) event for every event.
+pub fn events(span: &fastn_observer::Span, pid: u32, tid: u64) -> Vec<serde_json::Value> {
+    let mut events = vec![];
+    span_events(span, pid, tid, &mut events);
+    events
+}
+
+fn span_events(
+    span: &fastn_observer::Span,
+    pid: u32,
+    tid: u64,
+    events: &mut Vec<serde_json::Value>,
+) {
+    let start = fastn_observer::export::unix_nanos(span.started_at);
+
+    events.push(serde_json::json!({
+        "name": span.name,
+        "cat": "fastn",
+        "ph": "X",
+        "ts": micros(start),
+        "dur": micros(span.duration.as_nanos()),
+        "pid": pid,
+        "tid": tid,
+        "args": args(&span.shared),
+    }));
+
+    for node in span.nodes.iter() {
+        match node {
+            fastn_observer::Tree::Span(span) => span_events(span, pid, tid, events),
+            fastn_observer::Tree::Event(event) => events.push(serde_json::json!({
+                "name": event.message.as_deref().unwrap_or("event"),
+                "cat": "fastn",
+                "ph": "i",
+                "s": "t",
+                "ts": micros(start + event.shared.on.as_nanos()),
+                "pid": pid,
+                "tid": tid,
+                "args": args(&event.shared),
+            })),
+        }
+    }
+}
+
+fn args(shared: &fastn_observer::Shared) -> serde_json::Value {
+    let mut args = serde_json::Map::new();
+    args.insert("level".to_string(), shared.level.as_str().into());
+    for field in shared.fields.iter() {
+        args.insert(field.key().to_string(), field.value().into());
+    }
+    serde_json::Value::Object(args)
+}
+
+fn micros(nanos: u128) -> f64 {
+    nanos as f64 / 1000.0
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn events() {
+        let events = fastn_observer::chrome::events(&fastn_observer::tree::sample(), 7, 1);
+        assert_eq!(
+            events,
+            vec![
+                serde_json::json!({
+                    "name": "request", "cat": "fastn", "ph": "X", "ts": 1_000_000.0,
+                    "dur": 10_000.0, "pid": 7, "tid": 1,
+                    "args": { "level": "INFO", "path": "/" },
+                }),
+                serde_json::json!({
+                    "name": "render", "cat": "fastn", "ph": "X", "ts": 1_002_000.0,
+                    "dur": 3_000.0, "pid": 7, "tid": 1, "args": { "level": "INFO" },
+                }),
+                serde_json::json!({
+                    "name": "done", "cat": "fastn", "ph": "i", "s": "t", "ts": 1_008_000.0,
+                    "pid": 7, "tid": 1, "args": { "level": "INFO" },
+                }),
+            ]
+        );
+    }
+}
@@ -0,0 +1,182 @@
+pub const TRACE_OUTPUT_ENV: &str = "FASTN_TRACE_OUTPUT";
+pub const TRACE_FORMAT_ENV: &str = "FASTN_TRACE_FORMAT";
+
+/// The file format used when writing span trees to disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Chrome Trace Event JSON (array format), can be opened in `chrome://tracing` or
+    /// https://ui.perfetto.dev/.
+    Chrome,
+    /// OTLP JSON, one `ExportTraceServiceRequest` per line, the same layout the
+    /// OpenTelemetry collector file exporter uses.
+    Otlp,
+}
+
+impl std::str::FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "chrome" | "perfetto" => Ok(Format::Chrome),
+            "otlp" | "opentelemetry" | "otel" => Ok(Format::Otlp),
+            t => Err(format!(
+                "unknown trace format: {}, expected `chrome` or `otlp`",
+                t
+            )),
+        }
+    }
+}
+
+/// Writes every finished root span tree to a file.
+///
+/// The exporter is selected with `--trace-output <file>` (or the `FASTN_TRACE_OUTPUT`
+/// env var), and the format with `--trace-format chrome|otlp` (or `FASTN_TRACE_FORMAT`),
+/// defaulting to Chrome.
+#[derive(Debug)]
+pub struct Exporter {
+    format: Format,
+    file: std::sync::Mutex<ExporterFile>,
+}
+
+#[derive(Debug)]
+struct ExporterFile {
+    writer: std::io::BufWriter<std::fs::File>,
+    /// number of chrome trace events written so far, used to place the separators
+    written: usize,
+    /// every root span gets its own thread id in the chrome trace, so concurrent requests
+    /// in `fastn serve` show up as separate tracks
+    next_tid: u64,
+}
+
+impl Exporter {
+    pub fn new(path: &std::path::Path, format: Format) -> std::io::Result<Exporter> {
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+
+        let mut file = ExporterFile {
+            writer: std::io::BufWriter::new(std::fs::File::create(path)?),
+            written: 0,
+            next_tid: 1,
+        };
+
+        if format == Format::Chrome {
+            // The closing `]` is optional in the Chrome Trace Event array format, so we never
+            // have to rewrite the file, even if fastn gets killed midway.
+            use std::io::Write;
+            file.writer.write_all(b"[\n")?;
+            file.writer.flush()?;
+        }
+
+        Ok(Exporter {
+            format,
+            file: std::sync::Mutex::new(file),
+        })
+    }
+
+    /// Reads `--trace-output`/`--trace-format` from the command line, falling back to
+    /// `FASTN_TRACE_OUTPUT`/`FASTN_TRACE_FORMAT`. Returns `None` if no output is requested.
+    pub fn from_env() -> Option<Exporter> {
+        let path = arg_value("--trace-output").or_else(|| std::env::var(TRACE_OUTPUT_ENV).ok())?;
+        let format = match arg_value("--trace-format")
+            .or_else(|| std::env::var(TRACE_FORMAT_ENV).ok())
+            .map(|v| v.parse::<Format>())
+            .transpose()
+        {
+            Ok(v) => v.unwrap_or(Format::Chrome),
+            Err(e) => {
+                eprintln!("{}", e);
+                return None;
+            }
+        };
+
+        match Exporter::new(std::path::Path::new(path.as_str()), format) {
+            Ok(v) => Some(v),
+            Err(e) => {
+                eprintln!("failed to create trace output file {}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    pub fn export(&self, span: &fastn_observer::Span) -> std::io::Result<()> {
+        use std::io::Write;
+
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        match self.format {
+            Format::Chrome => {
+                let tid = file.next_tid;
+                file.next_tid += 1;
+                for event in fastn_observer::chrome::events(span, std::process::id(), tid) {
+                    let separator: &[u8] = if file.written == 0 { b"" } else { b",\n" };
+                    file.writer.write_all(separator)?;
+                    serde_json::to_writer(&mut file.writer, &event)?;
+                    file.written += 1;
+                }
+            }
+            Format::Otlp => {
+                serde_json::to_writer(&mut file.writer, &fastn_observer::otlp::request(span))?;
+                file.writer.write_all(b"\n")?;
+            }
+        }
+        file.writer.flush()
+    }
+}
+
+/// Finds `--name value` or `--name=value` in the process arguments.
+fn arg_value(name: &str) -> Option<String> {
+    let mut args = std::env::args();
+    while let Some(arg) = args.next() {
+        if arg == name {
+            return args.next();
+        }
+        if let Some(v) = arg.strip_prefix(name).and_then(|v| v.strip_prefix('=')) {
+            return Some(v.to_string());
+        }
+    }
+    None
+}
+
+pub(crate) fn unix_nanos(time: std::time::SystemTime) -> u128 {
+    time.duration_since(std::time::UNIX_EPOCH)
+        .map(|v| v.as_nanos())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn format() {
+        use fastn_observer::Format;
+
+        assert_eq!("chrome".parse::<Format>(), Ok(Format::Chrome));
+        assert_eq!("Perfetto".parse::<Format>(), Ok(Format::Chrome));
+        assert_eq!("otel".parse::<Format>(), Ok(Format::Otlp));
+        assert!("jaeger".parse::<Format>().is_err());
+    }
+
+    #[test]
+    fn chrome_file() {
+        let path = std::env::temp_dir().join(format!("fastn-trace-{}.json", std::process::id()));
+        let exporter =
+            fastn_observer::Exporter::new(path.as_path(), fastn_observer::Format::Chrome).unwrap();
+        exporter.export(&fastn_observer::tree::sample()).unwrap();
+        exporter.export(&fastn_observer::tree::sample()).unwrap();
+
+        // the file is left without its closing `]`
+        let content = std::fs::read_to_string(path.as_path()).unwrap();
+        std::fs::remove_file(path).unwrap();
+        let events: Vec<serde_json::Value> =
+            serde_json::from_str(format!("{}]", content).as_str()).unwrap();
+        assert_eq!(events.len(), 6);
+        // every root span gets its own track
+        assert_eq!(events[0]["tid"], 1);
+        assert_eq!(events[3]["tid"], 2);
+    }
+}
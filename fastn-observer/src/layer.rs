@@ -6,7 +6,17 @@ pub const OPENED_SPAN_NOT_IN_EXTENSIONS: &str =
 pub const WRITING_URGENT_ERROR: &str = "writing_urgent failed, this is a bug";
 
 #[derive(Default)]
-pub struct Layer {}
+pub struct Layer {
+    exporter: Option<fastn_observer::Exporter>,
+}
+
+impl Layer {
+    /// Also write every finished root span tree using the given exporter.
+    pub fn with_exporter(mut self, exporter: Option<fastn_observer::Exporter>) -> Self {
+        self.exporter = exporter;
+        self
+    }
+}
 
 impl<S> tracing_subscriber::Layer<S> for Layer
 where
@@ -116,6 +126,11 @@ where
                 .expect(OPENED_SPAN_NOT_IN_EXTENSIONS)
                 .record_span(span),
             None => {
+                if let Some(ref exporter) = self.exporter {
+                    if let Err(e) = exporter.export(&span) {
+                        eprintln!("failed to export trace: {}", e);
+                    }
+                }
                 if fastn_observer::is_traced() {
                     println!(
                         "{}",
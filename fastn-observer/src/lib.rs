@@ -1,13 +1,17 @@
 extern crate self as fastn_observer;
 
+pub mod chrome;
 mod duration_display;
+mod export;
 mod field;
 mod formatter;
 mod layer;
 mod opened_span;
+pub mod otlp;
 mod tree;
 
 pub(crate) use duration_display::DurationDisplay;
+pub use export::{Exporter, Format, TRACE_FORMAT_ENV, TRACE_OUTPUT_ENV};
 pub use field::{Field, FieldSet};
 pub use formatter::write_immediate;
 pub use layer::Layer;
@@ -15,6 +19,15 @@ pub use opened_span::OpenedSpan;
 pub use tree::{Event, Shared, Span, Tree};
 
 pub fn observe() {
+    observe_with(tracing_subscriber::layer::Identity::new())
+}
+
+/// Like [`observe`], with an additional layer (e.g. one that collects metrics) installed
+/// alongside ours.
+pub fn observe_with<L>(layer: L)
+where
+    L: tracing_subscriber::Layer<tracing_subscriber::Registry> + std::fmt::Debug + Send + Sync,
+{
     use tracing_subscriber::layer::SubscriberExt;
 
     // let level = std::env::var("TRACING")
@@ -23,8 +36,9 @@ pub fn observe() {
     //     .unwrap_or(tracing_forest::util::LevelFilter::INFO);
 
     let s = tracing_subscriber::registry()
+        .with(layer)
         //.with(level)
-        .with(Layer::default());
+        .with(Layer::default().with_exporter(Exporter::from_env()));
     tracing::subscriber::set_global_default(s).unwrap();
 }
 
@@ -0,0 +1,130 @@
+// https://opentelemetry.io/docs/specs/otlp/#json-protobuf-encoding
+
+static NEXT_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+
+/// Builds an OTLP `ExportTraceServiceRequest` containing every span of the tree, all sharing
+/// one trace id.
+pub fn request(span: &fastn_observer::Span) -> serde_json::Value {
+    let trace_id = format!("{:016x}{:016x}", process_seed(), next_id());
+    let mut spans = vec![];
+    collect_spans(span, trace_id.as_str(), None, &mut spans);
+
+    serde_json::json!({
+        "resourceSpans": [{
+            "resource": {
+                "attributes": [
+                    attribute("service.name", "fastn"),
+                    attribute("process.pid", std::process::id().to_string().as_str()),
+                ]
+            },
+            "scopeSpans": [{
+                "scope": { "name": "fastn-observer" },
+                "spans": spans,
+            }]
+        }]
+    })
+}
+
+fn collect_spans(
+    span: &fastn_observer::Span,
+    trace_id: &str,
+    parent_span_id: Option<&str>,
+    spans: &mut Vec<serde_json::Value>,
+) {
+    let span_id = format!("{:016x}", next_id());
+    let start = fastn_observer::export::unix_nanos(span.started_at);
+    let mut events = vec![];
+
+    for node in span.nodes.iter() {
+        match node {
+            fastn_observer::Tree::Span(child) => {
+                collect_spans(child, trace_id, Some(span_id.as_str()), spans)
+            }
+            fastn_observer::Tree::Event(event) => events.push(serde_json::json!({
+                "timeUnixNano": (start + event.shared.on.as_nanos()).to_string(),
+                "name": event.message.as_deref().unwrap_or("event"),
+                "attributes": attributes(&event.shared),
+            })),
+        }
+    }
+
+    spans.push(serde_json::json!({
+        "traceId": trace_id,
+        "spanId": span_id,
+        "parentSpanId": parent_span_id.unwrap_or_default(),
+        "name": span.name,
+        // SPAN_KIND_INTERNAL
+        "kind": 1,
+        "startTimeUnixNano": start.to_string(),
+        "endTimeUnixNano": (start + span.duration.as_nanos()).to_string(),
+        "attributes": attributes(&span.shared),
+        "events": events,
+    }));
+}
+
+fn attributes(shared: &fastn_observer::Shared) -> Vec<serde_json::Value> {
+    std::iter::once(attribute("level", shared.level.as_str()))
+        .chain(
+            shared
+                .fields
+                .iter()
+                .map(|field| attribute(field.key(), field.value())),
+        )
+        .collect()
+}
+
+fn attribute(key: &str, value: &str) -> serde_json::Value {
+    serde_json::json!({ "key": key, "value": { "stringValue": value } })
+}
+
+fn next_id() -> u64 {
+    NEXT_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+}
+
+/// Ids only have to be unique, so instead of pulling in a random number generator we mix
+/// the process id with the time the process started exporting.
+fn process_seed() -> u64 {
+    static SEED: std::sync::OnceLock<u64> = std::sync::OnceLock::new();
+
+    *SEED.get_or_init(|| {
+        let nanos = fastn_observer::export::unix_nanos(std::time::SystemTime::now()) as u64;
+        nanos ^ ((std::process::id() as u64) << 32)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn request() {
+        let request = fastn_observer::otlp::request(&fastn_observer::tree::sample());
+        let spans = request["resourceSpans"][0]["scopeSpans"][0]["spans"]
+            .as_array()
+            .unwrap();
+        // children are pushed before their parent
+        assert_eq!(spans.len(), 2);
+        let (render, root) = (&spans[0], &spans[1]);
+        assert_eq!(root["name"], "request");
+        assert_eq!(root["parentSpanId"], "");
+        assert_eq!(render["parentSpanId"], root["spanId"]);
+        assert_eq!(render["traceId"], root["traceId"]);
+        assert_ne!(render["spanId"], root["spanId"]);
+        assert_eq!(root["startTimeUnixNano"], "1000000000");
+        assert_eq!(root["endTimeUnixNano"], "1010000000");
+        assert_eq!(render["startTimeUnixNano"], "1002000000");
+        assert_eq!(
+            root["attributes"],
+            serde_json::json!([
+                { "key": "level", "value": { "stringValue": "INFO" } },
+                { "key": "path", "value": { "stringValue": "/" } },
+            ])
+        );
+        assert_eq!(
+            root["events"],
+            serde_json::json!([{
+                "timeUnixNano": "1008000000",
+                "name": "done",
+                "attributes": [{ "key": "level", "value": { "stringValue": "INFO" } }],
+            }])
+        );
+    }
+}
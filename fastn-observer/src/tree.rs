@@ -16,6 +16,8 @@ pub struct Event {
 pub struct Span {
     pub(crate) shared: Shared,
     pub(crate) name: &'static str,
+    /// wall clock time at which this span was opened, used by the exporters
+    pub(crate) started_at: std::time::SystemTime,
     pub(crate) duration: std::time::Duration,
     pub(crate) nodes: Vec<Tree>,
 }
@@ -33,8 +35,43 @@ impl Span {
         Span {
             shared,
             name,
+            started_at: std::time::SystemTime::now(),
             duration: std::time::Duration::ZERO,
             nodes: Vec::new(),
         }
     }
 }
+
+/// `request` (path: /) opened a second after the epoch, lasting 10ms, with a `render` span
+/// from 2ms to 5ms and a `done` event at 8ms.
+#[cfg(test)]
+pub(crate) fn sample() -> Span {
+    let shared = |on: u64, fields: Vec<(&'static str, &str)>| Shared {
+        level: tracing::Level::INFO,
+        fields: fields
+            .into_iter()
+            .map(|(k, v)| fastn_observer::Field::new(k, v.to_string()))
+            .collect(),
+        on: std::time::Duration::from_millis(on),
+    };
+    let started_at = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1);
+    Span {
+        shared: shared(0, vec![("path", "/")]),
+        name: "request",
+        started_at,
+        duration: std::time::Duration::from_millis(10),
+        nodes: vec![
+            Tree::Span(Span {
+                shared: shared(2, vec![]),
+                name: "render",
+                started_at: started_at + std::time::Duration::from_millis(2),
+                duration: std::time::Duration::from_millis(3),
+                nodes: vec![],
+            }),
+            Tree::Event(Event {
+                shared: shared(8, vec![]),
+                message: Some("done".to_string()),
+            }),
+        ],
+    }
+}
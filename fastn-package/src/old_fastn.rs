@@ -70,6 +70,8 @@ pub struct BackendHeader {
 #[derive(serde::Deserialize, Debug, Clone)]
 pub struct PackageTemp {
     pub name: String,
+    /// the version this `FASTN.ftd` describes, see `fastn.release` for the older ones
+    pub version: Option<String>,
     pub versioned: bool,
     #[serde(rename = "translation-of")]
     pub translation_of: Option<String>,
@@ -95,4 +97,7 @@ pub struct PackageTemp {
     pub backend_headers: Option<Vec<BackendHeader>>,
     #[serde(rename = "icon")]
     pub icon: Option<ftd::ImageSrc>,
+    /// `fastn` (the default) or `git`
+    #[serde(rename = "history-backend")]
+    pub history_backend: Option<String>,
 }
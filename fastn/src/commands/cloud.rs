@@ -10,13 +10,66 @@ pub enum Error {
 pub enum PublishStaticError {
     #[error("PublishStaticUploadError: {}", _0)]
     Upload(#[from] fastn_cloud::UploadError),
+    #[error("PublishStaticPublishError: {}", _0)]
+    Publish(#[from] fastn_cloud::PublishError),
 }
 
-pub(crate) async fn handle() -> Result<bool, Error> {
-    Ok(handle_publish_static().await?)
+pub(crate) async fn handle(matches: &clap::ArgMatches) -> Result<bool, Error> {
+    Ok(handle_publish_static(matches).await?)
 }
 
-pub(crate) async fn handle_publish_static() -> Result<bool, PublishStaticError> {
-    fastn_cloud::upload().await?;
+pub(crate) async fn handle_publish_static(
+    matches: &clap::ArgMatches,
+) -> Result<bool, PublishStaticError> {
+    let published = fastn_cloud::publish(
+        matches.get_one::<String>("target").map(String::as_str),
+        matches.get_flag("full"),
+        matches.get_flag("dry-run"),
+    )
+    .await?;
+    print_published(&published);
     Ok(true)
 }
+
+fn print_published(published: &fastn_cloud::Published) {
+    let (name, changed, deleted, unchanged, dry_run) = match published {
+        fastn_cloud::Published::FastnCloud { dry_run: true } => {
+            println!("fastn cloud finds the files to upload itself, there is nothing to show");
+            return;
+        }
+        fastn_cloud::Published::FastnCloud { dry_run: false } => return,
+        fastn_cloud::Published::Target {
+            name,
+            changed,
+            deleted,
+            unchanged,
+            dry_run,
+        } => (name, changed, deleted, unchanged, *dry_run),
+    };
+    println!(
+        "{} `{}`: {} changed, {} deleted, {} unchanged",
+        if dry_run {
+            "Publishing to"
+        } else {
+            "Published to"
+        },
+        name,
+        changed.len(),
+        deleted.len(),
+        unchanged
+    );
+    let (upload, delete) = if dry_run {
+        ("upload", "delete")
+    } else {
+        ("uploaded", "deleted")
+    };
+    for path in changed {
+        println!("{}: {}", upload, path);
+    }
+    for path in deleted {
+        println!("{}: {}", delete, path);
+    }
+    if !dry_run {
+        println!("publish-static to `{}` done", name);
+    }
+}
@@ -1,6 +1,6 @@
 mod commands;
 pub fn main() {
-    fastn_observer::observe();
+    fastn_observer::observe_with(fastn_core::metrics::Layer::default());
 
     tokio::runtime::Builder::new_multi_thread()
         .enable_all()
@@ -35,7 +35,7 @@ async fn async_main() -> Result<(), Error> {
 
 async fn cloud_commands(matches: &clap::ArgMatches) -> Result<bool, commands::cloud::Error> {
     match matches.subcommand() {
-        Some((commands::cloud::PUBLISH_STATIC, _matches)) => commands::cloud::handle().await,
+        Some((commands::cloud::PUBLISH_STATIC, matches)) => commands::cloud::handle(matches).await,
         _ => Ok(false),
     }
 }
@@ -45,12 +45,42 @@ async fn fastn_core_commands(matches: &clap::ArgMatches) -> fastn_core::Result<(
     use fastn_core::utils::ValueOf;
 
     match matches.subcommand() {
+        Some((fastn_core::commands::check::COMMAND, matches)) => {
+            return fastn_core::commands::check::handle_command(matches).await;
+        }
+        Some((fastn_core::commands::fmt::COMMAND, matches)) => {
+            return fastn_core::commands::fmt::handle_command(matches).await;
+        }
+        Some((fastn_core::commands::lsp::COMMAND, matches)) => {
+            return fastn_core::commands::lsp::handle_command(matches).await;
+        }
+        Some((fastn_core::commands::tokens::COMMAND, matches)) => {
+            return fastn_core::commands::tokens::handle_command(matches).await;
+        }
+        Some((fastn_core::commands::translate::COMMAND, matches)) => {
+            return fastn_core::commands::translate::handle_command(matches).await;
+        }
         Some((fastn_core::commands::stop_tracking::COMMAND, matches)) => {
             return fastn_core::commands::stop_tracking::handle_command(matches).await;
         }
         Some((fastn_core::commands::sync_status::COMMAND, matches)) => {
             return fastn_core::commands::sync_status::handle_command(matches).await;
         }
+        Some((fastn_core::commands::log::COMMAND, matches)) => {
+            return fastn_core::commands::log::handle_command(matches).await;
+        }
+        Some((fastn_core::commands::show::COMMAND, matches)) => {
+            return fastn_core::commands::show::handle_command(matches).await;
+        }
+        Some((fastn_core::commands::restore::COMMAND, matches)) => {
+            return fastn_core::commands::restore::handle_command(matches).await;
+        }
+        Some((fastn_core::commands::assign_reviewers::COMMAND, matches)) => {
+            return fastn_core::commands::assign_reviewers::handle_command(matches).await;
+        }
+        Some((fastn_core::commands::review_cr::COMMAND, matches)) => {
+            return fastn_core::commands::review_cr::handle_command(matches).await;
+        }
         _ => {}
     }
 
@@ -79,6 +109,13 @@ async fn fastn_core_commands(matches: &clap::ArgMatches) -> fastn_core::Result<(
         let inline_js = serve.values_of_("js");
         let external_css = serve.values_of_("external-css");
         let inline_css = serve.values_of_("css");
+        let access_log = serve.value_of_("access-log");
+
+        if serve.get_flag("offline") {
+            fastn_core::package_cache::set_mode(fastn_core::package_cache::Mode::Offline);
+            let config = fastn_core::Config::read(None, false, None).await?;
+            fastn_core::package_cache::check_offline(&config).await?;
+        }
 
         return fastn_core::listen(
             bind.as_str(),
@@ -89,6 +126,7 @@ async fn fastn_core_commands(matches: &clap::ArgMatches) -> fastn_core::Result<(
             inline_js,
             external_css,
             inline_css,
+            access_log.map(ToString::to_string),
         )
         .await;
     }
@@ -97,12 +135,23 @@ async fn fastn_core_commands(matches: &clap::ArgMatches) -> fastn_core::Result<(
         return fastn_core::clone(clone.value_of_("source").unwrap()).await;
     }
 
+    if let Some(build) = matches.subcommand_matches("build") {
+        // has to be set before the config is read, reading it resolves the dependencies
+        if build.get_flag("offline") {
+            fastn_core::package_cache::set_mode(fastn_core::package_cache::Mode::Offline);
+        }
+    }
+
     let mut config = fastn_core::Config::read(None, true, None).await?;
 
     if matches.subcommand_matches("update").is_some() {
         return fastn_core::update(&config).await;
     }
 
+    if matches.subcommand_matches("vendor").is_some() {
+        return fastn_core::vendor(&config).await;
+    }
+
     if let Some(edit) = matches.subcommand_matches("edit") {
         return fastn_core::edit(
             &config,
@@ -149,6 +198,8 @@ async fn fastn_core_commands(matches: &clap::ArgMatches) -> fastn_core::Result<(
             .add_external_css(external_css)
             .add_inline_css(inline_css);
 
+        fastn_core::package_cache::check_offline(&config).await?;
+
         return fastn_core::build(
             &mut config,
             build.value_of_("file"), // TODO: handle more than one files
@@ -199,8 +250,13 @@ async fn fastn_core_commands(matches: &clap::ArgMatches) -> fastn_core::Result<(
         // TODO: handle multiple files
         return fastn_core::status(&config, status.value_of_("file")).await;
     }
-    if matches.subcommand_matches("translation-status").is_some() {
-        return fastn_core::translation_status(&config).await;
+    if let Some(translation_status) = matches.subcommand_matches("translation-status") {
+        return fastn_core::translation_status(
+            &config,
+            translation_status.value_of_("format").unwrap_or("text"),
+            translation_status.get_one::<f64>("fail-under").copied(),
+        )
+        .await;
     }
     if let Some(diff) = matches.subcommand_matches("diff") {
         let all = diff.get_flag("all");
@@ -244,6 +300,8 @@ fn app(version: &'static str) -> clap::Command {
         .arg(clap::arg!(verbose: -v "Sets the level of verbosity"))
         .arg(clap::arg!(--test "Runs the command in test mode").hide(true))
         .arg(clap::arg!(--trace "Activate tracing").hide(true))
+        .arg(clap::arg!(--"trace-output" <FILE> "Write traces to this file (also FASTN_TRACE_OUTPUT)").global(true).hide(true))
+        .arg(clap::arg!(--"trace-format" <FORMAT> "Trace file format: chrome (default) or otlp").global(true).hide(true))
         .subcommand(
             // Initial subcommand format
             // fastn create-package <project-name> [project-path]
@@ -271,6 +329,7 @@ fn app(version: &'static str) -> clap::Command {
                 .arg(clap::arg!(--"css" <URL> "CSS text added in ftd files")
                     .action(clap::ArgAction::Append))
                 .arg(clap::arg!(--edition <EDITION> "The FTD edition"))
+                .arg(clap::arg!(--offline "Never download anything, fail if a dependency is not in .packages or the package cache"))
         )
         .subcommand(
             clap::Command::new("mark-resolved")
@@ -324,7 +383,7 @@ fn app(version: &'static str) -> clap::Command {
                 .about("Merge two manifests together")
                 .arg(clap::arg!(src: <SRC> "The source manifest to merge"))
                 .arg(clap::arg!(dest: <DEST> "The destination manifest to merge"))
-                .arg(clap::arg!(file: <FILE>... "The file(s) to merge"))
+                .arg(clap::arg!(file: <FILE>... "The file(s) to merge (leave empty to merge everything)").required(false))
                 .hide(true) // hidden since the feature is not being released yet.
         )
         .subcommand(
@@ -335,12 +394,18 @@ fn app(version: &'static str) -> clap::Command {
         )
         .subcommand(
             clap::Command::new("update")
-                .about("Reinstall all the dependency packages")
+                .about("Resolve the dependency versions again, rewrite FASTN.lock and reinstall the dependency packages")
+        )
+        .subcommand(
+            clap::Command::new("vendor")
+                .about("Copy all the dependency packages into vendor/, they are used from there instead of being downloaded")
         )
+        // the commands of the history, CR and translation features are hidden till the features
+        // are released, the ones added to them later too
         .subcommand(
             clap::Command::new("sync")
                 .about("Sync with fastn-repo (or .history folder if not using fastn-repo)")
-                .arg(clap::arg!(file: <FILE>... "The file(s) to sync (leave empty to sync entire package)"))
+                .arg(clap::arg!(file: <FILE>... "The file(s) to sync (leave empty to sync entire package)").required(false))
                 .hide(true) // hidden since the feature is not being released yet.
         )
         .subcommand(
@@ -350,6 +415,9 @@ fn app(version: &'static str) -> clap::Command {
                 .hide(true) // hidden since the feature is not being released yet.
         )
         .subcommand(fastn_core::commands::sync_status::command())
+        .subcommand(fastn_core::commands::log::command())
+        .subcommand(fastn_core::commands::show::command())
+        .subcommand(fastn_core::commands::restore::command())
         .subcommand(
             clap::Command::new("create-cr")
                 .about("Create a Change Request")
@@ -362,9 +430,21 @@ fn app(version: &'static str) -> clap::Command {
                 .arg(clap::arg!(cr: <CR> "The CR to Close"))
                 .hide(true) // hidden since the feature is not being released yet.
         )
+        .subcommand(fastn_core::commands::assign_reviewers::command())
+        .subcommand(fastn_core::commands::review_cr::command())
+        .subcommand(fastn_core::commands::translate::command())
         .subcommand(
             clap::Command::new("translation-status")
                 .about("Show the translation status of files in this fastn package")
+                .arg(
+                    clap::arg!(--format <FORMAT> "How to print the coverage report")
+                        .value_parser(["text", "json", "html"])
+                        .default_value("text"),
+                )
+                .arg(
+                    clap::arg!(--"fail-under" <PERCENT> "Fail if the coverage of any language is under this percentage")
+                        .value_parser(clap::value_parser!(f64)),
+                )
                 .hide(true) // hidden since the feature is not being released yet.
         )
         .subcommand(
@@ -376,7 +456,7 @@ fn app(version: &'static str) -> clap::Command {
         )
         .subcommand(
             clap::Command::new("resolve-conflict")
-                .about("Show un-synced changes to files in this fastn package")
+                .about("Resolve a conflict, merging the file with the common ancestor when possible")
                 .arg(clap::arg!(--"use-ours" "Use our version of the file"))
                 .arg(clap::arg!(--"use-theirs" "Use their version of the file"))
                 .arg(clap::arg!(--"revive-it" "Revive the file"))
@@ -385,11 +465,10 @@ fn app(version: &'static str) -> clap::Command {
                 .arg(clap::arg!(file: <FILE> "The file to resolve the conflict for"))
                 .hide(true) // hidden since the feature is not being released yet.
         )
-        .subcommand(
-            clap::Command::new("check")
-                .about("Check if everything is fine with current fastn package")
-                .hide(true) // hidden since the feature is not being released yet.
-        )
+        .subcommand(fastn_core::commands::check::command())
+        .subcommand(fastn_core::commands::fmt::command())
+        .subcommand(fastn_core::commands::lsp::command())
+        .subcommand(fastn_core::commands::tokens::command())
         .subcommand(
             clap::Command::new("mark-upto-date")
                 .about("Marks file as up to date.")
@@ -427,7 +506,9 @@ mod sub_command {
                 .action(clap::ArgAction::Append))
             .arg(clap::arg!(--"css" <URL> "CSS text added in ftd files")
                 .action(clap::ArgAction::Append))
-            .arg(clap::arg!(--"download-base-url" <URL> "If running without files locally, download needed files from here"));
+            .arg(clap::arg!(--"download-base-url" <URL> "If running without files locally, download needed files from here"))
+            .arg(clap::arg!(--"access-log" <FILE> "Write a JSON lines access log to this file (`-` for stdout)"))
+            .arg(clap::arg!(--offline "Never download anything, fail if a dependency is not in .packages or the package cache"));
         if cfg!(feature = "remote") {
             serve
         } else {
@@ -443,6 +524,9 @@ mod sub_command {
         clap::Command::new(crate::commands::cloud::PUBLISH_STATIC)
             .about("Publish fastn package statically")
             .after_help("Publish fastn packages to fastn-cloud as static")
+            .arg(clap::arg!(target: [TARGET] "The `fastn.publish` of the package to publish to, needed if it has more than one"))
+            .arg(clap::arg!(--full "Upload all the files, not only the ones changed since the last publish"))
+            .arg(clap::arg!(--"dry-run" "Print the files that would be uploaded and deleted, without publishing"))
     }
 }
 
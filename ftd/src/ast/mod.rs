@@ -47,6 +47,21 @@ pub enum Error {
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+impl Error {
+    /// The document and line number the error is about, if known.
+    pub fn location(&self) -> Option<(&str, usize)> {
+        match self {
+            Error::P1(e) => e.location(),
+            Error::Parse {
+                doc_id,
+                line_number,
+                ..
+            } => Some((doc_id.as_str(), *line_number)),
+            Error::ParseBool(_) => None,
+        }
+    }
+}
+
 pub fn parse_error<T, S1>(m: S1, doc_id: &str, line_number: usize) -> ftd::ast::Result<T>
 where
     S1: Into<String>,
@@ -2,20 +2,22 @@ static SYNTAX_DIR: include_dir::Dir<'_> = include_dir::include_dir!("$CARGO_MANI
 pub const DEFAULT_THEME: &str = "fastn-theme.dark";
 
 pub static SS: once_cell::sync::Lazy<syntect::parsing::SyntaxSet> =
-    once_cell::sync::Lazy::new(|| {
-        let mut builder = syntect::parsing::SyntaxSet::load_defaults_newlines().into_builder();
-        for f in SYNTAX_DIR.files() {
-            builder.add(
-                syntect::parsing::syntax_definition::SyntaxDefinition::load_from_str(
-                    f.contents_utf8().unwrap(),
-                    true,
-                    f.path().file_stem().and_then(|x| x.to_str()),
-                )
-                .unwrap(),
-            );
-        }
-        builder.build()
-    });
+    once_cell::sync::Lazy::new(|| builtin_syntaxes().build());
+
+fn builtin_syntaxes() -> syntect::parsing::SyntaxSetBuilder {
+    let mut builder = syntect::parsing::SyntaxSet::load_defaults_newlines().into_builder();
+    for f in SYNTAX_DIR.files() {
+        builder.add(
+            syntect::parsing::syntax_definition::SyntaxDefinition::load_from_str(
+                f.contents_utf8().unwrap(),
+                true,
+                f.path().file_stem().and_then(|x| x.to_str()),
+            )
+            .unwrap(),
+        );
+    }
+    builder
+}
 
 /*pub static KNOWN_EXTENSIONS: once_cell::sync::Lazy<std::collections::HashSet<String>> =
 once_cell::sync::Lazy::new(|| {
@@ -66,12 +68,131 @@ pub static TS1: once_cell::sync::Lazy<syntect::highlighting::ThemeSet> =
     theme_set
 }*/
 
+/// Syntax definitions and themes shipped by a package and its dependencies, used for the
+/// documents of that package in addition to the built-in ones. fastn builds them once per
+/// package and passes them to the interpreter with `ftd::interpreter::Document::code_assets`.
+#[derive(Debug)]
+pub struct CodeAssets {
+    /// hash of the source files, so fastn can reuse the assets while the files are unchanged
+    fingerprint: u64,
+    syntaxes: syntect::parsing::SyntaxSet,
+    themes: syntect::highlighting::ThemeSet,
+}
+
+impl PartialEq for CodeAssets {
+    fn eq(&self, other: &Self) -> bool {
+        self.fingerprint == other.fingerprint
+    }
+}
+
+/// A `.sublime-syntax` or `.tmTheme` file shipped by a package.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AssetFile {
+    /// used to report errors
+    pub path: String,
+    /// file stem, used as the syntax name fallback and as the theme name
+    pub name: String,
+    pub content: String,
+}
+
+impl CodeAssets {
+    /// The hash `CodeAssets::new()` would give these files.
+    pub fn fingerprint_of(syntaxes: &[AssetFile], themes: &[AssetFile]) -> u64 {
+        use std::hash::{Hash, Hasher};
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        syntaxes.hash(&mut hasher);
+        themes.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    pub fn fingerprint(&self) -> u64 {
+        self.fingerprint
+    }
+
+    pub fn new(syntaxes: &[AssetFile], themes: &[AssetFile]) -> ftd::executor::Result<CodeAssets> {
+        let mut builder = builtin_syntaxes();
+        for f in syntaxes {
+            builder.add(
+                syntect::parsing::syntax_definition::SyntaxDefinition::load_from_str(
+                    f.content.as_str(),
+                    true,
+                    Some(f.name.as_str()),
+                )
+                .map_err(|e| asset_error(f, e.to_string()))?,
+            );
+        }
+
+        let mut theme_set = syntect::highlighting::ThemeSet::new();
+        for f in themes {
+            let theme = syntect::highlighting::ThemeSet::load_from_reader(
+                &mut std::io::Cursor::new(f.content.as_bytes()),
+            )
+            .map_err(|e| asset_error(f, e.to_string()))?;
+            theme_set.themes.insert(f.name.to_string(), theme);
+        }
+
+        Ok(CodeAssets {
+            fingerprint: CodeAssets::fingerprint_of(syntaxes, themes),
+            syntaxes: builder.build(),
+            themes: theme_set,
+        })
+    }
+}
+
+fn asset_error(f: &AssetFile, message: String) -> ftd::executor::Error {
+    ftd::executor::Error::ParseError {
+        message,
+        doc_id: f.path.to_string(),
+        line_number: 0,
+    }
+}
+
+/// Returns true if `ext` or `theme` can only be highlighted using the syntax definitions or
+/// themes of `assets`.
+///
+/// The 2023 JS renderer highlights code in the browser using prism, which knows nothing about
+/// them, so such code is highlighted on the server instead.
+pub fn needs_package_assets(ext: &str, theme: &str, assets: Option<&CodeAssets>) -> bool {
+    let assets = match assets {
+        Some(v) => v,
+        None => return false,
+    };
+
+    (find_syntax(&assets.syntaxes, ext).is_some() && find_syntax(&SS, ext).is_none())
+        || (assets.themes.themes.contains_key(theme)
+            && !TS.themes.contains_key(theme)
+            && !TS1.themes.contains_key(theme))
+}
+
+fn find_syntax<'a>(
+    ss: &'a syntect::parsing::SyntaxSet,
+    ext: &str,
+) -> Option<&'a syntect::parsing::SyntaxReference> {
+    ss.find_syntax_by_extension(ext)
+        .or_else(|| ss.find_syntax_by_name(ext))
+}
+
 pub fn code(code: &str, ext: &str, theme: &str, doc_id: &str) -> ftd::executor::Result<String> {
-    let syntax = SS
-        .find_syntax_by_extension(ext)
-        .unwrap_or_else(|| SS.find_syntax_plain_text());
+    code_with_assets(code, ext, theme, None, doc_id)
+}
 
-    let theme = if let Some(theme) = TS.themes.get(theme).or(TS1.themes.get(theme)) {
+/// Same as `code()`, also using the syntax definitions and themes of `assets`.
+pub fn code_with_assets(
+    code: &str,
+    ext: &str,
+    theme: &str,
+    assets: Option<&CodeAssets>,
+    doc_id: &str,
+) -> ftd::executor::Result<String> {
+    let ss = assets.map(|v| &v.syntaxes).unwrap_or(&*SS);
+    let syntax = find_syntax(ss, ext).unwrap_or_else(|| ss.find_syntax_plain_text());
+
+    let theme = if let Some(theme) = assets
+        .and_then(|v| v.themes.themes.get(theme))
+        .or(TS.themes.get(theme))
+        .or(TS1.themes.get(theme))
+    {
         theme
     } else {
         return Err(ftd::executor::Error::ParseError {
@@ -81,17 +202,35 @@ pub fn code(code: &str, ext: &str, theme: &str, doc_id: &str) -> ftd::executor::
         });
     };
 
-    let code = code
-        .lines()
+    let code = trim(code) + "\n";
+
+    // TODO: handle various params
+    Ok(highlighted_html_for_string(code.as_str(), ext, ss, syntax, theme)?.replacen('\n', "", 1))
+}
+
+/// Drops the leading blank lines and the trailing whitespace, the lines of the highlighted
+/// html are the lines of the trimmed code.
+pub fn trim(code: &str) -> String {
+    code.lines()
         .skip_while(|l| l.trim().is_empty())
         .collect::<Vec<_>>()
         .join("\n")
         .trim_end()
         .to_string()
-        + "\n";
+}
 
-    // TODO: handle various params
-    Ok(highlighted_html_for_string(code.as_str(), ext, &SS, syntax, theme)?.replacen('\n', "", 1))
+/// Removes the `+` and `-` markers of the lines of a `diff: true` code block, and the space
+/// before the unchanged lines, the browser shows them in a gutter.
+pub fn strip_diff_markers(code: &str) -> String {
+    code.split('\n')
+        .map(|line| {
+            line.strip_prefix('+')
+                .or_else(|| line.strip_prefix('-'))
+                .or_else(|| line.strip_prefix(' '))
+                .unwrap_or(line)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 fn highlighted_html_for_string(
@@ -159,3 +298,30 @@ fn color_to_hex(c: &syntect::highlighting::Color) -> String {
     };
     format!("#{:02x}{:02x}{:02x}{}", c.r, c.g, c.b, a)
 }
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn strip_diff_markers() {
+        assert_eq!(
+            super::strip_diff_markers(indoc::indoc! {"
+                 fn main() {
+                -    println!(\"hello\");
+                +    println!(\"hello, world\");
+                +
+                     return;
+                 }
+                no marker
+                "}),
+            indoc::indoc! {"
+                fn main() {
+                    println!(\"hello\");
+                    println!(\"hello, world\");
+
+                    return;
+                }
+                no marker
+            "}
+        );
+    }
+}
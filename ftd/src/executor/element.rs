@@ -477,6 +477,7 @@ pub fn code_from_properties(
             text.value.unwrap_or_default().as_str(),
             lang.value.as_str(),
             theme.value.as_str(),
+            doc.code_assets,
             doc.name,
         )?,
         text.line_number,
@@ -643,20 +644,33 @@ pub fn markup_inline(s: &str) -> Rendered {
     }
 }
 
+/// Same as `markup_inline()`, using the markdown extensions of the package if it has any.
+pub fn markup_inline_with_options(
+    s: &str,
+    options: Option<&ftd::executor::markup::MarkdownOptions>,
+) -> Rendered {
+    Rendered {
+        original: s.to_string(),
+        rendered: ftd::executor::markup::markup_inline_with_options(s, options),
+    }
+}
+
 pub fn code_with_theme(
     code: &str,
     ext: &str,
     theme: &str,
+    assets: Option<&ftd::executor::code::CodeAssets>,
     doc_id: &str,
 ) -> ftd::executor::Result<Rendered> {
     Ok(Rendered {
         original: code.to_string(),
-        rendered: ftd::executor::code::code(
+        rendered: ftd::executor::code::code_with_assets(
             code.replace("\n\\-- ", "\n-- ")
                 .replace("\\$", "$")
                 .as_str(),
             ext,
             theme,
+            assets,
             doc_id,
         )?,
     })
@@ -801,7 +815,12 @@ pub fn text_from_properties(
             line_number,
         );
     }
-    let text = text.map(|v| ftd::executor::element::markup_inline(v.unwrap_or_default().as_str()));
+    let text = text.map(|v| {
+        ftd::executor::element::markup_inline_with_options(
+            v.unwrap_or_default().as_str(),
+            doc.markdown_options,
+        )
+    });
     let common = common_from_properties(
         properties,
         events,
@@ -9,6 +9,8 @@ pub struct ExecuteDoc<'a> {
     pub js: &'a mut std::collections::HashSet<String>,
     pub css: &'a mut std::collections::HashSet<String>,
     pub rive_data: &'a mut Vec<ftd::executor::RiveData>,
+    pub code_assets: Option<&'a ftd::executor::code::CodeAssets>,
+    pub markdown_options: Option<&'a ftd::executor::markup::MarkdownOptions>,
 }
 
 #[derive(serde::Deserialize, Debug, PartialEq, Clone, serde::Serialize)]
@@ -61,6 +63,8 @@ impl<'a> ExecuteDoc<'a> {
             js: &mut js,
             css: &mut css,
             rive_data: &mut rive_data,
+            code_assets: document.code_assets.as_deref(),
+            markdown_options: document.markdown_options.as_ref(),
         }
         .execute()?;
 
@@ -118,6 +122,8 @@ impl<'a> ExecuteDoc<'a> {
             js: self.js,
             css: self.css,
             rive_data: self.rive_data,
+            code_assets: self.code_assets,
+            markdown_options: self.markdown_options,
         };
 
         ExecuteDoc::execute_from_instructions_loop(self.instructions, &mut doc)
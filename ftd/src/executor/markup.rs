@@ -11,17 +11,67 @@ pub static MD: once_cell::sync::Lazy<comrak::ComrakOptions> = once_cell::sync::L
     m
 });
 
+/// Markdown extensions a package can turn on, in `FASTN.ftd` using `fastn.markdown`, on top of
+/// the ones in `MD`.
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct MarkdownOptions {
+    pub footnotes: bool,
+    #[serde(rename = "description-lists")]
+    pub description_lists: bool,
+    /// gives every heading an `id`, and an anchor link, generated from its text
+    #[serde(rename = "heading-anchors")]
+    pub heading_anchors: bool,
+    /// github style `> [!NOTE]` callouts, rendered as `<div class="admonition note">`
+    pub admonitions: bool,
+}
+
+impl MarkdownOptions {
+    fn comrak_options(&self) -> comrak::ComrakOptions {
+        let mut m = MD.clone();
+        m.extension.footnotes = self.footnotes;
+        m.extension.description_lists = self.description_lists;
+        if self.heading_anchors {
+            m.extension.header_ids = Some("".to_string());
+        }
+        m
+    }
+}
+
 pub fn markup(i: &str) -> String {
-    comrak::markdown_to_html(i.replace("![", MAGIC).trim(), &MD)
+    render(i, &MD)
+}
+
+pub fn markup_with_options(i: &str, options: &MarkdownOptions) -> String {
+    if options.admonitions {
+        render_with_admonitions(i, &options.comrak_options())
+    } else {
+        render(i, &options.comrak_options())
+    }
+}
+
+fn render(i: &str, options: &comrak::ComrakOptions) -> String {
+    comrak::markdown_to_html(i.replace("![", MAGIC).trim(), options)
         .trim()
         .replace(MAGIC, "![")
         .replace('\n', " ")
 }
 
 pub fn markup_inline(i: &str) -> String {
+    inline(i, markup(i))
+}
+
+/// Same as `markup_inline()`, using the markdown extensions of the package if it has any.
+pub fn markup_inline_with_options(i: &str, options: Option<&MarkdownOptions>) -> String {
+    match options {
+        Some(options) => inline(i, markup_with_options(i, options)),
+        None => markup_inline(i),
+    }
+}
+
+fn inline(i: &str, html: String) -> String {
     let (space_before, space_after) = spaces(i);
     let o = {
-        let mut g = ftd::executor::utils::replace_last_occurrence(markup(i).as_str(), "<p>", "");
+        let mut g = ftd::executor::utils::replace_last_occurrence(html.as_str(), "<p>", "");
         g = ftd::executor::utils::replace_last_occurrence(g.as_str(), "</p>", "");
         g
     };
@@ -43,6 +93,212 @@ pub fn markup_inline(i: &str) -> String {
     )
 }
 
+const ADMONITIONS: &[(&str, &str)] = &[
+    ("NOTE", "Note"),
+    ("TIP", "Tip"),
+    ("IMPORTANT", "Important"),
+    ("WARNING", "Warning"),
+    ("CAUTION", "Caution"),
+];
+
+const RAW_HTML_OMITTED: &str = "<!-- raw HTML omitted -->";
+
+/// Same as `render()`, also turning the blockquotes starting with `[!NOTE]`, `[!TIP]`,
+/// `[!IMPORTANT]`, `[!WARNING]` or `[!CAUTION]` into
+/// `<div class="admonition note"><p class="admonition-title">Note</p>..</div>`.
+fn render_with_admonitions(i: &str, options: &comrak::ComrakOptions) -> String {
+    let arena = comrak::Arena::new();
+    let root = comrak::parse_document(&arena, i.replace("![", MAGIC).trim(), options);
+
+    // the admonitions are added as html blocks, which comrak only renders with raw html
+    // enabled, so the raw html of the text is omitted here the way comrak does it
+    let mut options = options.clone();
+    if !options.render.unsafe_ {
+        for node in root.descendants() {
+            match node.data.borrow_mut().value {
+                comrak::nodes::NodeValue::HtmlBlock(ref mut block) => {
+                    block.literal = RAW_HTML_OMITTED.to_string()
+                }
+                comrak::nodes::NodeValue::HtmlInline(ref mut html) => {
+                    *html = RAW_HTML_OMITTED.to_string()
+                }
+                _ => {}
+            }
+        }
+        options.render.unsafe_ = true;
+    }
+
+    let blockquotes = root
+        .descendants()
+        .filter(|node| {
+            matches!(
+                node.data.borrow().value,
+                comrak::nodes::NodeValue::BlockQuote
+            )
+        })
+        .collect::<Vec<_>>();
+    for blockquote in blockquotes {
+        admonition(&arena, blockquote);
+    }
+
+    let mut html = vec![];
+    comrak::format_html(root, &options, &mut html).expect("writing to a Vec can not fail");
+    String::from_utf8_lossy(html.as_slice())
+        .trim()
+        .replace(MAGIC, "![")
+        .replace('\n', " ")
+}
+
+/// Replaces `blockquote` with the children of it wrapped in the admonition `<div>`, if its
+/// first paragraph starts with one of the `ADMONITIONS` markers.
+fn admonition<'a>(
+    arena: &'a comrak::Arena<comrak::nodes::AstNode<'a>>,
+    blockquote: &'a comrak::nodes::AstNode<'a>,
+) {
+    let paragraph = match blockquote.first_child() {
+        Some(node)
+            if matches!(
+                node.data.borrow().value,
+                comrak::nodes::NodeValue::Paragraph
+            ) =>
+        {
+            node
+        }
+        _ => return,
+    };
+
+    // `[!NOTE]` can be split in more than one text node, `[` may start a link
+    let texts = paragraph
+        .children()
+        .take_while(|node| matches!(node.data.borrow().value, comrak::nodes::NodeValue::Text(_)))
+        .collect::<Vec<_>>();
+    let text = texts
+        .iter()
+        .map(|node| match node.data.borrow().value {
+            comrak::nodes::NodeValue::Text(ref text) => text.to_string(),
+            _ => String::new(),
+        })
+        .collect::<String>();
+    let (marker, title) = match ADMONITIONS
+        .iter()
+        .find(|(marker, _)| text.starts_with(format!("[!{}]", marker).as_str()))
+    {
+        Some(v) => v,
+        None => return,
+    };
+
+    // drop the marker, and the line break after it
+    let mut remaining = marker.len() + 3;
+    for node in texts {
+        let mut data = node.data.borrow_mut();
+        if let comrak::nodes::NodeValue::Text(ref mut text) = data.value {
+            let len = text.len();
+            if remaining >= len {
+                remaining -= len;
+                drop(data);
+                node.detach();
+                continue;
+            }
+            *text = text[remaining..].trim_start().to_string();
+            if text.is_empty() {
+                drop(data);
+                node.detach();
+            }
+            break;
+        }
+    }
+    if let Some(first) = paragraph.first_child() {
+        if matches!(
+            first.data.borrow().value,
+            comrak::nodes::NodeValue::SoftBreak | comrak::nodes::NodeValue::LineBreak
+        ) {
+            first.detach();
+        }
+    }
+    // `> [!NOTE]` followed by an empty `>` line leaves an empty paragraph behind
+    if paragraph.first_child().is_none() {
+        paragraph.detach();
+    }
+
+    let html_block = |literal: String| {
+        let mut ast = blockquote.data.borrow().clone();
+        ast.value = comrak::nodes::NodeValue::HtmlBlock(comrak::nodes::NodeHtmlBlock {
+            block_type: 0,
+            literal,
+        });
+        arena.alloc(comrak::arena_tree::Node::new(std::cell::RefCell::new(ast)))
+    };
+
+    blockquote.insert_before(html_block(format!(
+        "<div class=\"admonition {}\"><p class=\"admonition-title\">{}</p>",
+        marker.to_lowercase(),
+        title
+    )));
+    for child in blockquote.children().collect::<Vec<_>>() {
+        blockquote.insert_before(child);
+    }
+    blockquote.insert_before(html_block("</div>".to_string()));
+    blockquote.detach();
+}
+
+/// A heading in a markdown text, see `headings()`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Heading {
+    pub level: u32,
+    pub title: String,
+    /// the `id` the heading gets when `heading-anchors` is enabled
+    pub id: String,
+}
+
+/// Returns the headings of the markdown text `i`, in document order.
+pub fn headings(i: &str) -> Vec<Heading> {
+    let arena = comrak::Arena::new();
+    let root = comrak::parse_document(&arena, i, &MD);
+    let mut ids = std::collections::HashSet::new();
+    let mut headings = vec![];
+
+    for node in root.descendants() {
+        let level: u32 = match node.data.borrow().value {
+            comrak::nodes::NodeValue::Heading(ref heading) => heading.level.into(),
+            _ => continue,
+        };
+        let title = node
+            .descendants()
+            .filter_map(|child| match child.data.borrow().value {
+                comrak::nodes::NodeValue::Text(ref text) => Some(text.to_string()),
+                comrak::nodes::NodeValue::Code(ref code) => Some(code.literal.to_string()),
+                _ => None,
+            })
+            .collect::<String>();
+        headings.push(Heading {
+            level,
+            id: unique_anchor(title.as_str(), &mut ids),
+            title,
+        });
+    }
+
+    headings
+}
+
+/// Same algorithm comrak uses for `header_ids` (which is github's): lowercase, drop
+/// punctuation, spaces become `-`, and a `-<n>` suffix for repeated ones.
+fn unique_anchor(title: &str, ids: &mut std::collections::HashSet<String>) -> String {
+    let anchor = title
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '_' || *c == '-' || *c == ' ')
+        .map(|c| if c == ' ' { '-' } else { c })
+        .collect::<String>();
+
+    let mut id = anchor.clone();
+    let mut n = 0;
+    while !ids.insert(id.clone()) {
+        n += 1;
+        id = format!("{}-{}", anchor, n);
+    }
+    id
+}
+
 fn repeated_space(n: usize) -> String {
     (0..n).map(|_| " ").collect::<String>()
 }
@@ -70,3 +326,63 @@ fn spaces(s: &str) -> (usize, usize) {
     }
     (space_before, space_after)
 }
+
+#[cfg(test)]
+mod test {
+    fn admonitions(i: &str) -> String {
+        super::markup_with_options(
+            i,
+            &super::MarkdownOptions {
+                admonitions: true,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn admonition() {
+        assert_eq!(
+            admonitions("> [!NOTE]\n> Read *this* first."),
+            "<div class=\"admonition note\"><p class=\"admonition-title\">Note</p> \
+             <p>Read <em>this</em> first.</p> </div>"
+        );
+        assert_eq!(
+            admonitions("> [!WARNING] Careful.\n>\n> Second paragraph."),
+            "<div class=\"admonition warning\"><p class=\"admonition-title\">Warning</p> \
+             <p>Careful.</p> <p>Second paragraph.</p> </div>"
+        );
+        // the marker alone in its paragraph
+        assert_eq!(
+            admonitions("> [!TIP]\n>\n> Use `fastn fmt`."),
+            "<div class=\"admonition tip\"><p class=\"admonition-title\">Tip</p> \
+             <p>Use <code>fastn fmt</code>.</p> </div>"
+        );
+    }
+
+    #[test]
+    fn not_an_admonition() {
+        assert_eq!(
+            admonitions("> [!UNKNOWN] quoted"),
+            super::markup("> [!UNKNOWN] quoted")
+        );
+        assert_eq!(admonitions("> quoted"), super::markup("> quoted"));
+        // nested in a list, the paragraphs of tight lists are not wrapped in `<p>`
+        assert_eq!(
+            admonitions("- > [!CAUTION] hot"),
+            "<ul> <li> <div class=\"admonition caution\"><p class=\"admonition-title\">Caution</p> \
+             hot </div> </li> </ul>"
+        );
+    }
+
+    #[test]
+    fn raw_html_is_omitted() {
+        let text = "> [!NOTE] <b>bold</b>\n\n<div>block</div>";
+        assert_eq!(
+            admonitions(text),
+            "<div class=\"admonition note\"><p class=\"admonition-title\">Note</p> \
+             <p><!-- raw HTML omitted -->bold<!-- raw HTML omitted --></p> </div> \
+             <!-- raw HTML omitted -->"
+        );
+        assert!(super::markup(text).contains("<!-- raw HTML omitted -->bold"));
+    }
+}
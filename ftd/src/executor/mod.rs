@@ -6,7 +6,7 @@ pub mod code;
 mod dummy;
 mod element;
 mod main;
-mod markup;
+pub mod markup;
 mod rive;
 mod styles;
 mod tdoc;
@@ -17,6 +17,7 @@ mod youtube_id;
 pub type FieldWithValue = (ftd::interpreter::Field, Option<ftd::ast::VariableValue>);
 
 pub use dummy::{DummyElement, ElementConstructor};
+pub(crate) use element::code_with_theme;
 pub use element::{
     CheckBox, Code, Column, Common, Container, ContainerElement, Document, Element, Event,
     HTMLData, Iframe, Image, ImageSrc, IterativeElement, RawElement, RawImage, Rive, Row, Text,
@@ -8,11 +8,15 @@ pub struct TDoc<'a> {
     pub js: &'a mut std::collections::HashSet<String>,
     pub css: &'a mut std::collections::HashSet<String>,
     pub rive_data: &'a mut Vec<ftd::executor::RiveData>,
+    pub code_assets: Option<&'a ftd::executor::code::CodeAssets>,
+    pub markdown_options: Option<&'a ftd::executor::markup::MarkdownOptions>,
 }
 
 impl<'a> TDoc<'a> {
     pub(crate) fn itdoc(&self) -> ftd::interpreter::TDoc {
         ftd::interpreter::TDoc::new(self.name, self.aliases, self.bag)
+            .with_code_assets(self.code_assets)
+            .with_markdown_options(self.markdown_options)
     }
 
     pub fn resolve_all_self_references(
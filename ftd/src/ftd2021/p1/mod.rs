@@ -68,3 +68,42 @@ pub enum Error {
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// The document and line number the error is about, if known.
+    pub fn location(&self) -> Option<(&str, usize)> {
+        match self {
+            Error::ParseError {
+                doc_id,
+                line_number,
+                ..
+            }
+            | Error::UnknownData {
+                doc_id,
+                line_number,
+                ..
+            }
+            | Error::MissingData {
+                doc_id,
+                line_number,
+                ..
+            }
+            | Error::ForbiddenUsage {
+                doc_id,
+                line_number,
+                ..
+            }
+            | Error::NotFound {
+                doc_id,
+                line_number,
+                ..
+            }
+            | Error::MoreThanOneSubSections {
+                doc_id,
+                line_number,
+                ..
+            } => Some((doc_id.as_str(), *line_number)),
+            Error::Serde { .. } | Error::Syntect { .. } => None,
+        }
+    }
+}
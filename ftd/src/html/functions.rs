@@ -7,20 +7,44 @@ impl FunctionGenerator {
         FunctionGenerator { id: id.to_string() }
     }
 
-    pub fn get_functions(&self, node_data: &ftd::node::NodeData) -> ftd::html::Result<String> {
+    /// The functions of the document `name`. `generated` is the rest of the generated html and
+    /// js of the document, the functions of the standard library are only included if it calls
+    /// them (other functions call `ftd.<name>` directly).
+    pub fn get_functions(
+        &self,
+        name: &str,
+        bag: &indexmap::IndexMap<String, ftd::interpreter::Thing>,
+        generated: &[&str],
+    ) -> ftd::html::Result<String> {
+        let stdlib = ftd::interpreter::stdlib::stdlib_bag()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect::<std::collections::HashSet<_>>();
+
         let mut vector = vec![];
         vector.extend(from_default_functions());
-        for function in node_data
-            .bag
+        for function in bag
             .values()
-            .filter_map(|v| v.to_owned().function(node_data.name.as_str(), 0).ok())
+            .filter_map(|v| v.to_owned().function(name, 0).ok())
         {
+            if stdlib.contains(function.name.as_str()) {
+                let js_name = self.js_function_name(function.name.as_str());
+                if !generated.iter().any(|v| v.contains(js_name.as_str())) {
+                    continue;
+                }
+            }
             vector.push(self.get_function(function)?)
         }
 
         Ok(vector.join("\n\n"))
     }
 
+    fn js_function_name(&self, name: &str) -> String {
+        ftd::html::utils::function_name_to_js_function(
+            ftd::html::utils::name_with_id(name, self.id.as_str()).as_str(),
+        )
+    }
+
     pub fn get_function(&self, function: ftd::interpreter::Function) -> ftd::html::Result<String> {
         use itertools::Itertools;
 
@@ -46,9 +70,7 @@ impl FunctionGenerator {
             ));
         }
         let expressions = result.join("\n");
-        let function_name = ftd::html::utils::function_name_to_js_function(
-            ftd::html::utils::name_with_id(function.name.as_str(), self.id.as_str()).as_str(),
-        );
+        let function_name = self.js_function_name(function.name.as_str());
 
         let mut arguments = arguments.iter().map(|(k, _)| k).join(",");
 
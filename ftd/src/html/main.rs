@@ -58,7 +58,6 @@ impl HtmlUI {
             &node_data.bag,
         );
 
-        let functions = ftd::html::FunctionGenerator::new(id).get_functions(&node_data)?;
         let (dependencies, var_dependencies) = ftd::html::dependencies::DependencyGenerator::new(
             id,
             &node_data.node,
@@ -77,6 +76,22 @@ impl HtmlUI {
         let raw_html = ftd::html::HelperHtmlGenerator::new(id, &tdoc)
             .as_string_from_raw_nodes(&node_data.raw_nodes);
 
+        let rive_data =
+            ftd::html::utils::get_rive_data_html(node_data.rive_data.as_slice(), id, &tdoc)?;
+        let functions = ftd::html::FunctionGenerator::new(id).get_functions(
+            node_data.name.as_str(),
+            &node_data.bag,
+            &[
+                html.as_str(),
+                dependencies.as_str(),
+                variable_dependencies.as_str(),
+                outer_events.as_str(),
+                dummy_html.as_str(),
+                raw_html.as_str(),
+                rive_data.as_str(),
+            ],
+        )?;
+
         /*for (dependency, raw_node) in node_data.raw_nodes {
             let raw_html = RawHtmlGenerator::from_node(id, &tdoc, raw_node.node);
             dbg!("raw_nodes", &dependency, &raw_html);
@@ -97,11 +112,7 @@ impl HtmlUI {
             html_data: node_data.html_data.to_html_data(),
             js: ftd::html::utils::get_js_html(node_data.js.into_iter().collect_vec().as_slice()),
             css: ftd::html::utils::get_css_html(node_data.css.into_iter().collect_vec().as_slice()),
-            rive_data: ftd::html::utils::get_rive_data_html(
-                node_data.rive_data.as_slice(),
-                id,
-                &tdoc,
-            )?,
+            rive_data,
         })
     }
 }
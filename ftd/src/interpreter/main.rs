@@ -411,6 +411,8 @@ impl InterpreterState {
                 name: self.id,
                 js: self.js,
                 css: self.css,
+                code_assets: None,
+                markdown_options: None,
             };
 
             Ok(Interpreter::Done { document })
@@ -1069,6 +1071,12 @@ pub struct Document {
     pub aliases: ftd::Map<String>,
     pub js: std::collections::HashSet<String>,
     pub css: std::collections::HashSet<String>,
+    /// set by fastn to the syntaxes and themes of the package the document belongs to
+    #[serde(skip)]
+    pub code_assets: Option<std::sync::Arc<ftd::executor::code::CodeAssets>>,
+    /// set by fastn to the markdown extensions of the package the document belongs to
+    #[serde(skip)]
+    pub markdown_options: Option<ftd::executor::markup::MarkdownOptions>,
 }
 
 impl Document {
@@ -1077,6 +1085,8 @@ impl Document {
             name: self.name.as_str(),
             aliases: &self.aliases,
             bag: ftd::interpreter::BagOrState::Bag(&self.data),
+            code_assets: self.code_assets.as_deref(),
+            markdown_options: self.markdown_options.as_ref(),
         }
     }
     pub fn get_instructions(&self, component_name: &str) -> Vec<ftd::interpreter::Component> {
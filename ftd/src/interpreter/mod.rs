@@ -41,6 +41,7 @@ pub use prelude::*;
 
 pub use tdoc::{BagOrState, TDoc};
 pub use things::expression;
+pub(crate) use things::stdlib;
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -70,6 +71,14 @@ pub enum Error {
         message: String,
     },
 
+    /// `name` is neither defined in the document nor in the documents it imports
+    #[error("NotFound: {doc_id}:{line_number} -> {name} not found")]
+    NotFound {
+        name: String,
+        doc_id: String,
+        line_number: usize,
+    },
+
     #[error("ParseIntError: {}", _0)]
     ParseIntError(#[from] std::num::ParseIntError),
 
@@ -97,7 +106,42 @@ pub enum Error {
 
     #[error("Invalid access: {message}, line_number: {line_number}")]
     InvalidAccessError { message: String, line_number: usize },
+
+    #[error("library not found: {module}")]
+    LibraryNotFound { module: String },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// The document and line number the error is about, if known.
+    pub fn location(&self) -> Option<(&str, usize)> {
+        match self {
+            Error::P1Error(e) => e.location(),
+            Error::OldP1Error(e) => e.location(),
+            Error::ASTError(e) => e.location(),
+            Error::InvalidKind {
+                doc_id,
+                line_number,
+                ..
+            }
+            | Error::ValueNotFound {
+                doc_id,
+                line_number,
+                ..
+            }
+            | Error::NotFound {
+                doc_id,
+                line_number,
+                ..
+            }
+            | Error::ParseError {
+                doc_id,
+                line_number,
+                ..
+            } => Some((doc_id.as_str(), *line_number)),
+            _ => None,
+        }
+    }
+}
 pub type ModuleThing = ftd::interpreter::things::ModuleThing;
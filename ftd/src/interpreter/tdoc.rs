@@ -3,6 +3,10 @@ pub struct TDoc<'a> {
     pub name: &'a str,
     pub aliases: &'a ftd::Map<String>,
     pub bag: BagOrState<'a>,
+    /// the syntaxes and themes of the package, for `ftd.code`
+    pub code_assets: Option<&'a ftd::executor::code::CodeAssets>,
+    /// the markdown extensions turned on by the package
+    pub markdown_options: Option<&'a ftd::executor::markup::MarkdownOptions>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -21,6 +25,8 @@ impl<'a> TDoc<'a> {
             name,
             aliases,
             bag: BagOrState::Bag(bag),
+            code_assets: None,
+            markdown_options: None,
         }
     }
 
@@ -33,6 +39,28 @@ impl<'a> TDoc<'a> {
             name,
             aliases,
             bag: BagOrState::State(state),
+            code_assets: None,
+            markdown_options: None,
+        }
+    }
+
+    pub fn with_markdown_options(
+        self,
+        markdown_options: Option<&'a ftd::executor::markup::MarkdownOptions>,
+    ) -> TDoc<'a> {
+        TDoc {
+            markdown_options,
+            ..self
+        }
+    }
+
+    pub fn with_code_assets(
+        self,
+        code_assets: Option<&'a ftd::executor::code::CodeAssets>,
+    ) -> TDoc<'a> {
+        TDoc {
+            code_assets,
+            ..self
         }
     }
 
@@ -1041,7 +1069,7 @@ impl<'a> TDoc<'a> {
         } {
             state
         } else {
-            return self.err("not found", name, "search_thing", line_number);
+            return self.not_found(name.as_str(), line_number);
         };
 
         if doc_name.eq(ftd::interpreter::FTD_INHERITED) {
@@ -1459,7 +1487,7 @@ impl<'a> TDoc<'a> {
         } {
             state
         } else {
-            return self.err("not found", name, "search_thing", line_number);
+            return self.not_found(name.as_str(), line_number);
         };
 
         let current_parsed_document = state.parsed_libs.get(state.id.as_str()).unwrap();
@@ -1502,7 +1530,7 @@ impl<'a> TDoc<'a> {
                 }
             } else if !current_doc_contains_thing.is_empty() && state.peek_stack().unwrap().1.gt(&4)
             {
-                return self.err("not found", name, "search_thing", line_number);
+                return self.not_found(name.as_str(), line_number);
             }
         }
 
@@ -1591,7 +1619,7 @@ impl<'a> TDoc<'a> {
                         return Ok(thing);
                     }
                 }*/
-                return self.err("not found", name, "search_thing", line_number);
+                return self.not_found(name.as_str(), line_number);
             }
 
             state
@@ -1613,7 +1641,7 @@ impl<'a> TDoc<'a> {
         }
 
         if doc_name.eq(self.name) {
-            return self.err("not found", name, "search_thing", line_number);
+            return self.not_found(name.as_str(), line_number);
         }
 
         state
@@ -1722,12 +1750,7 @@ impl<'a> TDoc<'a> {
                     None => match tdoc.bag().get(name).map(|v| (v.to_owned(), None)) {
                         Some(a) => a,
                         None => {
-                            return tdoc.err(
-                                "not found",
-                                splited_name,
-                                "get_initial_thing",
-                                line_number,
-                            );
+                            return tdoc.not_found(splited_name.as_str(), line_number);
                         }
                     },
                 };
@@ -2113,6 +2136,18 @@ impl<'a> TDoc<'a> {
         })
     }
 
+    pub(crate) fn not_found<T>(
+        &self,
+        name: &str,
+        line_number: usize,
+    ) -> ftd::interpreter::Result<T> {
+        Err(ftd::interpreter::Error::NotFound {
+            name: name.to_string(),
+            doc_id: self.name.to_string(),
+            line_number,
+        })
+    }
+
     pub(crate) fn err<T, T2: std::fmt::Debug>(
         &self,
         msg: &str,
@@ -258,3 +258,86 @@ fn evalexpr_test() {
         }
             */
 }
+
+#[test]
+fn not_found_errors() {
+    let not_found = |source: &str| match interpret_helper("foo", source) {
+        Err(ftd::interpreter::Error::NotFound {
+            name, line_number, ..
+        }) => (name, line_number),
+        result => panic!(
+            "expected a not found error, found: {:?}",
+            result.map(|_| ())
+        ),
+    };
+
+    assert_eq!(
+        not_found("-- ftd.text: hello\n\n-- ftd.text: $greeting\n"),
+        ("foo#greeting".to_string(), 3)
+    );
+    assert_eq!(
+        not_found("-- ftd.column:\n\n-- card:\n\n-- end: ftd.column\n"),
+        ("foo#card".to_string(), 3)
+    );
+    assert_eq!(
+        not_found(
+            "-- import: test\n\n-- string name:\n$processor$: test.missing\n\n-- ftd.text: $name\n"
+        ),
+        ("test.missing".to_string(), 3)
+    );
+}
+
+#[test]
+fn function_statement_errors() {
+    let line_number = |source: &str| match interpret_helper("foo", source) {
+        Err(ftd::interpreter::Error::ParseError {
+            message,
+            line_number,
+            ..
+        }) => (message, line_number),
+        result => panic!("expected a parse error, found: {:?}", result.map(|_| ())),
+    };
+
+    assert_eq!(
+        line_number(indoc::indoc!(
+            "
+            -- integer total(items):
+            integer list items:
+
+            let sum = 0;
+            for item in items {
+                let sum = item;
+            }
+            let sum = 1;
+            sum
+
+
+            -- integer list prices: 1, 2
+
+            -- ftd.integer: $total(items = $prices)
+            "
+        )),
+        (
+            "`sum` is already declared in this block of function `total`".to_string(),
+            8
+        )
+    );
+    assert_eq!(
+        line_number(indoc::indoc!(
+            "
+            -- integer size(n):
+            integer n:
+
+            if n > 100 {
+                return \"large\";
+            }
+            0
+
+
+            -- ftd.integer: $size(n = 1)
+            "
+        ))
+        .1,
+        5
+    );
+}
@@ -1044,7 +1044,7 @@ fn search_things_for_module(
             } {
                 state.parsed_libs.get_mut(state.id.as_str()).unwrap()
             } else {
-                return doc.err("not found", m_name, "search_thing", line_number);
+                return doc.not_found(m_name.as_str(), line_number);
             };
             let (module, alias) = ftd::ast::utils::get_import_alias(m_name.as_str());
             if !current_parsed_document
@@ -29,6 +29,9 @@ enable light mode in the application.
 4. `enable_system_mode` - This function takes no arguments and returns an empty value. It is used to
 enable system mode in the application, which means the application will use the system's default
 color scheme.
+
+The string, list, number and date functions of the standard library are added from
+`ftd::interpreter::things::stdlib`.
 **/
 pub fn default_functions() -> ftd::Map<fastn_grammar::evalexpr::Function> {
     use fastn_grammar::evalexpr::*;
@@ -127,6 +130,7 @@ pub fn default_functions() -> ftd::Map<fastn_grammar::evalexpr::Function> {
             Function::new(|_| Ok(Value::Empty)),
         ),
     ])
+    .chain(ftd::interpreter::things::stdlib::stdlib_functions())
     .collect()
 }
 
@@ -9446,6 +9450,7 @@ pub fn default_bag() -> indexmap::IndexMap<String, ftd::interpreter::Thing> {
             })
         )
     ])
+    .chain(ftd::interpreter::things::stdlib::stdlib_bag())
     .collect()
 }
 
@@ -9860,6 +9865,30 @@ pub fn code_function() -> ftd::interpreter::ComponentDefinition {
                     ftd::interpreter::Kind::boolean().into_kind_data(),
                     ftd::interpreter::Value::Boolean { value: false }.into_property_value(false, 0),
                 ),
+                // line numbers, starting from 1
+                ftd::interpreter::Argument::default(
+                    "highlight-lines",
+                    ftd::interpreter::Kind::integer()
+                        .into_list()
+                        .into_kind_data(),
+                ),
+                ftd::interpreter::Argument::default_with_value(
+                    "diff",
+                    ftd::interpreter::Kind::boolean().into_kind_data(),
+                    ftd::interpreter::Value::Boolean { value: false }.into_property_value(false, 0),
+                ),
+                // line numbers, consecutive ones are collapsed together
+                ftd::interpreter::Argument::default(
+                    "collapse",
+                    ftd::interpreter::Kind::integer()
+                        .into_list()
+                        .into_kind_data(),
+                ),
+                ftd::interpreter::Argument::default_with_value(
+                    "copy-button",
+                    ftd::interpreter::Kind::boolean().into_kind_data(),
+                    ftd::interpreter::Value::Boolean { value: false }.into_property_value(false, 0),
+                ),
             ],
         ]
         .concat()
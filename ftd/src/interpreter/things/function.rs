@@ -89,6 +89,33 @@ impl Function {
             function.line_number,
         )?);
 
+        let statements = fastn_grammar::evalexpr::build_statements(
+            function.definition.value.as_str(),
+        )
+        .map_err(|e| ftd::interpreter::Error::ParseError {
+            message: format!("Invalid body for function `{}`: {}", function.name, e),
+            doc_id: doc.name.to_string(),
+            line_number: function.definition.line_number,
+        })?;
+        let scope = arguments
+            .iter()
+            .map(|argument| (argument.name.to_string(), argument.kind.kind.clone()))
+            .collect();
+        // The body starts after the headers and the empty line that ends them
+        let body_line_number = arguments
+            .iter()
+            .map(|argument| argument.line_number)
+            .fold(function.line_number, usize::max)
+            + 2;
+        try_ok_state!(Function::check_statements(
+            statements.as_slice(),
+            &scope,
+            function.name.as_str(),
+            &kind,
+            doc,
+            body_line_number,
+        )?);
+
         let expression = vec![Expression {
             expression: function.definition.value.to_string(),
             line_number: function.definition.line_number,
@@ -104,6 +131,153 @@ impl Function {
         )))
     }
 
+    /// Checks the kinds of `let` bindings, `for` loops and `return` statements in the body.
+    /// Kinds that can't be known without evaluating the body are checked when it is resolved.
+    /// `body_line_number` is the line the body starts at, errors are reported at the line of the
+    /// statement.
+    fn check_statements(
+        statements: &[fastn_grammar::evalexpr::Statement],
+        scope: &ftd::Map<ftd::interpreter::Kind>,
+        function_name: &str,
+        return_kind: &ftd::interpreter::KindData,
+        doc: &mut ftd::interpreter::TDoc,
+        body_line_number: usize,
+    ) -> ftd::interpreter::Result<ftd::interpreter::StateWithThing<()>> {
+        use fastn_grammar::evalexpr::Statement;
+
+        let mut scope = scope.clone();
+        // `let` can't declare a name twice in the same block in JS
+        let mut declared = std::collections::HashSet::new();
+        for statement in statements {
+            match statement {
+                Statement::Expression(_) => {}
+                Statement::Let {
+                    name,
+                    kind,
+                    value,
+                    line,
+                } => {
+                    let line_number = body_line_number + line;
+                    if !declared.insert(name.as_str()) {
+                        return ftd::interpreter::utils::e2(
+                            format!(
+                                "`{}` is already declared in this block of function `{}`",
+                                name, function_name
+                            ),
+                            doc.name,
+                            line_number,
+                        );
+                    }
+                    let value_kind = expression_kind(value, &scope);
+                    let kind = match kind {
+                        Some(kind) => {
+                            let kind = try_ok_state!(ftd::interpreter::KindData::from_ast_kind(
+                                ftd::ast::VariableKind::get_kind(kind, doc.name, line_number)?,
+                                &Default::default(),
+                                doc,
+                                line_number,
+                            )?)
+                            .kind;
+                            check_kind(&kind, value_kind.as_ref(), doc.name, line_number)?;
+                            Some(kind)
+                        }
+                        None => value_kind,
+                    };
+                    match kind {
+                        Some(kind) => scope.insert(name.to_string(), kind),
+                        None => scope.remove(name),
+                    };
+                }
+                Statement::If {
+                    then, otherwise, ..
+                } => {
+                    for block in [then, otherwise] {
+                        try_ok_state!(Function::check_statements(
+                            block,
+                            &scope,
+                            function_name,
+                            return_kind,
+                            doc,
+                            body_line_number,
+                        )?);
+                    }
+                }
+                Statement::For {
+                    item,
+                    list,
+                    body,
+                    line,
+                } => {
+                    let mut body_scope = scope.clone();
+                    match expression_kind(list, &scope) {
+                        Some(kind) if kind.ref_inner().is_list() => {
+                            body_scope.insert(
+                                item.to_string(),
+                                kind.ref_inner().ref_inner_list().clone(),
+                            );
+                        }
+                        Some(kind) => {
+                            return ftd::interpreter::utils::e2(
+                                format!(
+                                    "Expected a list to loop over in function `{}`, found: `{:?}`",
+                                    function_name, kind
+                                ),
+                                doc.name,
+                                body_line_number + line,
+                            );
+                        }
+                        None => {
+                            body_scope.remove(item);
+                        }
+                    }
+                    try_ok_state!(Function::check_statements(
+                        body,
+                        &body_scope,
+                        function_name,
+                        return_kind,
+                        doc,
+                        body_line_number,
+                    )?);
+                }
+                Statement::Return { value: None, line } if !return_kind.is_void() => {
+                    return ftd::interpreter::utils::e2(
+                        format!(
+                            "Function `{}` must return a value of kind `{:?}`",
+                            function_name, return_kind.kind
+                        ),
+                        doc.name,
+                        body_line_number + line,
+                    );
+                }
+                Statement::Return {
+                    value: Some(_),
+                    line,
+                } if return_kind.is_void() => {
+                    return ftd::interpreter::utils::e2(
+                        format!(
+                            "Function `{}` is of kind `void` and can't return a value",
+                            function_name
+                        ),
+                        doc.name,
+                        body_line_number + line,
+                    );
+                }
+                Statement::Return {
+                    value: Some(value),
+                    line,
+                } => check_kind(
+                    &return_kind.kind,
+                    expression_kind(value, &scope).as_ref(),
+                    doc.name,
+                    body_line_number + line,
+                )?,
+                Statement::Return { value: None, .. } => {}
+            }
+        }
+
+        Ok(ftd::interpreter::StateWithThing::new_thing(()))
+    }
+
     pub(crate) fn resolve(
         &self,
         _kind: &ftd::interpreter::KindData,
@@ -172,10 +346,12 @@ impl Function {
             evalexpr_context.set_value(key.to_string(), context.value.to_owned())?;
         }
 
-        let expression = self.convert_to_evalexpr_expression();
+        let statements = fastn_grammar::evalexpr::build_statements(
+            self.convert_to_evalexpr_expression().as_str(),
+        )?;
 
-        let eval = fastn_grammar::evalexpr::eval_with_context_mut(
-            expression.as_str(),
+        let eval = fastn_grammar::evalexpr::eval_statements_with_context_mut(
+            statements.as_slice(),
             &mut evalexpr_context,
         )?;
 
@@ -185,7 +361,7 @@ impl Function {
                     let value = ftd::interpreter::Value::from_evalexpr_value(
                         evalexpr_context.get_value(key.as_str()).unwrap().clone(),
                         &context.kind,
-                        doc.name,
+                        doc,
                         line_number,
                     )?;
                     // TODO: insert new value in doc.bag
@@ -207,7 +383,7 @@ impl Function {
             return Ok(Some(ftd::interpreter::Value::from_evalexpr_value(
                 eval,
                 &self.return_kind.kind,
-                doc.name,
+                doc,
                 line_number,
             )?));
         }
@@ -225,6 +401,51 @@ impl Function {
     }
 }
 
+/// The kind of a literal or of a variable in scope, if it can be known without evaluating.
+fn expression_kind(
+    node: &fastn_grammar::evalexpr::ExprNode,
+    scope: &ftd::Map<ftd::interpreter::Kind>,
+) -> Option<ftd::interpreter::Kind> {
+    use fastn_grammar::evalexpr::{Operator, Value};
+
+    match node.operator() {
+        Operator::RootNode if node.children().len() == 1 => {
+            expression_kind(node.children().first()?, scope)
+        }
+        Operator::Tuple => Some(expression_kind(node.children().first()?, scope)?.into_list()),
+        Operator::Const { value } => match value {
+            Value::String(_) => Some(ftd::interpreter::Kind::string()),
+            Value::Int(_) => Some(ftd::interpreter::Kind::integer()),
+            Value::Float(_) => Some(ftd::interpreter::Kind::decimal()),
+            Value::Boolean(_) => Some(ftd::interpreter::Kind::boolean()),
+            _ => None,
+        },
+        Operator::VariableIdentifierRead { identifier } => scope.get(identifier).cloned(),
+        _ => None,
+    }
+}
+
+fn check_kind(
+    expected: &ftd::interpreter::Kind,
+    found: Option<&ftd::interpreter::Kind>,
+    doc_id: &str,
+    line_number: usize,
+) -> ftd::interpreter::Result<()> {
+    match found {
+        Some(found)
+            if !(expected.is_same_as(found)
+                || expected.ref_inner().is_decimal() && found.is_integer()) =>
+        {
+            ftd::interpreter::utils::e2(
+                format!("Expected kind: `{:?}` found: `{:?}`", expected, found),
+                doc_id,
+                line_number,
+            )
+        }
+        _ => Ok(()),
+    }
+}
+
 /*
 Todo: Convert Expression into
     #[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
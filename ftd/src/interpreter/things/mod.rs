@@ -5,6 +5,7 @@ pub(crate) mod function;
 pub(crate) mod kind;
 pub(crate) mod or_type;
 pub(crate) mod record;
+pub(crate) mod stdlib;
 pub(crate) mod value;
 pub(crate) mod variable;
 pub(crate) mod web_component;
@@ -400,6 +400,12 @@ impl Field {
                 line_number,
             ) {
                 Ok(swt) => Ok(swt),
+                // neither a component nor a web component has the name
+                Err(ftd::interpreter::Error::NotFound { .. })
+                    if matches!(e1, ftd::interpreter::Error::NotFound { .. }) =>
+                {
+                    Err(e1)
+                }
                 Err(e2) => {
                     ftd::interpreter::utils::e2(format!("{:?} {:?}", e1, e2), doc.name, line_number)
                 }
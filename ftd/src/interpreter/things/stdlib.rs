@@ -0,0 +1,959 @@
+/**
+The standard library of string, list, number and date functions.
+
+Every function is available as `ftd.<name>` in expressions and function bodies, for example
+`ftd.upper(name)`. The same functions are implemented in `fastn-js/js/stdlib.js` for the
+browser and the two implementations must be kept in sync.
+
+Functions that don't work on lists of arbitrary kinds are also declared as typed functions in
+the `ftd` module, so they can be called directly: `$ftd.upper(s = $name)`.
+
+Dates are ISO 8601 strings without time zones, `2023-10-05` or `2023-10-05T14:30:00`.
+**/
+use fastn_grammar::evalexpr::{EvalexprError, EvalexprResult, Function, Value};
+
+pub fn stdlib_functions() -> ftd::Map<Function> {
+    std::iter::IntoIterator::into_iter([
+        // Strings
+        ("split", Function::new(split)),
+        ("join", Function::new(join)),
+        ("replace", Function::new(replace)),
+        (
+            "upper",
+            Function::new(|argument| Ok(Value::from(argument.as_string()?.to_uppercase()))),
+        ),
+        (
+            "lower",
+            Function::new(|argument| Ok(Value::from(argument.as_string()?.to_lowercase()))),
+        ),
+        (
+            "trim",
+            Function::new(|argument| Ok(Value::from(argument.as_string()?.trim()))),
+        ),
+        ("contains", Function::new(contains)),
+        (
+            "starts_with",
+            Function::new(|argument| {
+                let [s, prefix] = fixed::<2>(argument)?;
+                Ok(Value::from(
+                    s.as_string()?.starts_with(&prefix.as_string()?),
+                ))
+            }),
+        ),
+        (
+            "ends_with",
+            Function::new(|argument| {
+                let [s, suffix] = fixed::<2>(argument)?;
+                Ok(Value::from(s.as_string()?.ends_with(&suffix.as_string()?)))
+            }),
+        ),
+        // Lists
+        ("slice", Function::new(slice)),
+        (
+            "reverse",
+            Function::new(|argument| {
+                let mut items = list(argument)?;
+                items.reverse();
+                Ok(Value::Tuple(items))
+            }),
+        ),
+        (
+            "sort",
+            Function::new(|argument| sort(list(argument)?, None)),
+        ),
+        (
+            "sort_by",
+            Function::new(|argument| {
+                let [items, field] = fixed::<2>(argument)?;
+                sort(list(&items)?, Some(field.as_string()?.as_str()))
+            }),
+        ),
+        ("pluck", Function::new(pluck)),
+        ("filter_by", Function::new(filter_by)),
+        // Numbers
+        (
+            "format_decimal",
+            Function::new(|argument| {
+                let [value, digits] = fixed::<2>(argument)?;
+                Ok(Value::from(format_number(
+                    value.as_number()?,
+                    digits_argument(&digits)?,
+                    "en",
+                    false,
+                )))
+            }),
+        ),
+        (
+            "format_number",
+            Function::new(|argument| {
+                let [value, digits, locale] = fixed::<3>(argument)?;
+                Ok(Value::from(format_number(
+                    value.as_number()?,
+                    digits_argument(&digits)?,
+                    locale.as_string()?.as_str(),
+                    true,
+                )))
+            }),
+        ),
+        ("format_currency", Function::new(format_currency)),
+        // Dates
+        (
+            "format_date",
+            Function::new(|argument| {
+                let [date, format] = fixed::<2>(argument)?;
+                Ok(Value::from(
+                    DateTime::parse_iso(date.as_string()?.as_str())?
+                        .format(format.as_string()?.as_str()),
+                ))
+            }),
+        ),
+        (
+            "parse_date",
+            Function::new(|argument| {
+                let [value, format] = fixed::<2>(argument)?;
+                Ok(Value::from(
+                    DateTime::parse(value.as_string()?.as_str(), format.as_string()?.as_str())?
+                        .to_iso(),
+                ))
+            }),
+        ),
+        (
+            "add_days",
+            Function::new(|argument| {
+                let [date, days] = fixed::<2>(argument)?;
+                Ok(Value::from(
+                    DateTime::parse_iso(date.as_string()?.as_str())?
+                        .add_days(days.as_int()?)
+                        .to_iso(),
+                ))
+            }),
+        ),
+        (
+            "days_between",
+            Function::new(|argument| {
+                let [from, to] = fixed::<2>(argument)?;
+                let from = DateTime::parse_iso(from.as_string()?.as_str())?;
+                let to = DateTime::parse_iso(to.as_string()?.as_str())?;
+                Ok(Value::Int(to.days() - from.days()))
+            }),
+        ),
+    ])
+    .map(|(name, function)| (format!("ftd.{}", name), function))
+    .collect()
+}
+
+/// The typed declarations of the functions that can be called directly.
+pub fn stdlib_bag() -> Vec<(String, ftd::interpreter::Thing)> {
+    use ftd::interpreter::Kind;
+
+    let string = || (Kind::string(), None);
+    let with_default = |kind: Kind, value: ftd::interpreter::Value| (kind, Some(value));
+
+    vec![
+        function(
+            "split",
+            Kind::string().into_list(),
+            vec![("s", string()), ("sep", string())],
+        ),
+        function(
+            "join",
+            Kind::string(),
+            vec![
+                ("items", (Kind::string().into_list(), None)),
+                ("sep", string()),
+            ],
+        ),
+        function(
+            "replace",
+            Kind::string(),
+            vec![("s", string()), ("from", string()), ("to", string())],
+        ),
+        function("upper", Kind::string(), vec![("s", string())]),
+        function("lower", Kind::string(), vec![("s", string())]),
+        function("trim", Kind::string(), vec![("s", string())]),
+        function(
+            "starts-with",
+            Kind::boolean(),
+            vec![("s", string()), ("prefix", string())],
+        ),
+        function(
+            "ends-with",
+            Kind::boolean(),
+            vec![("s", string()), ("suffix", string())],
+        ),
+        function(
+            "format-decimal",
+            Kind::string(),
+            vec![
+                ("value", (Kind::decimal(), None)),
+                (
+                    "digits",
+                    with_default(
+                        Kind::integer(),
+                        ftd::interpreter::Value::Integer { value: 2 },
+                    ),
+                ),
+            ],
+        ),
+        function(
+            "format-number",
+            Kind::string(),
+            vec![
+                ("value", (Kind::decimal(), None)),
+                (
+                    "digits",
+                    with_default(
+                        Kind::integer(),
+                        ftd::interpreter::Value::Integer { value: 0 },
+                    ),
+                ),
+                (
+                    "locale",
+                    with_default(Kind::string(), ftd::interpreter::Value::new_string("en")),
+                ),
+            ],
+        ),
+        function(
+            "format-currency",
+            Kind::string(),
+            vec![
+                ("value", (Kind::decimal(), None)),
+                ("currency", string()),
+                (
+                    "locale",
+                    with_default(Kind::string(), ftd::interpreter::Value::new_string("en")),
+                ),
+            ],
+        ),
+        function(
+            "format-date",
+            Kind::string(),
+            vec![("date", string()), ("format", string())],
+        ),
+        function(
+            "parse-date",
+            Kind::string(),
+            vec![("value", string()), ("format", string())],
+        ),
+        function(
+            "add-days",
+            Kind::string(),
+            vec![("date", string()), ("days", (Kind::integer(), None))],
+        ),
+        function(
+            "days-between",
+            Kind::integer(),
+            vec![("from", string()), ("to", string())],
+        ),
+    ]
+}
+
+#[allow(clippy::type_complexity)]
+fn function(
+    name: &str,
+    return_kind: ftd::interpreter::Kind,
+    arguments: Vec<(
+        &str,
+        (ftd::interpreter::Kind, Option<ftd::interpreter::Value>),
+    )>,
+) -> (String, ftd::interpreter::Thing) {
+    use itertools::Itertools;
+
+    let full_name = format!("ftd#{}", name);
+    let expression = format!(
+        "ftd.{}({})",
+        name.replace('-', "_"),
+        arguments.iter().map(|(name, _)| name).join(", ")
+    );
+    (
+        full_name.clone(),
+        ftd::interpreter::Thing::Function(ftd::interpreter::Function {
+            name: full_name,
+            return_kind: return_kind.into_kind_data(),
+            arguments: arguments
+                .into_iter()
+                .map(|(name, (kind, value))| ftd::interpreter::Argument {
+                    name: name.to_string(),
+                    kind: kind.into_kind_data(),
+                    mutable: false,
+                    value: value.map(|value| ftd::interpreter::PropertyValue::Value {
+                        value,
+                        is_mutable: false,
+                        line_number: 0,
+                    }),
+                    access_modifier: Default::default(),
+                    line_number: 0,
+                })
+                .collect_vec(),
+            expression: vec![ftd::interpreter::things::function::Expression {
+                expression,
+                line_number: 0,
+            }],
+            js: None,
+            line_number: 0,
+            external_implementation: true,
+        }),
+    )
+}
+
+fn error<T>(message: String) -> EvalexprResult<T> {
+    Err(EvalexprError::CustomMessage(message))
+}
+
+fn fixed<const N: usize>(argument: &Value) -> EvalexprResult<[Value; N]> {
+    let arguments = argument.as_fixed_len_tuple(N)?;
+    Ok(arguments
+        .try_into()
+        .unwrap_or_else(|_| unreachable!("the length is checked above")))
+}
+
+fn list(value: &Value) -> EvalexprResult<Vec<Value>> {
+    match value {
+        Value::Tuple(items) => Ok(items.clone()),
+        Value::Empty => Ok(vec![]),
+        value => Err(EvalexprError::expected_tuple(value.clone())),
+    }
+}
+
+fn digits_argument(value: &Value) -> EvalexprResult<usize> {
+    match value.as_int()? {
+        digits @ 0..=20 => Ok(digits as usize),
+        digits => error(format!("Expected 0 to 20 digits, found: {}", digits)),
+    }
+}
+
+/// A record field by its ftd name, `first-name`, or by its expression name, `first_name`.
+fn field(value: &Value, name: &str) -> EvalexprResult<Value> {
+    match value {
+        Value::Record(record) => Ok(record
+            .get(name)
+            .or_else(|| record.get(&name.replace('_', "-")))
+            .cloned()
+            .unwrap_or(Value::Empty)),
+        value => Err(EvalexprError::expected_record(value.clone())),
+    }
+}
+
+fn display(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.to_string(),
+        Value::Empty => "".to_string(),
+        value => value.to_string(),
+    }
+}
+
+fn split(argument: &Value) -> EvalexprResult<Value> {
+    let [s, separator] = fixed::<2>(argument)?;
+    let (s, separator) = (s.as_string()?, separator.as_string()?);
+    Ok(Value::Tuple(if separator.is_empty() {
+        s.chars().map(|c| Value::from(c.to_string())).collect()
+    } else {
+        s.split(separator.as_str()).map(Value::from).collect()
+    }))
+}
+
+fn join(argument: &Value) -> EvalexprResult<Value> {
+    let [items, separator] = fixed::<2>(argument)?;
+    Ok(Value::from(
+        list(&items)?
+            .iter()
+            .map(display)
+            .collect::<Vec<_>>()
+            .join(separator.as_string()?.as_str()),
+    ))
+}
+
+fn replace(argument: &Value) -> EvalexprResult<Value> {
+    let [s, from, to] = fixed::<3>(argument)?;
+    Ok(Value::from(s.as_string()?.replace(
+        from.as_string()?.as_str(),
+        to.as_string()?.as_str(),
+    )))
+}
+
+fn contains(argument: &Value) -> EvalexprResult<Value> {
+    let [value, part] = fixed::<2>(argument)?;
+    Ok(Value::from(match value {
+        Value::String(s) => s.contains(&part.as_string()?),
+        value => list(&value)?.iter().any(|item| same_value(item, &part)),
+    }))
+}
+
+/// Negative indices count from the end, and indices are clamped to the length, as in JS.
+fn slice(argument: &Value) -> EvalexprResult<Value> {
+    let [value, start, end] = fixed::<3>(argument)?;
+    let index = |index: i64, len: usize| {
+        if index < 0 {
+            (len as i64 + index).max(0) as usize
+        } else {
+            (index as usize).min(len)
+        }
+    };
+    let (start, end) = (start.as_int()?, end.as_int()?);
+    Ok(match value {
+        Value::String(s) => {
+            let chars = s.chars().collect::<Vec<_>>();
+            let (start, end) = (index(start, chars.len()), index(end, chars.len()));
+            Value::from(chars[start..end.max(start)].iter().collect::<String>())
+        }
+        value => {
+            let items = list(&value)?;
+            let (start, end) = (index(start, items.len()), index(end, items.len()));
+            Value::Tuple(items[start..end.max(start)].to_vec())
+        }
+    })
+}
+
+/// Sorts numbers, strings or booleans, or records by a field holding one of those. The sort is
+/// stable.
+fn sort(items: Vec<Value>, by: Option<&str>) -> EvalexprResult<Value> {
+    let mut keyed = vec![];
+    for item in items {
+        let key = match by {
+            Some(name) => field(&item, name)?,
+            None => item.clone(),
+        };
+        keyed.push((key, item));
+    }
+
+    let mut error = None;
+    keyed.sort_by(|(a, _), (c, _)| match (a, c) {
+        (Value::String(a), Value::String(c)) => a.cmp(c),
+        (Value::Boolean(a), Value::Boolean(c)) => a.cmp(c),
+        (a, c) if a.is_number() && c.is_number() => a
+            .as_number()
+            .unwrap()
+            .partial_cmp(&c.as_number().unwrap())
+            .unwrap_or(std::cmp::Ordering::Equal),
+        _ => {
+            error = Some("Can only sort numbers, strings or booleans of one kind");
+            std::cmp::Ordering::Equal
+        }
+    });
+
+    match error {
+        Some(message) => self::error(message.to_string()),
+        None => Ok(Value::Tuple(
+            keyed.into_iter().map(|(_, item)| item).collect(),
+        )),
+    }
+}
+
+/// `ftd.pluck(people, "name")` maps a list of records to one of their fields.
+fn pluck(argument: &Value) -> EvalexprResult<Value> {
+    let [items, name] = fixed::<2>(argument)?;
+    let name = name.as_string()?;
+    Ok(Value::Tuple(
+        list(&items)?
+            .iter()
+            .map(|item| field(item, name.as_str()))
+            .collect::<EvalexprResult<Vec<_>>>()?,
+    ))
+}
+
+/// `ftd.filter_by(people, "city", "Delhi")` keeps the records with a field equal to a value.
+fn filter_by(argument: &Value) -> EvalexprResult<Value> {
+    let [items, name, value] = fixed::<3>(argument)?;
+    let name = name.as_string()?;
+    let mut result = vec![];
+    for item in list(&items)? {
+        if same_value(&field(&item, name.as_str())?, &value) {
+            result.push(item);
+        }
+    }
+    Ok(Value::Tuple(result))
+}
+
+/// Integers and decimals are equal if their numeric values are, as in JS.
+fn same_value(a: &Value, b: &Value) -> bool {
+    if a.is_number() && b.is_number() {
+        a.as_number().ok() == b.as_number().ok()
+    } else {
+        a == b
+    }
+}
+
+/// Rounds half away from zero to `digits` decimal places.
+fn round(value: f64, digits: usize) -> f64 {
+    let factor = 10f64.powi(digits as i32);
+    value.signum() * ((value.abs() * factor).round() / factor)
+}
+
+/// The group separator, decimal separator and whether the locale groups digits the Indian way,
+/// `12,34,567`.
+fn separators(locale: &str) -> (&'static str, &'static str, bool) {
+    let locale = locale.to_lowercase().replace('_', "-");
+    let language = locale.split('-').next().unwrap_or_default();
+    match language {
+        _ if locale == "en-in" || language == "hi" => (",", ".", true),
+        "de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da" => (".", ",", false),
+        "fr" => ("\u{202f}", ",", false),
+        "ru" | "pl" | "sv" | "cs" | "fi" | "nb" | "uk" => ("\u{a0}", ",", false),
+        _ => (",", ".", false),
+    }
+}
+
+fn format_number(value: f64, digits: usize, locale: &str, grouped: bool) -> String {
+    let (group, decimal, indian) = separators(locale);
+    let value = round(value, digits);
+    let fixed = format!("{:.*}", digits, value.abs());
+    let (integer, fraction) = fixed.split_once('.').unwrap_or((fixed.as_str(), ""));
+
+    let mut groups = vec![];
+    let mut rest = integer;
+    let mut size = 3;
+    while grouped && rest.len() > size {
+        let (head, tail) = rest.split_at(rest.len() - size);
+        groups.push(tail);
+        rest = head;
+        if indian {
+            size = 2;
+        }
+    }
+    groups.push(rest);
+    groups.reverse();
+
+    format!(
+        "{}{}{}{}",
+        if value < 0.0 { "-" } else { "" },
+        groups.join(group),
+        if fraction.is_empty() { "" } else { decimal },
+        fraction
+    )
+}
+
+fn format_currency(argument: &Value) -> EvalexprResult<Value> {
+    let [value, currency, locale] = fixed::<3>(argument)?;
+    let (value, currency, locale) = (
+        value.as_number()?,
+        currency.as_string()?.to_uppercase(),
+        locale.as_string()?,
+    );
+    let symbol = match currency.as_str() {
+        "USD" => "$",
+        "EUR" => "€",
+        "GBP" => "£",
+        "INR" => "₹",
+        "JPY" | "CNY" => "¥",
+        "KRW" => "₩",
+        "AUD" => "A$",
+        "CAD" => "CA$",
+        currency => currency,
+    };
+    let digits = if matches!(currency.as_str(), "JPY" | "KRW") {
+        0
+    } else {
+        2
+    };
+    let number = format_number(value.abs(), digits, locale.as_str(), true);
+    let sign = if round(value, digits) < 0.0 { "-" } else { "" };
+    Ok(Value::from(if separators(locale.as_str()).1 == "," {
+        format!("{}{}\u{a0}{}", sign, number, symbol)
+    } else if symbol == currency {
+        format!("{}{}\u{a0}{}", sign, symbol, number)
+    } else {
+        format!("{}{}{}", sign, symbol, number)
+    }))
+}
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+const WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+#[derive(Debug, PartialEq)]
+struct DateTime {
+    year: i64,
+    month: i64,
+    day: i64,
+    hour: i64,
+    minute: i64,
+    second: i64,
+    has_time: bool,
+}
+
+impl DateTime {
+    fn new(
+        (year, month, day): (i64, i64, i64),
+        (hour, minute, second): (i64, i64, i64),
+        has_time: bool,
+    ) -> EvalexprResult<DateTime> {
+        if !(1..=12).contains(&month)
+            || !(1..=days_in_month(year, month)).contains(&day)
+            || !(0..24).contains(&hour)
+            || !(0..60).contains(&minute)
+            || !(0..60).contains(&second)
+        {
+            return error(format!(
+                "Invalid date: {:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+                year, month, day, hour, minute, second
+            ));
+        }
+        Ok(DateTime {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            has_time,
+        })
+    }
+
+    /// Parses `YYYY-MM-DD`, `YYYY-MM-DDTHH:MM` or `YYYY-MM-DDTHH:MM:SS`.
+    fn parse_iso(value: &str) -> EvalexprResult<DateTime> {
+        let invalid = || error(format!("Expected an ISO 8601 date, found: `{}`", value));
+        let number = |s: &str, len: usize| -> Option<i64> {
+            if s.len() == len && s.bytes().all(|b| b.is_ascii_digit()) {
+                s.parse().ok()
+            } else {
+                None
+            }
+        };
+
+        let (date, time) = match value.split_once(['T', ' ']) {
+            Some((date, time)) => (date, Some(time)),
+            None => (value, None),
+        };
+        let date = date.split('-').collect::<Vec<_>>();
+        let time = time.map(|time| time.split(':').collect::<Vec<_>>());
+        let (Some(year), Some(month), Some(day)) = (
+            date.first().and_then(|s| number(s, 4)),
+            date.get(1).and_then(|s| number(s, 2)),
+            date.get(2).and_then(|s| number(s, 2)),
+        ) else {
+            return invalid();
+        };
+        if date.len() != 3 {
+            return invalid();
+        }
+        let (hour, minute, second) = match &time {
+            None => (0, 0, 0),
+            Some(time) if time.len() == 2 || time.len() == 3 => {
+                let parts = time
+                    .iter()
+                    .map(|s| number(s, 2))
+                    .collect::<Option<Vec<_>>>();
+                match parts {
+                    Some(parts) => (parts[0], parts[1], parts.get(2).copied().unwrap_or(0)),
+                    None => return invalid(),
+                }
+            }
+            Some(_) => return invalid(),
+        };
+        DateTime::new((year, month, day), (hour, minute, second), time.is_some())
+    }
+
+    /// Parses a date with the directives of [`DateTime::format`]. Fields missing from the
+    /// format default to `1970-01-01T00:00:00`.
+    fn parse(value: &str, format: &str) -> EvalexprResult<DateTime> {
+        let invalid = || {
+            error(format!(
+                "`{}` does not match the date format `{}`",
+                value, format
+            ))
+        };
+        let (mut year, mut month, mut day) = (1970, 1, 1);
+        let (mut hour, mut minute, mut second) = (0, 0, 0);
+        let (mut pm, mut has_time) = (None, false);
+        let mut input = value;
+        let mut directives = format.chars();
+
+        while let Some(c) = directives.next() {
+            if c != '%' {
+                match input.strip_prefix(c) {
+                    Some(rest) => input = rest,
+                    None => return invalid(),
+                }
+                continue;
+            }
+            let directive = directives.next().unwrap_or('%');
+            let mut digits = |max: usize| -> Option<i64> {
+                let len = input
+                    .bytes()
+                    .take(max)
+                    .take_while(|b| b.is_ascii_digit())
+                    .count();
+                let (number, rest) = input.split_at(len);
+                input = rest;
+                number.parse().ok()
+            };
+            let parsed = match directive {
+                'Y' => digits(4).map(|v| year = v),
+                'y' => digits(2).map(|v| year = 2000 + v),
+                'm' => digits(2).map(|v| month = v),
+                'd' | 'e' => digits(2).map(|v| day = v),
+                'H' | 'I' => digits(2).map(|v| {
+                    hour = v;
+                    has_time = true;
+                }),
+                'M' => digits(2).map(|v| {
+                    minute = v;
+                    has_time = true;
+                }),
+                'S' => digits(2).map(|v| {
+                    second = v;
+                    has_time = true;
+                }),
+                'p' => {
+                    let upper = input.get(..2).map(str::to_uppercase);
+                    match upper.as_deref() {
+                        Some(marker @ ("AM" | "PM")) => {
+                            pm = Some(marker == "PM");
+                            input = &input[2..];
+                            Some(())
+                        }
+                        _ => None,
+                    }
+                }
+                'b' | 'B' | 'a' | 'A' => {
+                    let names: &[&str] = if matches!(directive, 'b' | 'B') {
+                        &MONTHS
+                    } else {
+                        &WEEKDAYS
+                    };
+                    let full = matches!(directive, 'B' | 'A');
+                    names.iter().enumerate().find_map(|(index, name)| {
+                        let name = if full { name } else { &name[..3] };
+                        let prefix = input.get(..name.len())?;
+                        if !prefix.eq_ignore_ascii_case(name) {
+                            return None;
+                        }
+                        input = &input[name.len()..];
+                        if matches!(directive, 'b' | 'B') {
+                            month = index as i64 + 1;
+                        }
+                        Some(())
+                    })
+                }
+                '%' => input.strip_prefix('%').map(|rest| input = rest),
+                _ => None,
+            };
+            if parsed.is_none() {
+                return invalid();
+            }
+        }
+
+        if !input.is_empty() {
+            return invalid();
+        }
+        match pm {
+            Some(_) if !(1..=12).contains(&hour) => return invalid(),
+            Some(true) if hour < 12 => hour += 12,
+            Some(false) if hour == 12 => hour = 0,
+            _ => {}
+        }
+        DateTime::new((year, month, day), (hour, minute, second), has_time)
+    }
+
+    fn to_iso(&self) -> String {
+        let date = format!("{:04}-{:02}-{:02}", self.year, self.month, self.day);
+        if self.has_time {
+            format!(
+                "{}T{:02}:{:02}:{:02}",
+                date, self.hour, self.minute, self.second
+            )
+        } else {
+            date
+        }
+    }
+
+    /// Formats with `strftime` like directives: `%Y`, `%y`, `%m`, `%d`, `%e`, `%H`, `%I`, `%M`,
+    /// `%S`, `%p`, `%b`, `%B`, `%a`, `%A`, `%j` and `%%`. Names are in English.
+    fn format(&self, format: &str) -> String {
+        let mut result = String::new();
+        let mut directives = format.chars();
+        while let Some(c) = directives.next() {
+            if c != '%' {
+                result.push(c);
+                continue;
+            }
+            let month = MONTHS[self.month as usize - 1];
+            let weekday = WEEKDAYS[(self.days() + 4).rem_euclid(7) as usize];
+            match directives.next() {
+                Some('Y') => result.push_str(&format!("{:04}", self.year)),
+                Some('y') => result.push_str(&format!("{:02}", self.year.rem_euclid(100))),
+                Some('m') => result.push_str(&format!("{:02}", self.month)),
+                Some('d') => result.push_str(&format!("{:02}", self.day)),
+                Some('e') => result.push_str(&self.day.to_string()),
+                Some('H') => result.push_str(&format!("{:02}", self.hour)),
+                Some('I') => result.push_str(&format!("{:02}", (self.hour + 11) % 12 + 1)),
+                Some('M') => result.push_str(&format!("{:02}", self.minute)),
+                Some('S') => result.push_str(&format!("{:02}", self.second)),
+                Some('p') => result.push_str(if self.hour < 12 { "AM" } else { "PM" }),
+                Some('b') => result.push_str(&month[..3]),
+                Some('B') => result.push_str(month),
+                Some('a') => result.push_str(&weekday[..3]),
+                Some('A') => result.push_str(weekday),
+                Some('j') => result.push_str(&format!(
+                    "{:03}",
+                    self.days() - days_from_civil(self.year, 1, 1) + 1
+                )),
+                Some('%') => result.push('%'),
+                Some(c) => {
+                    result.push('%');
+                    result.push(c);
+                }
+                None => result.push('%'),
+            }
+        }
+        result
+    }
+
+    /// Days since 1970-01-01.
+    fn days(&self) -> i64 {
+        days_from_civil(self.year, self.month, self.day)
+    }
+
+    fn add_days(self, days: i64) -> DateTime {
+        let (year, month, day) = civil_from_days(self.days() + days);
+        DateTime {
+            year,
+            month,
+            day,
+            ..self
+        }
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// source: http://howardhinnant.github.io/date_algorithms.html
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod test {
+    use fastn_grammar::evalexpr::{ContextWithMutableFunctions, Value};
+    use pretty_assertions::assert_eq;
+
+    #[track_caller]
+    fn e(expression: &str) -> Result<Value, String> {
+        let mut context = fastn_grammar::evalexpr::HashMapContext::new();
+        for (name, function) in super::stdlib_functions() {
+            context.set_function(name, function).unwrap();
+        }
+        fastn_grammar::evalexpr::eval_with_context(expression, &context).map_err(|e| e.to_string())
+    }
+
+    #[track_caller]
+    fn s(expression: &str, expected: &str) {
+        assert_eq!(e(expression), Ok(Value::from(expected)), "{}", expression);
+    }
+
+    #[test]
+    fn strings_and_lists() {
+        s(r#"ftd.join(ftd.split("a,,b", ","), "|")"#, "a||b");
+        s(r#"ftd.join(ftd.split("héllo", ""), " ")"#, "h é l l o");
+        s(r#"ftd.join((1, 2.5, true, "x"), "-")"#, "1-2.5-true-x");
+        s(r#"ftd.replace("a.b.c", ".", "$&")"#, "a$&b$&c");
+        s(r#"ftd.slice("hello", -3, 100)"#, "llo");
+        s(r#"ftd.slice("hello", 3, 1)"#, "");
+        s(r#"ftd.join(ftd.sort(("b", "c", "a")), "")"#, "abc");
+        s(
+            r#"ftd.join(ftd.reverse(ftd.slice((1, 2, 3, 4), 1, -1)), "")"#,
+            "32",
+        );
+        assert_eq!(e(r#"ftd.contains((1, 2), 2.0)"#), Ok(Value::from(true)));
+        assert_eq!(
+            e(r#"ftd.sort((1, "a"))"#),
+            Err("Error: Can only sort numbers, strings or booleans of one kind".to_string())
+        );
+    }
+
+    #[test]
+    fn numbers() {
+        s("ftd.format_decimal(3.14159, 3)", "3.142");
+        s("ftd.format_decimal(-0.001, 2)", "0.00");
+        s(r#"ftd.format_number(1234567.891, 2, "en")"#, "1,234,567.89");
+        s(r#"ftd.format_number(-1234.5, 0, "fr")"#, "-1\u{202f}235");
+        s(
+            r#"ftd.format_number(123456789, 0, "en-IN")"#,
+            "12,34,56,789",
+        );
+        s(r#"ftd.format_currency(-5, "EUR", "de")"#, "-5,00\u{a0}€");
+        s(r#"ftd.format_currency(1500.4, "JPY", "en")"#, "¥1,500");
+        s(r#"ftd.format_currency(10, "CHF", "en")"#, "CHF\u{a0}10.00");
+    }
+
+    #[test]
+    fn dates() {
+        s(
+            r#"ftd.format_date("2024-02-29T15:04:05", "%a %d %b %y %I:%M:%S %p, day %j")"#,
+            "Thu 29 Feb 24 03:04:05 PM, day 060",
+        );
+        s(
+            r#"ftd.parse_date("March 5, 2024 7:30 PM", "%B %e, %Y %I:%M %p")"#,
+            "2024-03-05T19:30:00",
+        );
+        s(r#"ftd.parse_date("05/03/2024", "%d/%m/%Y")"#, "2024-03-05");
+        s(r#"ftd.add_days("2024-01-31", -365)"#, "2023-01-31");
+        s(
+            r#"ftd.add_days("1969-12-31T23:59", 1)"#,
+            "1970-01-01T23:59:00",
+        );
+        assert_eq!(
+            e(r#"ftd.days_between("2000-01-01", "2024-01-01")"#),
+            Ok(Value::Int(8766))
+        );
+        assert_eq!(
+            e(r#"ftd.add_days("2023-02-29", 1)"#),
+            Err("Error: Invalid date: 2023-02-29T00:00:00".to_string())
+        );
+        assert_eq!(
+            e(r#"ftd.parse_date("5 March", "%d/%m")"#),
+            Err("Error: `5 March` does not match the date format `%d/%m`".to_string())
+        );
+    }
+}
@@ -1636,16 +1636,38 @@ impl Value {
                     fastn_grammar::evalexpr::Value::Empty
                 }
             }
-            t => unimplemented!("{:?}", t),
+            Value::Record { fields, .. } | Value::Object { values: fields } => {
+                let mut record = fastn_grammar::evalexpr::RecordType::new();
+                for (key, field) in fields {
+                    let value = field.clone().resolve(doc, field.line_number())?;
+                    // Components and modules have no expression counterpart
+                    if matches!(value, Value::UI { .. } | Value::Module { .. }) {
+                        continue;
+                    }
+                    record.insert(
+                        key.to_string(),
+                        value.to_evalexpr_value(doc, field.line_number())?,
+                    );
+                }
+                fastn_grammar::evalexpr::Value::Record(record)
+            }
+            t => {
+                return ftd::interpreter::utils::e2(
+                    format!("Cannot use `{:?}` in an expression", t.kind()),
+                    doc.name,
+                    line_number,
+                )
+            }
         })
     }
 
     pub(crate) fn from_evalexpr_value(
         value: fastn_grammar::evalexpr::Value,
         expected_kind: &ftd::interpreter::Kind,
-        doc_name: &str,
+        doc: &ftd::interpreter::TDoc,
         line_number: usize,
     ) -> ftd::interpreter::Result<Value> {
+        let doc_name = doc.name;
         Ok(match value {
             fastn_grammar::evalexpr::Value::String(text) if expected_kind.is_string() => {
                 Value::String { text }
@@ -1664,7 +1686,7 @@ impl Value {
                 let val_kind = expected_kind.list_type(doc_name, line_number)?;
                 for val in data {
                     values.push(ftd::interpreter::PropertyValue::Value {
-                        value: Value::from_evalexpr_value(val, &val_kind, doc_name, line_number)?,
+                        value: Value::from_evalexpr_value(val, &val_kind, doc, line_number)?,
                         is_mutable: false,
                         line_number,
                     });
@@ -1680,6 +1702,44 @@ impl Value {
                     kind: ftd::interpreter::KindData::new(expected_kind.clone()),
                 }
             }
+            fastn_grammar::evalexpr::Value::Record(mut data) if expected_kind.is_record() => {
+                let name = expected_kind.get_record_name().unwrap();
+                let record = doc.get_record(name, line_number)?;
+                let mut fields: ftd::Map<ftd::interpreter::PropertyValue> = Default::default();
+                for field in record.fields {
+                    let value = match data.remove(field.name.as_str()) {
+                        Some(val) => ftd::interpreter::PropertyValue::Value {
+                            value: Value::from_evalexpr_value(
+                                val,
+                                &field.kind.kind,
+                                doc,
+                                line_number,
+                            )?,
+                            is_mutable: false,
+                            line_number,
+                        },
+                        // fields that can not be expressed, like components, keep their default
+                        None => match field.value {
+                            Some(value) => value,
+                            None if field.kind.is_optional() => {
+                                ftd::interpreter::PropertyValue::new_none(field.kind, line_number)
+                            }
+                            None => {
+                                return ftd::interpreter::utils::e2(
+                                    format!("Field `{}` of `{}` has no value", field.name, name),
+                                    doc_name,
+                                    line_number,
+                                )
+                            }
+                        },
+                    };
+                    fields.insert(field.name, value);
+                }
+                Value::Record {
+                    name: name.to_string(),
+                    fields,
+                }
+            }
             t => {
                 return ftd::interpreter::utils::e2(
                     format!("Expected kind: `{:?}`, found: `{:?}`", expected_kind, t),
@@ -1745,12 +1805,22 @@ impl Value {
                 let line_number = value.line_number();
                 value.resolve(doc, line_number)?.into_evalexpr_value(doc)
             }
-            ftd::interpreter::Value::Record { .. } => {
-                if let Ok(Some(value)) = ftd::interpreter::utils::get_value(doc, &self) {
-                    Ok(fastn_grammar::evalexpr::Value::String(value.to_string()))
-                } else {
-                    unimplemented!("{:?}", self)
+            ftd::interpreter::Value::Record { fields, .. }
+            | ftd::interpreter::Value::Object { values: fields } => {
+                let mut record = fastn_grammar::evalexpr::RecordType::new();
+                for (key, field) in fields {
+                    let line_number = field.line_number();
+                    let value = field.resolve(doc, line_number)?;
+                    // Components and modules have no expression counterpart
+                    if matches!(
+                        value,
+                        ftd::interpreter::Value::UI { .. } | ftd::interpreter::Value::Module { .. }
+                    ) {
+                        continue;
+                    }
+                    record.insert(key, value.into_evalexpr_value(doc)?);
                 }
+                Ok(fastn_grammar::evalexpr::Value::Record(record))
             }
             ftd::interpreter::Value::List { data, .. } => {
                 let mut values = vec![];
@@ -1990,3 +2060,66 @@ fn get_kind(
         found_kind.clone()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use fastn_grammar::evalexpr::Value as EValue;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn record_from_evalexpr_value() {
+        let document = ftd::interpreter::test::interpret_helper(
+            "foo",
+            indoc::indoc!(
+                "
+                -- record person:
+                caption name:
+                integer age: 20
+                optional string email:
+
+                -- person alice: Alice
+
+                -- ftd.text: $alice.name
+                "
+            ),
+        )
+        .unwrap();
+        let doc = document.tdoc();
+        let kind = ftd::interpreter::Kind::record("foo#person");
+
+        // a record passed through an expression comes back unchanged
+        let alice = doc.get_value(0, "foo#alice").unwrap();
+        let value = alice.to_evalexpr_value(&doc, 0).unwrap();
+        let back = super::Value::from_evalexpr_value(value.clone(), &kind, &doc, 0).unwrap();
+        assert_eq!(back.kind(), alice.kind());
+        assert_eq!(back.to_evalexpr_value(&doc, 0).unwrap(), value);
+
+        // missing fields take their default
+        let bob = EValue::Record([("name".to_string(), EValue::from("Bob"))].into());
+        let bob = super::Value::from_evalexpr_value(bob, &kind, &doc, 0).unwrap();
+        let fields = bob.record_fields("foo", 0).unwrap();
+        assert_eq!(
+            fields["age"].clone().resolve(&doc, 0).unwrap(),
+            super::Value::Integer { value: 20 }
+        );
+        assert!(fields["email"].clone().resolve(&doc, 0).unwrap().is_null());
+
+        assert!(super::Value::from_evalexpr_value(
+            EValue::Record(Default::default()),
+            &kind,
+            &doc,
+            0
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn to_evalexpr_value_error() {
+        let document = ftd::interpreter::test::interpret_helper("foo", "").unwrap();
+        let module = super::Value::Module {
+            name: "foo".to_string(),
+            things: Default::default(),
+        };
+        assert!(module.to_evalexpr_value(&document.tdoc(), 0).is_err());
+    }
+}
@@ -154,12 +154,7 @@ impl Variable {
                 }
                 Ok(result)
             } else {
-                doc.err(
-                    "not found",
-                    processor,
-                    "Variable::from_ast",
-                    variable_definition.line_number,
-                )
+                doc.not_found(processor.as_str(), variable_definition.line_number)
             };
         }
 
@@ -470,6 +470,10 @@ pub struct Code {
     pub lang: ftd::js::Value,
     pub theme: ftd::js::Value,
     pub show_line_number: ftd::js::Value,
+    pub highlight_lines: Option<ftd::js::Value>,
+    pub diff: Option<ftd::js::Value>,
+    pub collapse: Option<ftd::js::Value>,
+    pub copy_button: Option<ftd::js::Value>,
 }
 
 impl Code {
@@ -516,9 +520,72 @@ impl Code {
                 component_definition.arguments.as_slice(),
             )
             .unwrap(),
+            highlight_lines: ftd::js::value::get_optional_js_value(
+                "highlight-lines",
+                component.properties.as_slice(),
+                component_definition.arguments.as_slice(),
+            ),
+            diff: ftd::js::value::get_optional_js_value(
+                "diff",
+                component.properties.as_slice(),
+                component_definition.arguments.as_slice(),
+            ),
+            collapse: ftd::js::value::get_optional_js_value(
+                "collapse",
+                component.properties.as_slice(),
+                component_definition.arguments.as_slice(),
+            ),
+            copy_button: ftd::js::value::get_optional_js_value(
+                "copy-button",
+                component.properties.as_slice(),
+                component_definition.arguments.as_slice(),
+            ),
         }
     }
 
+    /// Prism, which highlights code in the browser, does not know about the syntax definitions
+    /// and themes shipped by packages, so code using them is highlighted here with syntect.
+    /// Only static code, language, theme and `diff` can be highlighted ahead of time.
+    ///
+    /// Returns the trimmed code along with its highlighted html, the browser adds the line
+    /// decorations using the code.
+    fn highlighted_with_package_assets(
+        &self,
+        doc: &ftd::interpreter::TDoc,
+    ) -> Option<(String, String)> {
+        let code = self.code.get_string_data()?;
+        let lang = self.lang.get_string_data()?;
+        let theme = self.theme.get_string_data()?;
+        if !ftd::executor::code::needs_package_assets(
+            lang.as_str(),
+            theme.as_str(),
+            doc.code_assets,
+        ) {
+            return None;
+        }
+        let diff = match self.diff {
+            Some(ftd::js::Value::Data(ftd::interpreter::Value::Boolean { value })) => value,
+            Some(_) => return None,
+            None => false,
+        };
+
+        let code = ftd::executor::code::trim(code.as_str());
+        let highlighted = ftd::executor::code_with_theme(
+            if diff {
+                ftd::executor::code::strip_diff_markers(code.as_str())
+            } else {
+                code.clone()
+            }
+            .as_str(),
+            lang.as_str(),
+            theme.as_str(),
+            doc.code_assets,
+            doc.name,
+        )
+        .ok()?;
+        Some((code, highlighted.rendered))
+    }
+
     pub fn to_component_statements(
         &self,
         parent: &str,
@@ -531,15 +598,36 @@ impl Code {
         let kernel = create_element(fastn_js::ElementKind::Code, parent, index, rdata);
         component_statements.push(fastn_js::ComponentStatement::CreateKernel(kernel.clone()));
 
-        component_statements.push(fastn_js::ComponentStatement::SetProperty(
-            self.code.to_set_property(
-                fastn_js::PropertyKind::Code,
-                doc,
-                kernel.name.as_str(),
-                rdata,
-            ),
-        ));
+        if let Some((code, highlighted)) = self.highlighted_with_package_assets(doc) {
+            // set before the code, so the browser does not highlight it again
+            component_statements.push(fastn_js::ComponentStatement::SetProperty(
+                ftd::js::Value::from_str_value(highlighted.as_str()).to_set_property(
+                    fastn_js::PropertyKind::CodeHighlighted,
+                    doc,
+                    kernel.name.as_str(),
+                    rdata,
+                ),
+            ));
+            component_statements.push(fastn_js::ComponentStatement::SetProperty(
+                ftd::js::Value::from_str_value(code.as_str()).to_set_property(
+                    fastn_js::PropertyKind::Code,
+                    doc,
+                    kernel.name.as_str(),
+                    rdata,
+                ),
+            ));
+        } else {
+            component_statements.push(fastn_js::ComponentStatement::SetProperty(
+                self.code.to_set_property(
+                    fastn_js::PropertyKind::Code,
+                    doc,
+                    kernel.name.as_str(),
+                    rdata,
+                ),
+            ));
+        }
 
+        // the language and theme classes are set on highlighted code too, prism leaves it alone
         component_statements.push(fastn_js::ComponentStatement::SetProperty(
             self.lang.to_set_property(
                 fastn_js::PropertyKind::CodeLanguage,
@@ -558,6 +646,50 @@ impl Code {
             ),
         ));
 
+        if let Some(ref highlight_lines) = self.highlight_lines {
+            component_statements.push(fastn_js::ComponentStatement::SetProperty(
+                highlight_lines.to_set_property(
+                    fastn_js::PropertyKind::CodeHighlightLines,
+                    doc,
+                    kernel.name.as_str(),
+                    rdata,
+                ),
+            ));
+        }
+
+        if let Some(ref diff) = self.diff {
+            component_statements.push(fastn_js::ComponentStatement::SetProperty(
+                diff.to_set_property(
+                    fastn_js::PropertyKind::CodeDiff,
+                    doc,
+                    kernel.name.as_str(),
+                    rdata,
+                ),
+            ));
+        }
+
+        if let Some(ref collapse) = self.collapse {
+            component_statements.push(fastn_js::ComponentStatement::SetProperty(
+                collapse.to_set_property(
+                    fastn_js::PropertyKind::CodeCollapse,
+                    doc,
+                    kernel.name.as_str(),
+                    rdata,
+                ),
+            ));
+        }
+
+        if let Some(ref copy_button) = self.copy_button {
+            component_statements.push(fastn_js::ComponentStatement::SetProperty(
+                copy_button.to_set_property(
+                    fastn_js::PropertyKind::CodeCopyButton,
+                    doc,
+                    kernel.name.as_str(),
+                    rdata,
+                ),
+            ));
+        }
+
         component_statements.push(fastn_js::ComponentStatement::SetProperty(
             self.show_line_number.to_set_property(
                 fastn_js::PropertyKind::CodeShowLineNumber,
@@ -936,6 +1068,17 @@ impl Text {
         }
     }
 
+    /// The browser renders markdown with a fixed set of extensions, so static text of a package
+    /// that configured `fastn.markdown` is rendered here instead.
+    fn markdown_with_package_options(&self, doc: &ftd::interpreter::TDoc) -> Option<String> {
+        let options = doc.markdown_options?;
+        let text = self.text.get_string_data()?;
+        Some(ftd::executor::markup::markup_inline_with_options(
+            text.as_str(),
+            Some(options),
+        ))
+    }
+
     pub fn to_component_statements(
         &self,
         parent: &str,
@@ -947,14 +1090,24 @@ impl Text {
         let mut component_statements = vec![];
         let kernel = create_element(fastn_js::ElementKind::Text, parent, index, rdata);
         component_statements.push(fastn_js::ComponentStatement::CreateKernel(kernel.clone()));
-        component_statements.push(fastn_js::ComponentStatement::SetProperty(
-            fastn_js::SetProperty {
-                kind: fastn_js::PropertyKind::StringValue,
-                value: self.text.to_set_property_value(doc, rdata),
-                element_name: kernel.name.to_string(),
-                inherited: rdata.inherited_variable_name.to_string(),
-            },
-        ));
+        match self.markdown_with_package_options(doc) {
+            Some(html) => component_statements.push(fastn_js::ComponentStatement::SetProperty(
+                ftd::js::Value::from_str_value(html.as_str()).to_set_property(
+                    fastn_js::PropertyKind::MarkdownHtml,
+                    doc,
+                    kernel.name.as_str(),
+                    rdata,
+                ),
+            )),
+            None => component_statements.push(fastn_js::ComponentStatement::SetProperty(
+                fastn_js::SetProperty {
+                    kind: fastn_js::PropertyKind::StringValue,
+                    value: self.text.to_set_property_value(doc, rdata),
+                    element_name: kernel.name.to_string(),
+                    inherited: rdata.inherited_variable_name.to_string(),
+                },
+            )),
+        }
         component_statements.extend(self.common.to_set_properties(
             kernel.name.as_str(),
             doc,
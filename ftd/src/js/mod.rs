@@ -28,6 +28,8 @@ pub fn default_bag_into_js_ast() -> Vec<fastn_js::Ast> {
         name: "",
         aliases: &ftd::interpreter::default::default_aliases(),
         bag: ftd::interpreter::BagOrState::Bag(&bag),
+        code_assets: None,
+        markdown_options: None,
     };
     let mut export_asts = vec![];
     for thing in ftd::interpreter::default::default_bag().values() {
@@ -82,7 +84,9 @@ pub struct JSAstData {
 
 pub fn document_into_js_ast(document: ftd::interpreter::Document) -> JSAstData {
     use itertools::Itertools;
-    let doc = ftd::interpreter::TDoc::new(&document.name, &document.aliases, &document.data);
+    let doc = ftd::interpreter::TDoc::new(&document.name, &document.aliases, &document.data)
+        .with_code_assets(document.code_assets.as_deref())
+        .with_markdown_options(document.markdown_options.as_ref());
     // Check if document tree has rive. This is used to add rive script.
     let mut has_rive_components = false;
     let mut document_asts = vec![ftd::js::from_tree(
@@ -144,8 +148,8 @@ impl ftd::interpreter::Function {
             self.name.as_str(),
             self.expression
                 .iter()
-                .map(|e| {
-                    fastn_grammar::evalexpr::build_operator_tree(e.expression.as_str()).unwrap()
+                .flat_map(|e| {
+                    fastn_grammar::evalexpr::build_statements(e.expression.as_str()).unwrap()
                 })
                 .collect_vec(),
             self.arguments
@@ -228,6 +228,12 @@ impl Header {
             Header::BlockRecordHeader(b) => {
                 let mut blockrecord = (*b).clone();
                 blockrecord.line_number = 0;
+                blockrecord.body.1 = None;
+                blockrecord.fields = blockrecord
+                    .fields
+                    .iter()
+                    .map(|v| v.without_line_number())
+                    .collect_vec();
                 Header::BlockRecordHeader(blockrecord)
             }
         }
@@ -5,12 +5,14 @@ mod test;
 pub(crate) mod header;
 mod parser;
 mod section;
+mod to_string;
 pub mod utils;
 
 pub use header::{Header, Headers, Section as HSection, KV};
 pub use parser::{parse, parse_with_line_number};
 pub use section::Body;
 pub use section::Section;
+pub use to_string::{format, to_string};
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -76,3 +78,50 @@ pub enum Error {
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// The document and line number the error is about, if known.
+    pub fn location(&self) -> Option<(&str, usize)> {
+        match self {
+            Error::SectionNotFound {
+                doc_id,
+                line_number,
+            }
+            | Error::MoreThanOneCaption {
+                doc_id,
+                line_number,
+            }
+            | Error::ParseError {
+                doc_id,
+                line_number,
+                ..
+            }
+            | Error::MoreThanOneHeader {
+                doc_id,
+                line_number,
+                ..
+            }
+            | Error::HeaderNotFound {
+                doc_id,
+                line_number,
+                ..
+            }
+            | Error::ForbiddenUsage {
+                doc_id,
+                line_number,
+                ..
+            }
+            | Error::NotFound {
+                doc_id,
+                line_number,
+                ..
+            }
+            | Error::MoreThanOneSubSections {
+                doc_id,
+                line_number,
+                ..
+            } => Some((doc_id.as_str(), *line_number)),
+            Error::Serde { .. } | Error::Syntect { .. } => None,
+        }
+    }
+}
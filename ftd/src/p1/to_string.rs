@@ -0,0 +1,660 @@
+//! Prints `ftd::p1::Section`s back to source, in one canonical layout:
+//!
+//! - the caption is on the section line, unless it spans more than one line
+//! - headers keep their order: single line headers go right below the section line, the others
+//!   are written as `-- section.header:` blocks
+//! - the body comes after the headers, as a `-- section.body:` block if any header is a block
+//! - sections are separated by a blank line, except consecutive imports, and `-- end:` is only
+//!   written for sections with children
+//!
+//! `;;` comments are dropped by the parser, so `format()` puts the ones of the original source
+//! back next to the line they were attached to.
+
+pub fn to_string(sections: &[ftd::p1::Section]) -> String {
+    let mut printer = Printer::default();
+    printer.sections(sections, None);
+    printer.finish()
+}
+
+/// Formats an ftd document, keeping its comments. Fails if the document does not parse, or if
+/// the formatted document would not parse back to the same sections.
+pub fn format(content: &str, doc_id: &str) -> ftd::p1::Result<String> {
+    let sections = ftd::p1::parse(content, doc_id)?;
+    let mut printer = Printer::default();
+    printer.sections(sections.as_slice(), None);
+    printer.restore_comments(content);
+    let formatted = printer.finish();
+
+    let unchanged = match ftd::p1::parse(formatted.as_str(), doc_id) {
+        Ok(formatted) => without_line_numbers(&formatted) == without_line_numbers(&sections),
+        Err(_) => false,
+    };
+    if !unchanged {
+        return Err(ftd::p1::Error::ParseError {
+            message: "can not format this document without changing its meaning".to_string(),
+            doc_id: doc_id.to_string(),
+            line_number: 0,
+        });
+    }
+
+    Ok(formatted)
+}
+
+fn without_line_numbers(sections: &[ftd::p1::Section]) -> Vec<ftd::p1::Section> {
+    sections.iter().map(|s| s.without_line_number()).collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LineKind {
+    /// `-- ` and `/-- ` lines starting a section
+    Section,
+    /// `-- section.header:` and `-- end:` lines, the parser reads them as they are, comments
+    /// included
+    Block,
+    /// inline headers and `if:` lines
+    Header,
+    /// fields of a record header, read as they are too
+    Field,
+    /// a line of a caption, header or body value, trailing whitespace is part of the value except
+    /// on its `last` line
+    Value {
+        last: bool,
+    },
+    Comment,
+    Blank,
+}
+
+impl LineKind {
+    /// Lines the parser does not remove comments from.
+    fn is_raw(self) -> bool {
+        matches!(self, LineKind::Block | LineKind::Field)
+    }
+}
+
+#[derive(Debug)]
+struct Line {
+    text: String,
+    kind: LineKind,
+}
+
+#[derive(Debug, Default)]
+struct Printer {
+    lines: Vec<Line>,
+}
+
+impl Printer {
+    fn push(&mut self, kind: LineKind, text: String) {
+        self.lines.push(Line { text, kind });
+    }
+
+    fn blank(&mut self) {
+        self.push(LineKind::Blank, "".to_string());
+    }
+
+    fn value(&mut self, value: &str) {
+        let lines: Vec<&str> = value.split('\n').collect();
+        for (i, line) in lines.iter().enumerate() {
+            self.push(
+                LineKind::Value {
+                    last: i + 1 == lines.len(),
+                },
+                escape_value_line(line),
+            );
+        }
+    }
+
+    fn condition(&mut self, condition: &Option<String>) {
+        if let Some(condition) = condition {
+            self.push(
+                LineKind::Header,
+                escape_value_line(format!("{}: {}", ftd::p1::utils::IF, condition).as_str()),
+            );
+        }
+    }
+
+    /// `parent` is the name `-- end:` of the section or header `sections` are in.
+    fn sections(&mut self, sections: &[ftd::p1::Section], parent: Option<&str>) {
+        for (i, section) in sections.iter().enumerate() {
+            let imports = i > 0 && is_import(section) && is_import(&sections[i - 1]);
+            if i > 0 && !imports {
+                self.blank();
+            }
+            self.section(section, parent);
+        }
+    }
+
+    fn section(&mut self, section: &ftd::p1::Section, parent: Option<&str>) {
+        let caption = section.caption.as_ref().and_then(value_of);
+        let mut line = format!(
+            "{}{}{}:",
+            if section.is_commented { "/-- " } else { "-- " },
+            section
+                .kind
+                .as_ref()
+                .map(|k| format!("{} ", k))
+                .unwrap_or_default(),
+            section.name
+        );
+        if let Some(caption) = caption.filter(|c| !c.contains('\n')) {
+            line.push(' ');
+            line.push_str(caption);
+        }
+        self.push(LineKind::Section, escape(line.as_str()));
+
+        let (inline, block): (Vec<_>, Vec<_>) =
+            section.headers.0.iter().partition(|h| is_inline(h));
+        for header in inline {
+            self.inline_header(header, LineKind::Header);
+        }
+
+        let mut has_block = false;
+        if let Some(caption) = caption.filter(|c| c.contains('\n')) {
+            self.blank();
+            self.push(LineKind::Block, format!("-- {}.caption:", section.name));
+            self.blank();
+            self.value(caption);
+            has_block = true;
+        }
+        for header in block {
+            has_block |= self.block_header(section.name.as_str(), header);
+        }
+
+        if let Some(ref body) = section.body {
+            self.blank();
+            if has_block {
+                self.push(LineKind::Block, format!("-- {}.body:", section.name));
+                self.blank();
+            }
+            self.value(body.value.as_str());
+        }
+
+        if !section.sub_sections.is_empty() {
+            self.blank();
+            self.sections(section.sub_sections.as_slice(), Some(section.name.as_str()));
+            self.blank();
+        }
+        // `-- end: <parent>` would end a section with the same name inside it first
+        if !section.sub_sections.is_empty() || parent == Some(section.name.as_str()) {
+            self.push(LineKind::Block, format!("-- end: {}", section.name));
+        }
+    }
+
+    fn inline_header(&mut self, header: &ftd::p1::Header, kind: LineKind) {
+        if let ftd::p1::Header::KV(kv) = header {
+            let mut line = format!("{}{}", kind_of(&kv.kind, &kv.access_modifier), kv.key);
+            if let Some(ref condition) = kv.condition {
+                line.push_str(ftd::p1::utils::INLINE_IF);
+                line.push_str(condition);
+            }
+            line.push(':');
+            if let Some(ref value) = kv.value {
+                line.push(' ');
+                line.push_str(value);
+            }
+            if kind == LineKind::Header {
+                line = escape_value_line(line.as_str());
+            }
+            self.push(kind, line);
+        }
+    }
+
+    /// Returns `false` if nothing was printed.
+    fn block_header(&mut self, section_name: &str, header: &ftd::p1::Header) -> bool {
+        match header {
+            ftd::p1::Header::KV(kv) => {
+                let name = format!(
+                    "-- {}{}.{}:",
+                    kind_of(&kv.kind, &kv.access_modifier),
+                    section_name,
+                    kv.key
+                );
+                self.blank();
+                match kv.value {
+                    Some(ref value)
+                        if !value.contains('\n')
+                            && !matches!(kv.source, ftd::p1::header::KVSource::Body) =>
+                    {
+                        self.push(LineKind::Block, format!("{} {}", name, value));
+                        self.condition(&kv.condition);
+                    }
+                    ref value => {
+                        self.push(LineKind::Block, name);
+                        self.condition(&kv.condition);
+                        if let Some(value) = value {
+                            self.blank();
+                            self.value(value);
+                        }
+                    }
+                }
+            }
+            ftd::p1::Header::Section(s) => {
+                let name = format!("{}.{}", section_name, s.key);
+                self.blank();
+                self.push(
+                    LineKind::Block,
+                    format!("-- {}{}:", kind_of(&s.kind, &Default::default()), name),
+                );
+                self.condition(&s.condition);
+                self.blank();
+                self.sections(s.section.as_slice(), Some(name.as_str()));
+                self.blank();
+                self.push(LineKind::Block, format!("-- end: {}", name));
+            }
+            ftd::p1::Header::BlockRecordHeader(b) => {
+                let (inline, dotted): (Vec<_>, Vec<_>) =
+                    b.fields.iter().partition(|h| is_inline(h));
+                let mut printed = false;
+                // a record with only `-- section.header.field:` fields is created by the first one
+                if b.caption.is_some() || b.body.0.is_some() || !inline.is_empty() {
+                    let mut line = format!(
+                        "-- {}{}.{}:",
+                        kind_of(&b.kind, &Default::default()),
+                        section_name,
+                        b.key
+                    );
+                    if let Some(ref caption) = b.caption {
+                        line.push(' ');
+                        line.push_str(caption);
+                    }
+                    self.blank();
+                    self.push(LineKind::Block, line);
+                    self.condition(&b.condition);
+                    for field in inline {
+                        self.inline_header(field, LineKind::Field);
+                    }
+                    if let Some(ref body) = b.body.0 {
+                        self.blank();
+                        self.value(body);
+                    }
+                    printed = true;
+                }
+                let record = format!("{}.{}", section_name, b.key);
+                for field in dotted {
+                    printed |= self.block_header(record.as_str(), field);
+                }
+                return printed;
+            }
+        }
+        true
+    }
+
+    /// Puts the comments of `source` back in `self.lines`. A comment line is put before the line
+    /// that followed it in `source`, and an inline comment after the line it was on.
+    fn restore_comments(&mut self, source: &str) {
+        let mut before: std::collections::BTreeMap<usize, Vec<Line>> = Default::default();
+        let mut pending: Vec<String> = vec![];
+        let mut blank_after_pending = false;
+        let mut cursor = 0;
+
+        for line in source.split('\n') {
+            if line.starts_with(";;") {
+                if blank_after_pending && !pending.is_empty() {
+                    pending.push("".to_string());
+                }
+                pending.push(line.trim_end().to_string());
+                blank_after_pending = false;
+                continue;
+            }
+            if line.trim().is_empty() {
+                blank_after_pending = !pending.is_empty();
+                continue;
+            }
+
+            let (code, mut comment) = split_inline_comment(line);
+            let (code, full) = (normalize(code), normalize(line));
+            let found = (cursor..self.lines.len()).find(|i| {
+                let l = &self.lines[*i];
+                l.kind != LineKind::Blank
+                    && normalize(l.text.as_str()).eq(if l.kind.is_raw() { &full } else { &code })
+            });
+            if found.map(|i| self.lines[i].kind.is_raw()).unwrap_or(false) {
+                comment = None;
+            }
+            let anchor = found.unwrap_or_else(|| self.next_non_blank(cursor));
+            let is_section = self
+                .lines
+                .get(anchor)
+                .map(|l| matches!(l.kind, LineKind::Section | LineKind::Block))
+                .unwrap_or(true);
+
+            let comments = before.entry(anchor).or_default();
+            for comment in pending.drain(..) {
+                // blank lines in a value are part of it, so they are only kept between sections
+                if comment.is_empty() && !is_section {
+                    continue;
+                }
+                comments.push(Line {
+                    kind: if comment.is_empty() {
+                        LineKind::Blank
+                    } else {
+                        LineKind::Comment
+                    },
+                    text: comment,
+                });
+            }
+            if blank_after_pending && is_section && !comments.is_empty() {
+                comments.push(Line {
+                    text: "".to_string(),
+                    kind: LineKind::Blank,
+                });
+            }
+            blank_after_pending = false;
+
+            match (found, comment) {
+                (Some(i), Some(comment)) => {
+                    let l = &mut self.lines[i];
+                    // trailing whitespace of a value line is part of the value
+                    if !matches!(l.kind, LineKind::Value { last: false })
+                        && !l.text.ends_with(char::is_whitespace)
+                    {
+                        l.text.push(' ');
+                    }
+                    l.text.push_str(comment.trim_end());
+                }
+                (None, Some(comment)) => comments.push(Line {
+                    text: comment.trim().to_string(),
+                    kind: LineKind::Comment,
+                }),
+                _ => {}
+            }
+            if let Some(i) = found {
+                cursor = i + 1;
+            }
+        }
+
+        let count = self.lines.len();
+        let mut lines = vec![];
+        for (i, line) in std::mem::take(&mut self.lines).into_iter().enumerate() {
+            if let Some(comments) = before.remove(&i) {
+                lines.extend(comments);
+            }
+            lines.push(line);
+        }
+        if let Some(comments) = before.remove(&count) {
+            lines.extend(comments);
+        }
+        if !pending.is_empty() {
+            if !lines.is_empty() {
+                lines.push(Line {
+                    text: "".to_string(),
+                    kind: LineKind::Blank,
+                });
+            }
+            lines.extend(pending.into_iter().map(|text| Line {
+                text,
+                kind: LineKind::Comment,
+            }));
+        }
+        self.lines = lines;
+    }
+
+    fn next_non_blank(&self, from: usize) -> usize {
+        (from..self.lines.len())
+            .find(|i| self.lines[*i].kind != LineKind::Blank)
+            .unwrap_or(self.lines.len())
+    }
+
+    fn finish(self) -> String {
+        let mut output = self
+            .lines
+            .iter()
+            .map(|l| l.text.as_str())
+            .collect::<Vec<&str>>()
+            .join("\n")
+            .trim_end_matches('\n')
+            .to_string();
+        if !output.is_empty() {
+            output.push('\n');
+        }
+        output
+    }
+}
+
+/// Consecutive imports are written without blank lines in between, if they only have inline
+/// headers.
+fn is_import(section: &ftd::p1::Section) -> bool {
+    section.name.eq("import")
+        && section.body.is_none()
+        && section.sub_sections.is_empty()
+        && section.headers.0.iter().all(is_inline)
+        && !section
+            .caption
+            .as_ref()
+            .and_then(value_of)
+            .map(|c| c.contains('\n'))
+            .unwrap_or(false)
+}
+
+fn value_of(header: &ftd::p1::Header) -> Option<&str> {
+    match header {
+        ftd::p1::Header::KV(kv) => kv.value.as_deref(),
+        _ => None,
+    }
+}
+
+/// Headers written on a single line below the section line, as opposed to `-- section.header:`
+/// blocks.
+fn is_inline(header: &ftd::p1::Header) -> bool {
+    match header {
+        ftd::p1::Header::KV(kv) => {
+            matches!(kv.source, ftd::p1::header::KVSource::Header)
+                && !kv.value.as_ref().map(|v| v.contains('\n')).unwrap_or(false)
+        }
+        _ => false,
+    }
+}
+
+fn kind_of(kind: &Option<String>, access_modifier: &ftd::p1::header::AccessModifier) -> String {
+    let mut parts = vec![];
+    if !access_modifier.is_public() {
+        parts.push("private");
+    }
+    if let Some(kind) = kind.as_deref().filter(|k| !k.is_empty()) {
+        parts.push(kind);
+    }
+    parts.iter().map(|p| format!("{} ", p)).collect()
+}
+
+/// Escapes `;;` in a value written on the same line as its key, so it is not read as a comment.
+fn escape(value: &str) -> String {
+    if value.contains("<hl>") {
+        return value.to_string();
+    }
+    value.replace(";;", "\\;;")
+}
+
+fn escape_value_line(line: &str) -> String {
+    if line.starts_with("-- ") || line.starts_with(";;") {
+        return format!("\\{}", line);
+    }
+    escape(line)
+}
+
+/// Splits `;; comment` off the end of `line`, the same way the parser does.
+fn split_inline_comment(line: &str) -> (&str, Option<&str>) {
+    if line.starts_with("\\;;") || line.starts_with("\\-- ") || line.contains("<hl>") {
+        return (line, None);
+    }
+
+    let bytes = line.as_bytes();
+    let mut backslashes = 0;
+    for (i, b) in bytes.iter().enumerate() {
+        match b {
+            b'\\' => backslashes += 1,
+            b';' if backslashes % 2 == 0 && bytes.get(i + 1) == Some(&b';') => {
+                return (&line[..i], Some(&line[i..]));
+            }
+            _ => backslashes = 0,
+        }
+    }
+    (line, None)
+}
+
+fn normalize(line: &str) -> String {
+    line.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use {indoc::indoc, pretty_assertions::assert_eq};
+
+    #[track_caller]
+    fn f(s: &str, t: &str) {
+        let formatted = super::format(s, "foo").unwrap_or_else(|e| panic!("{:?}", e));
+        assert_eq!(t, formatted);
+        assert_eq!(
+            formatted,
+            super::format(formatted.as_str(), "foo").unwrap(),
+            "formatting is not idempotent"
+        );
+    }
+
+    #[test]
+    fn to_string() {
+        assert_eq!(
+            indoc!(
+                "
+                -- ftd.column foo: hello
+                padding: 10
+
+                -- foo.text: world
+
+                -- foo.body:
+
+                body ho
+                \\-- not a section
+
+                -- ftd.text: child
+
+                -- end: foo
+
+                -- bar:
+
+                bar body
+                "
+            ),
+            super::to_string(&[
+                ftd::p1::Section::with_name("foo")
+                    .kind("ftd.column")
+                    .and_caption("hello")
+                    .add_header_str("padding", "10")
+                    .add_header_str_with_source(
+                        "text",
+                        "world",
+                        Some(ftd::p1::header::KVSource::Caption)
+                    )
+                    .and_body("body ho\n-- not a section")
+                    .add_sub_section(ftd::p1::Section::with_name("ftd.text").and_caption("child")),
+                ftd::p1::Section::with_name("bar").and_body("bar body"),
+            ]),
+        );
+    }
+
+    #[test]
+    fn whitespace() {
+        f(
+            indoc!(
+                "
+                -- ftd.column:
+                  padding:10
+                   spacing:   5
+
+
+
+                -- ftd.text:   hello
+
+
+
+                -- end:   ftd.column
+                -- ftd.text: bye
+                "
+            ),
+            indoc!(
+                "
+                -- ftd.column:
+                padding: 10
+                spacing: 5
+
+                -- ftd.text: hello
+
+                -- end: ftd.column
+
+                -- ftd.text: bye
+                "
+            ),
+        );
+    }
+
+    #[test]
+    fn comments() {
+        f(
+            indoc!(
+                "
+                ;; This file is about foo
+
+                -- import: foo   ;; for bar
+
+                -- import: bar
+                ;; what follows is a text
+                -- ftd.text: hello ;; the caption
+                color: red
+                ;; not blue
+                size: 10
+
+                -- ftd.text.body:
+
+                first line
+                ;; in the body
+                second line ;; inline
+                escaped \\;; not a comment
+
+                /-- ftd.text: commented
+
+
+
+                ;; the end
+                "
+            ),
+            indoc!(
+                "
+                ;; This file is about foo
+
+                -- import: foo ;; for bar
+                -- import: bar
+
+                ;; what follows is a text
+                -- ftd.text: hello ;; the caption
+                color: red
+                ;; not blue
+                size: 10
+
+                first line
+                ;; in the body
+                second line ;; inline
+                escaped \\;; not a comment
+
+                /-- ftd.text: commented
+
+                ;; the end
+                "
+            ),
+        );
+    }
+
+    #[test]
+    fn p1_files() {
+        let mut files = ftd::utils::find_all_files_matching_extension_recursively("t/p1", "ftd");
+        files.sort();
+        for file in files {
+            let s = std::fs::read_to_string(&file).unwrap();
+            let formatted = super::format(s.as_str(), "foo")
+                .unwrap_or_else(|e| panic!("{}: {:?}", file.display(), e));
+            assert_eq!(
+                formatted,
+                super::format(formatted.as_str(), "foo").unwrap(),
+                "{}",
+                file.display()
+            );
+        }
+    }
+}